@@ -7,6 +7,8 @@ use cosmwasm_std::{
     DepsMut, Env, Fraction, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError,
     StdResult, SubMsg, Uint128, Uint256, Uint64, WasmMsg,
 };
+#[cfg(feature = "token-factory")]
+use cosmwasm_std::{coin, wasm_execute, BankMsg};
 
 use crate::response::MsgInstantiateContractResponse;
 use astroport::asset::{
@@ -15,15 +17,20 @@ use astroport::asset::{
 };
 use astroport::factory::PairType;
 use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+#[cfg(feature = "token-factory")]
+use astroport::generator::ExecuteMsg as GeneratorExecuteMsg;
 use astroport::pair::{
-    migration_check, ConfigResponse, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+    calc_depth, migration_check, ConfigResponse, DepthLevel, XYKPoolConfig, XYKPoolParams,
+    XYKPoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
 };
 use astroport::pair::{
     CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
     QueryMsg, ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
 };
 use astroport::querier::{query_factory_config, query_fee_info, query_supply};
+use astroport::token_factory::{is_token_factory_denom, tf_denom, LP_SUBDENOM};
+#[cfg(feature = "token-factory")]
+use astroport::token_factory::{tf_burn_msg, tf_create_denom_msg, tf_mint_msg};
 use astroport::{token::InstantiateMsg as TokenInstantiateMsg, U256};
 use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
@@ -58,18 +65,32 @@ pub fn instantiate(
     }
 
     let mut track_asset_balances = false;
+    let mut token_factory_lp = false;
 
     if let Some(init_params) = msg.init_params {
         let params: XYKPoolParams = from_binary(&init_params)?;
         track_asset_balances = params.track_asset_balances.unwrap_or_default();
+        token_factory_lp = params.token_factory_lp.unwrap_or_default();
+    }
+
+    // Token factory messages are only available if the chain supports them
+    if token_factory_lp && !cfg!(feature = "token-factory") {
+        return Err(ContractError::TokenFactoryNotSupported {});
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    // The token factory denom is known in advance, so there is no need to wait for a reply
+    let liquidity_token = if token_factory_lp {
+        Addr::unchecked(tf_denom(&env.contract.address, LP_SUBDENOM))
+    } else {
+        Addr::unchecked("")
+    };
+
     let config = Config {
         pair_info: PairInfo {
             contract_addr: env.contract.address.clone(),
-            liquidity_token: Addr::unchecked(""),
+            liquidity_token,
             asset_infos: msg.asset_infos.clone(),
             pair_type: PairType::Xyk {},
         },
@@ -88,6 +109,28 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &config)?;
 
+    let balances_tracking_attr = (
+        "asset_balances_tracking",
+        if config.track_asset_balances {
+            "enabled"
+        } else {
+            "disabled"
+        },
+    );
+
+    #[cfg(feature = "token-factory")]
+    if token_factory_lp {
+        return Ok(Response::new()
+            .add_message(tf_create_denom_msg(&env.contract.address, LP_SUBDENOM))
+            .add_attributes([
+                (
+                    "liquidity_token_addr",
+                    config.pair_info.liquidity_token.as_str(),
+                ),
+                balances_tracking_attr,
+            ]));
+    }
+
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create the LP token contract
@@ -115,15 +158,9 @@ pub fn instantiate(
         reply_on: ReplyOn::Success,
     }];
 
-    Ok(Response::new().add_submessages(sub_msg).add_attribute(
-        "asset_balances_tracking".to_owned(),
-        if config.track_asset_balances {
-            "enabled"
-        } else {
-            "disabled"
-        }
-        .to_owned(),
-    ))
+    Ok(Response::new()
+        .add_submessages(sub_msg)
+        .add_attributes([balances_tracking_attr]))
 }
 
 /// The entry point to the contract for processing replies from submessages.
@@ -230,6 +267,19 @@ pub fn execute(
                 to_addr,
             )
         }
+        ExecuteMsg::WithdrawLiquidity { .. } => {
+            let lp_token = &cfg.pair_info.liquidity_token;
+            if !is_token_factory_denom(lp_token.as_str()) {
+                return Err(ContractError::NonSupported {});
+            }
+
+            let amount = match info.funds.as_slice() {
+                [lp] if lp.denom == lp_token.as_str() && !lp.amount.is_zero() => lp.amount,
+                _ => return Err(ContractError::InvalidLpFunds {}),
+            };
+
+            withdraw_liquidity(deps, env, info.sender, amount)
+        }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
         _ => Err(ContractError::NonSupported {}),
     }
@@ -283,13 +333,14 @@ pub fn receive_cw20(
                 to_addr,
             )
         }
-        Ok(Cw20HookMsg::WithdrawLiquidity { .. }) => withdraw_liquidity(
-            deps,
-            env,
-            info,
-            Addr::unchecked(cw20_msg.sender),
-            cw20_msg.amount,
-        ),
+        Ok(Cw20HookMsg::WithdrawLiquidity { .. }) => {
+            let config = CONFIG.load(deps.storage)?;
+            if info.sender != config.pair_info.liquidity_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            withdraw_liquidity(deps, env, Addr::unchecked(cw20_msg.sender), cw20_msg.amount)
+        }
         Err(err) => Err(err.into()),
     }
 }
@@ -368,7 +419,7 @@ pub fn provide_liquidity(
         }
     }
 
-    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    let total_share = query_lp_supply(&deps.querier, &config)?;
     let share = if total_share.is_zero() {
         // Initial share = collateral amount
         let share = Uint128::new(
@@ -468,6 +519,18 @@ fn mint_liquidity_token_message(
 ) -> Result<Vec<CosmosMsg>, ContractError> {
     let lp_token = &config.pair_info.liquidity_token;
 
+    #[cfg(feature = "token-factory")]
+    if is_token_factory_denom(lp_token.as_str()) {
+        return mint_tf_liquidity_token_message(
+            querier,
+            config,
+            contract_address,
+            recipient,
+            amount,
+            auto_stake,
+        );
+    }
+
     // If no auto-stake - just mint to recipient
     if !auto_stake {
         return Ok(vec![CosmosMsg::Wasm(WasmMsg::Execute {
@@ -508,6 +571,55 @@ fn mint_liquidity_token_message(
     }
 }
 
+/// Mint token factory LP tokens for a beneficiary and auto stake the tokens in the Generator contract
+/// (if auto staking is specified). The token factory mints coins to the pair which then sends them further.
+///
+/// * **recipient** is the LP token recipient.
+///
+/// * **amount** is the amount of LP tokens that will be minted for the recipient.
+///
+/// * **auto_stake** determines whether the newly minted LP tokens will
+/// be automatically staked in the Generator on behalf of the recipient.
+#[cfg(feature = "token-factory")]
+fn mint_tf_liquidity_token_message(
+    querier: QuerierWrapper,
+    config: &Config,
+    contract_address: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+    auto_stake: bool,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let lp_coin = coin(amount.u128(), config.pair_info.liquidity_token.as_str());
+    let mut messages = vec![tf_mint_msg(contract_address, &lp_coin)];
+
+    if auto_stake {
+        let generator = query_factory_config(&querier, &config.factory_addr)?
+            .generator_address
+            .ok_or(ContractError::AutoStakeError {})?;
+
+        messages.push(
+            wasm_execute(
+                generator,
+                &GeneratorExecuteMsg::Deposit {
+                    recipient: Some(recipient.to_string()),
+                },
+                vec![lp_coin],
+            )?
+            .into(),
+        );
+    } else if recipient != contract_address {
+        messages.push(
+            BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![lp_coin],
+            }
+            .into(),
+        );
+    }
+
+    Ok(messages)
+}
+
 /// Withdraw liquidity from the pool.
 /// * **sender** is the address that will receive assets back from the pair contract.
 ///
@@ -515,16 +627,11 @@ fn mint_liquidity_token_message(
 pub fn withdraw_liquidity(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
     sender: Addr,
     amount: Uint128,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage).unwrap();
 
-    if info.sender != config.pair_info.liquidity_token {
-        return Err(ContractError::Unauthorized {});
-    }
-
     let (pools, total_share) = pool_info(deps.querier, &config)?;
     let refund_assets = get_share_in_assets(&pools, amount, total_share);
 
@@ -553,11 +660,7 @@ pub fn withdraw_liquidity(
     let messages: Vec<CosmosMsg> = vec![
         refund_assets[0].clone().into_msg(sender.clone())?,
         refund_assets[1].clone().into_msg(sender.clone())?,
-        CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: config.pair_info.liquidity_token.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
-            funds: vec![],
-        }),
+        burn_liquidity_token_message(&config, amount)?,
     ];
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
@@ -571,6 +674,27 @@ pub fn withdraw_liquidity(
     ]))
 }
 
+/// Returns a message which burns the specified amount of LP tokens held by the pair.
+///
+/// * **amount** is the amount of LP tokens to burn.
+fn burn_liquidity_token_message(config: &Config, amount: Uint128) -> StdResult<CosmosMsg> {
+    let lp_token = &config.pair_info.liquidity_token;
+
+    #[cfg(feature = "token-factory")]
+    if is_token_factory_denom(lp_token.as_str()) {
+        return Ok(tf_burn_msg(
+            &config.pair_info.contract_addr,
+            &coin(amount.u128(), lp_token.as_str()),
+        ));
+    }
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: lp_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }))
+}
+
 /// Returns the amount of pool assets that correspond to an amount of LP tokens.
 ///
 /// * **pools** is the array with assets in the pool.
@@ -891,6 +1015,10 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             asset_info,
             block_height,
         } => to_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::Depth {
+            offer_asset_info,
+            price_impacts,
+        } => to_binary(&query_depth(deps, offer_asset_info, price_impacts)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -966,6 +1094,44 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
     })
 }
 
+/// Returns the largest offer amounts which keep the swap price impact within each of the given levels.
+///
+/// * **offer_asset_info** is the asset to swap from.
+///
+/// * **price_impacts** is the list of price impact levels.
+pub fn query_depth(
+    deps: Deps,
+    offer_asset_info: AssetInfo,
+    price_impacts: Vec<Decimal>,
+) -> StdResult<Vec<DepthLevel>> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let (offer_pool, ask_pool) = if offer_asset_info.equal(&pools[0].info) {
+        (pools[0].amount, pools[1].amount)
+    } else if offer_asset_info.equal(&pools[1].info) {
+        (pools[1].amount, pools[0].amount)
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    };
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        config.factory_addr,
+        config.pair_info.pair_type,
+    )?;
+
+    calc_depth(offer_pool, ask_pool, &price_impacts, |offer_amount| {
+        compute_swap(offer_pool, ask_pool, offer_amount, fee_info.total_fee_rate)
+            .map(|(return_amount, ..)| return_amount)
+    })
+}
+
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 ///
 /// * **ask_asset** is the asset to swap to as well as the desired amount of ask
@@ -1282,11 +1448,23 @@ pub fn pool_info(querier: QuerierWrapper, config: &Config) -> StdResult<(Vec<Ass
     let pools = config
         .pair_info
         .query_pools(&querier, &config.pair_info.contract_addr)?;
-    let total_share = query_supply(&querier, &config.pair_info.liquidity_token)?;
+    let total_share = query_lp_supply(&querier, config)?;
 
     Ok((pools, total_share))
 }
 
+/// Returns the total amount of LP tokens currently minted. Token factory LP supply is read from the bank module.
+pub fn query_lp_supply(querier: &QuerierWrapper, config: &Config) -> StdResult<Uint128> {
+    let lp_token = &config.pair_info.liquidity_token;
+
+    #[cfg(feature = "token-factory")]
+    if is_token_factory_denom(lp_token.as_str()) {
+        return Ok(querier.query_supply(lp_token.as_str())?.amount);
+    }
+
+    query_supply(querier, lp_token)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::contract::compute_swap;
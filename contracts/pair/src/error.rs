@@ -52,6 +52,12 @@ pub enum ContractError {
 
     #[error("Asset balances tracking is already enabled")]
     AssetBalancesTrackingIsAlreadyEnabled {},
+
+    #[error("Only the token factory LP tokens of the pair must be sent")]
+    InvalidLpFunds {},
+
+    #[error("Token factory LP tokens are not supported by this build of the pair")]
+    TokenFactoryNotSupported {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -11,9 +11,13 @@ use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::factory::PairType;
 use astroport::pair::{
     Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse, TWAP_PRECISION,
+    SimulationResponse, XYKPoolParams, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+#[cfg(feature = "token-factory")]
+use astroport::token_factory::{tf_burn_msg, tf_create_denom_msg, tf_denom, LP_SUBDENOM};
+#[cfg(feature = "token-factory")]
+use cosmwasm_std::coin;
 
 use crate::contract::compute_offer_amount;
 use crate::contract::reply;
@@ -741,6 +745,111 @@ fn withdraw_liquidity() {
     );
 }
 
+#[cfg(not(feature = "token-factory"))]
+#[test]
+fn token_factory_lp_requires_feature() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(
+            to_binary(&XYKPoolParams {
+                track_asset_balances: None,
+                token_factory_lp: Some(true),
+            })
+            .unwrap(),
+        ),
+    };
+
+    let err = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(err, ContractError::TokenFactoryNotSupported {});
+}
+
+#[cfg(feature = "token-factory")]
+#[test]
+fn withdraw_token_factory_liquidity() {
+    let lp_denom = tf_denom(&Addr::unchecked(MOCK_CONTRACT_ADDR), LP_SUBDENOM);
+
+    // LP tokens are already sent to the pair along with the withdraw message
+    let mut deps = mock_dependencies(&[coin(100, "uusd"), coin(100, &lp_denom)]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(100u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: Some(
+            to_binary(&XYKPoolParams {
+                track_asset_balances: None,
+                token_factory_lp: Some(true),
+            })
+            .unwrap(),
+        ),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(tf_create_denom_msg(
+            &env.contract.address,
+            LP_SUBDENOM
+        ))]
+    );
+
+    let pair_info = CONFIG.load(deps.as_ref().storage).unwrap().pair_info;
+    assert_eq!(pair_info.liquidity_token, lp_denom);
+
+    let msg = ExecuteMsg::WithdrawLiquidity { assets: vec![] };
+
+    let info = mock_info("addr0000", &[coin(100, "uusd")]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::InvalidLpFunds {});
+
+    let info = mock_info("addr0000", &[coin(100, &lp_denom)]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![coin(100, "uusd")],
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0000"),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            SubMsg::new(tf_burn_msg(&env.contract.address, &coin(100, &lp_denom))),
+        ]
+    );
+}
+
 #[test]
 fn try_native_to_token() {
     let total_share = Uint128::new(30000000000u128);
@@ -1,15 +1,16 @@
-use astroport::asset::{native_asset_info, Asset, AssetInfo, PairInfo};
+use astroport::asset::{native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo};
 use astroport::factory::{
     ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
     QueryMsg as FactoryQueryMsg,
 };
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams, TWAP_PRECISION,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, DepthLevel, ExecuteMsg, InstantiateMsg,
+    QueryMsg, SimulationResponse, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
+    TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_pair::error::ContractError;
-use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, Uint128};
+use cosmwasm_std::{attr, to_binary, Addr, Coin, Decimal, Fraction, Uint128};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{App, ContractWrapper, Executor};
 
@@ -1097,6 +1098,7 @@ fn asset_balances_tracking_works_correctly() {
         init_params: Some(
             to_binary(&XYKPoolParams {
                 track_asset_balances: Some(true),
+                token_factory_lp: None,
             })
             .unwrap(),
         ),
@@ -1496,3 +1498,127 @@ fn update_pair_config() {
         }
     );
 }
+
+#[test]
+fn query_depth() {
+    let owner = Addr::unchecked("owner");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    // Depth of an empty pool can't be calculated
+    let uluna = native_asset_info("uluna".to_string());
+    let err = app
+        .wrap()
+        .query_wasm_smart::<Vec<DepthLevel>>(
+            &pair_instance,
+            &QueryMsg::Depth {
+                offer_asset_info: uluna.clone(),
+                price_impacts: vec![Decimal::percent(1)],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: One of the pools is empty"
+    );
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1_000_000_000000),
+        Uint128::new(1_000_000_000000),
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let price_impacts = vec![
+        Decimal::permille(1),
+        Decimal::percent(1),
+        Decimal::percent(10),
+    ];
+    let levels: Vec<DepthLevel> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::Depth {
+                offer_asset_info: uluna.clone(),
+                price_impacts: price_impacts.clone(),
+            },
+        )
+        .unwrap();
+
+    assert_eq!(levels.len(), price_impacts.len());
+    for (level, price_impact) in levels.iter().zip(&price_impacts) {
+        assert_eq!(level.price_impact, *price_impact);
+
+        // Depth levels are consistent with the regular simulation
+        let sim: SimulationResponse = app
+            .wrap()
+            .query_wasm_smart(
+                &pair_instance,
+                &QueryMsg::Simulation {
+                    offer_asset: uluna.with_balance(level.offer_amount),
+                    ask_asset_info: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(sim.return_amount, level.return_amount);
+
+        // Without fees the XYK price impact of offering x is x / (offer_pool + x),
+        // so the level is reached at offer_pool * impact / (1 - impact)
+        let expected = Uint128::new(1_000_000_000000).multiply_ratio(
+            price_impact.numerator(),
+            (Decimal::one() - *price_impact).numerator(),
+        );
+        let diff = expected.max(level.offer_amount) - expected.min(level.offer_amount);
+        assert!(
+            diff <= expected / Uint128::new(1000),
+            "offer amount {} is too far from {expected}",
+            level.offer_amount
+        );
+    }
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<Vec<DepthLevel>>(
+            &pair_instance,
+            &QueryMsg::Depth {
+                offer_asset_info: uluna,
+                price_impacts: vec![Decimal::one()],
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Price impact must be within (0, 1)"
+    );
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<Vec<DepthLevel>>(
+            &pair_instance,
+            &QueryMsg::Depth {
+                offer_asset_info: native_asset_info("random".to_string()),
+                price_impacts,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Given offer asset does not belong in the pair"
+    );
+}
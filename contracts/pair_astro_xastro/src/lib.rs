@@ -9,7 +9,7 @@ use astroport::pair_bonded::{ExecuteMsg, QueryMsg};
 use astroport_pair_bonded::base::PairBonded;
 use astroport_pair_bonded::error::ContractError;
 use cosmwasm_std::{
-    entry_point, from_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    entry_point, from_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
 };
 use cw2::{get_contract_version, set_contract_version};
 
@@ -45,6 +45,13 @@ pub fn execute(
     contract.execute(deps, env, info, msg)
 }
 
+/// The entry point to the contract for processing replies from submessages.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let contract = Contract::new("params");
+    contract.reply(deps, env, msg)
+}
+
 /// Exposes all the queries available in the contract via a pair-bonded template.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
@@ -1,3 +1,4 @@
+use astroport::pair_bonded::LpModeParams;
 use astroport_pair_bonded::error::ContractError;
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{Addr, Api};
@@ -11,6 +12,8 @@ pub struct Params {
     pub xastro_addr: Addr,
     /// Astroport Staking contract address.
     pub staking_addr: Addr,
+    /// LP mode parameters. If set, the pair performs conversions from its own reserves.
+    pub lp_mode: Option<LpModeParams>,
 }
 
 /// This structure stores a ASTRO-xASTRO pool's init params.
@@ -22,6 +25,8 @@ pub struct InitParams {
     pub xastro_addr: String,
     /// Astroport Staking contract address.
     pub staking_addr: String,
+    /// LP mode parameters. If set, the pair performs conversions from its own reserves.
+    pub lp_mode: Option<LpModeParams>,
 }
 
 impl InitParams {
@@ -30,6 +35,7 @@ impl InitParams {
             astro_addr: api.addr_validate(&self.astro_addr)?,
             xastro_addr: api.addr_validate(&self.xastro_addr)?,
             staking_addr: api.addr_validate(&self.staking_addr)?,
+            lp_mode: self.lp_mode,
         })
     }
 }
@@ -1,18 +1,22 @@
+use anyhow::Result as AnyResult;
 use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::factory::{InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType};
 use astroport::pair::{
-    ConfigResponse, Cw20HookMsg, InstantiateMsg as PairInstantiateMsg, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, InstantiateMsg as PairInstantiateMsg,
+    ReverseSimulationResponse, SimulationResponse,
 };
 use astroport::staking::{
     ConfigResponse as StakingConfigResponse, InstantiateMsg as StakingInstantiateMsg,
     QueryMsg as StakingQueryMsg,
 };
 
-use astroport::pair_bonded::{ExecuteMsg, QueryMsg};
+use astroport::pair::PoolResponse;
+use astroport::pair_bonded::{ExecuteMsg, LpModeParams, QueryMsg};
+use astroport::pair_concentrated::OracleObservation;
+use astroport::staking::Cw20HookMsg as StakingCw20HookMsg;
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_pair_astro_xastro::state::Params;
-use cosmwasm_std::{to_binary, Addr, Coin, Uint128};
+use cosmwasm_std::{to_binary, Addr, Coin, Decimal, Uint128};
 use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{App, ContractWrapper, Executor};
 
@@ -21,6 +25,7 @@ struct AstroportContracts {
     pair_instance: Addr,
     astro_instance: Addr,
     xastro_instance: Addr,
+    staking_instance: Addr,
 }
 
 fn mock_app(owner: Addr, coins: Vec<Coin>) -> App {
@@ -28,11 +33,14 @@ fn mock_app(owner: Addr, coins: Vec<Coin>) -> App {
 }
 
 fn store_pair_code(app: &mut App) -> u64 {
-    let pair_contract = Box::new(ContractWrapper::new_with_empty(
-        astroport_pair_astro_xastro::execute,
-        astroport_pair_astro_xastro::instantiate,
-        astroport_pair_astro_xastro::query,
-    ));
+    let pair_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_astro_xastro::execute,
+            astroport_pair_astro_xastro::instantiate,
+            astroport_pair_astro_xastro::query,
+        )
+        .with_reply_empty(astroport_pair_astro_xastro::reply),
+    );
 
     app.store_code(pair_contract)
 }
@@ -167,8 +175,17 @@ fn instantiate_staking(app: &mut App, owner: Addr, token_instance: &Addr) -> (Ad
     (staking_instance, resp.share_token_addr)
 }
 
-fn instantiate_astroport(mut router: &mut App, owner: &Addr) -> AstroportContracts {
+fn instantiate_astroport(router: &mut App, owner: &Addr) -> AstroportContracts {
+    instantiate_astroport_with_lp_mode(router, owner, None).unwrap()
+}
+
+fn instantiate_astroport_with_lp_mode(
+    mut router: &mut App,
+    owner: &Addr,
+    lp_mode: Option<LpModeParams>,
+) -> AnyResult<AstroportContracts> {
     let pair_code_id = store_pair_code(&mut router);
+    let lp_token_code_id = store_astro_code(&mut router);
 
     let factory_instance = instantiate_factory_contract(router, owner.clone(), pair_code_id);
     let token_instance = instantiate_token(router, owner.clone());
@@ -185,35 +202,35 @@ fn instantiate_astroport(mut router: &mut App, owner: &Addr) -> AstroportContrac
                 contract_addr: xastro_instance.clone(),
             },
         ],
-        token_code_id: 123,
+        token_code_id: lp_token_code_id,
         factory_addr: factory_instance.to_string(),
         init_params: Some(
             to_binary(&Params {
                 astro_addr: token_instance.clone(),
                 xastro_addr: xastro_instance.clone(),
                 staking_addr: staking_instance.clone(),
+                lp_mode,
             })
             .unwrap(),
         ),
     };
 
-    let pair_instance = router
-        .instantiate_contract(
-            pair_code_id,
-            owner.clone(),
-            &msg,
-            &[],
-            String::from("ASTRO-xASTRO pair"),
-            None,
-        )
-        .unwrap();
+    let pair_instance = router.instantiate_contract(
+        pair_code_id,
+        owner.clone(),
+        &msg,
+        &[],
+        String::from("ASTRO-xASTRO pair"),
+        None,
+    )?;
 
-    AstroportContracts {
+    Ok(AstroportContracts {
         pair_instance,
         astro_instance: token_instance,
         xastro_instance,
         factory_instance,
-    }
+        staking_instance,
+    })
 }
 
 fn mint_tokens(router: &mut App, owner: Addr, token_addr: Addr, amount: Uint128, to: Addr) {
@@ -303,6 +320,7 @@ fn test_pair_instantiation() {
                 astro_addr: token_instance.clone(),
                 xastro_addr: xastro_instance.clone(),
                 staking_addr: staking_instance.clone(),
+                lp_mode: None,
             })
             .unwrap(),
         ),
@@ -686,7 +704,7 @@ fn test_queries() {
     assert_eq!(
         res,
         ConfigResponse {
-            block_time_last: 0u64,
+            block_time_last: router.block_info().time.seconds(),
             params: None,
             owner,
             factory_addr: contracts.factory_instance
@@ -714,3 +732,392 @@ fn test_queries() {
         }
     );
 }
+
+#[test]
+fn test_lp_mode() {
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+
+    let mut router = mock_app(owner.clone(), vec![]);
+
+    let err = instantiate_astroport_with_lp_mode(
+        &mut router,
+        &owner,
+        Some(LpModeParams {
+            spread: Decimal::percent(20),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "LP mode spread must not exceed 0.1"
+    );
+
+    let contracts = instantiate_astroport_with_lp_mode(
+        &mut router,
+        &owner,
+        Some(LpModeParams {
+            spread: Decimal::percent(1),
+        }),
+    )
+    .unwrap();
+
+    let pair_info: PairInfo = router
+        .wrap()
+        .query_wasm_smart(&contracts.pair_instance, &QueryMsg::Pair {})
+        .unwrap();
+    let lp_token = pair_info.liquidity_token;
+    assert_ne!(lp_token, Addr::unchecked(""));
+
+    // Get xASTRO from the staking contract at 1:1 rate
+    mint_tokens(
+        &mut router,
+        owner.clone(),
+        contracts.astro_instance.clone(),
+        Uint128::from(200_000u64),
+        user.clone(),
+    );
+    router
+        .execute_contract(
+            user.clone(),
+            contracts.astro_instance.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.staking_instance.to_string(),
+                amount: Uint128::from(100_000u64),
+                msg: to_binary(&StakingCw20HookMsg::Enter {}).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    for token in [&contracts.astro_instance, &contracts.xastro_instance] {
+        router
+            .execute_contract(
+                user.clone(),
+                token.clone(),
+                &Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: contracts.pair_instance.to_string(),
+                    amount: Uint128::from(50_000u64),
+                    expires: None,
+                },
+                &[],
+            )
+            .unwrap();
+    }
+
+    let provide_msg = ExecuteMsg::ProvideLiquidity {
+        assets: [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: contracts.astro_instance.clone(),
+                },
+                amount: Uint128::from(50_000u64),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: contracts.xastro_instance.clone(),
+                },
+                amount: Uint128::from(50_000u64),
+            },
+        ],
+        slippage_tolerance: None,
+        auto_stake: None,
+        receiver: None,
+    };
+    router
+        .execute_contract(
+            user.clone(),
+            contracts.pair_instance.clone(),
+            &provide_msg,
+            &[],
+        )
+        .unwrap();
+
+    // Initial share is the deposit value in ASTRO minus the locked minimum liquidity
+    assert_user_balance(&mut router, &lp_token, &user, 99_000);
+
+    let res: PoolResponse = router
+        .wrap()
+        .query_wasm_smart(&contracts.pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.total_share, Uint128::from(100_000u64));
+    assert_eq!(res.assets[0].amount, Uint128::from(50_000u64));
+    assert_eq!(res.assets[1].amount, Uint128::from(50_000u64));
+
+    // LP spread is returned as the commission
+    let res: SimulationResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &contracts.pair_instance,
+            &QueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contracts.astro_instance.clone(),
+                    },
+                    amount: Uint128::from(10_000u64),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        SimulationResponse {
+            return_amount: Uint128::from(9_900u64),
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::from(100u64)
+        }
+    );
+    let res: ReverseSimulationResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &contracts.pair_instance,
+            &QueryMsg::ReverseSimulation {
+                ask_asset: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contracts.xastro_instance.clone(),
+                    },
+                    amount: Uint128::from(9_900u64),
+                },
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        ReverseSimulationResponse {
+            offer_amount: Uint128::from(10_000u64),
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::from(100u64)
+        }
+    );
+
+    // Swap ASTRO->xASTRO from the pool reserves
+    router
+        .execute_contract(
+            user.clone(),
+            contracts.astro_instance.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.pair_instance.to_string(),
+                amount: Uint128::from(10_000u64),
+                msg: to_binary(&Cw20HookMsg::Swap {
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_user_balance(&mut router, &contracts.astro_instance, &user, 40_000);
+    assert_user_balance(&mut router, &contracts.xastro_instance, &user, 59_900);
+
+    // Staking contract is not involved in LP mode
+    assert_user_balance(
+        &mut router,
+        &contracts.astro_instance,
+        &contracts.staking_instance,
+        100_000,
+    );
+
+    let res: Vec<Asset> = router
+        .wrap()
+        .query_wasm_smart(
+            &contracts.pair_instance,
+            &QueryMsg::Share {
+                amount: Uint128::from(50_000u64),
+            },
+        )
+        .unwrap();
+    assert_eq!(res[0].amount, Uint128::from(30_000u64));
+    assert_eq!(res[1].amount, Uint128::from(20_050u64));
+
+    // Withdraw all user liquidity
+    router
+        .execute_contract(
+            user.clone(),
+            lp_token.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.pair_instance.to_string(),
+                amount: Uint128::from(99_000u64),
+                msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] }).unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+    assert_user_balance(&mut router, &lp_token, &user, 0);
+    assert_user_balance(&mut router, &contracts.astro_instance, &user, 99_400);
+    assert_user_balance(&mut router, &contracts.xastro_instance, &user, 99_599);
+
+    // The pool doesn't have enough xASTRO left
+    let err = router
+        .execute_contract(
+            user.clone(),
+            contracts.astro_instance.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.pair_instance.to_string(),
+                amount: Uint128::from(1_000u64),
+                msg: to_binary(&Cw20HookMsg::Swap {
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Not enough {} in the pool to perform the swap",
+            contracts.xastro_instance
+        )
+    );
+}
+
+#[test]
+fn test_cumulative_prices() {
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+
+    let mut router = mock_app(owner.clone(), vec![]);
+
+    let contracts = instantiate_astroport(&mut router, &owner);
+    let start_time = router.block_info().time.seconds();
+
+    mint_tokens(
+        &mut router,
+        owner.clone(),
+        contracts.astro_instance.clone(),
+        Uint128::from(10_000u64),
+        user.clone(),
+    );
+
+    // The staking is empty, so the rate is 1:1
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(100);
+        block.height += 1;
+    });
+    router
+        .execute_contract(
+            user.clone(),
+            contracts.astro_instance.clone(),
+            &Cw20ExecuteMsg::Send {
+                contract: contracts.pair_instance.to_string(),
+                amount: Uint128::from(10_000u64),
+                msg: to_binary(&Cw20HookMsg::Swap {
+                    ask_asset_info: None,
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Staking rewards double the ASTRO per xASTRO rate
+    mint_tokens(
+        &mut router,
+        owner.clone(),
+        contracts.astro_instance.clone(),
+        Uint128::from(10_000u64),
+        contracts.staking_instance.clone(),
+    );
+
+    // Anyone can accumulate the rate without swapping
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(100);
+        block.height += 1;
+    });
+    router
+        .execute_contract(
+            user.clone(),
+            contracts.pair_instance.clone(),
+            &ExecuteMsg::Update {},
+            &[],
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&contracts.pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.block_time_last, start_time + 200);
+
+    // Prices are accumulated up to the current block
+    router.update_block(|block| {
+        block.time = block.time.plus_seconds(100);
+        block.height += 1;
+    });
+    let res: CumulativePricesResponse = router
+        .wrap()
+        .query_wasm_smart(&contracts.pair_instance, &QueryMsg::CumulativePrices {})
+        .unwrap();
+    let astro = AssetInfo::Token {
+        contract_addr: contracts.astro_instance.clone(),
+    };
+    let xastro = AssetInfo::Token {
+        contract_addr: contracts.xastro_instance.clone(),
+    };
+    assert_eq!(
+        res.cumulative_prices,
+        vec![
+            (
+                astro.clone(),
+                xastro.clone(),
+                Uint128::from(200_000_000u128)
+            ),
+            (xastro, astro, Uint128::from(500_000_000u128)),
+        ]
+    );
+
+    let res: OracleObservation = router
+        .wrap()
+        .query_wasm_smart(
+            &contracts.pair_instance,
+            &QueryMsg::Observe { seconds_ago: 0 },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        OracleObservation {
+            timestamp: start_time + 300,
+            price: Decimal::from_ratio(2u8, 1u8),
+        }
+    );
+
+    // The rate between observations is interpolated
+    let res: OracleObservation = router
+        .wrap()
+        .query_wasm_smart(
+            &contracts.pair_instance,
+            &QueryMsg::Observe { seconds_ago: 150 },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        OracleObservation {
+            timestamp: start_time + 150,
+            price: Decimal::from_ratio(3u8, 2u8),
+        }
+    );
+
+    let err = router
+        .wrap()
+        .query_wasm_smart::<OracleObservation>(
+            &contracts.pair_instance,
+            &QueryMsg::Observe { seconds_ago: 250 },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Generic error: Querier contract error: Generic error: Requested observation is too old. Last known observation is at {}",
+            start_time + 100
+        )
+    );
+}
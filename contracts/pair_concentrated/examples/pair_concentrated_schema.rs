@@ -1,5 +1,5 @@
-use astroport::pair::{ExecuteMsg, InstantiateMsg, MigrateMsg};
-use astroport::pair_concentrated::QueryMsg;
+use astroport::pair::{InstantiateMsg, MigrateMsg};
+use astroport::pair_concentrated::{ExecuteMsg, QueryMsg};
 use cosmwasm_schema::write_api;
 
 fn main() {
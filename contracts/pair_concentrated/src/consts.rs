@@ -26,6 +26,8 @@ pub const TWO: Decimal256 = Decimal256::raw(2000000000000000000);
 pub const MAX_ITER: usize = 64;
 /// TWAP constant for external oracle prices
 pub const TWAP_PRECISION_DEC: Decimal256 = Decimal256::raw((1e6 * 1e18) as u128);
+/// Observations buffer size
+pub const OBSERVATIONS_SIZE: u32 = 3000;
 
 /// ## Validation constants
 /// 0.001
@@ -46,6 +48,9 @@ pub const REPEG_PROFIT_THRESHOLD_MAX: Decimal = Decimal::raw(10000000000000000);
 pub const PRICE_SCALE_DELTA_MIN: Decimal = Decimal::raw(10000000);
 pub const PRICE_SCALE_DELTA_MAX: Decimal = Decimal::one();
 
+/// 0.5
+pub const MAX_INCENTIVIZER_FEE_SHARE: Decimal = Decimal::raw(500000000000000000);
+
 pub const MA_HALF_TIME_LIMITS: RangeInclusive<u64> = 1..=(7 * 86400);
 
 /// 0.1
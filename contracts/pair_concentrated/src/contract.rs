@@ -17,27 +17,30 @@ use astroport::asset::{
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport::factory::PairType;
-use astroport::pair::{Cw20HookMsg, ExecuteMsg, InstantiateMsg};
+use astroport::observation::accumulate_swap_sizes;
+use astroport::pair::{Cw20HookMsg, InstantiateMsg};
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, MigrateMsg, UpdatePoolParams,
+    ConcentratedPoolParams, ConcentratedPoolUpdateParams, ExecuteMsg, MigrateMsg, UpdatePoolParams,
 };
 use astroport::querier::{query_factory_config, query_fee_info, query_supply};
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use astroport_circular_buffer::BufferManager;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
+use crate::consts::OBSERVATIONS_SIZE;
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp};
 use crate::migration::{migrate_config, migrate_config_from_v140};
 use crate::state::{
-    store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState, BALANCES,
-    CONFIG, OWNERSHIP_PROPOSAL,
+    store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState,
+    ACCRUED_FEES, BALANCES, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL,
 };
 use crate::utils::{
     accumulate_prices, assert_max_spread, assert_slippage_tolerance, before_swap_check,
     calc_last_prices, calc_provide_fee, check_asset_infos, check_assets, check_cw20_in_pool,
     check_pair_registered, compute_swap, get_share_in_assets, mint_liquidity_token_message,
-    query_pools,
+    query_pool_balances, query_pools, settle_accrued_fees,
 };
 
 /// Contract name that is used for migration.
@@ -100,6 +103,9 @@ pub fn instantiate(
         repeg_profit_threshold: Some(params.repeg_profit_threshold),
         min_price_scale_delta: Some(params.min_price_scale_delta),
         ma_half_time: Some(params.ma_half_time),
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
     })?;
 
     let pool_state = PoolState {
@@ -131,6 +137,8 @@ pub fn instantiate(
         pool_state,
         owner: None,
         track_asset_balances: params.track_asset_balances.unwrap_or_default(),
+        incentivizer: None,
+        incentivizer_fee_share: Decimal::zero(),
     };
 
     if config.track_asset_balances {
@@ -141,6 +149,8 @@ pub fn instantiate(
 
     CONFIG.save(deps.storage, &config)?;
 
+    BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+
     let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
 
     // Create LP token
@@ -225,6 +235,8 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             max_spread,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::ClaimFees {}** Sends all fees accrued for the pool incentivizer to its address.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -316,6 +328,7 @@ pub fn execute(
             })
             .map_err(Into::into)
         }
+        ExecuteMsg::ClaimFees {} => claim_fees(deps),
     }
 }
 
@@ -423,7 +436,13 @@ pub fn provide_liquidity(
         .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
 
     let precisions = Precisions::new(deps.storage)?;
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let mut pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
 
     if pools[0].info.equal(&assets[1].info) {
         assets.swap(0, 1);
@@ -620,6 +639,7 @@ fn withdraw_liquidity(
     let precisions = Precisions::new(deps.storage)?;
     let pools = query_pools(
         deps.querier,
+        deps.storage,
         &config.pair_info.contract_addr,
         &config,
         &precisions,
@@ -732,7 +752,13 @@ fn swap(
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
     let mut config = CONFIG.load(deps.storage)?;
 
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let mut pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
 
     let (offer_ind, _) = pools
         .iter()
@@ -767,8 +793,12 @@ fn swap(
         &env,
         maker_fee_share,
     )?;
+    // A share of LP fees is accrued for the pool incentivizer
+    let incentivizer_fee =
+        (swap_result.total_fee - swap_result.maker_fee) * config.incentivizer_fee_share();
+
     xs[offer_ind] += offer_asset_dec.amount;
-    xs[ask_ind] -= swap_result.dy + swap_result.maker_fee;
+    xs[ask_ind] -= swap_result.dy + swap_result.maker_fee + incentivizer_fee;
 
     assert_max_spread(
         belief_price,
@@ -811,8 +841,23 @@ fn swap(
         }
     }
 
+    let incentivizer_fee = incentivizer_fee.to_uint(ask_asset_prec)?;
+    if !incentivizer_fee.is_zero() {
+        ACCRUED_FEES.update::<_, StdError>(deps.storage, &pools[ask_ind].info, |accrued| {
+            Ok(accrued.unwrap_or_default() + incentivizer_fee)
+        })?;
+    }
+
     accumulate_prices(&env, &mut config, old_real_price);
 
+    // Store observation from this swap
+    let (base_amount, quote_amount) = if offer_ind == 0 {
+        (offer_asset.amount, return_amount)
+    } else {
+        (return_amount, offer_asset.amount)
+    };
+    accumulate_swap_sizes(deps.storage, &env, OBSERVATIONS, base_amount, quote_amount)?;
+
     CONFIG.save(deps.storage, &config)?;
 
     if config.track_asset_balances {
@@ -825,7 +870,10 @@ fn swap(
         BALANCES.save(
             deps.storage,
             &pools[ask_ind].info,
-            &(pools[ask_ind].amount.to_uint(ask_asset_prec)? - return_amount - maker_fee),
+            &(pools[ask_ind].amount.to_uint(ask_asset_prec)?
+                - return_amount
+                - maker_fee
+                - incentivizer_fee),
             env.block.height,
         )?;
     }
@@ -844,6 +892,28 @@ fn swap(
             swap_result.total_fee.to_uint(ask_asset_prec)?,
         ),
         attr("maker_fee_amount", maker_fee),
+        attr("incentivizer_fee_amount", incentivizer_fee),
+    ]))
+}
+
+/// Sends all fees accrued for the pool incentivizer to its address.
+fn claim_fees(deps: DepsMut) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let incentivizer = config
+        .incentivizer
+        .ok_or(ContractError::IncentivizerIsNotSet {})?;
+
+    let (messages, claimed): (Vec<CosmosMsg>, _) =
+        settle_accrued_fees(deps.storage, &config.pair_info.asset_infos, &incentivizer)?;
+
+    if messages.is_empty() {
+        return Err(ContractError::NoAccruedFees {});
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "claim_fees"),
+        attr("incentivizer", incentivizer),
+        attr("claimed_fees", claimed.iter().join(", ")),
     ]))
 }
 
@@ -864,8 +934,27 @@ fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
+    let mut response = Response::new();
     let action = match from_binary::<ConcentratedPoolUpdateParams>(&params)? {
         ConcentratedPoolUpdateParams::Update(update_params) => {
+            let old_incentivizer = config.incentivizer.clone();
+            config.update_incentivizer(deps.api, &update_params)?;
+            // Fees accrued so far belong to the previous incentivizer
+            if let Some(old_incentivizer) = old_incentivizer {
+                if config.incentivizer.as_ref() != Some(&old_incentivizer) {
+                    let (messages, settled): (Vec<CosmosMsg>, _) = settle_accrued_fees(
+                        deps.storage,
+                        &config.pair_info.asset_infos,
+                        &old_incentivizer,
+                    )?;
+                    if !messages.is_empty() {
+                        response = response.add_messages(messages).add_attributes([
+                            attr("previous_incentivizer", old_incentivizer),
+                            attr("settled_fees", settled.iter().join(", ")),
+                        ]);
+                    }
+                }
+            }
             config.pool_params.update_params(update_params)?;
             "update_params"
         }
@@ -883,9 +972,12 @@ fn update_config(
             }
             config.track_asset_balances = true;
 
-            let pools = config
-                .pair_info
-                .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+            let pools = query_pool_balances(
+                deps.querier,
+                deps.storage,
+                &config.pair_info.contract_addr,
+                &config,
+            )?;
 
             for pool in pools.iter() {
                 BALANCES.save(deps.storage, &pool.info, &pool.amount, env.block.height)?;
@@ -896,7 +988,7 @@ fn update_config(
     };
     CONFIG.save(deps.storage, &config)?;
 
-    Ok(Response::new().add_attribute("action", action))
+    Ok(response.add_attribute("action", action))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -904,11 +996,17 @@ pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, C
     let contract_version = get_contract_version(deps.storage)?;
 
     match contract_version.contract.as_ref() {
-        "astroport-pair-concentrated" => match contract_version.version.as_ref() {
-            "1.0.0" | "1.1.0" | "1.1.1" | "1.1.2" => migrate_config(deps.storage)?,
-            "1.1.4" => migrate_config_from_v140(deps.storage)?,
-            _ => return Err(ContractError::MigrationError {}),
-        },
+        "astroport-pair-concentrated" => {
+            match contract_version.version.as_ref() {
+                "1.0.0" | "1.1.0" | "1.1.1" | "1.1.2" => migrate_config(deps.storage)?,
+                "1.1.4" => migrate_config_from_v140(deps.storage)?,
+                "1.2.0" => {}
+                _ => return Err(ContractError::MigrationError {}),
+            }
+
+            // Observations buffer was introduced in v1.3.0
+            BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+        }
         _ => return Err(ContractError::MigrationError {}),
     }
 
@@ -1,7 +1,7 @@
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::PairType;
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, StdError, Storage, Uint128};
+use cosmwasm_std::{Addr, Decimal, StdError, Storage, Uint128};
 use cw_storage_plus::Item;
 
 use crate::state::{Config, CONFIG};
@@ -71,6 +71,8 @@ pub(crate) fn migrate_config(storage: &mut dyn Storage) -> Result<(), StdError>
         pool_state: old_config.pool_state,
         owner: old_config.owner,
         track_asset_balances: false,
+        incentivizer: None,
+        incentivizer_fee_share: Decimal::zero(),
     };
 
     CONFIG.save(storage, &new_config)?;
@@ -112,6 +114,8 @@ pub(crate) fn migrate_config_from_v140(storage: &mut dyn Storage) -> Result<(),
         pool_state: old_config.pool_state,
         owner: old_config.owner,
         track_asset_balances: false,
+        incentivizer: None,
+        incentivizer_fee_share: Decimal::zero(),
     };
 
     CONFIG.save(storage, &new_config)?;
@@ -6,18 +6,22 @@ use cosmwasm_std::{
 use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo};
-use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
+use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
+use astroport::observation::query_observation;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    calc_depth, ConfigResponse, CumulativePricesResponse, DepthLevel, PoolResponse,
+    ReverseSimulationResponse, SimulationResponse,
+};
+use astroport::pair_concentrated::{
+    AccruedFeesResponse, ConcentratedPoolConfig, ConcentratedPoolUpdateParams, QueryMsg,
+    SimulateWithParamsResponse,
 };
-use astroport::pair_concentrated::{ConcentratedPoolConfig, QueryMsg};
 use astroport::querier::{query_factory_config, query_fee_info, query_supply};
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
 use crate::math::calc_d;
-use crate::state::{Precisions, BALANCES, CONFIG};
+use crate::state::{Precisions, ACCRUED_FEES, BALANCES, CONFIG, OBSERVATIONS};
 use crate::utils::{
     accumulate_prices, before_swap_check, calc_last_prices, compute_offer_amount, compute_swap,
     get_share_in_assets, pool_info, query_pools,
@@ -46,6 +50,18 @@ use crate::utils::{
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified
 /// asset that was in the pool just preceding the moment of the specified block height creation.
+///
+/// * **QueryMsg::Observe { seconds_ago }** Returns the price observation at the point that was
+/// `seconds_ago` seconds ago using an [`OracleObservation`](astroport::pair_concentrated::OracleObservation) object.
+///
+/// * **QueryMsg::SimulateWithParams { params, offer_asset }** Returns the result of a swap simulation
+/// with hypothetical pool parameters using a [`SimulateWithParamsResponse`] object.
+///
+/// * **QueryMsg::Depth { offer_asset_info, price_impacts }** Returns the largest offer amounts which keep
+/// the swap price impact within each of the given levels in a vector of [`DepthLevel`] objects.
+///
+/// * **QueryMsg::AccruedFees {}** Returns fees accrued for the pool incentivizer using an
+/// [`AccruedFeesResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -73,6 +89,27 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             asset_info,
             block_height,
         } => to_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::Observe { seconds_ago } => to_binary(&query_observation(
+            deps.storage,
+            env,
+            OBSERVATIONS,
+            seconds_ago,
+        )?),
+        QueryMsg::SimulateWithParams {
+            params,
+            offer_asset,
+        } => to_binary(
+            &query_simulate_with_params(deps, env, params, offer_asset)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?,
+        ),
+        QueryMsg::Depth {
+            offer_asset_info,
+            price_impacts,
+        } => to_binary(
+            &query_depth(deps, env, offer_asset_info, price_impacts)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?,
+        ),
+        QueryMsg::AccruedFees {} => to_binary(&query_accrued_fees(deps)?),
     }
 }
 
@@ -80,7 +117,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
 /// tokens currently minted in an object of type [`PoolResponse`].
 fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let (assets, total_share) = pool_info(deps.querier, deps.storage, &config)?;
 
     let resp = PoolResponse {
         assets,
@@ -99,6 +136,7 @@ fn query_share(deps: Deps, amount: Uint128) -> Result<Vec<Asset>, ContractError>
     let precisions = Precisions::new(deps.storage)?;
     let pools = query_pools(
         deps.querier,
+        deps.storage,
         &config.pair_info.contract_addr,
         &config,
         &precisions,
@@ -132,7 +170,13 @@ pub fn query_simulation(
     let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
     let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
 
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
 
     let (offer_ind, _) = pools
         .iter()
@@ -172,6 +216,186 @@ pub fn query_simulation(
     })
 }
 
+/// Returns the pool incentivizer, its share of LP fees and fees accrued since the last claim.
+fn query_accrued_fees(deps: Deps) -> StdResult<AccruedFeesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let fees = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .map(|asset_info| {
+            Ok(Asset {
+                info: asset_info.clone(),
+                amount: ACCRUED_FEES
+                    .may_load(deps.storage, asset_info)?
+                    .unwrap_or_default(),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AccruedFeesResponse {
+        incentivizer: config.incentivizer,
+        fee_share: config.incentivizer_fee_share,
+        fees,
+    })
+}
+
+/// Returns the largest offer amounts which keep the swap price impact within each of the given levels.
+/// Each level is found by binary search over swap simulations against the current pool state.
+///
+/// * **offer_asset_info** is the asset to swap from.
+///
+/// * **price_impacts** is the list of price impact levels.
+pub fn query_depth(
+    deps: Deps,
+    env: Env,
+    offer_asset_info: AssetInfo,
+    price_impacts: Vec<Decimal>,
+) -> Result<Vec<DepthLevel>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+
+    let pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
+
+    let (offer_ind, offer_pool) = pools
+        .iter()
+        .find_position(|asset| asset.info == offer_asset_info)
+        .ok_or_else(|| ContractError::InvalidAsset(offer_asset_info.to_string()))?;
+    let ask_ind = 1 - offer_ind;
+    let offer_asset_prec = precisions.get_precision(&offer_asset_info)?;
+    let ask_asset_prec = precisions.get_precision(&pools[ask_ind].info)?;
+
+    let xs = pools.iter().map(|asset| asset.amount).collect_vec();
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+    let mut maker_fee_share = Decimal256::zero();
+    if fee_info.fee_address.is_some() {
+        maker_fee_share = fee_info.maker_fee_rate.into();
+    }
+
+    let levels = calc_depth(
+        offer_pool.amount.to_uint(offer_asset_prec)?,
+        pools[ask_ind].amount.to_uint(ask_asset_prec)?,
+        &price_impacts,
+        |offer_amount| {
+            let offer_amount = offer_amount.to_decimal256(offer_asset_prec)?;
+            before_swap_check(&pools, offer_amount)?;
+
+            let swap_result =
+                compute_swap(&xs, offer_amount, ask_ind, &config, &env, maker_fee_share)?;
+
+            Ok(swap_result.dy.to_uint(ask_asset_prec)?)
+        },
+    )?;
+
+    Ok(levels)
+}
+
+/// Returns information about a swap simulation performed with hypothetical pool parameters.
+/// The parameters are validated the same way as in `update_config`.
+/// Promoted amp and gamma are considered as already reached.
+///
+/// * **params** pool parameters which would be applied.
+///
+/// * **offer_asset** proposed asset for swapping.
+pub fn query_simulate_with_params(
+    deps: Deps,
+    env: Env,
+    params: ConcentratedPoolUpdateParams,
+    offer_asset: Asset,
+) -> Result<SimulateWithParamsResponse, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    match params {
+        ConcentratedPoolUpdateParams::Update(update_params) => {
+            config.pool_params.update_params(update_params)?
+        }
+        ConcentratedPoolUpdateParams::Promote(promote_params) => {
+            config.pool_state.promote_params(&env, promote_params)?;
+            config.pool_state.initial = config.pool_state.future;
+            config.pool_state.future_time = env.block.time.seconds();
+        }
+        ConcentratedPoolUpdateParams::StopChangingAmpGamma {} => {
+            config.pool_state.stop_promotion(&env)
+        }
+        ConcentratedPoolUpdateParams::EnableAssetBalancesTracking {} => {}
+    }
+
+    let precisions = Precisions::new(deps.storage)?;
+    let offer_asset_prec = precisions.get_precision(&offer_asset.info)?;
+    let offer_asset_dec = offer_asset.to_decimal_asset(offer_asset_prec)?;
+
+    let pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
+
+    let (offer_ind, _) = pools
+        .iter()
+        .find_position(|asset| asset.info == offer_asset.info)
+        .ok_or_else(|| ContractError::InvalidAsset(offer_asset_dec.info.to_string()))?;
+    let ask_ind = 1 - offer_ind;
+    let ask_asset_prec = precisions.get_precision(&pools[ask_ind].info)?;
+
+    before_swap_check(&pools, offer_asset_dec.amount)?;
+
+    let mut xs = pools.iter().map(|asset| asset.amount).collect_vec();
+
+    // Get fee info from the factory
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+    let mut maker_fee_share = Decimal256::zero();
+    if fee_info.fee_address.is_some() {
+        maker_fee_share = fee_info.maker_fee_rate.into();
+    }
+
+    let swap_result = compute_swap(
+        &xs,
+        offer_asset_dec.amount,
+        ask_ind,
+        &config,
+        &env,
+        maker_fee_share,
+    )?;
+    xs[offer_ind] += offer_asset_dec.amount;
+    xs[ask_ind] -= swap_result.dy + swap_result.maker_fee;
+
+    let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?
+        .to_decimal256(LP_TOKEN_PRECISION)?;
+    let (last_price, _) = swap_result.calc_last_prices(offer_asset_dec.amount, offer_ind);
+
+    // update_price() works only with internal representation
+    xs[1] *= config.pool_state.price_state.price_scale;
+    config
+        .pool_state
+        .update_price(&config.pool_params, &env, total_share, &xs, last_price)?;
+
+    Ok(SimulateWithParamsResponse {
+        return_amount: swap_result.dy.to_uint(ask_asset_prec)?,
+        spread_amount: swap_result.spread_fee.to_uint(ask_asset_prec)?,
+        commission_amount: swap_result.total_fee.to_uint(ask_asset_prec)?,
+        maker_fee_amount: swap_result.maker_fee.to_uint(ask_asset_prec)?,
+        price_scale: config.pool_state.price_state.price_scale,
+    })
+}
+
 /// Returns information about a reverse swap simulation.
 pub fn query_reverse_simulation(
     deps: Deps,
@@ -183,7 +407,13 @@ pub fn query_reverse_simulation(
     let ask_asset_prec = precisions.get_precision(&ask_asset.info)?;
     let ask_asset_dec = ask_asset.to_decimal_asset(ask_asset_prec)?;
 
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
 
     let (ask_ind, _) = pools
         .iter()
@@ -210,14 +440,20 @@ fn query_cumulative_prices(
 ) -> Result<CumulativePricesResponse, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
     let precisions = Precisions::new(deps.storage)?;
-    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let pools = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )?;
 
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
     let (_, last_real_price) = calc_last_prices(&xs, &config, &env)?;
 
     accumulate_prices(&env, &mut config, last_real_price);
 
-    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let (assets, total_share) = pool_info(deps.querier, deps.storage, &config)?;
 
     Ok(CumulativePricesResponse {
         assets,
@@ -277,11 +513,17 @@ pub fn query_compute_d(deps: Deps, env: Env) -> StdResult<Decimal256> {
     let config = CONFIG.load(deps.storage)?;
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)
-        .map_err(|e| StdError::generic_err(e.to_string()))?
-        .into_iter()
-        .map(|a| a.amount)
-        .collect_vec();
+    let mut xs = query_pools(
+        deps.querier,
+        deps.storage,
+        &env.contract.address,
+        &config,
+        &precisions,
+    )
+    .map_err(|e| StdError::generic_err(e.to_string()))?
+    .into_iter()
+    .map(|a| a.amount)
+    .collect_vec();
 
     if xs[0].is_zero() || xs[1].is_zero() {
         return Err(StdError::generic_err("Pools are empty"));
@@ -303,3 +545,106 @@ pub fn query_asset_balances_at(
 ) -> StdResult<Option<Uint128>> {
     BALANCES.may_load_at_height(deps.storage, &asset_info, block_height.u64())
 }
+
+#[cfg(test)]
+mod testing {
+    use std::error::Error;
+    use std::str::FromStr;
+
+    use astroport::observation::Observation;
+    use astroport::pair_concentrated::OracleObservation;
+    use astroport_circular_buffer::BufferManager;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::Timestamp;
+
+    use super::*;
+
+    pub fn f64_to_dec<T>(val: f64) -> T
+    where
+        T: FromStr,
+        T::Err: Error,
+    {
+        T::from_str(&val.to_string()).unwrap()
+    }
+
+    #[test]
+    fn observations_full_buffer() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100_000);
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 20).unwrap();
+
+        let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+
+        let err = query_observation(&deps.storage, env.clone(), OBSERVATIONS, 11000).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Buffer is empty");
+
+        let array = (1..=30)
+            .map(|i| Observation {
+                timestamp: env.block.time.seconds() + i * 1000,
+                base_sma: Default::default(),
+                base_amount: i.into(),
+                quote_sma: Default::default(),
+                quote_amount: (i * i).into(),
+            })
+            .collect_vec();
+        buffer.push_many(&array);
+        buffer.commit(&mut deps.storage).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(30_000);
+
+        assert_eq!(
+            OracleObservation {
+                timestamp: 120_000,
+                price: f64_to_dec(20.0 / 400.0),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 10000).unwrap()
+        );
+
+        assert_eq!(
+            OracleObservation {
+                timestamp: 124_411,
+                price: f64_to_dec(0.04098166666666694),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 5589).unwrap()
+        );
+
+        let err = query_observation(&deps.storage, env, OBSERVATIONS, 35_000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Requested observation is too old. Last known observation is at 111000"
+        );
+    }
+
+    #[test]
+    fn observations_incomplete_buffer() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100_000);
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 3000).unwrap();
+
+        let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+
+        let array = (1..=30)
+            .map(|i| Observation {
+                timestamp: env.block.time.seconds() + i * 1000,
+                base_sma: Default::default(),
+                base_amount: i.into(),
+                quote_sma: Default::default(),
+                quote_amount: (i * i).into(),
+            })
+            .collect_vec();
+        buffer.push_many(&array);
+        buffer.commit(&mut deps.storage).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(30_000);
+
+        assert_eq!(
+            OracleObservation {
+                timestamp: 120_000,
+                price: f64_to_dec(20.0 / 400.0),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 10000).unwrap()
+        );
+    }
+}
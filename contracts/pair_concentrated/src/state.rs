@@ -2,19 +2,22 @@ use std::fmt::Display;
 
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::{
-    Addr, Decimal, Decimal256, DepsMut, Env, Order, StdError, StdResult, Storage, Uint128,
+    Addr, Api, Decimal, Decimal256, DepsMut, Env, Order, StdError, StdResult, Storage, Uint128,
 };
 use cw_storage_plus::{Item, Map, SnapshotMap};
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::common::OwnershipProposal;
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::observation::Observation;
 use astroport::pair_concentrated::{PromoteParams, UpdatePoolParams};
+use astroport_circular_buffer::CircularBuffer;
 
 use crate::consts::{
     AMP_MAX, AMP_MIN, FEE_GAMMA_MAX, FEE_GAMMA_MIN, FEE_TOL, GAMMA_MAX, GAMMA_MIN, MAX_CHANGE,
-    MAX_FEE, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME, MIN_FEE, N_POW2, PRICE_SCALE_DELTA_MAX,
-    PRICE_SCALE_DELTA_MIN, REPEG_PROFIT_THRESHOLD_MAX, REPEG_PROFIT_THRESHOLD_MIN, TWO,
+    MAX_FEE, MAX_INCENTIVIZER_FEE_SHARE, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME, MIN_FEE,
+    N_POW2, PRICE_SCALE_DELTA_MAX, PRICE_SCALE_DELTA_MIN, REPEG_PROFIT_THRESHOLD_MAX,
+    REPEG_PROFIT_THRESHOLD_MIN, TWO,
 };
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp, half_float_pow};
@@ -38,6 +41,50 @@ pub struct Config {
     pub owner: Option<Addr>,
     /// Whether asset balances are tracked over blocks or not.
     pub track_asset_balances: bool,
+    /// The pool incentivizer which accrues a share of LP fees
+    #[serde(default)]
+    pub incentivizer: Option<Addr>,
+    /// The share of LP fees accrued for the pool incentivizer
+    #[serde(default)]
+    pub incentivizer_fee_share: Decimal,
+}
+
+impl Config {
+    /// Updates the pool incentivizer and its share of LP fees. Performs validation of the new parameters.
+    ///
+    /// * `update_params` - an object which contains new pool parameters. Any of the parameters may be omitted.
+    pub fn update_incentivizer(
+        &mut self,
+        api: &dyn Api,
+        update_params: &UpdatePoolParams,
+    ) -> Result<(), ContractError> {
+        if update_params.remove_incentivizer == Some(true) {
+            self.incentivizer = None;
+        } else if let Some(incentivizer) = &update_params.incentivizer {
+            self.incentivizer = Some(api.addr_validate(incentivizer)?);
+        }
+
+        if let Some(fee_share) = update_params.incentivizer_fee_share {
+            validate_param(
+                "incentivizer_fee_share",
+                fee_share,
+                Decimal::zero(),
+                MAX_INCENTIVIZER_FEE_SHARE,
+            )?;
+            self.incentivizer_fee_share = fee_share;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the share of LP fees which should be accrued for the pool incentivizer.
+    pub fn incentivizer_fee_share(&self) -> Decimal256 {
+        if self.incentivizer.is_some() {
+            self.incentivizer_fee_share.into()
+        } else {
+            Decimal256::zero()
+        }
+    }
 }
 
 /// This structure stores the pool parameters which may be adjusted via the `update_pool_params`.
@@ -412,6 +459,9 @@ const PRECISIONS: Map<String, u8> = Map::new("precisions");
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
+/// Stores fees accrued for the pool incentivizer which are not claimed yet
+pub const ACCRUED_FEES: Map<&AssetInfo, Uint128> = Map::new("accrued_fees");
+
 /// Stores asset balances to query them later at any block height
 pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances",
@@ -420,6 +470,10 @@ pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     cw_storage_plus::Strategy::EveryBlock,
 );
 
+/// Circular buffer to store trade size observations
+pub const OBSERVATIONS: CircularBuffer<Observation> =
+    CircularBuffer::new("observations_state", "observations_buffer");
+
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
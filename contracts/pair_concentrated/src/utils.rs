@@ -1,6 +1,6 @@
 use cosmwasm_std::{
-    to_binary, wasm_execute, Addr, Api, CosmosMsg, Decimal, Decimal256, Env, Fraction,
-    QuerierWrapper, StdError, StdResult, Uint128,
+    to_binary, wasm_execute, Addr, Api, CosmosMsg, CustomMsg, Decimal, Decimal256, Env, Fraction,
+    QuerierWrapper, StdError, StdResult, Storage, Uint128,
 };
 use cw20::Cw20ExecuteMsg;
 use itertools::Itertools;
@@ -13,7 +13,7 @@ use astroport_factory::state::pair_key;
 use crate::consts::{DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, N, OFFER_PERCENT, TWAP_PRECISION_DEC};
 use crate::error::ContractError;
 use crate::math::{calc_d, calc_y};
-use crate::state::{Config, PoolParams, Precisions};
+use crate::state::{Config, PoolParams, Precisions, ACCRUED_FEES};
 
 /// Helper function to check the given asset infos are valid.
 pub(crate) fn check_asset_infos(
@@ -187,28 +187,75 @@ pub(crate) fn assert_max_spread(
 }
 
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
+/// Fees accrued for the pool incentivizer are not considered as pool assets.
 pub(crate) fn pool_info(
     querier: QuerierWrapper,
+    storage: &dyn Storage,
     config: &Config,
 ) -> StdResult<(Vec<Asset>, Uint128)> {
-    let pools = config
-        .pair_info
-        .query_pools(&querier, &config.pair_info.contract_addr)?;
+    let pools = query_pool_balances(querier, storage, &config.pair_info.contract_addr, config)?;
     let total_share = query_supply(&querier, &config.pair_info.liquidity_token)?;
 
     Ok((pools, total_share))
 }
 
+/// Returns the pool's asset balances excluding fees accrued for the pool incentivizer.
+pub(crate) fn query_pool_balances(
+    querier: QuerierWrapper,
+    storage: &dyn Storage,
+    addr: &Addr,
+    config: &Config,
+) -> StdResult<Vec<Asset>> {
+    config
+        .pair_info
+        .query_pools(&querier, addr)?
+        .into_iter()
+        .map(|mut asset| -> StdResult<Asset> {
+            let accrued_fee = ACCRUED_FEES
+                .may_load(storage, &asset.info)?
+                .unwrap_or_default();
+            asset.amount = asset.amount.checked_sub(accrued_fee)?;
+            Ok(asset)
+        })
+        .collect()
+}
+
+/// Removes all fees accrued for the pool incentivizer from the storage.
+/// Returns messages which send them to the `incentivizer` along with the list of settled fees.
+pub fn settle_accrued_fees<T: CustomMsg>(
+    storage: &mut dyn Storage,
+    asset_infos: &[AssetInfo],
+    incentivizer: &Addr,
+) -> StdResult<(Vec<CosmosMsg<T>>, Vec<Asset>)> {
+    let mut messages = vec![];
+    let mut settled = vec![];
+    for asset_info in asset_infos {
+        let amount = ACCRUED_FEES
+            .may_load(storage, asset_info)?
+            .unwrap_or_default();
+        if !amount.is_zero() {
+            ACCRUED_FEES.remove(storage, asset_info);
+            let fee = Asset {
+                info: asset_info.clone(),
+                amount,
+            };
+            messages.push(fee.clone().into_msg(incentivizer)?);
+            settled.push(fee);
+        }
+    }
+
+    Ok((messages, settled))
+}
+
 /// Returns current pool's volumes where amoint is in [`Decimal256`] form.
 pub(crate) fn query_pools(
     querier: QuerierWrapper,
+    storage: &dyn Storage,
     addr: &Addr,
     config: &Config,
     precisions: &Precisions,
 ) -> Result<Vec<DecimalAsset>, ContractError> {
-    config
-        .pair_info
-        .query_pools(&querier, addr)?
+    query_pool_balances(querier, storage, addr, config)?
         .into_iter()
         .map(|asset| {
             asset
@@ -16,11 +16,12 @@ use anyhow::Result as AnyResult;
 use astroport::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, PairInfo};
 use astroport::factory::{PairConfig, PairType};
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, ReverseSimulationResponse,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, DepthLevel, ReverseSimulationResponse,
     SimulationResponse,
 };
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, QueryMsg,
+    AccruedFeesResponse, ConcentratedPoolParams, ConcentratedPoolUpdateParams, ExecuteMsg,
+    QueryMsg, SimulateWithParamsResponse,
 };
 use astroport_pair_concentrated::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated::queries::query;
@@ -350,6 +351,49 @@ impl Helper {
         )
     }
 
+    pub fn simulate_with_params(
+        &self,
+        params: &ConcentratedPoolUpdateParams,
+        offer_asset: &Asset,
+    ) -> StdResult<SimulateWithParamsResponse> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::SimulateWithParams {
+                params: params.clone(),
+                offer_asset: offer_asset.clone(),
+            },
+        )
+    }
+
+    pub fn claim_fees(&mut self, sender: &Addr) -> AnyResult<AppResponse> {
+        self.app.execute_contract(
+            sender.clone(),
+            self.pair_addr.clone(),
+            &ExecuteMsg::ClaimFees {},
+            &[],
+        )
+    }
+
+    pub fn query_accrued_fees(&self) -> StdResult<AccruedFeesResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::AccruedFees {})
+    }
+
+    pub fn query_depth(
+        &self,
+        offer_asset_info: &AssetInfo,
+        price_impacts: Vec<Decimal>,
+    ) -> StdResult<Vec<DepthLevel>> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::Depth {
+                offer_asset_info: offer_asset_info.clone(),
+                price_impacts,
+            },
+        )
+    }
+
     pub fn simulate_reverse_swap(
         &self,
         ask_asset: &Asset,
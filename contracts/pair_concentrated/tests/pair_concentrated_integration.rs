@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, Decimal, Uint128};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
 
 use cw_multi_test::{next_block, Executor};
 use itertools::Itertools;
@@ -744,6 +744,9 @@ fn check_amp_gamma_change() {
         repeg_profit_threshold: None,
         min_price_scale_delta: None,
         ma_half_time: None,
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
     });
 
     let err = helper.update_config(&random_user, &action).unwrap_err();
@@ -1314,3 +1317,358 @@ fn provides_and_swaps_and_withdraw() {
 
     assert_eq!(res.total_share.u128(), 1000u128);
 }
+
+#[test]
+fn simulate_with_params() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+
+    // Simulation with unchanged parameters matches the usual simulation
+    let current = helper.simulate_swap(&offer_asset, None).unwrap();
+    let same_params = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
+    });
+    let resp = helper
+        .simulate_with_params(&same_params, &offer_asset)
+        .unwrap();
+    assert_eq!(resp.return_amount, current.return_amount);
+    assert_eq!(resp.commission_amount, current.commission_amount);
+    assert_eq!(resp.price_scale, Decimal256::one());
+
+    // Higher fees decrease return amount
+    let higher_fees = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.005)),
+        out_fee: Some(f64_to_dec(0.01)),
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
+    });
+    let resp = helper
+        .simulate_with_params(&higher_fees, &offer_asset)
+        .unwrap();
+    assert!(resp.return_amount < current.return_amount);
+    assert!(resp.commission_amount > current.commission_amount);
+
+    // Invalid parameters are rejected the same way as in update_config
+    let invalid = ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+        mid_fee: Some(f64_to_dec(0.9)),
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
+    });
+    helper
+        .simulate_with_params(&invalid, &offer_asset)
+        .unwrap_err();
+
+    // Promoted amp is considered as already reached
+    helper.app.next_block(86400);
+    let promote = ConcentratedPoolUpdateParams::Promote(PromoteParams {
+        next_amp: f64_to_dec(44f64),
+        next_gamma: f64_to_dec(0.000145),
+        future_time: helper.app.block_info().time.seconds() + 86400,
+    });
+    let resp = helper.simulate_with_params(&promote, &offer_asset).unwrap();
+    assert!(resp.return_amount > current.return_amount);
+
+    // Queries do not change the pool
+    assert_eq!(helper.simulate_swap(&offer_asset, None).unwrap(), current);
+}
+
+#[test]
+fn query_depth() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let offer_asset_info = helper.assets[&test_coins[0]].clone();
+    let price_impacts = vec![f64_to_dec(0.001), f64_to_dec(0.01), f64_to_dec(0.1)];
+    let levels = helper
+        .query_depth(&offer_asset_info, price_impacts.clone())
+        .unwrap();
+
+    assert_eq!(levels.len(), price_impacts.len());
+    for (level, price_impact) in levels.iter().zip(&price_impacts) {
+        assert_eq!(level.price_impact, *price_impact);
+        // Depth levels are consistent with the regular simulation
+        let sim = helper
+            .simulate_swap(&offer_asset_info.with_balance(level.offer_amount), None)
+            .unwrap();
+        assert_eq!(sim.return_amount, level.return_amount);
+    }
+    // Deeper price impact levels require larger offer amounts
+    assert!(levels
+        .windows(2)
+        .all(|pair| pair[0].offer_amount < pair[1].offer_amount));
+
+    // The maximum number of unsorted levels is answered in the given order without draining the pool
+    let price_impacts = [0.3, 0.001, 0.5, 0.01, 0.9, 0.05, 0.2, 0.005, 0.7, 0.1]
+        .into_iter()
+        .map(f64_to_dec)
+        .collect::<Vec<Decimal>>();
+    let levels = helper
+        .query_depth(&offer_asset_info, price_impacts.clone())
+        .unwrap();
+    assert_eq!(
+        levels
+            .iter()
+            .map(|level| level.price_impact)
+            .collect::<Vec<_>>(),
+        price_impacts
+    );
+    for level in &levels {
+        assert!(level.return_amount < Uint128::new(100_000_000000));
+        let sim = helper
+            .simulate_swap(&offer_asset_info.with_balance(level.offer_amount), None)
+            .unwrap();
+        assert_eq!(sim.return_amount, level.return_amount);
+    }
+    let mut sorted = levels;
+    sorted.sort_by_key(|level| level.price_impact);
+    assert!(sorted
+        .windows(2)
+        .all(|pair| pair[0].offer_amount < pair[1].offer_amount));
+
+    let err = helper
+        .query_depth(&offer_asset_info, vec![Decimal::one()])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Price impact must be within (0, 1)"
+    );
+
+    let err = helper
+        .query_depth(&native_asset_info("random".to_string()), price_impacts)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: The asset random does not belong to the pair"
+    );
+}
+
+#[test]
+fn incentivizer_fee_share() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: Decimal::one(),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let err = helper.claim_fees(&owner).unwrap_err();
+    assert_eq!(
+        ContractError::IncentivizerIsNotSet {},
+        err.downcast().unwrap()
+    );
+
+    let incentivizer = Addr::unchecked("incentivizer");
+    let mut update_params = UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+        incentivizer: Some(incentivizer.to_string()),
+        incentivizer_fee_share: Some(f64_to_dec(0.6)),
+        remove_incentivizer: None,
+    };
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::Update(update_params.clone()),
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncorrectPoolParam(
+            "incentivizer_fee_share".to_string(),
+            "0".to_string(),
+            "0.5".to_string()
+        ),
+        err.downcast().unwrap()
+    );
+
+    update_params.incentivizer_fee_share = Some(f64_to_dec(0.5));
+    helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::Update(update_params))
+        .unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let accrued = helper.query_accrued_fees().unwrap();
+    assert_eq!(accrued.incentivizer, Some(incentivizer.clone()));
+    assert_eq!(accrued.fee_share, f64_to_dec(0.5));
+    assert_eq!(accrued.fees[0].amount, Uint128::zero());
+    let accrued_fee = accrued.fees[1].amount;
+    assert!(!accrued_fee.is_zero());
+
+    // Accrued fees are not considered as pool liquidity
+    let pool: PoolResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(
+        pool.assets[1].amount + accrued_fee,
+        helper
+            .coin_balance(&test_coins[1], &helper.pair_addr)
+            .into()
+    );
+
+    // Anyone can trigger the claim, fees are always sent to the incentivizer
+    helper.claim_fees(&user).unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &incentivizer),
+        accrued_fee.u128()
+    );
+    let accrued = helper.query_accrued_fees().unwrap();
+    assert!(accrued.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    let err = helper.claim_fees(&user).unwrap_err();
+    assert_eq!(ContractError::NoAccruedFees {}, err.downcast().unwrap());
+
+    // Fees accrued so far are settled with the previous incentivizer when it is replaced
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let accrued_fee = helper.query_accrued_fees().unwrap().fees[1].amount;
+    let balance_before = helper.coin_balance(&test_coins[1], &incentivizer);
+
+    let new_incentivizer = Addr::unchecked("new_incentivizer");
+    let update_params = UpdatePoolParams {
+        mid_fee: None,
+        out_fee: None,
+        fee_gamma: None,
+        repeg_profit_threshold: None,
+        min_price_scale_delta: None,
+        ma_half_time: None,
+        incentivizer: Some(new_incentivizer.to_string()),
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
+    };
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::Update(update_params.clone()),
+        )
+        .unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &incentivizer),
+        balance_before + accrued_fee.u128()
+    );
+    let accrued = helper.query_accrued_fees().unwrap();
+    assert_eq!(accrued.incentivizer, Some(new_incentivizer.clone()));
+    assert!(accrued.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // The incentivizer can be unset. Its fees are settled as well
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let accrued_fee = helper.query_accrued_fees().unwrap().fees[1].amount;
+    assert!(!accrued_fee.is_zero());
+
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::Update(UpdatePoolParams {
+                remove_incentivizer: Some(true),
+                ..update_params
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &new_incentivizer),
+        accrued_fee.u128()
+    );
+    let accrued = helper.query_accrued_fees().unwrap();
+    assert_eq!(accrued.incentivizer, None);
+    assert!(accrued.fees.iter().all(|fee| fee.amount.is_zero()));
+
+    // No fees are accrued without the incentivizer
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+    let accrued = helper.query_accrued_fees().unwrap();
+    assert!(accrued.fees.iter().all(|fee| fee.amount.is_zero()));
+    let err = helper.claim_fees(&user).unwrap_err();
+    assert_eq!(
+        ContractError::IncentivizerIsNotSet {},
+        err.downcast().unwrap()
+    );
+}
@@ -19,6 +19,7 @@ use astroport::asset::{
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
 use astroport::factory::PairType;
+use astroport::orderbook::{record_fill, OrderbookVenue, FILLS, FILLS_SIZE};
 use astroport::pair::{Cw20HookMsg, InstantiateMsg};
 use astroport::pair_concentrated::UpdatePoolParams;
 use astroport::pair_concentrated_inj::{
@@ -30,10 +31,13 @@ use astroport_circular_buffer::BufferManager;
 
 use crate::error::ContractError;
 use crate::math::{calc_d, get_xcp};
-use crate::orderbook::state::OrderbookState;
+use crate::orderbook::state::{
+    init_orderbook_state, validate_ladder, validate_risk_limits, OrderbookState,
+};
 use crate::orderbook::utils::{
     get_subaccount_balances, is_contract_active, leave_orderbook, process_cumulative_trade,
 };
+use crate::orderbook::venue::InjectiveVenue;
 use crate::state::{
     store_precisions, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState, CONFIG,
     OBSERVATIONS, OWNERSHIP_PROPOSAL,
@@ -85,17 +89,16 @@ pub fn instantiate(
 
     store_precisions(deps.branch(), &msg.asset_infos, &factory_addr)?;
 
-    let ob_state = OrderbookState::new(
+    let ob_state = init_orderbook_state(
         deps.querier,
         &env,
-        &orderbook_params.orderbook_config.market_id,
-        orderbook_params.orderbook_config.orders_number,
-        orderbook_params.orderbook_config.min_trades_to_avg,
+        &orderbook_params.orderbook_config,
         &msg.asset_infos,
     )?;
     ob_state.save(deps.storage)?;
 
     BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+    BufferManager::init(deps.storage, FILLS, FILLS_SIZE)?;
 
     let mut pool_params = PoolParams::default();
     pool_params.update_params(UpdatePoolParams {
@@ -105,6 +108,9 @@ pub fn instantiate(
         repeg_profit_threshold: Some(params.repeg_profit_threshold),
         min_price_scale_delta: Some(params.min_price_scale_delta),
         ma_half_time: Some(params.ma_half_time),
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
     })?;
 
     let pool_state = PoolState {
@@ -436,26 +442,27 @@ where
     let mut xs = pools.iter().map(|asset| asset.amount).collect_vec();
 
     let mut messages = vec![];
-    let subacc_balances = get_subaccount_balances(
-        &config.pair_info.asset_infos,
-        &InjectiveQuerier::new(&deps.querier),
-        &ob_state.subaccount,
-    )?;
+    let venue = InjectiveVenue::new(
+        &env.contract.address,
+        &ob_state,
+        precisions.get_precision(&config.pair_info.asset_infos[0])?,
+        precisions.get_precision(&config.pair_info.asset_infos[1])?,
+    );
+    let subacc_balances = venue.query_balances(&deps.querier, &config.pair_info.asset_infos)?;
     // In case begin blocker logic wasn't executed, we need to update price and send maker fees
     if ob_state.last_balances != subacc_balances {
-        let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
-        let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, fill) = process_cumulative_trade(
             deps.querier,
             &env,
-            &ob_state,
+            &venue,
             &mut config,
             &mut xs,
             &subacc_balances,
-            base_asset_precision,
-            quote_asset_precision,
         )
         .map_err(StdError::from)?;
+        if let Some(fill) = fill {
+            record_fill(deps.storage, fill).map_err(StdError::from)?;
+        }
 
         ob_state.last_balances = subacc_balances;
 
@@ -752,26 +759,27 @@ where
 
     let mut messages = vec![];
 
-    let subacc_balances = get_subaccount_balances(
-        &config.pair_info.asset_infos,
-        &InjectiveQuerier::new(&deps.querier),
-        &ob_state.subaccount,
-    )?;
+    let venue = InjectiveVenue::new(
+        &env.contract.address,
+        &ob_state,
+        precisions.get_precision(&config.pair_info.asset_infos[0])?,
+        precisions.get_precision(&config.pair_info.asset_infos[1])?,
+    );
+    let subacc_balances = venue.query_balances(&deps.querier, &config.pair_info.asset_infos)?;
     // In case begin blocker logic wasn't executed, we need to update price and send maker fees
     if ob_state.last_balances != subacc_balances {
-        let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
-        let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, fill) = process_cumulative_trade(
             deps.querier,
             &env,
-            &ob_state,
+            &venue,
             &mut config,
             &mut xs,
             &subacc_balances,
-            base_asset_precision,
-            quote_asset_precision,
         )
         .map_err(StdError::from)?;
+        if let Some(fill) = fill {
+            record_fill(deps.storage, fill).map_err(StdError::from)?;
+        }
 
         ob_state.last_balances = subacc_balances;
 
@@ -878,6 +886,15 @@ fn update_config<T>(
 
     let action = match from_binary::<ConcentratedObPoolUpdateParams>(&params)? {
         ConcentratedObPoolUpdateParams::Update(update_params) => {
+            if update_params.incentivizer.is_some()
+                || update_params.incentivizer_fee_share.is_some()
+                || update_params.remove_incentivizer.is_some()
+            {
+                return Err(StdError::generic_err(
+                    "Pool incentivizer is not supported by this pair",
+                )
+                .into());
+            }
             config.pool_params.update_params(update_params)?;
             "update_params"
         }
@@ -891,10 +908,26 @@ fn update_config<T>(
         }
         ConcentratedObPoolUpdateParams::UpdateOrderbookParams { orders_number } => {
             let mut ob_config = OrderbookState::load(deps.storage)?;
+            validate_ladder(&ob_config.ladder, ob_config.spread, orders_number)?;
             ob_config.orders_number = orders_number;
             ob_config.save(deps.storage)?;
             "update_orderbook_params"
         }
+        ConcentratedObPoolUpdateParams::UpdateOrderLadder { ladder, spread } => {
+            let mut ob_config = OrderbookState::load(deps.storage)?;
+            validate_ladder(&ladder, spread, ob_config.orders_number)?;
+            ob_config.ladder = ladder;
+            ob_config.spread = spread;
+            ob_config.reconcile(deps.storage)?;
+            "update_order_ladder"
+        }
+        ConcentratedObPoolUpdateParams::UpdateRiskLimits(risk_limits) => {
+            let mut ob_config = OrderbookState::load(deps.storage)?;
+            validate_risk_limits(&risk_limits)?;
+            ob_config.risk_limits = risk_limits;
+            ob_config.reconcile(deps.storage)?;
+            "update_risk_limits"
+        }
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -918,7 +951,15 @@ pub fn orderbook_emergency_withdraw(
     }
 
     let ob_state = OrderbookState::load(deps.storage)?;
-    let balances = get_subaccount_balances(&ob_state.asset_infos, &querier, &ob_state.subaccount)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+    let venue = InjectiveVenue::new(
+        &env.contract.address,
+        &ob_state,
+        precisions.get_precision(&config.pair_info.asset_infos[0])?,
+        precisions.get_precision(&config.pair_info.asset_infos[1])?,
+    );
+    let balances = venue.query_balances(&deps.querier, &ob_state.asset_infos)?;
 
     let mut response = if !(balances[0].amount + balances[1].amount).is_zero() {
         leave_orderbook(&ob_state, balances.clone(), &env).map_err(StdError::from)?
@@ -927,8 +968,6 @@ pub fn orderbook_emergency_withdraw(
     };
 
     if ob_state.last_balances != balances {
-        let mut config = CONFIG.load(deps.storage)?;
-        let precisions = Precisions::new(deps.storage)?;
         let mut pools = query_pools(
             deps.querier,
             &env.contract.address,
@@ -940,19 +979,18 @@ pub fn orderbook_emergency_withdraw(
         .iter()
         .map(|asset| asset.amount)
         .collect_vec();
-        let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
-        let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
-        let maker_fee_message = process_cumulative_trade(
+        let (maker_fee_message, fill) = process_cumulative_trade(
             deps.querier,
             &env,
-            &ob_state,
+            &venue,
             &mut config,
             &mut pools,
             &balances,
-            base_asset_precision,
-            quote_asset_precision,
         )
         .map_err(StdError::from)?;
+        if let Some(fill) = fill {
+            record_fill(deps.storage, fill).map_err(StdError::from)?;
+        }
         CONFIG.save(deps.storage, &config)?;
 
         response = response.add_messages(maker_fee_message);
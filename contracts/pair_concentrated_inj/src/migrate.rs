@@ -1,21 +1,24 @@
 use astroport::asset::PairInfo;
 use astroport::factory::PairType;
+use astroport::orderbook::{FILLS, FILLS_SIZE};
 use cosmwasm_std::{entry_point, DepsMut, Env, Response, StdError, StdResult};
 use cw2::{set_contract_version, CONTRACT};
 use cw_storage_plus::Item;
 use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveQueryWrapper};
+use itertools::Itertools;
 
 use crate::consts::OBSERVATIONS_SIZE;
 use crate::contract::{CONTRACT_NAME, CONTRACT_VERSION};
-use crate::orderbook::state::OrderbookState;
+use crate::orderbook::state::init_orderbook_state;
 use astroport::pair_concentrated_inj::MigrateMsg;
 use astroport_circular_buffer::BufferManager;
-use astroport_pair_concentrated::state::Config as CLConfig;
+use astroport_pair_concentrated::state::{Config as CLConfig, ACCRUED_FEES};
+use astroport_pair_concentrated::utils::settle_accrued_fees;
 
 use crate::state::{AmpGamma, Config, PoolParams, PoolState, PriceState, CONFIG, OBSERVATIONS};
 
 const MIGRATE_FROM: &str = "astroport-pair-concentrated";
-const MIGRATION_VERSION: &str = "1.2.0";
+const MIGRATION_VERSIONS: &[&str] = &["1.2.0", "1.3.0"];
 
 /// Manages the contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -25,30 +28,45 @@ pub fn migrate(
     msg: MigrateMsg,
 ) -> StdResult<Response<InjectiveMsgWrapper>> {
     let mut attrs = vec![];
+    let mut messages = vec![];
+    let mut settled_fees = String::new();
 
     let contract_info = CONTRACT.load(deps.storage)?;
     match msg {
         MigrateMsg::MigrateToOrderbook { params } => {
-            if contract_info.contract != MIGRATE_FROM || contract_info.version != MIGRATION_VERSION
+            if contract_info.contract != MIGRATE_FROM
+                || !MIGRATION_VERSIONS.contains(&contract_info.version.as_str())
             {
                 return Err(StdError::generic_err(format!(
                     "Can't migrate from {} {}",
                     contract_info.contract, contract_info.version
                 )));
             }
-            BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+            // Since v1.3.0 the plain concentrated pair keeps the same observations buffer
+            if BufferManager::new(deps.storage, OBSERVATIONS).is_err() {
+                BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+            }
 
             let config: CLConfig = Item::new("config").load(deps.storage)?;
-            let ob_state = OrderbookState::new(
-                deps.querier,
-                &env,
-                &params.market_id,
-                params.orders_number,
-                params.min_trades_to_avg,
-                &config.pair_info.asset_infos,
-            )?;
+
+            // This pair doesn't support the pool incentivizer. Fees accrued for it are sent out
+            // as they would be counted as pool liquidity otherwise.
+            if let Some(incentivizer) = &config.incentivizer {
+                let (fee_msgs, settled) =
+                    settle_accrued_fees(deps.storage, &config.pair_info.asset_infos, incentivizer)?;
+                messages = fee_msgs;
+                settled_fees = settled.iter().join(", ");
+            } else {
+                for asset_info in &config.pair_info.asset_infos {
+                    ACCRUED_FEES.remove(deps.storage, asset_info);
+                }
+            }
+
+            let ob_state =
+                init_orderbook_state(deps.querier, &env, &params, &config.pair_info.asset_infos)?;
             CONFIG.save(deps.storage, &config.into())?;
             ob_state.save(deps.storage)?;
+            BufferManager::init(deps.storage, FILLS, FILLS_SIZE)?;
 
             attrs.push(("action", "migrate_to_orderbook"));
         }
@@ -63,7 +81,12 @@ pub fn migrate(
         ("new_contract_name", CONTRACT_NAME),
         ("new_contract_version", CONTRACT_VERSION),
     ]);
-    Ok(Response::default().add_attributes(attrs))
+    if !messages.is_empty() {
+        attrs.push(("settled_fees", settled_fees.as_str()));
+    }
+    Ok(Response::default()
+        .add_messages(messages)
+        .add_attributes(attrs))
 }
 
 impl From<CLConfig> for Config {
@@ -1,3 +1,5 @@
+use cosmwasm_std::Decimal;
+
 use crate::consts::OBSERVATIONS_SIZE;
 use std::ops::RangeInclusive;
 
@@ -8,6 +10,25 @@ pub const ORDER_SIZE_LIMITS: RangeInclusive<u8> = 1..=30;
 /// why we need such exotic limits.
 pub const MIN_TRADES_TO_AVG_LIMITS: RangeInclusive<u32> = 1..=(OBSERVATIONS_SIZE - 1);
 
+/// Maximum distance between orders and the oracle price (10%).
+pub const MAX_SPREAD: Decimal = Decimal::raw(1e17 as u128);
+
+/// Validation limits for the price step of the uniform order ladder (0.01% - 10%).
+pub const PRICE_STEP_LIMITS: RangeInclusive<Decimal> =
+    Decimal::raw(1e14 as u128)..=Decimal::raw(1e17 as u128);
+
+/// Validation limits for the size ratio of the geometric order ladder.
+pub const SIZE_RATIO_LIMITS: RangeInclusive<Decimal> =
+    Decimal::raw(5e17 as u128)..=Decimal::raw(2e18 as u128);
+
+/// Validation limits for the maximum fraction of pool reserves placed as orders (1% - 100%).
+pub const MAX_EXPOSURE_LIMITS: RangeInclusive<Decimal> =
+    Decimal::raw(1e16 as u128)..=Decimal::raw(1e18 as u128);
+
+/// Validation limits for the maximum inventory drift (1% - 100%).
+pub const MAX_INVENTORY_DRIFT_LIMITS: RangeInclusive<Decimal> =
+    Decimal::raw(1e16 as u128)..=Decimal::raw(1e18 as u128);
+
 /// Starting from v1.10 injective uses default subaccount (nonce = 0) to automatically transfer
 /// funds from bank module when creating an order. We need to avoid it.
 pub const SUBACC_NONCE: u32 = 1;
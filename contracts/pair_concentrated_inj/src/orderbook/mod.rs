@@ -1,6 +1,8 @@
 pub mod consts;
 pub mod error;
 pub mod msg;
+pub mod risk;
 pub mod state;
 pub mod sudo;
 pub mod utils;
+pub mod venue;
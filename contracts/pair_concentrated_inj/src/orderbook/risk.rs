@@ -0,0 +1,201 @@
+use std::cmp::Ordering;
+
+use cosmwasm_std::Decimal256;
+
+use astroport::orderbook::{Ladder, LadderOrder};
+use astroport::pair_concentrated_inj::OrderbookRiskLimits;
+
+/// Calculates relative difference between base and quote reserves.
+/// Returns the drift and which of the reserves is bigger.
+///
+/// * **xs** - pool reserves where quote reserve is multiplied by the price scale.
+pub fn inventory_drift(xs: &[Decimal256]) -> (Decimal256, Ordering) {
+    let total = xs[0] + xs[1];
+    if total.is_zero() {
+        return (Decimal256::zero(), Ordering::Equal);
+    }
+
+    let ordering = xs[0].cmp(&xs[1]);
+    let drift = match ordering {
+        Ordering::Greater => (xs[0] - xs[1]) / total,
+        Ordering::Less => (xs[1] - xs[0]) / total,
+        Ordering::Equal => Decimal256::zero(),
+    };
+
+    (drift, ordering)
+}
+
+/// Returns flags (buys_paused, sells_paused) according to the inventory drift limit.
+/// If the pool holds too much base asset, buy orders are pulled and vice versa.
+pub fn paused_sides(limits: &OrderbookRiskLimits, xs: &[Decimal256]) -> (bool, bool) {
+    let (drift, ordering) = inventory_drift(xs);
+    if drift <= limits.max_inventory_drift.into() {
+        return (false, false);
+    }
+
+    match ordering {
+        Ordering::Greater => (true, false),
+        Ordering::Less => (false, true),
+        Ordering::Equal => (false, false),
+    }
+}
+
+/// Keeps the closest orders while their cumulative volume does not exceed the limit.
+fn truncate_side<F>(orders: &mut Vec<LadderOrder>, limit: Decimal256, volume: F)
+where
+    F: Fn(&LadderOrder) -> Decimal256,
+{
+    let mut total = Decimal256::zero();
+    let allowed = orders
+        .iter()
+        .take_while(|order| {
+            total += volume(order);
+            total <= limit
+        })
+        .count();
+    orders.truncate(allowed);
+}
+
+/// Applies risk limits to the ladder:
+/// pulls orders on the side which would further increase the inventory drift and
+/// drops the farthest orders so the pool doesn't place more than max_exposure of its reserves.
+///
+/// * **pools** - pool reserves including subaccount balances.
+///
+/// * **xs** - pool reserves where quote reserve is multiplied by the price scale.
+pub fn apply_risk_limits(
+    ladder: &mut Ladder,
+    limits: &OrderbookRiskLimits,
+    pools: &[Decimal256],
+    xs: &[Decimal256],
+) {
+    let (buys_paused, sells_paused) = paused_sides(limits, xs);
+    if buys_paused {
+        ladder.buys.clear();
+    }
+    if sells_paused {
+        ladder.sells.clear();
+    }
+
+    let max_exposure = Decimal256::from(limits.max_exposure);
+    truncate_side(&mut ladder.sells, pools[0] * max_exposure, |order| {
+        order.amount
+    });
+    truncate_side(&mut ladder.buys, pools[1] * max_exposure, |order| {
+        order.price * order.amount
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Decimal;
+    use std::str::FromStr;
+
+    fn dec(val: &str) -> Decimal256 {
+        Decimal256::from_str(val).unwrap()
+    }
+
+    fn limits(max_exposure: &str, max_inventory_drift: &str) -> OrderbookRiskLimits {
+        OrderbookRiskLimits {
+            max_exposure: Decimal::from_str(max_exposure).unwrap(),
+            max_inventory_drift: Decimal::from_str(max_inventory_drift).unwrap(),
+        }
+    }
+
+    fn ladder() -> Ladder {
+        let order = |price: &str| LadderOrder {
+            price: dec(price),
+            amount: dec("10"),
+        };
+        Ladder {
+            sells: vec![order("2.1"), order("2.2"), order("2.3")],
+            buys: vec![order("1.9"), order("1.8"), order("1.7")],
+        }
+    }
+
+    #[test]
+    fn drift_calculation() {
+        assert_eq!(
+            inventory_drift(&[dec("100"), dec("100")]),
+            (Decimal256::zero(), Ordering::Equal)
+        );
+        assert_eq!(
+            inventory_drift(&[dec("150"), dec("50")]),
+            (dec("0.5"), Ordering::Greater)
+        );
+        assert_eq!(
+            inventory_drift(&[dec("40"), dec("60")]),
+            (dec("0.2"), Ordering::Less)
+        );
+        assert_eq!(
+            inventory_drift(&[Decimal256::zero(), Decimal256::zero()]),
+            (Decimal256::zero(), Ordering::Equal)
+        );
+    }
+
+    #[test]
+    fn default_limits_keep_ladder() {
+        let mut result = ladder();
+        apply_risk_limits(
+            &mut result,
+            &OrderbookRiskLimits::default(),
+            &[dec("30"), dec("60")],
+            &[dec("1000"), dec("1")],
+        );
+
+        assert_eq!(result.sells.len(), 3);
+        assert_eq!(result.buys.len(), 3);
+    }
+
+    #[test]
+    fn exposure_limit() {
+        let mut result = ladder();
+        // 30% of 100 base allows 3 sells, 30% of 100 quote allows only 1 buy
+        apply_risk_limits(
+            &mut result,
+            &limits("0.3", "1"),
+            &[dec("100"), dec("100")],
+            &[dec("100"), dec("100")],
+        );
+
+        assert_eq!(result.sells.len(), 3);
+        assert_eq!(result.buys.len(), 1);
+        assert_eq!(result.buys[0].price, dec("1.9"));
+    }
+
+    #[test]
+    fn inventory_drift_limit() {
+        let pools = [dec("1000"), dec("1000")];
+
+        let mut result = ladder();
+        apply_risk_limits(
+            &mut result,
+            &limits("1", "0.2"),
+            &pools,
+            &[dec("130"), dec("70")],
+        );
+        assert!(result.buys.is_empty());
+        assert_eq!(result.sells.len(), 3);
+
+        let mut result = ladder();
+        apply_risk_limits(
+            &mut result,
+            &limits("1", "0.2"),
+            &pools,
+            &[dec("70"), dec("130")],
+        );
+        assert!(result.sells.is_empty());
+        assert_eq!(result.buys.len(), 3);
+
+        let mut result = ladder();
+        apply_risk_limits(
+            &mut result,
+            &limits("1", "0.3"),
+            &pools,
+            &[dec("130"), dec("70")],
+        );
+        assert_eq!(result.sells.len(), 3);
+        assert_eq!(result.buys.len(), 3);
+    }
+}
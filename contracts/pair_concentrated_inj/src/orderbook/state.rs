@@ -1,15 +1,18 @@
-use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Decimal256, Env, QuerierWrapper, StdError, StdResult, Storage};
-use cw_storage_plus::Item;
+use cosmwasm_std::{Decimal, Env, QuerierWrapper, StdError, StdResult};
 use injective_cosmwasm::{
     InjectiveQuerier, InjectiveQueryWrapper, MarketId, MarketType, SubaccountId,
 };
 
-use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
+use astroport::asset::{AssetInfo, AssetInfoExt};
 use astroport::cosmwasm_ext::ConvertInto;
-use astroport::pair_concentrated_inj::OrderbookStateResponse;
+use astroport::pair_concentrated_inj::{
+    OrderLadder, OrderbookConfig, OrderbookRiskLimits, OrderbookStateResponse,
+};
 
-use crate::orderbook::consts::{MIN_TRADES_TO_AVG_LIMITS, ORDER_SIZE_LIMITS};
+use crate::orderbook::consts::{
+    MAX_EXPOSURE_LIMITS, MAX_INVENTORY_DRIFT_LIMITS, MAX_SPREAD, MIN_TRADES_TO_AVG_LIMITS,
+    ORDER_SIZE_LIMITS, PRICE_STEP_LIMITS, SIZE_RATIO_LIMITS,
+};
 use crate::orderbook::error::OrderbookError;
 use crate::orderbook::utils::{calc_market_ids, get_subaccount};
 
@@ -27,189 +30,183 @@ macro_rules! validate_param {
     };
 }
 
-#[cw_serde]
-pub struct OrderbookState {
-    /// Market which is being used to deploy liquidity to
-    pub market_id: MarketId,
-    /// Subaccount used for the orderbook
-    pub subaccount: SubaccountId,
-    /// Stores asset infos. We duplicate it in OB state to decrease noop gas usage on begin blocker.
-    pub asset_infos: Vec<AssetInfo>,
-    /// Minimum allowed price tick size in the orderbook
-    pub min_price_tick_size: Decimal256,
-    /// Minimum allowed quantity tick size in the orderbook
-    pub min_quantity_tick_size: Decimal256,
-    /// This flag is set when trades, deposits or withdrawals have occurred in the previous block.
-    pub need_reconcile: bool,
-    /// Last balances of the subaccount on the previous begin blocker
-    pub last_balances: Vec<Asset>,
-    /// The number of trades on each side of the order book.
-    /// The higher this number is, the more gas the contract consumes on begin blocker and
-    /// the more liquidity the contract places in the order book.
-    pub orders_number: u8,
-    /// Minimum number of trades to accumulate average trade size.
-    /// Orderbook integration will not be enabled until this number is reached.
-    pub min_trades_to_avg: u32,
-    /// Whether the pool is ready to integrate with the orderbook (MIN_TRADES_TO_AVG is reached)
-    pub ready: bool,
-}
-
-const OB_CONFIG: Item<OrderbookState> = Item::new("orderbook_config");
-
-impl OrderbookState {
-    pub fn new(
-        querier: QuerierWrapper<InjectiveQueryWrapper>,
-        env: &Env,
-        market_id: &str,
-        orders_number: u8,
-        min_trades_to_avg: u32,
-        asset_infos: &[AssetInfo],
-    ) -> StdResult<Self> {
-        let market_id = MarketId::new(market_id)?;
-
-        Self::validate(
-            querier,
-            asset_infos,
-            &market_id,
-            orders_number,
-            min_trades_to_avg,
-        )?;
-
-        let mut state = Self {
-            market_id,
-            subaccount: get_subaccount(&env.contract.address),
-            asset_infos: asset_infos.to_vec(),
-            min_price_tick_size: Default::default(),
-            min_quantity_tick_size: Default::default(),
-            need_reconcile: true,
-            last_balances: vec![
-                asset_infos[0].with_balance(0u8),
-                asset_infos[1].with_balance(0u8),
-            ],
-            orders_number,
-            min_trades_to_avg,
-            ready: false,
-        };
-
-        state.set_ticks(querier)?;
-
-        Ok(state)
-    }
+/// Orderbook state where the venue market and account are identified by Injective ids
+pub type OrderbookState = astroport::orderbook::OrderbookState<MarketId, SubaccountId>;
+
+/// Validates orderbook params and creates the initial orderbook state.
+pub fn init_orderbook_state(
+    querier: QuerierWrapper<InjectiveQueryWrapper>,
+    env: &Env,
+    params: &OrderbookConfig,
+    asset_infos: &[AssetInfo],
+) -> StdResult<OrderbookState> {
+    let market_id = MarketId::new(&params.market_id)?;
+
+    validate(
+        querier,
+        asset_infos,
+        &market_id,
+        params.orders_number,
+        params.min_trades_to_avg,
+    )?;
+    validate_ladder(&params.ladder, params.spread, params.orders_number)?;
+    validate_risk_limits(&params.risk_limits)?;
+
+    let mut state = OrderbookState {
+        market_id,
+        subaccount: get_subaccount(&env.contract.address),
+        asset_infos: asset_infos.to_vec(),
+        min_price_tick_size: Default::default(),
+        min_quantity_tick_size: Default::default(),
+        need_reconcile: true,
+        last_balances: vec![
+            asset_infos[0].with_balance(0u8),
+            asset_infos[1].with_balance(0u8),
+        ],
+        orders_number: params.orders_number,
+        min_trades_to_avg: params.min_trades_to_avg,
+        ready: false,
+        ladder: params.ladder.clone(),
+        spread: params.spread,
+        risk_limits: params.risk_limits.clone(),
+    };
 
-    pub fn load(storage: &dyn Storage) -> StdResult<OrderbookState> {
-        OB_CONFIG.load(storage)
-    }
+    set_ticks(&mut state, querier)?;
 
-    pub fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
-        OB_CONFIG.save(storage, self)
-    }
+    Ok(state)
+}
 
-    /// Validates orderbook params
-    fn validate(
-        querier: QuerierWrapper<InjectiveQueryWrapper>,
-        asset_infos: &[AssetInfo],
-        market_id: &MarketId,
-        orders_number: u8,
-        min_trades_to_avg: u32,
-    ) -> StdResult<()> {
-        validate_param!(
-            orders_number,
-            orders_number,
-            *ORDER_SIZE_LIMITS.start(),
-            *ORDER_SIZE_LIMITS.end()
-        );
-
-        validate_param!(
-            min_trades_to_avg,
-            min_trades_to_avg,
-            *MIN_TRADES_TO_AVG_LIMITS.start(),
-            *MIN_TRADES_TO_AVG_LIMITS.end()
-        );
-
-        let market_ids = calc_market_ids(asset_infos)?;
-
-        if market_id.as_str() == market_ids[1] {
-            // If we call this from instantiate context, we could just swap asset_infos to have correct order.
-            // However, in that case we'll need to invert initial price scale which is bad UX.
-            // We want to avoid implicit actions thus we prohibit pair creation for market id with wrong order.
-            return Err(StdError::generic_err(format!(
+/// Validates orderbook params
+fn validate(
+    querier: QuerierWrapper<InjectiveQueryWrapper>,
+    asset_infos: &[AssetInfo],
+    market_id: &MarketId,
+    orders_number: u8,
+    min_trades_to_avg: u32,
+) -> StdResult<()> {
+    validate_param!(
+        orders_number,
+        orders_number,
+        *ORDER_SIZE_LIMITS.start(),
+        *ORDER_SIZE_LIMITS.end()
+    );
+
+    validate_param!(
+        min_trades_to_avg,
+        min_trades_to_avg,
+        *MIN_TRADES_TO_AVG_LIMITS.start(),
+        *MIN_TRADES_TO_AVG_LIMITS.end()
+    );
+
+    let market_ids = calc_market_ids(asset_infos)?;
+
+    if market_id.as_str() == market_ids[1] {
+        // If we call this from instantiate context, we could just swap asset_infos to have correct order.
+        // However, in that case we'll need to invert initial price scale which is bad UX.
+        // We want to avoid implicit actions thus we prohibit pair creation for market id with wrong order.
+        return Err(StdError::generic_err(format!(
                     "Pair asset infos have different order than market: {first}-{second} while market has {second}-{first}",
                     first = asset_infos[0], second = asset_infos[1]
                 )));
-        } else if market_id.as_str() != market_ids[0] {
-            return Err(StdError::generic_err(format!(
-                "Invalid market id. Must be: {}",
-                market_ids[0]
-            )));
-        }
-
-        market_id
-            .clone()
-            .validate(&InjectiveQuerier::new(&querier), MarketType::Spot)?;
-
-        Ok(())
+    } else if market_id.as_str() != market_ids[0] {
+        return Err(StdError::generic_err(format!(
+            "Invalid market id. Must be: {}",
+            market_ids[0]
+        )));
     }
 
-    /// Querying exchange module, converting into [`Decimal256`] and caching tick sizes.
-    /// Cashed values help to save gas on begin blocker iterations.
-    fn set_ticks(&mut self, querier: QuerierWrapper<InjectiveQueryWrapper>) -> StdResult<()> {
-        let querier = InjectiveQuerier::new(&querier);
-        let market_info = querier
-            .query_spot_market(&self.market_id)?
-            .market
-            .ok_or_else(|| OrderbookError::MarketNotFound(self.market_id.clone().into()))?;
+    market_id
+        .clone()
+        .validate(&InjectiveQuerier::new(&querier), MarketType::Spot)?;
 
-        self.min_price_tick_size = market_info.min_price_tick_size.conv()?;
-        self.min_quantity_tick_size = market_info.min_quantity_tick_size.conv()?;
+    Ok(())
+}
 
-        Ok(())
+/// Validates order ladder params. Orders on the buy side must have positive prices.
+pub fn validate_ladder(ladder: &OrderLadder, spread: Decimal, orders_number: u8) -> StdResult<()> {
+    validate_param!(spread, spread, Decimal::zero(), MAX_SPREAD);
+
+    match ladder {
+        OrderLadder::LiquidityCurve {} => {}
+        OrderLadder::Uniform { price_step } => {
+            validate_param!(
+                price_step,
+                *price_step,
+                *PRICE_STEP_LIMITS.start(),
+                *PRICE_STEP_LIMITS.end()
+            );
+
+            let max_offset =
+                spread + *price_step * Decimal::from_ratio(orders_number.saturating_sub(1), 1u8);
+            if max_offset >= Decimal::one() {
+                return Err(StdError::generic_err(format!(
+                        "Incorrect orderbook params: the last order is {max_offset} away from the oracle price which must be less than 1"
+                    )));
+            }
+        }
+        OrderLadder::Geometric { size_ratio } => {
+            validate_param!(
+                size_ratio,
+                *size_ratio,
+                *SIZE_RATIO_LIMITS.start(),
+                *SIZE_RATIO_LIMITS.end()
+            );
+        }
     }
 
-    /// Set flag to trigger reconciliation on next begin blocker
-    pub fn reconcile(self, storage: &mut dyn Storage) -> StdResult<()> {
-        OB_CONFIG.save(
-            storage,
-            &OrderbookState {
-                need_reconcile: true,
-                ..self
-            },
-        )
-    }
+    Ok(())
+}
 
-    /// Set flag that reconciliation is done. Save current subaccount balances.
-    pub fn reconciliation_done(
-        self,
-        storage: &mut dyn Storage,
-        new_balances: Vec<Asset>,
-    ) -> StdResult<()> {
-        OB_CONFIG.save(
-            storage,
-            &OrderbookState {
-                need_reconcile: false,
-                last_balances: new_balances,
-                ..self
-            },
-        )
-    }
+/// Validates limits on the liquidity deployed into the orderbook.
+pub fn validate_risk_limits(limits: &OrderbookRiskLimits) -> StdResult<()> {
+    validate_param!(
+        max_exposure,
+        limits.max_exposure,
+        *MAX_EXPOSURE_LIMITS.start(),
+        *MAX_EXPOSURE_LIMITS.end()
+    );
+    validate_param!(
+        max_inventory_drift,
+        limits.max_inventory_drift,
+        *MAX_INVENTORY_DRIFT_LIMITS.start(),
+        *MAX_INVENTORY_DRIFT_LIMITS.end()
+    );
+
+    Ok(())
+}
 
-    /// If min_trades_to_avg has been reached, set ready flag to true.
-    pub fn ready(&mut self, ready: bool) {
-        self.ready = ready;
-    }
+/// Querying exchange module, converting into [`Decimal256`] and caching tick sizes.
+/// Cashed values help to save gas on begin blocker iterations.
+fn set_ticks(
+    state: &mut OrderbookState,
+    querier: QuerierWrapper<InjectiveQueryWrapper>,
+) -> StdResult<()> {
+    let querier = InjectiveQuerier::new(&querier);
+    let market_info = querier
+        .query_spot_market(&state.market_id)?
+        .market
+        .ok_or_else(|| OrderbookError::MarketNotFound(state.market_id.clone().into()))?;
+
+    state.min_price_tick_size = market_info.min_price_tick_size.conv()?;
+    state.min_quantity_tick_size = market_info.min_quantity_tick_size.conv()?;
+
+    Ok(())
 }
 
-impl From<OrderbookState> for OrderbookStateResponse {
-    fn from(value: OrderbookState) -> Self {
-        Self {
-            market_id: value.market_id.as_str().to_string(),
-            subaccount: value.subaccount.as_str().to_string(),
-            min_price_tick_size: value.min_price_tick_size,
-            min_quantity_tick_size: value.min_quantity_tick_size,
-            need_reconcile: value.need_reconcile,
-            last_balances: value.last_balances,
-            orders_number: value.orders_number,
-            min_trades_to_avg: value.min_trades_to_avg,
-            ready: value.ready,
-        }
+/// Converts the orderbook state into the query response.
+pub fn orderbook_state_response(value: OrderbookState) -> OrderbookStateResponse {
+    OrderbookStateResponse {
+        market_id: value.market_id.as_str().to_string(),
+        subaccount: value.subaccount.as_str().to_string(),
+        min_price_tick_size: value.min_price_tick_size,
+        min_quantity_tick_size: value.min_quantity_tick_size,
+        need_reconcile: value.need_reconcile,
+        last_balances: value.last_balances,
+        orders_number: value.orders_number,
+        min_trades_to_avg: value.min_trades_to_avg,
+        ready: value.ready,
+        ladder: value.ladder,
+        spread: value.spread,
+        risk_limits: value.risk_limits,
     }
 }
@@ -1,23 +1,22 @@
-use cosmwasm_std::{entry_point, Decimal256, DepsMut, Env, Response, StdResult};
-use injective_cosmwasm::{
-    create_deposit_msg, create_withdraw_msg, InjectiveMsgWrapper, InjectiveQuerier,
-    InjectiveQueryWrapper,
-};
+use cosmwasm_std::{entry_point, DepsMut, Env, Response, StdResult};
+use injective_cosmwasm::{InjectiveMsgWrapper, InjectiveQuerier, InjectiveQueryWrapper};
 use itertools::Itertools;
-use std::cmp::Ordering;
 
 use astroport::asset::AssetInfoExt;
 use astroport::cosmwasm_ext::IntegerToDecimal;
+use astroport::orderbook::{build_ladder, reconcile_msgs, record_fill, LadderParams};
 use astroport_circular_buffer::BufferManager;
 
+use crate::error::ContractError;
 use crate::math::calc_d;
 use crate::orderbook::error::OrderbookError;
 use crate::orderbook::msg::SudoMsg;
+use crate::orderbook::risk::apply_risk_limits;
 use crate::orderbook::state::OrderbookState;
 use crate::orderbook::utils::{
-    cancel_all_orders, compute_swap, get_subaccount_balances, leave_orderbook,
-    process_cumulative_trade, update_spot_orders, SpotOrdersFactory,
+    compute_swap, get_subaccount_balances, leave_orderbook, process_cumulative_trade,
 };
+use crate::orderbook::venue::InjectiveVenue;
 use crate::state::{Precisions, CONFIG, OBSERVATIONS};
 use crate::utils::query_pools;
 
@@ -64,21 +63,28 @@ fn begin_blocker(
 
         let base_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
         let quote_asset_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
+        let venue = InjectiveVenue::new(
+            &env.contract.address,
+            &ob_state,
+            base_asset_precision,
+            quote_asset_precision,
+        );
 
         // If subaccount balances have changed, then trades have occurred
         // and we need to repeg and reconcile orderbook
         if ob_state.last_balances != balances {
-            let maker_fee_message = process_cumulative_trade(
+            let (maker_fee_message, fill) = process_cumulative_trade(
                 deps.querier,
                 &env,
-                &ob_state,
+                &venue,
                 &mut config,
                 &mut pools,
                 &balances,
-                base_asset_precision,
-                quote_asset_precision,
             )?;
             messages.extend(maker_fee_message);
+            if let Some(fill) = fill {
+                record_fill(deps.storage, fill)?;
+            }
 
             CONFIG.save(deps.storage, &config)?;
         }
@@ -100,14 +106,6 @@ fn begin_blocker(
             .ok_or(OrderbookError::NoObservationFound {})?;
         // This shouldn't happen since we wait until MIN_TRADES_TO_AVG is reached. However, we keep this check just for safety.
 
-        let mut orders_factory = SpotOrdersFactory::new(
-            &ob_state.market_id,
-            &ob_state.subaccount,
-            ob_state.min_price_tick_size,
-            base_asset_precision,
-            quote_asset_precision,
-        );
-
         // Adjusting to min quantity tick size on Injective market
         let avg_base_trade_size = (avg_base_trade_size / ob_state.min_quantity_tick_size).floor()
             * ob_state.min_quantity_tick_size;
@@ -122,80 +120,47 @@ fn begin_blocker(
         ixs[1] *= config.pool_state.price_state.price_scale;
         let d = calc_d(&ixs, &amp_gamma)?;
 
-        // Equal heights algorithm
-        for i in 1..=ob_state.orders_number {
-            let quote_sell_amount = avg_quote_trade_size * Decimal256::from_ratio(i, 1u8);
-            let base_sell_amount = compute_swap(&ixs, quote_sell_amount, 0, &config, amp_gamma, d)?;
-            let sell_amount = (base_sell_amount * Decimal256::from_ratio(1u8, i)
-                / ob_state.min_quantity_tick_size)
-                .floor()
-                * ob_state.min_quantity_tick_size;
-
-            let sell_price = if i > 1 {
-                (quote_sell_amount - orders_factory.orderbook_one_side_liquidity(false))
-                    / sell_amount
-            } else {
-                quote_sell_amount / sell_amount
-            };
-
-            let buy_amount = avg_base_trade_size;
-            let base_buy_amount = buy_amount * Decimal256::from_ratio(i, 1u8);
-            let quote_buy_amount = compute_swap(&ixs, base_buy_amount, 1, &config, amp_gamma, d)?;
-            let buy_price = if i > 1 {
-                (quote_buy_amount - orders_factory.orderbook_one_side_liquidity(true)) / buy_amount
-            } else {
-                quote_buy_amount / base_buy_amount
-            };
-
-            // If price is zero we cancel all orders and withdraw liquidity.
-            if sell_price.is_zero() || buy_price.is_zero() {
-                return leave_orderbook(&ob_state, balances, &env);
-            }
-
-            orders_factory.sell(sell_price, sell_amount);
-            orders_factory.buy(buy_price, buy_amount);
-        }
-
-        let total_deposits =
-            orders_factory.total_deposit(&config.pair_info.asset_infos, &precisions)?;
+        // Oracle price is denominated in base asset.
+        // If it is zero the ladder can not be built, thus we cancel all orders and withdraw liquidity.
+        let oracle_price = match config.pool_state.price_state.oracle_price.inv() {
+            Some(oracle_price) => oracle_price,
+            None => return leave_orderbook(&ob_state, balances, &env),
+        };
+
+        let ladder_params = LadderParams {
+            orders_number: ob_state.orders_number,
+            oracle_price,
+            spread: ob_state.spread.into(),
+            avg_base_trade_size,
+            avg_quote_trade_size,
+            min_price_tick_size: ob_state.min_price_tick_size,
+            min_quantity_tick_size: ob_state.min_quantity_tick_size,
+        };
+        let ladder = build_ladder(&ob_state.ladder, &ladder_params, |offer_amount, ask_ind| {
+            compute_swap(&ixs, offer_amount, ask_ind, &config, amp_gamma, d)
+        })?;
+
+        // If the ladder can not be built we cancel all orders and withdraw liquidity.
+        let mut ladder = match ladder {
+            Some(ladder) => ladder,
+            None => return leave_orderbook(&ob_state, balances, &env),
+        };
+        apply_risk_limits(&mut ladder, &ob_state.risk_limits, &pools, &ixs);
+
+        let total_deposits = ladder
+            .total_deposit()
+            .into_iter()
+            .zip(&config.pair_info.asset_infos)
+            .map(|(amount, asset_info)| {
+                let precision = precisions.get_precision(asset_info)?;
+                asset_info
+                    .with_dec_balance(amount)
+                    .into_asset(precision)
+                    .map_err(Into::into)
+            })
+            .collect::<Result<Vec<_>, ContractError>>()?;
 
-        // Cancel all orders first
-        messages.push(cancel_all_orders(
-            &env.contract.address,
-            &ob_state.subaccount,
-            &ob_state.market_id,
-        ));
-
-        // Adjust subaccount balances
-        total_deposits
-            .iter()
-            .zip(balances.iter())
-            .try_for_each::<_, StdResult<_>>(|(need, current)| {
-                match need.amount.cmp(&current.amount) {
-                    Ordering::Greater => messages.push(create_deposit_msg(
-                        env.contract.address.clone(),
-                        ob_state.subaccount.clone(),
-                        need.info
-                            .with_balance(need.amount - current.amount)
-                            .as_coin()?,
-                    )),
-                    Ordering::Less => {
-                        messages.push(create_withdraw_msg(
-                            env.contract.address.clone(),
-                            ob_state.subaccount.clone(),
-                            need.info
-                                .with_balance(current.amount - need.amount)
-                                .as_coin()?,
-                        ));
-                    }
-                    Ordering::Equal => {}
-                }
-
-                Ok(())
-            })?;
-
-        let new_orders = orders_factory.collect_orders(&env.contract.address)?;
-        messages.push(update_spot_orders(&env.contract.address, new_orders));
+        messages.extend(reconcile_msgs(&venue, &ladder, &balances, &total_deposits)?);
 
         ob_state.reconciliation_done(deps.storage, total_deposits)?;
 
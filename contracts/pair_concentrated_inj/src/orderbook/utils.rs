@@ -1,9 +1,8 @@
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt, DecimalAsset};
 use cosmwasm_std::{
-    Addr, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Env, QuerierWrapper, Response,
-    StdError, StdResult,
+    Addr, CosmosMsg, CustomMsg, Decimal, Decimal256, Env, QuerierWrapper, Response, StdError,
+    StdResult,
 };
-use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use tiny_keccak::Hasher;
@@ -14,12 +13,16 @@ use crate::math::calc_y;
 use crate::orderbook::consts::SUBACC_NONCE;
 use crate::orderbook::error::OrderbookError;
 use crate::orderbook::state::OrderbookState;
+use crate::orderbook::venue::InjectiveVenue;
 use crate::state::{AmpGamma, Config, Precisions};
-use astroport::cosmwasm_ext::{AbsDiff, ConvertInto, IntegerToDecimal};
+use astroport::cosmwasm_ext::{ConvertInto, IntegerToDecimal};
+use astroport::orderbook::query_cumulative_trade;
+use astroport::pair_concentrated_inj::OrderbookFill;
 use astroport::querier::{query_fee_info, query_supply};
 use injective_cosmwasm::{
     checked_address_to_subaccount_id, create_batch_update_orders_msg, create_withdraw_msg,
-    InjectiveMsgWrapper, InjectiveQuerier, MarketId, OrderType, SpotOrder, SubaccountId,
+    InjectiveMsgWrapper, InjectiveQuerier, InjectiveQueryWrapper, MarketId, OrderType, SpotOrder,
+    SubaccountId,
 };
 
 /// Calculate hash from two binary slices.
@@ -128,7 +131,7 @@ pub fn get_subaccount_balances(
     asset_infos: &[AssetInfo],
     querier: &InjectiveQuerier,
     subaccount: &SubaccountId,
-) -> Result<Vec<Asset>, ContractError> {
+) -> StdResult<Vec<Asset>> {
     asset_infos
         .iter()
         .map(|asset_info| match asset_info {
@@ -140,9 +143,7 @@ pub fn get_subaccount_balances(
                 };
                 Ok(dec_asset)
             }
-            AssetInfo::Token { .. } => {
-                Err(StdError::generic_err("CW20 tokens are not supported").into())
-            }
+            AssetInfo::Token { .. } => Err(StdError::generic_err("CW20 tokens are not supported")),
         })
         .collect()
 }
@@ -284,47 +285,6 @@ impl<'a> SpotOrdersFactory<'a> {
         });
     }
 
-    /// Calculate total sell/buy liquidity measured in quote asset.
-    pub fn orderbook_one_side_liquidity(&self, is_buy: bool) -> Decimal256 {
-        self.orders
-            .iter()
-            .filter(|order| order.is_buy == is_buy)
-            .fold(Decimal256::zero(), |acc, order| {
-                acc + order.price * order.amount
-            })
-    }
-
-    /// Calculates total subaccount balance the contract will need to place all orders.
-    pub(crate) fn total_deposit(
-        &self,
-        asset_infos: &[AssetInfo],
-        precisions: &Precisions,
-    ) -> Result<Vec<Asset>, ContractError> {
-        let init = vec![
-            // base
-            asset_infos[0].with_dec_balance(Decimal256::zero()),
-            // quote
-            asset_infos[1].with_dec_balance(Decimal256::zero()),
-        ];
-
-        let dec_deposits = self.orders.iter().fold(init, |mut acc, order| {
-            if order.is_buy {
-                acc[1].amount += order.price * order.amount;
-            } else {
-                acc[0].amount += order.amount;
-            }
-            acc
-        });
-
-        dec_deposits
-            .into_iter()
-            .map(|dec_asset| {
-                let precision = precisions.get_precision(&dec_asset.info)?;
-                dec_asset.into_asset(precision).map_err(Into::into)
-            })
-            .collect()
-    }
-
     /// Aggregates orders with the same price. Adjusts price to min_price_tick_size and converts
     /// orders into Injective representation.
     pub fn collect_orders(&self, fee_receiver: &Addr) -> StdResult<Vec<SpotOrder>> {
@@ -371,28 +331,18 @@ impl<'a> SpotOrdersFactory<'a> {
 }
 
 /// Process filled orders as one cumulative trade. Send maker fees and run repegging algorithm.
-#[allow(clippy::too_many_arguments)]
-pub fn process_cumulative_trade<C, T>(
-    querier: QuerierWrapper<C>,
+/// Returns maker fee messages and the fill record. Fill id is assigned when the record is saved.
+pub fn process_cumulative_trade<T>(
+    querier: QuerierWrapper<InjectiveQueryWrapper>,
     env: &Env,
-    ob_state: &OrderbookState,
+    venue: &InjectiveVenue,
     config: &mut Config,
     pools: &mut [Decimal256],
     subacc_balances: &[Asset],
-    base_precision: u8,
-    quote_precision: u8,
-) -> Result<Vec<CosmosMsg<T>>, OrderbookError>
+) -> Result<(Vec<CosmosMsg<T>>, Option<OrderbookFill>), OrderbookError>
 where
-    C: CustomQuery,
     T: CustomMsg,
 {
-    let bal_diffs = ob_state
-        .last_balances
-        .iter()
-        .zip(subacc_balances.iter())
-        .map(|(a, b)| a.amount.diff(b.amount))
-        .collect::<Vec<_>>();
-
     let mut ixs = pools.to_vec();
     // converting into internal representation
     ixs[1] *= config.pool_state.price_state.price_scale;
@@ -403,51 +353,51 @@ where
         config.pair_info.pair_type.clone(),
     )?;
 
+    let maker_fee_rate = match &fee_info.fee_address {
+        Some(_) => {
+            // This is safe conversion because fee_rate is always <= 1
+            let dynamic_fee_rate: Decimal = config.pool_params.fee(&ixs).conv()?;
+            Some(dynamic_fee_rate * fee_info.maker_fee_rate)
+        }
+        None => None,
+    };
+
+    let trade = match query_cumulative_trade(
+        venue,
+        &querier,
+        &venue.ob_state.last_balances,
+        subacc_balances,
+        maker_fee_rate,
+    )? {
+        Some(trade) => trade,
+        // this should never happen as we supposed to call this function only
+        // if there was at least one trade
+        None => return Ok((vec![], None)),
+    };
+
     let mut messages = vec![];
-    if let Some(fee_addr) = fee_info.fee_address {
-        // This is safe conversion because fee_rate is always <= 1
-        let dynamic_fee_rate: Decimal = config.pool_params.fee(&ixs).conv()?;
-        let maker_fee_rate = dynamic_fee_rate * fee_info.maker_fee_rate;
-
-        // Send maker fees
-        match ob_state.last_balances[0]
-            .amount
-            .cmp(&subacc_balances[0].amount)
-        {
-            Ordering::Greater => {
-                // quote -> base i.e. buy direction. Charging fees in base asset
-                let maker_fee = bal_diffs[0] * maker_fee_rate;
-                let maker_fee_dec = maker_fee.to_decimal256(base_precision)?;
-                ixs[0] -= maker_fee_dec;
-                pools[0] -= maker_fee_dec;
-                messages.push(
-                    config.pair_info.asset_infos[0]
-                        .with_balance(maker_fee)
-                        .into_msg(fee_addr)?,
-                );
-            }
-            Ordering::Less => {
-                // base -> quote i.e. sell direction. Charging fees in quote asset
-                let maker_fee = bal_diffs[1] * maker_fee_rate;
-                let maker_fee_dec = maker_fee.to_decimal256(quote_precision)?;
-                ixs[1] -= maker_fee_dec * config.pool_state.price_state.price_scale;
-                pools[1] -= maker_fee_dec;
-                messages.push(
-                    config.pair_info.asset_infos[1]
-                        .with_balance(maker_fee)
-                        .into_msg(fee_addr)?,
-                );
-            }
-            Ordering::Equal => {
-                // this should never happen as we supposed to call this function only
-                // if there was at least one trade
-                return Ok(messages);
-            }
+    if let (Some(fee_addr), Some(maker_fee)) = (fee_info.fee_address, &trade.maker_fee) {
+        if trade.fill.is_buy {
+            // quote -> base i.e. buy direction. Charging fees in quote asset
+            let maker_fee_dec = maker_fee.amount.to_decimal256(venue.quote_precision)?;
+            ixs[1] -= maker_fee_dec * config.pool_state.price_state.price_scale;
+            pools[1] -= maker_fee_dec;
+        } else {
+            // base -> quote i.e. sell direction. Charging fees in base asset
+            let maker_fee_dec = maker_fee.amount.to_decimal256(venue.base_precision)?;
+            ixs[0] -= maker_fee_dec;
+            pools[0] -= maker_fee_dec;
         }
+        messages.push(maker_fee.clone().into_msg(fee_addr)?);
     }
 
-    let fba_price = bal_diffs[0].to_decimal256(base_precision)?
-        / bal_diffs[1].to_decimal256(quote_precision)?;
+    let fba_price = trade.fill.base_amount.to_decimal256(venue.base_precision)?
+        / trade
+            .fill
+            .quote_amount
+            .to_decimal256(venue.quote_precision)?;
+
+    let fill = trade.into_fill(&env.block, venue.base_precision, venue.quote_precision)?;
 
     let total_lp = query_supply(&querier, &config.pair_info.liquidity_token)?
         .to_decimal256(LP_TOKEN_PRECISION)?;
@@ -456,7 +406,7 @@ where
         .pool_state
         .update_price(&config.pool_params, env, total_lp, &ixs, fba_price)?;
 
-    Ok(messages)
+    Ok((messages, Some(fill)))
 }
 
 #[cfg(test)]
@@ -0,0 +1,98 @@
+use cosmwasm_std::{Addr, CosmosMsg, QuerierWrapper, StdResult};
+use injective_cosmwasm::{
+    create_deposit_msg, create_withdraw_msg, InjectiveMsgWrapper, InjectiveQuerier,
+    InjectiveQueryWrapper,
+};
+
+use astroport::asset::{Asset, AssetInfo};
+use astroport::orderbook::{Ladder, OrderbookVenue};
+
+use crate::orderbook::state::OrderbookState;
+use crate::orderbook::utils::{
+    cancel_all_orders, get_subaccount_balances, update_spot_orders, SpotOrdersFactory,
+};
+
+/// Injective exchange module spot market. The pair trades from its own subaccount.
+pub struct InjectiveVenue<'a> {
+    contract_addr: &'a Addr,
+    pub(crate) ob_state: &'a OrderbookState,
+    pub(crate) base_precision: u8,
+    pub(crate) quote_precision: u8,
+}
+
+impl<'a> InjectiveVenue<'a> {
+    pub fn new(
+        contract_addr: &'a Addr,
+        ob_state: &'a OrderbookState,
+        base_precision: u8,
+        quote_precision: u8,
+    ) -> Self {
+        Self {
+            contract_addr,
+            ob_state,
+            base_precision,
+            quote_precision,
+        }
+    }
+}
+
+impl OrderbookVenue for InjectiveVenue<'_> {
+    type Msg = InjectiveMsgWrapper;
+    type Query = InjectiveQueryWrapper;
+
+    fn query_balances(
+        &self,
+        querier: &QuerierWrapper<Self::Query>,
+        asset_infos: &[AssetInfo],
+    ) -> StdResult<Vec<Asset>> {
+        get_subaccount_balances(
+            asset_infos,
+            &InjectiveQuerier::new(querier),
+            &self.ob_state.subaccount,
+        )
+    }
+
+    fn deposit_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>> {
+        Ok(create_deposit_msg(
+            self.contract_addr.clone(),
+            self.ob_state.subaccount.clone(),
+            asset.as_coin()?,
+        ))
+    }
+
+    fn withdraw_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>> {
+        Ok(create_withdraw_msg(
+            self.contract_addr.clone(),
+            self.ob_state.subaccount.clone(),
+            asset.as_coin()?,
+        ))
+    }
+
+    fn cancel_all_orders_msg(&self) -> StdResult<CosmosMsg<Self::Msg>> {
+        Ok(cancel_all_orders(
+            self.contract_addr,
+            &self.ob_state.subaccount,
+            &self.ob_state.market_id,
+        ))
+    }
+
+    fn place_orders_msg(&self, ladder: &Ladder) -> StdResult<CosmosMsg<Self::Msg>> {
+        let mut orders_factory = SpotOrdersFactory::new(
+            &self.ob_state.market_id,
+            &self.ob_state.subaccount,
+            self.ob_state.min_price_tick_size,
+            self.base_precision,
+            self.quote_precision,
+        );
+        for order in &ladder.sells {
+            orders_factory.sell(order.price, order.amount);
+        }
+        for order in &ladder.buys {
+            orders_factory.buy(order.price, order.amount);
+        }
+
+        let new_orders = orders_factory.collect_orders(self.contract_addr)?;
+
+        Ok(update_spot_orders(self.contract_addr, new_orders))
+    }
+}
@@ -1,29 +1,40 @@
 use cosmwasm_std::{
     entry_point, to_binary, Binary, CustomQuery, Decimal, Decimal256, Deps, Env, StdError,
-    StdResult, Storage, Uint128,
+    StdResult, Uint128,
 };
 use injective_cosmwasm::InjectiveQueryWrapper;
 use itertools::Itertools;
 
 use astroport::asset::Asset;
 use astroport::cosmwasm_ext::{AbsDiff, DecimalToInteger, IntegerToDecimal};
+use astroport::observation::query_observation;
+use astroport::orderbook::{FILLS, FILLS_TOTAL};
 use astroport::pair::{
     ConfigResponse, PoolResponse, ReverseSimulationResponse, SimulationResponse,
 };
 use astroport::pair_concentrated::ConcentratedPoolParams;
-use astroport::pair_concentrated_inj::{OracleObservation, OrderbookStateResponse, QueryMsg};
+use astroport::pair_concentrated_inj::{
+    OrderbookFill, OrderbookPnlResponse, OrderbookRiskResponse, OrderbookStateResponse, QueryMsg,
+};
 use astroport::querier::{query_factory_config, query_fee_info, query_supply};
 use astroport_circular_buffer::BufferManager;
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
 use crate::math::calc_d;
-use crate::orderbook::state::OrderbookState;
-use crate::state::{Observation, Precisions, CONFIG, OBSERVATIONS};
+use crate::orderbook::risk::{inventory_drift, paused_sides};
+use crate::orderbook::state::{orderbook_state_response, OrderbookState};
+use crate::orderbook::utils::get_subaccount_balances;
+use crate::state::{Precisions, CONFIG, OBSERVATIONS};
 use crate::utils::{
     before_swap_check, compute_offer_amount, compute_swap, get_share_in_assets, query_pools,
 };
 
+/// Default number of fills returned by [`QueryMsg::OrderbookFills`]
+const DEFAULT_FILLS_LIMIT: u32 = 10;
+/// Maximum number of fills returned by [`QueryMsg::OrderbookFills`]
+const MAX_FILLS_LIMIT: u32 = 30;
+
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
@@ -68,11 +79,27 @@ pub fn query(deps: Deps<InjectiveQueryWrapper>, env: Env, msg: QueryMsg) -> StdR
         QueryMsg::CumulativePrices {} => Err(StdError::generic_err(
             stringify!(Not implemented. Use {"observe": {"seconds_ago": ... }} instead.),
         )),
-        QueryMsg::Observe { seconds_ago } => to_binary(&query_observation(deps, env, seconds_ago)?),
+        QueryMsg::Observe { seconds_ago } => to_binary(&query_observation(
+            deps.storage,
+            env,
+            OBSERVATIONS,
+            seconds_ago,
+        )?),
         QueryMsg::OrderbookState {} => {
-            let resp: OrderbookStateResponse = OrderbookState::load(deps.storage)?.into();
+            let resp: OrderbookStateResponse =
+                orderbook_state_response(OrderbookState::load(deps.storage)?);
             to_binary(&resp)
         }
+        QueryMsg::OrderbookRisk {} => to_binary(
+            &query_orderbook_risk(deps, env)
+                .map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
+        QueryMsg::OrderbookFills { start_after, limit } => {
+            to_binary(&query_orderbook_fills(deps, start_after, limit)?)
+        }
+        QueryMsg::OrderbookPnl {} => to_binary(
+            &query_orderbook_pnl(deps).map_err(|err| StdError::generic_err(err.to_string()))?,
+        ),
     }
 }
 
@@ -322,111 +349,127 @@ pub fn query_compute_d(deps: Deps<InjectiveQueryWrapper>, env: Env) -> StdResult
     calc_d(&xs, &amp_gamma)
 }
 
-/// Performs binary search in circular buffer. Returns left and right bounds of target value.
-/// Either left or right bound may hit in target value.
-fn binary_search(
-    storage: &dyn Storage,
-    buffer: &BufferManager<Observation>,
-    target: u64,
-    mut start: u32,
-    mut end: u32,
-) -> StdResult<(Observation, Observation)> {
-    loop {
-        let mid = (start + end) / 2;
-
-        // We've checked bounds before calling this function thus these errors should be impossible.
-        let leftward_or_hit = buffer.read_single(storage, mid)?.ok_or_else(|| {
-            StdError::generic_err(format!(
-                "Unexpected error in binary_search: leftward_or_hit is None at index {mid}",
-            ))
-        })?;
-        let rightward_or_hit = buffer.read_single(storage, mid + 1)?.ok_or_else(|| {
-            StdError::generic_err(format!(
-                "Unexpected error in binary_search: rightward_or_hit is None at index {}",
-                mid + 1
-            ))
-        })?;
-
-        if leftward_or_hit.timestamp <= target && target <= rightward_or_hit.timestamp {
-            break Ok((leftward_or_hit, rightward_or_hit));
-        }
-        if leftward_or_hit.timestamp > target {
-            end = mid - 1;
-        } else {
-            start = mid + 1;
-        }
-    }
+/// Returns current orderbook exposure against the risk limits.
+fn query_orderbook_risk(
+    deps: Deps<InjectiveQueryWrapper>,
+    env: Env,
+) -> Result<OrderbookRiskResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+    let ob_state = OrderbookState::load(deps.storage)?;
+
+    let querier = injective_cosmwasm::InjectiveQuerier::new(&deps.querier);
+    let subaccount_balances =
+        get_subaccount_balances(&ob_state.asset_infos, &querier, &ob_state.subaccount)?;
+
+    let pools = query_pools(
+        deps.querier,
+        &env.contract.address,
+        &config,
+        &ob_state,
+        &precisions,
+        Some(&subaccount_balances),
+    )?
+    .into_iter()
+    .map(|asset| asset.amount)
+    .collect_vec();
+
+    let exposure = subaccount_balances
+        .iter()
+        .zip(&pools)
+        .map(|(deposit, pool)| {
+            let deposit = deposit
+                .amount
+                .to_decimal256(precisions.get_precision(&deposit.info)?)?;
+            Ok(deposit
+                .checked_div(*pool)
+                .unwrap_or_else(|_| Decimal256::zero()))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    let mut xs = pools;
+    xs[1] *= config.pool_state.price_state.price_scale;
+    let (drift, _) = inventory_drift(&xs);
+    let (buys_paused, sells_paused) = paused_sides(&ob_state.risk_limits, &xs);
+
+    Ok(OrderbookRiskResponse {
+        limits: ob_state.risk_limits,
+        subaccount_balances,
+        exposure,
+        inventory_drift: drift,
+        buys_paused,
+        sells_paused,
+    })
 }
 
-/// Returns price observation at point that was 'seconds_ago' seconds ago.
-pub fn query_observation<C>(
+/// Returns recent orderbook fills ordered by fill id.
+/// Only the last [`astroport::orderbook::FILLS_SIZE`] fills are available.
+pub fn query_orderbook_fills<C>(
     deps: Deps<C>,
-    env: Env,
-    seconds_ago: u64,
-) -> StdResult<OracleObservation>
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<OrderbookFill>>
 where
     C: CustomQuery,
 {
-    let buffer = BufferManager::new(deps.storage, OBSERVATIONS)?;
-    let target = env.block.time.seconds() - seconds_ago;
-
-    let mut oldest_ind = buffer.head();
-    let mut newest_ind = buffer.head() + buffer.capacity() - 1;
-    if !buffer.exists(deps.storage, oldest_ind) {
-        if buffer.head() > 0 {
-            oldest_ind = 0;
-            newest_ind %= buffer.capacity();
-        } else {
-            return Err(StdError::generic_err("Buffer is empty"));
-        }
-    }
+    let buffer = match BufferManager::new(deps.storage, FILLS) {
+        Ok(buffer) => buffer,
+        // No fills were recorded yet
+        Err(_) => return Ok(vec![]),
+    };
+    let capacity = buffer.capacity() as u64;
+    let total = FILLS_TOTAL
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .fills;
+
+    let oldest = total.saturating_sub(capacity);
+    let start = start_after
+        .map(|id| id.saturating_add(1))
+        .unwrap_or_default()
+        .max(oldest);
+    let limit = limit.unwrap_or(DEFAULT_FILLS_LIMIT).min(MAX_FILLS_LIMIT) as u64;
+    let end = start.saturating_add(limit).min(total);
+
+    (start..end)
+        .map(|id| {
+            buffer
+                .read_single(deps.storage, (id % capacity) as u32)?
+                .ok_or_else(|| StdError::generic_err(format!("Fill {id} not found")))
+        })
+        .collect()
+}
 
-    let newest_obs = buffer.read_single(deps.storage, newest_ind)?.unwrap();
-    if target >= newest_obs.timestamp {
-        return Ok(OracleObservation {
-            timestamp: target,
-            price: Decimal::from_ratio(newest_obs.base_amount, newest_obs.quote_amount),
-        });
-    }
-    let oldest_obs = buffer.read_single(deps.storage, oldest_ind)?.unwrap();
-    if target == oldest_obs.timestamp {
-        return Ok(OracleObservation {
-            timestamp: target,
-            price: Decimal::from_ratio(oldest_obs.base_amount, oldest_obs.quote_amount),
-        });
-    }
-    if target < oldest_obs.timestamp {
-        return Err(StdError::generic_err(format!(
-            "Requested observation is too old. Last known observation is at {}",
-            oldest_obs.timestamp
-        )));
-    }
+/// Returns aggregated results of the orderbook leg. Net base asset inventory and maker fees paid
+/// in base asset are valued at the current price scale.
+fn query_orderbook_pnl(
+    deps: Deps<InjectiveQueryWrapper>,
+) -> Result<OrderbookPnlResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+    let totals = FILLS_TOTAL.may_load(deps.storage)?.unwrap_or_default();
 
-    let (left, right) = binary_search(deps.storage, &buffer, target, oldest_ind, newest_ind)?;
-
-    let price_left = Decimal::from_ratio(left.base_amount, left.quote_amount);
-    let price_right = Decimal::from_ratio(right.base_amount, right.quote_amount);
-    let price = if left.timestamp == target {
-        price_left
-    } else if right.timestamp == target {
-        price_right
-    } else if price_left == price_right {
-        price_left
-    } else {
-        // Interpolate.
-        let price_slope = price_right.diff(price_left)
-            * Decimal::from_ratio(1u8, right.timestamp - left.timestamp);
-        let time_interval = Decimal::from_ratio(target - left.timestamp, 1u8);
-        if price_left > price_right {
-            price_left - price_slope * time_interval
-        } else {
-            price_left + price_slope * time_interval
-        }
-    };
+    let base_precision = precisions.get_precision(&config.pair_info.asset_infos[0])?;
+    let quote_precision = precisions.get_precision(&config.pair_info.asset_infos[1])?;
 
-    Ok(OracleObservation {
-        timestamp: target,
-        price,
+    // Price scale is denominated in base asset
+    let mark_price = config
+        .pool_state
+        .price_state
+        .price_scale
+        .inv()
+        .ok_or_else(|| StdError::generic_err("Price scale is zero"))?;
+
+    let gains = totals.quote_received.to_decimal256(quote_precision)?
+        + totals.base_bought.to_decimal256(base_precision)? * mark_price;
+    let costs = (totals.quote_spent + totals.quote_fees).to_decimal256(quote_precision)?
+        + (totals.base_sold + totals.base_fees).to_decimal256(base_precision)? * mark_price;
+
+    Ok(OrderbookPnlResponse {
+        profit: gains.saturating_sub(costs),
+        loss: costs.saturating_sub(gains),
+        mark_price,
+        totals,
     })
 }
 
@@ -438,7 +481,9 @@ mod testing {
     use cosmwasm_std::testing::{mock_dependencies, mock_env};
     use cosmwasm_std::Timestamp;
 
-    use crate::state::Observation;
+    use astroport::observation::Observation;
+    use astroport::orderbook::record_fill;
+    use astroport::pair_concentrated_inj::OracleObservation;
 
     use super::*;
 
@@ -450,6 +495,55 @@ mod testing {
         T::from_str(&val.to_string()).unwrap()
     }
 
+    #[test]
+    fn orderbook_fills_pagination() {
+        let mut deps = mock_dependencies();
+
+        assert_eq!(
+            query_orderbook_fills(deps.as_ref(), None, None).unwrap(),
+            vec![]
+        );
+
+        BufferManager::init(&mut deps.storage, FILLS, 5).unwrap();
+        for i in 1..=12u128 {
+            let fill = OrderbookFill {
+                id: 0,
+                height: i as u64,
+                timestamp: i as u64,
+                is_buy: i % 2 == 0,
+                base_amount: Uint128::new(i),
+                quote_amount: Uint128::new(2 * i),
+                price: Decimal256::from_ratio(2u8, 1u8),
+                maker_fee: None,
+            };
+            record_fill(&mut deps.storage, fill).unwrap();
+        }
+
+        let totals = FILLS_TOTAL.load(&deps.storage).unwrap();
+        assert_eq!(totals.fills, 12);
+        assert_eq!(totals.base_bought.u128(), 2 + 4 + 6 + 8 + 10 + 12);
+        assert_eq!(totals.base_sold.u128(), 1 + 3 + 5 + 7 + 9 + 11);
+        assert_eq!(totals.quote_spent.u128(), 2 * 42);
+
+        // Only the last 5 fills are available
+        let ids = |fills: Vec<OrderbookFill>| fills.into_iter().map(|fill| fill.id).collect_vec();
+        let fills = query_orderbook_fills(deps.as_ref(), None, None).unwrap();
+        assert_eq!(fills[0].height, 8);
+        assert_eq!(ids(fills), vec![7, 8, 9, 10, 11]);
+        assert_eq!(
+            ids(query_orderbook_fills(deps.as_ref(), Some(2), Some(2)).unwrap()),
+            vec![7, 8]
+        );
+        assert_eq!(
+            ids(query_orderbook_fills(deps.as_ref(), Some(8), None).unwrap()),
+            vec![9, 10, 11]
+        );
+        assert_eq!(
+            ids(query_orderbook_fills(deps.as_ref(), Some(11), None).unwrap()),
+            vec![]
+        );
+    }
+
     #[test]
     fn observations_checking_triple_capacity_step_by_step() {
         let mut deps = mock_dependencies();
@@ -491,7 +585,13 @@ mod testing {
                             timestamp: ts + i as u64 * 1000 + 500,
                             price: f64_to_dec(i as f64 + 0.5),
                         },
-                        query_observation(deps.as_ref(), env.clone(), shift * 1000 - 500).unwrap()
+                        query_observation(
+                            &deps.storage,
+                            env.clone(),
+                            OBSERVATIONS,
+                            shift * 1000 - 500
+                        )
+                        .unwrap()
                     );
                 }
                 assert_eq!(
@@ -499,7 +599,8 @@ mod testing {
                         timestamp: ts + i as u64 * 1000,
                         price: f64_to_dec(i as f64),
                     },
-                    query_observation(deps.as_ref(), env.clone(), shift * 1000).unwrap()
+                    query_observation(&deps.storage, env.clone(), OBSERVATIONS, shift * 1000)
+                        .unwrap()
                 );
             }
         }
@@ -514,7 +615,7 @@ mod testing {
 
         let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
 
-        let err = query_observation(deps.as_ref(), env.clone(), 11000).unwrap_err();
+        let err = query_observation(&deps.storage, env.clone(), OBSERVATIONS, 11000).unwrap_err();
         assert_eq!(err.to_string(), "Generic error: Buffer is empty");
 
         let array = (1..=30)
@@ -537,7 +638,7 @@ mod testing {
                 timestamp: 120_000,
                 price: f64_to_dec(20.0 / 400.0),
             },
-            query_observation(deps.as_ref(), env.clone(), 10000).unwrap()
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 10000).unwrap()
         );
 
         assert_eq!(
@@ -545,10 +646,10 @@ mod testing {
                 timestamp: 124_411,
                 price: f64_to_dec(0.04098166666666694),
             },
-            query_observation(deps.as_ref(), env.clone(), 5589).unwrap()
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 5589).unwrap()
         );
 
-        let err = query_observation(deps.as_ref(), env, 35_000).unwrap_err();
+        let err = query_observation(&deps.storage, env, OBSERVATIONS, 35_000).unwrap_err();
         assert_eq!(
             err.to_string(),
             "Generic error: Requested observation is too old. Last known observation is at 111000"
@@ -564,7 +665,7 @@ mod testing {
 
         let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
 
-        let err = query_observation(deps.as_ref(), env.clone(), 11000).unwrap_err();
+        let err = query_observation(&deps.storage, env.clone(), OBSERVATIONS, 11000).unwrap_err();
         assert_eq!(err.to_string(), "Generic error: Buffer is empty");
 
         let array = (1..=30)
@@ -587,7 +688,7 @@ mod testing {
                 timestamp: 120_000,
                 price: f64_to_dec(20.0 / 400.0),
             },
-            query_observation(deps.as_ref(), env.clone(), 10000).unwrap()
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 10000).unwrap()
         );
 
         assert_eq!(
@@ -595,7 +696,7 @@ mod testing {
                 timestamp: 124_411,
                 price: f64_to_dec(0.04098166666666694),
             },
-            query_observation(deps.as_ref(), env.clone(), 5589).unwrap()
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 5589).unwrap()
         );
     }
 }
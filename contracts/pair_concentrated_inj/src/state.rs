@@ -10,6 +10,7 @@ use cw_storage_plus::{Item, Map};
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::common::OwnershipProposal;
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::observation::Observation;
 use astroport::pair_concentrated::{PromoteParams, UpdatePoolParams};
 use astroport_circular_buffer::CircularBuffer;
 
@@ -404,22 +405,6 @@ impl Precisions {
     }
 }
 
-/// Stores trade size observations. We use it in orderbook integration
-/// and derive prices for external contracts/users.
-#[cw_serde]
-#[derive(Copy)]
-pub struct Observation {
-    pub timestamp: u64,
-    /// Base asset simple moving average (mean)
-    pub base_sma: Uint128,
-    /// Base asset amount that was added at this observation
-    pub base_amount: Uint128,
-    /// Quote asset simple moving average (mean)
-    pub quote_sma: Uint128,
-    /// Quote asset amount that was added at this observation
-    pub quote_amount: Uint128,
-}
-
 /// Stores pool parameters and state.
 pub const CONFIG: Item<Config> = Item::new("config");
 
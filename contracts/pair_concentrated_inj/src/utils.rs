@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     to_binary, wasm_execute, Addr, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Env,
-    Fraction, QuerierWrapper, StdError, StdResult, Storage, Uint128, Uint256,
+    Fraction, QuerierWrapper, StdError, StdResult, Storage, Uint128,
 };
 use cw20::Cw20ExecuteMsg;
 use injective_cosmwasm::InjectiveQueryWrapper;
@@ -8,6 +8,7 @@ use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, DecimalAsset};
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::observation;
 use astroport::querier::query_factory_config;
 use astroport_circular_buffer::error::BufferResult;
 use astroport_circular_buffer::BufferManager;
@@ -18,7 +19,7 @@ use crate::error::ContractError;
 use crate::math::{calc_d, calc_y};
 use crate::orderbook::state::OrderbookState;
 use crate::orderbook::utils::get_subaccount_balances_dec;
-use crate::state::{Config, Observation, PoolParams, Precisions, OBSERVATIONS};
+use crate::state::{Config, PoolParams, Precisions, OBSERVATIONS};
 
 /// Helper function to check the given asset infos are valid.
 pub(crate) fn check_asset_infos(asset_infos: &[AssetInfo]) -> Result<(), ContractError> {
@@ -393,20 +394,8 @@ pub fn compute_offer_amount(
     Ok((dy, spread_fee, fee))
 }
 
-/// Internal function to calculate new moving average using Uint256.
-/// Overflow is possible only if new average order size is greater than 2^128 - 1 which is unlikely.
-fn safe_sma_calculation(
-    sma: Uint128,
-    oldest_amount: Uint128,
-    count: u32,
-    new_amount: Uint128,
-) -> StdResult<Uint128> {
-    let res = (sma.full_mul(count) + Uint256::from(new_amount) - Uint256::from(oldest_amount))
-        .checked_div(count.into())?;
-    res.try_into().map_err(StdError::from)
-}
-
-/// Calculate and save moving averages of swap sizes.
+/// Calculate and save moving averages of swap sizes. Enables the orderbook once there are
+/// enough observations.
 pub fn accumulate_swap_sizes(
     storage: &mut dyn Storage,
     env: &Env,
@@ -414,63 +403,17 @@ pub fn accumulate_swap_sizes(
     base_amount: Uint128,
     quote_amount: Uint128,
 ) -> BufferResult<()> {
-    let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
-
-    let new_observation;
-    if let Some(last_obs) = buffer.read_last(storage)? {
-        // Since this is circular buffer the next index contains the oldest value
-        let count = buffer.capacity();
-        if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
-            let new_base_sma = safe_sma_calculation(
-                last_obs.base_sma,
-                oldest_obs.base_amount,
-                count,
-                base_amount,
-            )?;
-            let new_quote_sma = safe_sma_calculation(
-                last_obs.quote_sma,
-                oldest_obs.quote_amount,
-                count,
-                quote_amount,
-            )?;
-            new_observation = Observation {
-                base_amount,
-                quote_amount,
-                base_sma: new_base_sma,
-                quote_sma: new_quote_sma,
-                timestamp: env.block.time.seconds(),
-            };
-        } else {
-            // Buffer is not full yet
-            let count = Uint128::from(buffer.head());
-            let new_base_sma = (last_obs.base_sma * count + base_amount) / (count + Uint128::one());
-            let new_quote_sma =
-                (last_obs.quote_sma * count + quote_amount) / (count + Uint128::one());
-            new_observation = Observation {
-                base_amount,
-                quote_amount,
-                base_sma: new_base_sma,
-                quote_sma: new_quote_sma,
-                timestamp: env.block.time.seconds(),
-            };
-        }
+    let buffer = BufferManager::new(storage, OBSERVATIONS)?;
 
-        // Enable orderbook if we have enough observations
-        if !ob_state.ready && buffer.head() > ob_state.min_trades_to_avg {
-            ob_state.ready(true)
-        }
-    } else {
-        // Buffer is empty
-        new_observation = Observation {
-            timestamp: env.block.time.seconds(),
-            base_sma: base_amount,
-            base_amount,
-            quote_sma: quote_amount,
-            quote_amount,
-        };
+    // Enable orderbook if we have enough observations
+    if !ob_state.ready
+        && buffer.read_last(storage)?.is_some()
+        && buffer.head() > ob_state.min_trades_to_avg
+    {
+        ob_state.ready(true)
     }
 
-    buffer.instant_push(storage, &new_observation)
+    observation::accumulate_swap_sizes(storage, env, OBSERVATIONS, base_amount, quote_amount)
 }
 
 /// Calculate provide fee applied on the amount of LP tokens. Only charged for imbalanced provide.
@@ -579,7 +522,7 @@ mod tests {
     }
 
     #[test]
-    fn test_swap_obeservations() {
+    fn test_swap_observations() {
         let mut store = MockStorage::new();
         let env = mock_env();
         let mut ob_state = OrderbookState {
@@ -593,6 +536,9 @@ mod tests {
             orders_number: 0,
             min_trades_to_avg: *MIN_TRADES_TO_AVG_LIMITS.start(),
             ready: false,
+            ladder: Default::default(),
+            spread: Default::default(),
+            risk_limits: Default::default(),
         };
         BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
 
@@ -14,6 +14,7 @@ use cosmwasm_std::{
     attr, coin, coins, to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CustomMsg,
     CustomQuery, Decimal256, DepsMut, Empty, Env, GovMsg, IbcMsg, IbcQuery, MemoryStorage,
     OverflowError, Querier, Reply, Response, StdError, Storage, SubMsgResponse, SubMsgResult,
+    Uint128,
 };
 use cw_multi_test::{
     App, AppResponse, BankKeeper, CosmosRouter, DistributionKeeper, FailingModule, Module, Router,
@@ -153,6 +154,13 @@ pub trait InjAppExt {
     fn enable_contract(&mut self, contract_addr: Addr) -> AnyResult<()>;
     fn deactivate_contract(&mut self, contract_addr: Addr) -> AnyResult<AppResponse>;
     fn begin_blocker(&mut self, block: &BlockInfo, gas_free: bool) -> AnyResult<()>;
+    fn market_order(
+        &mut self,
+        trader: &Addr,
+        market_id: &str,
+        is_buy: bool,
+        quantity: u128,
+    ) -> AnyResult<u128>;
 }
 
 impl InjAppExt for InjApp {
@@ -244,6 +252,81 @@ impl InjAppExt for InjApp {
 
         Ok(())
     }
+
+    /// Emulates a market order from an external trader. The order is matched against resting
+    /// orders starting from the best price. Makers' subaccount deposits are settled immediately
+    /// while the trader pays and receives coins from the bank module.
+    /// Returns filled base quantity.
+    fn market_order(
+        &mut self,
+        trader: &Addr,
+        market_id: &str,
+        is_buy: bool,
+        quantity: u128,
+    ) -> AnyResult<u128> {
+        let block = self.block_info();
+        self.init_modules(|router, api, storage| {
+            let market_id = MarketId::new(market_id)?;
+            let (base_denom, quote_denom) = router
+                .custom
+                .markets
+                .borrow()
+                .get(&market_id)
+                .cloned()
+                .ok_or_else(|| StdError::generic_err("market does not exist"))?;
+
+            let mut base_total = 0u128;
+            let mut quote_total = 0u128;
+            for (subaccount, base_amount, quote_amount) in
+                router
+                    .custom
+                    .match_orders(&market_id, is_buy, FPDecimal::from(quantity))
+            {
+                router
+                    .custom
+                    .update_deposit(&subaccount, &base_denom, base_amount, !is_buy)?;
+                router
+                    .custom
+                    .update_deposit(&subaccount, &quote_denom, quote_amount, is_buy)?;
+                base_total += base_amount;
+                quote_total += quote_amount;
+            }
+
+            let (pay, receive) = if is_buy {
+                (coin(quote_total, quote_denom), coin(base_total, base_denom))
+            } else {
+                (coin(base_total, base_denom), coin(quote_total, quote_denom))
+            };
+            if !pay.amount.is_zero() {
+                router.execute(
+                    api,
+                    storage,
+                    &block,
+                    trader.clone(),
+                    BankMsg::Send {
+                        to_address: router.custom.module_addr.to_string(),
+                        amount: vec![pay],
+                    }
+                    .into(),
+                )?;
+            }
+            if !receive.amount.is_zero() {
+                router.execute(
+                    api,
+                    storage,
+                    &block,
+                    router.custom.module_addr.clone(),
+                    BankMsg::Send {
+                        to_address: trader.to_string(),
+                        amount: vec![receive],
+                    }
+                    .into(),
+                )?;
+            }
+
+            Ok(base_total)
+        })
+    }
 }
 
 pub struct InjMockModule {
@@ -266,6 +349,86 @@ impl InjMockModule {
             enabled_contracts: Default::default(),
         }
     }
+
+    /// Matches a market order against resting orders on the opposite side starting from the best price.
+    /// Fully filled orders are removed from the orderbook.
+    /// Returns filled (maker subaccount, base amount, quote amount).
+    pub fn match_orders(
+        &self,
+        market_id: &MarketId,
+        is_buy: bool,
+        mut quantity: FPDecimal,
+    ) -> Vec<(SubaccountId, u128, u128)> {
+        let mut orderbook = self.orderbook.borrow_mut();
+        let orders = match orderbook.get_mut(market_id) {
+            Some(orders) => orders,
+            None => return vec![],
+        };
+
+        let mut resting = orders
+            .iter_mut()
+            .map(|(_, order)| order)
+            .filter(|order| BUY_TYPES.contains(&order.order_type) != is_buy)
+            .collect_vec();
+        resting.sort_by(|a, b| {
+            let ordering = a.order_info.price.partial_cmp(&b.order_info.price).unwrap();
+            if is_buy {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+
+        let mut fills = vec![];
+        for order in resting {
+            if quantity == FPDecimal::ZERO {
+                break;
+            }
+
+            let filled = if order.order_info.quantity < quantity {
+                order.order_info.quantity
+            } else {
+                quantity
+            };
+            order.order_info.quantity = order.order_info.quantity - filled;
+            quantity = quantity - filled;
+
+            fills.push((
+                order.order_info.subaccount_id.clone(),
+                filled.into(),
+                (filled * order.order_info.price).into(),
+            ));
+        }
+        orders.retain(|(_, order)| order.order_info.quantity != FPDecimal::ZERO);
+
+        fills
+    }
+
+    /// Increases or decreases subaccount deposit.
+    pub fn update_deposit(
+        &self,
+        subaccount: &SubaccountId,
+        denom: &str,
+        amount: u128,
+        increase: bool,
+    ) -> AnyResult<()> {
+        let mut deposits = self.deposit.borrow_mut();
+        let coins = deposits.entry(subaccount.clone()).or_default();
+        match coins.iter_mut().find(|deposit| deposit.denom == denom) {
+            Some(deposit) if increase => deposit.amount += Uint128::from(amount),
+            Some(deposit) => deposit.amount = deposit.amount.checked_sub(amount.into())?,
+            None if increase => coins.push(coin(amount, denom)),
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "deposit for subaccount {} does not have {denom}",
+                    subaccount.as_str()
+                ))
+                .into())
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Module for InjMockModule {
@@ -27,7 +27,8 @@ use astroport::pair::{
 };
 use astroport::pair_concentrated::{ConcentratedPoolParams, ConcentratedPoolUpdateParams};
 use astroport::pair_concentrated_inj::{
-    ConcentratedInjObParams, OrderbookConfig, OrderbookStateResponse, QueryMsg,
+    ConcentratedInjObParams, OrderbookConfig, OrderbookFill, OrderbookPnlResponse,
+    OrderbookRiskResponse, OrderbookStateResponse, QueryMsg,
 };
 use astroport_pair_concentrated_injective::contract::{execute, instantiate, reply};
 use astroport_pair_concentrated_injective::migrate::migrate;
@@ -303,6 +304,9 @@ impl Helper {
                     market_id,
                     orders_number: 5,
                     min_trades_to_avg: 1,
+                    ladder: Default::default(),
+                    spread: Default::default(),
+                    risk_limits: Default::default(),
                 },
             })
             .unwrap()
@@ -496,6 +500,29 @@ impl Helper {
             .query_wasm_smart(&self.pair_addr, &QueryMsg::OrderbookState {})
     }
 
+    pub fn query_orderbook_fills(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<OrderbookFill>> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::OrderbookFills { start_after, limit },
+        )
+    }
+
+    pub fn query_orderbook_pnl(&self) -> StdResult<OrderbookPnlResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::OrderbookPnl {})
+    }
+
+    pub fn query_orderbook_risk(&self) -> StdResult<OrderbookRiskResponse> {
+        self.app
+            .wrap()
+            .query_wasm_smart(&self.pair_addr, &QueryMsg::OrderbookRisk {})
+    }
+
     fn init_token(
         app: &mut InjApp,
         token_code: u64,
@@ -1,4 +1,4 @@
-use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+use cosmwasm_std::{coins, to_binary, Addr, Decimal, Decimal256, Uint128};
 use cw_multi_test::{next_block, Executor};
 use injective_cosmwasm::InjectiveQuerier;
 use injective_testing::generate_inj_address;
@@ -9,7 +9,10 @@ use astroport::factory::PairType;
 use astroport::pair_concentrated::{
     ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, UpdatePoolParams,
 };
-use astroport::pair_concentrated_inj::{ExecuteMsg, MigrateMsg, OrderbookConfig};
+use astroport::pair_concentrated_inj::{
+    ConcentratedObPoolUpdateParams, ExecuteMsg, MigrateMsg, OrderLadder, OrderbookConfig,
+    OrderbookRiskLimits,
+};
 use astroport_pair_concentrated_injective::consts::{AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS};
 use astroport_pair_concentrated_injective::error::ContractError;
 use astroport_pair_concentrated_injective::orderbook::consts::MIN_TRADES_TO_AVG_LIMITS;
@@ -703,6 +706,9 @@ fn check_amp_gamma_change() {
         repeg_profit_threshold: None,
         min_price_scale_delta: None,
         ma_half_time: None,
+        incentivizer: None,
+        incentivizer_fee_share: None,
+        remove_incentivizer: None,
     });
 
     let err = helper.update_config(&random_user, &action).unwrap_err();
@@ -1095,6 +1101,313 @@ fn check_orderbook_integration() {
     assert_eq!(astro_pool, total_astro);
 }
 
+#[test]
+fn check_orderbook_fills_cycle() {
+    let owner = generate_inj_address();
+    let test_coins = vec![TestCoin::native("inj"), TestCoin::native("astro")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: f64_to_dec(0.5),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+
+    let mut app = mock_inj_app(|_, _, _| {});
+    let market_id = app.create_market("inj", "astro").unwrap();
+    let mut helper =
+        Helper::new_with_app(app, &owner, test_coins.clone(), params, true, None).unwrap();
+    helper
+        .app
+        .enable_contract(helper.pair_addr.clone())
+        .unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance((500_000f64 * 1e18) as u128),
+        helper.assets[&test_coins[1]].with_balance((1_000_000f64 * 1e6) as u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    for _ in 0..50 {
+        helper
+            .swap(
+                &owner,
+                &helper.assets[&test_coins[1]].with_balance((1000.0 * 1e6) as u128),
+                None,
+            )
+            .unwrap();
+        helper.next_block(true).unwrap();
+        helper
+            .swap(
+                &owner,
+                &helper.assets[&test_coins[0]].with_balance((500.0 * 1e18) as u128),
+                None,
+            )
+            .unwrap();
+    }
+    // Place orders
+    helper.next_block(true).unwrap();
+    assert_eq!(helper.query_orderbook_fills(None, None).unwrap(), vec![]);
+
+    let trader = generate_inj_address();
+    helper.give_me_money(
+        &[
+            helper.assets[&test_coins[0]].with_balance((1000f64 * 1e18) as u128),
+            helper.assets[&test_coins[1]].with_balance((1000f64 * 1e6) as u128),
+        ],
+        &trader,
+    );
+
+    // Trader buys INJ from the pool's sell orders
+    let astro_before = helper.coin_balance(&test_coins[1], &trader);
+    let filled = helper
+        .app
+        .market_order(&trader, &market_id, true, (100f64 * 1e18) as u128)
+        .unwrap();
+    assert_eq!(filled, (100f64 * 1e18) as u128);
+    assert_eq!(
+        helper.coin_balance(&test_coins[0], &trader),
+        (1100f64 * 1e18) as u128
+    );
+    let paid = astro_before - helper.coin_balance(&test_coins[1], &trader);
+
+    // Begin blocker processes the fill and places new orders
+    helper.next_block(true).unwrap();
+    let fills = helper.query_orderbook_fills(None, None).unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].id, 0);
+    assert!(!fills[0].is_buy);
+    assert_eq!(fills[0].base_amount.u128(), filled);
+    assert_eq!(fills[0].quote_amount.u128(), paid);
+
+    // Trader sells INJ back to the pool's buy orders
+    let inj_before = helper.coin_balance(&test_coins[0], &trader);
+    let astro_before = helper.coin_balance(&test_coins[1], &trader);
+    let filled = helper
+        .app
+        .market_order(&trader, &market_id, false, (100f64 * 1e18) as u128)
+        .unwrap();
+    assert_eq!(filled, (100f64 * 1e18) as u128);
+    assert_eq!(
+        inj_before - helper.coin_balance(&test_coins[0], &trader),
+        filled
+    );
+    let received = helper.coin_balance(&test_coins[1], &trader) - astro_before;
+    assert!(received < paid);
+
+    helper.next_block(true).unwrap();
+    let fills = helper.query_orderbook_fills(Some(0), None).unwrap();
+    assert_eq!(fills.len(), 1);
+    assert_eq!(fills[0].id, 1);
+    assert!(fills[0].is_buy);
+    assert_eq!(fills[0].quote_amount.u128(), received);
+
+    let pnl = helper.query_orderbook_pnl().unwrap();
+    assert_eq!(pnl.totals.fills, 2);
+    assert_eq!(pnl.totals.base_sold.u128(), filled);
+    assert_eq!(pnl.totals.base_bought.u128(), filled);
+    assert_eq!(pnl.totals.quote_received.u128(), paid);
+    assert_eq!(pnl.totals.quote_spent.u128(), received);
+}
+
+#[test]
+fn check_orderbook_risk_limits() {
+    let owner = generate_inj_address();
+    let test_coins = vec![TestCoin::native("inj"), TestCoin::native("astro")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: f64_to_dec(0.5),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+
+    let mut app = mock_inj_app(|_, _, _| {});
+    app.create_market("inj", "astro").unwrap();
+    let mut helper =
+        Helper::new_with_app(app, &owner, test_coins.clone(), params, true, None).unwrap();
+    helper
+        .app
+        .enable_contract(helper.pair_addr.clone())
+        .unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance((500_000f64 * 1e18) as u128),
+        helper.assets[&test_coins[1]].with_balance((1_000_000f64 * 1e6) as u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let risk = helper.query_orderbook_risk().unwrap();
+    assert_eq!(risk.limits, OrderbookRiskLimits::default());
+    assert!(risk.exposure.iter().all(|exposure| exposure.is_zero()));
+    assert!(!risk.buys_paused && !risk.sells_paused);
+
+    let update_risk_limits = |helper: &mut Helper, max_exposure: f64, max_inventory_drift: f64| {
+        helper.app.execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_binary(&ConcentratedObPoolUpdateParams::UpdateRiskLimits(
+                    OrderbookRiskLimits {
+                        max_exposure: f64_to_dec(max_exposure),
+                        max_inventory_drift: f64_to_dec(max_inventory_drift),
+                    },
+                ))
+                .unwrap(),
+            },
+            &[],
+        )
+    };
+
+    let err = update_risk_limits(&mut helper, 0.0, 0.5).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0.01 <= max_exposure <= 1, but value is 0"
+    );
+    let err = update_risk_limits(&mut helper, 0.5, 1.5).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0.01 <= max_inventory_drift <= 1, but value is 1.5"
+    );
+
+    update_risk_limits(&mut helper, 0.01, 0.5).unwrap();
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(ob_state.risk_limits.max_exposure, f64_to_dec(0.01));
+    assert_eq!(ob_state.risk_limits.max_inventory_drift, f64_to_dec(0.5));
+
+    for _ in 0..50 {
+        helper
+            .swap(
+                &owner,
+                &helper.assets[&test_coins[1]].with_balance((1000.0 * 1e6) as u128),
+                None,
+            )
+            .unwrap();
+        helper.next_block(true).unwrap();
+        helper
+            .swap(
+                &owner,
+                &helper.assets[&test_coins[0]].with_balance((500.0 * 1e18) as u128),
+                None,
+            )
+            .unwrap();
+    }
+    helper.next_block(true).unwrap();
+
+    let risk = helper.query_orderbook_risk().unwrap();
+    assert!(risk
+        .exposure
+        .iter()
+        .all(|exposure| *exposure > Decimal256::zero() && *exposure <= Decimal256::percent(1)));
+    assert!(!risk.buys_paused && !risk.sells_paused);
+}
+
+#[test]
+fn check_update_order_ladder() {
+    let owner = generate_inj_address();
+    let test_coins = vec![TestCoin::native("inj"), TestCoin::native("astro")];
+
+    let params = ConcentratedPoolParams {
+        amp: f64_to_dec(40f64),
+        gamma: f64_to_dec(0.000145),
+        mid_fee: f64_to_dec(0.0026),
+        out_fee: f64_to_dec(0.0045),
+        fee_gamma: f64_to_dec(0.00023),
+        repeg_profit_threshold: f64_to_dec(0.000002),
+        min_price_scale_delta: f64_to_dec(0.000146),
+        price_scale: f64_to_dec(0.5),
+        ma_half_time: 600,
+        track_asset_balances: None,
+    };
+    let mut helper = Helper::new(&owner, test_coins, params, true).unwrap();
+
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(ob_state.ladder, OrderLadder::LiquidityCurve {});
+
+    let update_order_ladder = |helper: &mut Helper, sender: &Addr, ladder, spread| {
+        helper.app.execute_contract(
+            sender.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_binary(&ConcentratedObPoolUpdateParams::UpdateOrderLadder {
+                    ladder,
+                    spread,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+    };
+
+    let uniform = OrderLadder::Uniform {
+        price_step: f64_to_dec(0.01),
+    };
+
+    let err = update_order_ladder(
+        &mut helper,
+        &Addr::unchecked("random"),
+        uniform.clone(),
+        f64_to_dec(0.005),
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = update_order_ladder(
+        &mut helper,
+        &owner,
+        OrderLadder::Uniform {
+            price_step: f64_to_dec(0.2),
+        },
+        f64_to_dec(0.005),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0.0001 <= price_step <= 0.1, but value is 0.2"
+    );
+
+    let err =
+        update_order_ladder(&mut helper, &owner, uniform.clone(), f64_to_dec(0.2)).unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0 <= spread <= 0.1, but value is 0.2"
+    );
+
+    let err = update_order_ladder(
+        &mut helper,
+        &owner,
+        OrderLadder::Geometric {
+            size_ratio: f64_to_dec(3.0),
+        },
+        f64_to_dec(0.005),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Incorrect orderbook params: must be 0.5 <= size_ratio <= 2, but value is 3"
+    );
+
+    update_order_ladder(&mut helper, &owner, uniform.clone(), f64_to_dec(0.005)).unwrap();
+
+    let ob_state = helper.query_ob_config_smart().unwrap();
+    assert_eq!(ob_state.ladder, uniform);
+    assert_eq!(ob_state.spread, f64_to_dec(0.005));
+    // The orders are replaced on the next begin blocker
+    assert!(helper.query_ob_config().unwrap().need_reconcile);
+}
+
 #[test]
 fn check_last_withdraw() {
     let owner = generate_inj_address();
@@ -1599,6 +1912,9 @@ fn test_migrate_cl_to_orderbook_cl() {
                 .to_string(),
             orders_number: 5,
             min_trades_to_avg: 1,
+            ladder: Default::default(),
+            spread: Default::default(),
+            risk_limits: Default::default(),
         },
     };
 
@@ -1625,6 +1941,9 @@ fn test_migrate_cl_to_orderbook_cl() {
             market_id,
             orders_number: 5,
             min_trades_to_avg: 1,
+            ladder: Default::default(),
+            spread: Default::default(),
+            risk_limits: Default::default(),
         },
     };
     helper
@@ -1731,6 +2050,9 @@ fn test_wrong_assets_order() {
             market_id,
             orders_number: 5,
             min_trades_to_avg: *MIN_TRADES_TO_AVG_LIMITS.start(),
+            ladder: Default::default(),
+            spread: Default::default(),
+            risk_limits: Default::default(),
         },
     };
     let new_code_id = helper.app.store_code(orderbook_pair_contract());
@@ -15,14 +15,14 @@ use cw_utils::parse_instantiate_response_data;
 use itertools::Itertools;
 
 use astroport::asset::{
-    addr_opt_validate, check_swap_parameters, format_lp_token_name, Asset, AssetInfo, CoinsExt,
-    Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+    addr_opt_validate, check_swap_parameters, format_lp_token_name, Asset, AssetInfo, AssetInfoExt,
+    CoinsExt, Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::PairType;
 use astroport::pair::{
-    migration_check, ConfigResponse, InstantiateMsg, StablePoolParams, StablePoolUpdateParams,
-    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
+    calc_depth, migration_check, ConfigResponse, DepthLevel, InstantiateMsg, StablePoolParams,
+    StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE,
 };
 
 use crate::migration::migrate_config_to_v210;
@@ -273,6 +273,7 @@ pub fn execute(
             )
             .map_err(|e| e.into())
         }
+        ExecuteMsg::WithdrawLiquidity { .. } => Err(ContractError::NonSupported {}),
         ExecuteMsg::ClaimOwnership {} => {
             claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
                 CONFIG.update::<_, StdError>(deps.storage, |mut config| {
@@ -794,6 +795,10 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::CumulativePrices {} => to_binary(&query_cumulative_prices(deps, env)?),
         QueryMsg::Config {} => to_binary(&query_config(deps, env)?),
         QueryMsg::QueryComputeD {} => to_binary(&query_compute_d(deps, env)?),
+        QueryMsg::Depth {
+            offer_asset_info,
+            price_impacts,
+        } => to_binary(&query_depth(deps, env, offer_asset_info, price_impacts)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -896,6 +901,64 @@ pub fn query_simulation(
     })
 }
 
+/// Returns the largest offer amounts which keep the swap price impact within each of the given levels.
+///
+/// * **offer_asset_info** is the asset to swap from.
+///
+/// * **price_impacts** is the list of price impact levels.
+pub fn query_depth(
+    deps: Deps,
+    env: Env,
+    offer_asset_info: AssetInfo,
+    price_impacts: Vec<Decimal>,
+) -> StdResult<Vec<DepthLevel>> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config.pair_info.query_pools_decimal(
+        &deps.querier,
+        &config.pair_info.contract_addr,
+        &config.factory_addr,
+    )?;
+
+    let (offer_pool, ask_pool) = select_pools(Some(&offer_asset_info), None, &pools)
+        .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+    let offer_precision = get_precision(deps.storage, &offer_pool.info)?;
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+
+    let ask_precision = get_precision(deps.storage, &ask_pool.info)?;
+
+    calc_depth(
+        offer_pool
+            .amount
+            .to_uint128_with_precision(offer_precision)?,
+        ask_pool.amount.to_uint128_with_precision(ask_precision)?,
+        &price_impacts,
+        |offer_amount| {
+            let offer_asset = offer_asset_info
+                .with_balance(offer_amount)
+                .to_decimal_asset(offer_precision)?;
+            let SwapResult { return_amount, .. } = compute_swap(
+                deps.storage,
+                &env,
+                &config,
+                &offer_asset,
+                &offer_pool,
+                &ask_pool,
+                &pools,
+            )
+            .map_err(|err| StdError::generic_err(format!("{err}")))?;
+
+            let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
+            Ok(return_amount.saturating_sub(commission_amount))
+        },
+    )
+}
+
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 ///
 /// * **ask_asset** is the asset to swap to as well as the desired amount of ask
@@ -20,6 +20,9 @@ pub enum ContractError {
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
+    #[error("Operation non supported")]
+    NonSupported {},
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use anyhow::Result as AnyResult;
-use cosmwasm_std::{coin, to_binary, Addr, Coin, Empty, StdResult, Uint128};
+use cosmwasm_std::{coin, to_binary, Addr, Coin, Decimal, Empty, StdResult, Uint128};
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
 use cw_multi_test::{App, AppResponse, Contract, ContractWrapper, Executor};
 use derivative::Derivative;
@@ -10,8 +10,8 @@ use itertools::Itertools;
 use astroport::asset::{native_asset_info, token_asset_info, Asset, AssetInfo, PairInfo};
 use astroport::factory::{PairConfig, PairType};
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse, StablePoolParams,
+    CumulativePricesResponse, Cw20HookMsg, DepthLevel, ExecuteMsg, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse, StablePoolParams,
 };
 pub const NATIVE_TOKEN_PRECISION: u8 = 6;
 use astroport_pair_stable::contract::{execute, instantiate, query, reply};
@@ -333,6 +333,20 @@ impl Helper {
         )
     }
 
+    pub fn query_depth(
+        &self,
+        offer_asset_info: &AssetInfo,
+        price_impacts: Vec<Decimal>,
+    ) -> StdResult<Vec<DepthLevel>> {
+        self.app.wrap().query_wasm_smart(
+            &self.pair_addr,
+            &QueryMsg::Depth {
+                offer_asset_info: offer_asset_info.clone(),
+                price_impacts,
+            },
+        )
+    }
+
     pub fn query_prices(&self) -> StdResult<CumulativePricesResponse> {
         self.app
             .wrap()
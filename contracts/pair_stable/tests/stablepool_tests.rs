@@ -1,7 +1,7 @@
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use itertools::Itertools;
 
-use astroport::asset::AssetInfoExt;
+use astroport::asset::{native_asset_info, AssetInfoExt};
 use astroport_pair_stable::error::ContractError;
 use helper::AppExtension;
 
@@ -270,6 +270,63 @@ fn check_swaps() {
     assert_eq!(99_949011, helper.coin_balance(&test_coins[1], &user));
 }
 
+#[test]
+fn query_depth() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let offer_asset_info = helper.assets[&test_coins[0]].clone();
+    let price_impacts = vec![
+        Decimal::permille(1),
+        Decimal::percent(1),
+        Decimal::percent(10),
+    ];
+    let levels = helper
+        .query_depth(&offer_asset_info, price_impacts.clone())
+        .unwrap();
+
+    assert_eq!(levels.len(), price_impacts.len());
+    for (level, price_impact) in levels.iter().zip(&price_impacts) {
+        assert_eq!(level.price_impact, *price_impact);
+        // Depth levels are consistent with the regular simulation
+        let sim = helper
+            .simulate_swap(&offer_asset_info.with_balance(level.offer_amount), None)
+            .unwrap();
+        assert_eq!(sim.return_amount, level.return_amount);
+    }
+    // Deeper price impact levels require larger offer amounts
+    assert!(levels
+        .windows(2)
+        .all(|pair| pair[0].offer_amount < pair[1].offer_amount));
+    // The stableswap curve is much deeper than XYK around the peg
+    assert!(levels[0].offer_amount > Uint128::new(100_000000));
+
+    let err = helper
+        .query_depth(&offer_asset_info, vec![Decimal::one()])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Price impact must be within (0, 1)"
+    );
+
+    let err = helper
+        .query_depth(&native_asset_info("random".to_string()), price_impacts)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Native token balance mismatch between the argument and the transferred"
+    );
+}
+
 #[test]
 fn check_wrong_initializations() {
     let owner = Addr::unchecked("owner");
@@ -1,9 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
 use cosmwasm_std::{
-    attr, entry_point, from_binary, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal,
-    Deps, DepsMut, Empty, Env, MessageInfo, Order, QuerierWrapper, Reply, Response, StdError,
-    StdResult, SubMsg, Uint128, Uint64, WasmMsg,
+    attr, entry_point, from_binary, to_binary, wasm_execute, Addr, BankMsg, Binary, CosmosMsg,
+    Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, QuerierWrapper, Reply, Response,
+    StdError, StdResult, SubMsg, Timestamp, Uint128, Uint64, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
@@ -15,13 +15,14 @@ use crate::migration;
 
 use astroport::asset::{addr_opt_validate, pair_info_by_pool, Asset, AssetInfo, PairInfo};
 
-use astroport::common::{
-    claim_ownership, drop_ownership_proposal, propose_new_owner, validate_addresses,
-};
+use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::PairType;
-use astroport::generator::{Config, ExecuteOnReply, PoolInfo};
+use astroport::generator::{
+    Config, ExecuteOnReply, IncentiveInfo, IncentivizationFeeInfo, LockupPosition, LockupTier,
+    PoolInfo, ScheduledAllocation,
+};
 use astroport::generator::{StakerResponse, UserInfoV2};
-use astroport::querier::query_token_balance;
+use astroport::token_factory::is_token_factory_denom;
 use astroport::{
     factory::{ConfigResponse as FactoryConfigResponse, QueryMsg as FactoryQueryMsg},
     generator::{
@@ -37,9 +38,12 @@ use astroport::{
 
 use crate::response::MsgInstantiateContractResponse;
 use crate::state::{
-    accumulate_pool_proxy_rewards, query_lp_balance, update_proxy_asset, update_user_balance,
-    update_virtual_amount, CompatibleLoader, CHECKPOINT_GENERATORS_LIMIT, CONFIG, DEFAULT_LIMIT,
-    MAX_LIMIT, OWNERSHIP_PROPOSAL, POOL_INFO, PROXY_REWARDS_HOLDER, PROXY_REWARD_ASSET, USER_INFO,
+    accumulate_incentive_rewards, accumulate_pool_proxy_rewards, checkpoint_user_incentives,
+    effective_virtual_amount, locked_amount, lp_token_asset_info, query_lp_balance,
+    update_proxy_asset, update_user_balance, update_virtual_amount, validate_lp_token,
+    CompatibleLoader, CHECKPOINT_GENERATORS_LIMIT, CONFIG, DEFAULT_LIMIT, MAX_INCENTIVE_DURATION,
+    MAX_LIMIT, MAX_LOCKUP_TIERS, MAX_POOL_INCENTIVES, MAX_USER_LOCKUPS, OWNERSHIP_PROPOSAL,
+    POOL_INFO, PROXY_REWARDS_HOLDER, PROXY_REWARD_ASSET, SCHEDULED_ALLOCATIONS, USER_INFO,
 };
 
 /// Contract name that is used for migration.
@@ -81,6 +85,11 @@ pub fn instantiate(
         checkpoint_generator_limit: None,
         voting_escrow_delegation,
         voting_escrow,
+        tokens_per_second: msg.tokens_per_second,
+        start_time: msg.start_time.unwrap_or_default(),
+        lockup_tiers: vec![],
+        incentive_tokens: vec![],
+        incentivization_fee: None,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -104,6 +113,12 @@ pub fn instantiate(
 ///
 /// * **ExecuteMsg::SetupPools { pools }** Setting up a new list of pools with allocation points.
 ///
+/// * **ExecuteMsg::ScheduleSetupPools { pools, effective_at }** Schedules a new list of pools with
+/// allocation points which takes effect at the specified block height or timestamp.
+///
+/// * **ExecuteMsg::CancelScheduledSetupPools { effective_at }** Cancels the allocation points
+/// scheduled at the specified block height or timestamp.
+///
 /// * **UpdatePool {
 ///             lp_token,
 ///             has_asset_rewards,
@@ -124,8 +139,13 @@ pub fn instantiate(
 /// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
 /// it depending on the received template.
 ///
+/// * **ExecuteMsg::Deposit { recipient }** Deposits token factory LP tokens sent along with the message.
+///
 /// * **ExecuteMsg::SetTokensPerBlock { amount }** Sets a new amount of ASTRO that's distributed per block among all active generators.
 ///
+/// * **ExecuteMsg::SetTokensPerSecond { amount }** Sets a new amount of ASTRO that's distributed per second among all active generators
+/// and switches the contract to time based emissions.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a new request to change contract ownership.
 /// Only the current owner can call this.
 ///
@@ -143,6 +163,26 @@ pub fn instantiate(
 ///
 /// * **ExecuteMsg::CheckpointUserBoost { user, generators }** Updates the boost emissions for
 /// specified user and generators
+///
+/// * **ExecuteMsg::Incentivize { lp_token, reward, duration }** Schedules an external reward stream
+/// for the specified LP token.
+///
+/// * **ExecuteMsg::ClaimIncentives { lp_token, rewards }** Sends the specified pending external incentive
+/// rewards to the staker. Useful if a failing reward token transfer blocks claiming all rewards at once.
+///
+/// * **ExecuteMsg::RefundIncentive { lp_token, reward }** Returns the undistributed rewards of an ended
+/// reward stream to its creator.
+///
+/// * **ExecuteMsg::UpdateIncentiveTokens { add, remove }** Adds or removes tokens which can be used as
+/// external incentive rewards.
+///
+/// * **ExecuteMsg::SetLockupTiers { tiers }** Sets the lockup tiers available to stakers.
+///
+/// * **ExecuteMsg::Lock { lp_token, amount, duration }** Locks deposited LP tokens for the duration
+/// of a lockup tier in exchange for a virtual amount multiplier.
+///
+/// * **ExecuteMsg::EarlyUnlock { lp_token, index }** Removes an active lockup position before it ends.
+/// The penalty is sent to the Maker.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     mut deps: DepsMut,
@@ -158,13 +198,13 @@ pub fn execute(
             deactivate_blacklisted(deps, env, pair_types)
         }
         ExecuteMsg::DeactivatePool { lp_token } => {
-            let cfg = CONFIG.load(deps.storage)?;
+            let mut cfg = CONFIG.load(deps.storage)?;
             if info.sender != cfg.factory {
                 return Err(ContractError::Unauthorized {});
             }
-            let lp_token_addr = deps.api.addr_validate(&lp_token)?;
+            let lp_token_addr = validate_lp_token(deps.api, &lp_token)?;
             let active_pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
-            mass_update_pools(deps.branch(), &env, &cfg, &active_pools)?;
+            mass_update_pools(deps.branch(), &env, &mut cfg, &active_pools)?;
             deactivate_pool(deps, cfg, lp_token_addr)
         }
         ExecuteMsg::UpdateBlockedTokenslist { add, remove } => {
@@ -195,12 +235,22 @@ pub fn execute(
             checkpoint_generator_limit,
         ),
         ExecuteMsg::SetupPools { pools } => execute_setup_pools(deps, env, info, pools),
+        ExecuteMsg::ScheduleSetupPools {
+            pools,
+            effective_at,
+        } => execute_schedule_setup_pools(deps, env, info, pools, effective_at),
+        ExecuteMsg::CancelScheduledSetupPools { effective_at } => {
+            execute_cancel_scheduled_setup_pools(deps, env, info, effective_at)
+        }
         ExecuteMsg::UpdatePool {
             lp_token,
             has_asset_rewards,
         } => execute_update_pool(deps, info, lp_token, has_asset_rewards),
         ExecuteMsg::ClaimRewards { lp_tokens } => {
-            let lp_tokens_addr = validate_addresses(deps.api, &lp_tokens)?;
+            let lp_tokens_addr = lp_tokens
+                .iter()
+                .map(|lp_token| validate_lp_token(deps.api, lp_token))
+                .collect::<StdResult<Vec<_>>>()?;
 
             update_rewards_and_execute(
                 deps,
@@ -216,7 +266,7 @@ pub fn execute(
             if amount.is_zero() {
                 return Err(ContractError::ZeroWithdraw {});
             }
-            let lp_token = deps.api.addr_validate(&lp_token)?;
+            let lp_token = validate_lp_token(deps.api, &lp_token)?;
 
             update_rewards_and_execute(
                 deps.branch(),
@@ -229,18 +279,23 @@ pub fn execute(
                 },
             )
         }
-        ExecuteMsg::EmergencyWithdraw { lp_token } => emergency_withdraw(deps, info, lp_token),
+        ExecuteMsg::EmergencyWithdraw { lp_token } => emergency_withdraw(deps, env, info, lp_token),
         ExecuteMsg::SendOrphanProxyReward {
             recipient,
             lp_token,
         } => send_orphan_proxy_rewards(deps, info, recipient, lp_token),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::Deposit { recipient } => deposit_token_factory_lp(deps, env, info, recipient),
         ExecuteMsg::SetTokensPerBlock { amount } => {
             let cfg = CONFIG.load(deps.storage)?;
             if info.sender != cfg.owner {
                 return Err(ContractError::Unauthorized {});
             }
 
+            if cfg.tokens_per_second.is_some() {
+                return Err(ContractError::TimeBasedEmissions {});
+            }
+
             update_rewards_and_execute(
                 deps,
                 env,
@@ -248,6 +303,19 @@ pub fn execute(
                 ExecuteOnReply::SetTokensPerBlock { amount },
             )
         }
+        ExecuteMsg::SetTokensPerSecond { amount } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if info.sender != cfg.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                None,
+                ExecuteOnReply::SetTokensPerSecond { amount },
+            )
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config = CONFIG.load(deps.storage)?;
 
@@ -279,6 +347,57 @@ pub fn execute(
             })
             .map_err(Into::into)
         }
+        ExecuteMsg::Incentivize {
+            lp_token,
+            reward,
+            duration,
+        } => incentivize(deps, env, info, lp_token, reward, duration),
+        ExecuteMsg::ClaimIncentives { lp_token, rewards } => {
+            claim_incentives(deps, env, info, lp_token, rewards)
+        }
+        ExecuteMsg::RefundIncentive { lp_token, reward } => {
+            refund_incentive(deps, env, info, lp_token, reward)
+        }
+        ExecuteMsg::UpdateIncentiveTokens { add, remove } => {
+            update_incentive_tokens(deps, info, add, remove)
+        }
+        ExecuteMsg::SetLockupTiers { tiers } => set_lockup_tiers(deps, info, tiers),
+        ExecuteMsg::SetIncentivizationFee { fee_info } => {
+            set_incentivization_fee(deps, info, fee_info)
+        }
+        ExecuteMsg::Lock {
+            lp_token,
+            amount,
+            duration,
+        } => {
+            let lp_token = validate_lp_token(deps.api, &lp_token)?;
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::Lock {
+                    lp_token,
+                    account: info.sender,
+                    amount,
+                    duration,
+                },
+            )
+        }
+        ExecuteMsg::EarlyUnlock { lp_token, index } => {
+            let lp_token = validate_lp_token(deps.api, &lp_token)?;
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::EarlyUnlock {
+                    lp_token,
+                    account: info.sender,
+                    index,
+                },
+            )
+        }
         ExecuteMsg::Callback { action } => {
             if info.sender != env.contract.address {
                 return Err(ContractError::Unauthorized {});
@@ -295,13 +414,14 @@ pub fn execute(
 ///
 /// * **user** address for which the virtual amount will be recalculated.
 fn checkpoint_user_boost(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     generators: Vec<String>,
     user: Option<String>,
 ) -> Result<Response, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    apply_scheduled_allocations(deps.branch(), &env, &mut config)?;
     let recipient_addr = if let Some(user) = user {
         deps.api.addr_validate(&user)?
     } else {
@@ -315,9 +435,9 @@ fn checkpoint_user_boost(
         return Err(ContractError::GeneratorsLimitExceeded {});
     }
 
-    let mut send_rewards_msg: Vec<WasmMsg> = vec![];
+    let mut send_rewards_msg: Vec<CosmosMsg> = vec![];
     for generator in generators {
-        let lp_token = deps.api.addr_validate(&generator)?;
+        let lp_token = validate_lp_token(deps.api, &generator)?;
 
         // calculates the emission boost only for user who has LP in generator
         if USER_INFO.has(deps.storage, (&lp_token, &recipient_addr)) {
@@ -333,11 +453,13 @@ fn checkpoint_user_boost(
                 &pool,
                 &user_info,
                 &recipient_addr,
+                env.block.time.seconds(),
             )?);
 
             // Update user's amount
             let amount = user_info.amount;
-            let mut user_info = update_user_balance(user_info, &pool, amount)?;
+            let mut user_info =
+                update_user_balance(user_info, &pool, amount, env.block.time.seconds())?;
             let lp_balance =
                 query_lp_balance(deps.as_ref(), &env.contract.address, &lp_token, &pool)?;
 
@@ -349,6 +471,7 @@ fn checkpoint_user_boost(
                 &mut user_info,
                 &recipient_addr,
                 lp_balance,
+                env.block.time.seconds(),
             )?;
 
             USER_INFO.save(deps.storage, (&lp_token, &recipient_addr), &user_info)?;
@@ -395,7 +518,7 @@ fn deactivate_blacklisted(
     }
 
     let active_pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
-    mass_update_pools(deps.branch(), &env, &cfg, &active_pools)?;
+    mass_update_pools(deps.branch(), &env, &mut cfg, &active_pools)?;
 
     // find active pools with blacklisted pair type
     for pool in &mut cfg.active_pools {
@@ -454,7 +577,7 @@ fn update_blocked_tokens_list(
     // Add tokens to the blacklist
     if let Some(asset_infos) = add {
         let active_pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
-        mass_update_pools(deps.branch(), &env, &cfg, &active_pools)?;
+        mass_update_pools(deps.branch(), &env, &mut cfg, &active_pools)?;
 
         for asset_info in asset_infos {
             // ASTRO or chain's native assets (ust, uluna, inj, etc) cannot be blacklisted
@@ -562,71 +685,254 @@ pub fn execute_setup_pools(
         return Err(ContractError::Unauthorized {});
     }
 
+    let setup_pools = validate_setup_pools(deps.as_ref(), &cfg, pools)?;
+    let prev_pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
+
+    mass_update_pools(deps.branch(), &env, &mut cfg, &prev_pools)?;
+
+    for (lp_token, _) in &setup_pools {
+        if !POOL_INFO.has(deps.storage, lp_token) {
+            create_pool(deps.branch(), &env, lp_token, &cfg)?;
+        }
+    }
+
+    cfg.total_alloc_point = setup_pools.iter().map(|(_, alloc_point)| alloc_point).sum();
+    cfg.active_pools = setup_pools;
+
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "setup_pools"))
+}
+
+/// Schedules a new list of pools with allocation points which replaces the active pools once
+/// the specified block height (or timestamp in the time based emission mode) is reached.
+/// Generators for the scheduled pools are created right away.
+///
+/// * **pools** is a vector of set that contains LP token address and allocation point.
+///
+/// * **effective_at** block height or timestamp when the allocation points take effect.
+///
+/// ## Executor
+/// Can only be called by the owner or generator controller
+pub fn execute_schedule_setup_pools(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pools: Vec<(String, Uint128)>,
+    effective_at: u64,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner && Some(info.sender) != cfg.generator_controller {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if effective_at <= block_height_or_time(&env, &cfg) {
+        return Err(ContractError::InvalidEffectiveAt {});
+    }
+
+    let setup_pools = validate_setup_pools(deps.as_ref(), &cfg, pools)?;
+
+    for (lp_token, _) in &setup_pools {
+        if !POOL_INFO.has(deps.storage, lp_token) {
+            create_pool(deps.branch(), &env, lp_token, &cfg)?;
+        }
+    }
+
+    SCHEDULED_ALLOCATIONS.save(deps.storage, effective_at, &setup_pools)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "schedule_setup_pools")
+        .add_attribute("effective_at", effective_at.to_string()))
+}
+
+/// Cancels the allocation points scheduled at `effective_at`. Schedules which became effective
+/// are applied first, so they can't be cancelled anymore.
+///
+/// * **effective_at** block height or timestamp of the schedule.
+///
+/// ## Executor
+/// Can only be called by the owner or generator controller
+pub fn execute_cancel_scheduled_setup_pools(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    effective_at: u64,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner && Some(info.sender) != cfg.generator_controller {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    apply_scheduled_allocations(deps.branch(), &env, &mut cfg)?;
+
+    if !SCHEDULED_ALLOCATIONS.has(deps.storage, effective_at) {
+        return Err(ContractError::ScheduledAllocationNotFound { effective_at });
+    }
+    SCHEDULED_ALLOCATIONS.remove(deps.storage, effective_at);
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_scheduled_setup_pools")
+        .add_attribute("effective_at", effective_at.to_string()))
+}
+
+/// Validates the pools which are set up with allocation points.
+///
+/// * **pools** is a vector of set that contains LP token address and allocation point.
+fn validate_setup_pools(
+    deps: Deps,
+    cfg: &Config,
+    pools: Vec<(String, Uint128)>,
+) -> Result<Vec<(Addr, Uint128)>, ContractError> {
     let pools_set: HashSet<String> = pools.clone().into_iter().map(|pc| pc.0).collect();
     if pools_set.len() != pools.len() {
         return Err(ContractError::PoolDuplicate {});
     }
 
-    let mut setup_pools: Vec<(Addr, Uint128)> = vec![];
-
     let blacklisted_pair_types: Vec<PairType> = deps
         .querier
         .query_wasm_smart(&cfg.factory, &FactoryQueryMsg::BlacklistedPairTypes {})?;
 
-    for (addr, alloc_point) in pools {
-        let pool_addr = deps.api.addr_validate(&addr)?;
-        let pair_info = pair_info_by_pool(&deps.querier, &pool_addr)?;
+    pools
+        .into_iter()
+        .map(|(addr, alloc_point)| {
+            let pool_addr = validate_lp_token(deps.api, &addr)?;
+            validate_pool(deps.querier, cfg, &blacklisted_pair_types, &pool_addr)?;
+            Ok((pool_addr, alloc_point))
+        })
+        .collect()
+}
 
-        // check if assets in the blocked list
-        for asset in &pair_info.asset_infos {
-            if cfg.blocked_tokens_list.contains(asset) {
-                return Err(StdError::generic_err(format!("Token {asset} is blocked!")).into());
-            }
-        }
+/// Checks that the pool can receive ASTRO emissions.
+///
+/// * **blacklisted_pair_types** pair types which are blacklisted in the factory.
+///
+/// * **lp_token** LP token of the pool.
+fn validate_pool(
+    querier: QuerierWrapper,
+    cfg: &Config,
+    blacklisted_pair_types: &[PairType],
+    lp_token: &Addr,
+) -> Result<(), ContractError> {
+    let pair_info = pair_info_by_pool(&querier, lp_token)?;
 
-        // check if pair type is blacklisted
-        if blacklisted_pair_types.contains(&pair_info.pair_type) {
-            return Err(StdError::generic_err(format!(
-                "Pair type ({}) is blacklisted!",
-                pair_info.pair_type
-            ))
-            .into());
+    // check if assets in the blocked list
+    for asset in &pair_info.asset_infos {
+        if cfg.blocked_tokens_list.contains(asset) {
+            return Err(StdError::generic_err(format!("Token {asset} is blocked!")).into());
         }
+    }
 
-        // If a pair gets deregistered from the factory, we should raise error.
-        let _: PairInfo = deps
-            .querier
-            .query_wasm_smart(
-                &cfg.factory,
-                &FactoryQueryMsg::Pair {
-                    asset_infos: pair_info.asset_infos.clone(),
-                },
-            )
-            .map_err(|_| {
-                ContractError::Std(StdError::generic_err(format!(
-                    "The pair is not registered: {}-{}",
-                    pair_info.asset_infos[0], pair_info.asset_infos[1]
-                )))
-            })?;
+    // check if pair type is blacklisted
+    if blacklisted_pair_types.contains(&pair_info.pair_type) {
+        return Err(StdError::generic_err(format!(
+            "Pair type ({}) is blacklisted!",
+            pair_info.pair_type
+        ))
+        .into());
+    }
+
+    // If a pair gets deregistered from the factory, we should raise error.
+    let _: PairInfo = querier
+        .query_wasm_smart(
+            &cfg.factory,
+            &FactoryQueryMsg::Pair {
+                asset_infos: pair_info.asset_infos.clone(),
+            },
+        )
+        .map_err(|_| {
+            ContractError::Std(StdError::generic_err(format!(
+                "The pair is not registered: {}-{}",
+                pair_info.asset_infos[0], pair_info.asset_infos[1]
+            )))
+        })?;
+
+    Ok(())
+}
+
+/// Returns the scheduled pools which can still receive ASTRO emissions. Pools which were blocked,
+/// blacklisted or deregistered since they were scheduled are skipped.
+///
+/// * **blacklisted_pair_types** pair types which are blacklisted in the factory.
+///
+/// * **pools** scheduled pools with allocation points.
+fn filter_scheduled_pools(
+    querier: QuerierWrapper,
+    cfg: &Config,
+    blacklisted_pair_types: &[PairType],
+    pools: Vec<(Addr, Uint128)>,
+) -> Vec<(Addr, Uint128)> {
+    pools
+        .into_iter()
+        .filter(|(lp_token, _)| {
+            validate_pool(querier, cfg, blacklisted_pair_types, lp_token).is_ok()
+        })
+        .collect()
+}
+
+/// Applies the scheduled allocation points which became effective. Before the active pools are
+/// replaced, the rewards of the affected generators are accumulated up to the scheduled block
+/// height or timestamp. Pools which were blocked, blacklisted or deregistered in the meantime are skipped.
+pub fn apply_scheduled_allocations(
+    mut deps: DepsMut,
+    env: &Env,
+    cfg: &mut Config,
+) -> Result<(), ContractError> {
+    let due_allocations = SCHEDULED_ALLOCATIONS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(block_height_or_time(env, cfg))),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
 
-        setup_pools.push((pool_addr, alloc_point));
+    if due_allocations.is_empty() {
+        return Ok(());
     }
-    let prev_pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
 
-    mass_update_pools(deps.branch(), &env, &cfg, &prev_pools)?;
+    let blacklisted_pair_types: Vec<PairType> = deps
+        .querier
+        .query_wasm_smart(&cfg.factory, &FactoryQueryMsg::BlacklistedPairTypes {})?;
 
-    for (lp_token, _) in &setup_pools {
-        if !POOL_INFO.has(deps.storage, lp_token) {
-            create_pool(deps.branch(), &env, lp_token, &cfg)?;
+    let mut updated_pools: Vec<Addr> = vec![];
+    for (effective_at, pools) in due_allocations {
+        let setup_pools = filter_scheduled_pools(deps.querier, cfg, &blacklisted_pair_types, pools);
+
+        let mut schedule_env = env.clone();
+        if cfg.tokens_per_second.is_some() {
+            schedule_env.block.time = Timestamp::from_seconds(effective_at);
+        } else {
+            schedule_env.block.height = effective_at;
+        }
+
+        for (lp_token, _) in cfg.active_pools.iter().chain(setup_pools.iter()) {
+            if !updated_pools.contains(lp_token) {
+                updated_pools.push(lp_token.clone());
+            }
         }
+        update_pools(deps.branch(), &schedule_env, cfg, &updated_pools)?;
+
+        cfg.total_alloc_point = setup_pools.iter().map(|(_, alloc_point)| alloc_point).sum();
+        cfg.active_pools = setup_pools;
+
+        SCHEDULED_ALLOCATIONS.remove(deps.storage, effective_at);
     }
 
-    cfg.total_alloc_point = setup_pools.iter().map(|(_, alloc_point)| alloc_point).sum();
-    cfg.active_pools = setup_pools;
+    // Accumulate rewards up to the current block using the new allocation points
+    update_pools(deps.branch(), env, cfg, &updated_pools)?;
 
-    CONFIG.save(deps.storage, &cfg)?;
+    CONFIG.save(deps.storage, cfg)?;
 
-    Ok(Response::new().add_attribute("action", "setup_pools"))
+    Ok(())
+}
+
+/// Returns the current block height or, if ASTRO emissions are time based, the current timestamp.
+fn block_height_or_time(env: &Env, cfg: &Config) -> u64 {
+    if cfg.tokens_per_second.is_some() {
+        env.block.time.seconds()
+    } else {
+        env.block.height
+    }
 }
 
 /// Updates the given generator's ASTRO allocation points.
@@ -643,7 +949,7 @@ pub fn execute_update_pool(
     lp_token: String,
     has_asset_rewards: bool,
 ) -> Result<Response, ContractError> {
-    let lp_token_addr = deps.api.addr_validate(&lp_token)?;
+    let lp_token_addr = validate_lp_token(deps.api, &lp_token)?;
 
     let cfg = CONFIG.load(deps.storage)?;
     if info.sender != cfg.owner {
@@ -670,11 +976,14 @@ pub fn execute_update_pool(
 ///
 /// * **on_reply** action to be performed on reply.
 fn update_rewards_and_execute(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     update_specified_pools: Option<Vec<Addr>>,
     action_on_reply: ExecuteOnReply,
 ) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    apply_scheduled_allocations(deps.branch(), &env, &mut config)?;
+
     let pools = match update_specified_pools {
         Some(lp_tokens) => {
             // Check for duplicate lp tokens
@@ -690,17 +999,11 @@ fn update_rewards_and_execute(
                 .map(|lp_token| Ok((lp_token.clone(), POOL_INFO.load(deps.storage, lp_token)?)))
                 .collect::<StdResult<Vec<_>>>()?
         }
-        None => {
-            let config = CONFIG.load(deps.storage)?;
-
-            config
-                .active_pools
-                .iter()
-                .map(|(lp_token, _)| {
-                    Ok((lp_token.clone(), POOL_INFO.load(deps.storage, lp_token)?))
-                })
-                .collect::<StdResult<Vec<_>>>()?
-        }
+        None => config
+            .active_pools
+            .iter()
+            .map(|(lp_token, _)| Ok((lp_token.clone(), POOL_INFO.load(deps.storage, lp_token)?)))
+            .collect::<StdResult<Vec<_>>>()?,
     };
 
     let mut messages = vec![];
@@ -797,6 +1100,7 @@ fn handle_callback(
             amount,
         } => withdraw(deps, env, lp_token, account, amount),
         ExecuteOnReply::SetTokensPerBlock { amount } => set_tokens_per_block(deps, env, amount),
+        ExecuteOnReply::SetTokensPerSecond { amount } => set_tokens_per_second(deps, env, amount),
         ExecuteOnReply::MigrateProxy {
             lp_addr,
             new_proxy_addr,
@@ -806,6 +1110,17 @@ fn handle_callback(
             prev_proxy_addr,
             amount,
         } => migrate_proxy_deposit_lp(deps, lp_addr, prev_proxy_addr, amount),
+        ExecuteOnReply::Lock {
+            lp_token,
+            account,
+            amount,
+            duration,
+        } => lock(deps, env, lp_token, account, amount, duration),
+        ExecuteOnReply::EarlyUnlock {
+            lp_token,
+            account,
+            index,
+        } => early_unlock(deps, env, lp_token, account, index),
     }
 }
 
@@ -845,7 +1160,7 @@ fn set_tokens_per_block(
 
     let pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
 
-    mass_update_pools(deps.branch(), &env, &cfg, &pools)?;
+    mass_update_pools(deps.branch(), &env, &mut cfg, &pools)?;
 
     cfg.tokens_per_block = amount;
     CONFIG.save(deps.storage, &cfg)?;
@@ -853,10 +1168,62 @@ fn set_tokens_per_block(
     Ok(Response::new().add_attribute("action", "set_tokens_per_block"))
 }
 
-/// Updates the amount of accrued rewards for all generators.
+/// Sets a new amount of ASTRO distributed per second among all active generators. If emissions are
+/// still block based, the rewards accrued up to the current block are accounted for and the active
+/// generators start accruing rewards by time from the current block time.
+///
+/// * **amount** new count of tokens per second.
+fn set_tokens_per_second(
+    mut deps: DepsMut,
+    env: Env,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    let pools: Vec<_> = cfg.active_pools.iter().map(|pool| pool.0.clone()).collect();
+
+    mass_update_pools(deps.branch(), &env, &mut cfg, &pools)?;
+
+    if cfg.tokens_per_second.is_none() {
+        // Scheduled block heights can't be converted to timestamps
+        if SCHEDULED_ALLOCATIONS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+        {
+            return Err(ContractError::ScheduledAllocationsPending {});
+        }
+
+        cfg.start_time = Uint64::from(env.block.time.seconds());
+        cfg.tokens_per_second = Some(amount);
+        // Snapshot the reward time for the active generators
+        mass_update_pools(deps.branch(), &env, &mut cfg, &pools)?;
+    } else {
+        cfg.tokens_per_second = Some(amount);
+    }
+
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "set_tokens_per_second"))
+}
+
+/// Applies the scheduled allocation points which became effective and updates the amount of
+/// accrued rewards for the specified generators.
 ///
 /// * **lp_tokens** is the list of LP tokens which should be updated.
 pub fn mass_update_pools(
+    mut deps: DepsMut,
+    env: &Env,
+    cfg: &mut Config,
+    lp_tokens: &[Addr],
+) -> Result<(), ContractError> {
+    apply_scheduled_allocations(deps.branch(), env, cfg)?;
+
+    update_pools(deps, env, cfg, lp_tokens)
+}
+
+/// Accrues the rewards of the specified generators.
+fn update_pools(
     deps: DepsMut,
     env: &Env,
     cfg: &Config,
@@ -882,9 +1249,9 @@ pub fn claim_rewards(
     lp_tokens: Vec<Addr>,
     account: Addr,
 ) -> Result<Response, ContractError> {
-    let cfg = CONFIG.load(deps.storage)?;
+    let mut cfg = CONFIG.load(deps.storage)?;
 
-    mass_update_pools(deps.branch(), &env, &cfg, &lp_tokens)?;
+    mass_update_pools(deps.branch(), &env, &mut cfg, &lp_tokens)?;
 
     let mut send_rewards_msg = vec![];
     for lp_token in &lp_tokens {
@@ -897,11 +1264,18 @@ pub fn claim_rewards(
             &pool,
             &user,
             &account,
+            env.block.time.seconds(),
         )?);
 
         // Update user's amount
         let amount = user.amount;
-        let mut user = update_user_balance(user, &pool, amount)?;
+        let mut user = update_user_balance(user, &pool, amount, env.block.time.seconds())?;
+
+        // External incentive rewards are paid out along with ASTRO and proxy rewards
+        for reward in user.pending_incentives.drain(..) {
+            send_rewards_msg.push(reward.into_msg(&account)?);
+        }
+
         let lp_balance = query_lp_balance(deps.as_ref(), &env.contract.address, lp_token, &pool)?;
 
         // Update user's virtual amount
@@ -912,6 +1286,7 @@ pub fn claim_rewards(
             &mut user,
             &account,
             lp_balance,
+            env.block.time.seconds(),
         )?;
 
         USER_INFO.save(deps.storage, (lp_token, &account), &user)?;
@@ -954,25 +1329,29 @@ pub fn accumulate_rewards_per_share(
         }
     }
 
+    accumulate_incentive_rewards(pool, env.block.time.seconds())?;
+
     // we should calculate rewards by previous virtual amount
     let lp_supply = pool.total_virtual_supply;
 
-    if env.block.height > pool.last_reward_block.u64() {
-        if !lp_supply.is_zero() {
-            let alloc_point = get_alloc_point(&cfg.active_pools, lp_token);
-            let token_rewards = calculate_rewards(
-                env.block.height - pool.last_reward_block.u64(),
-                &alloc_point,
-                cfg,
-            )?;
+    let elapsed = elapsed_since_last_reward(env, pool, cfg);
+    if elapsed > 0 && !lp_supply.is_zero() {
+        let alloc_point = get_alloc_point(&cfg.active_pools, lp_token);
+        let token_rewards = calculate_rewards(elapsed, &alloc_point, cfg)?;
 
-            let share = Decimal::from_ratio(token_rewards, lp_supply);
-            pool.reward_global_index = pool.reward_global_index.checked_add(share)?;
-        }
+        let share = Decimal::from_ratio(token_rewards, lp_supply);
+        pool.reward_global_index = pool.reward_global_index.checked_add(share)?;
+    }
 
+    if env.block.height > pool.last_reward_block.u64() {
         pool.last_reward_block = Uint64::from(env.block.height);
     }
 
+    if cfg.tokens_per_second.is_some() {
+        let last_reward_time = pool.last_reward_time.unwrap_or(cfg.start_time);
+        pool.last_reward_time = Some(last_reward_time.max(Uint64::from(env.block.time.seconds())));
+    }
+
     Ok(())
 }
 
@@ -1019,38 +1398,81 @@ fn receive_cw20(
     }
 }
 
-/// Distributes pending proxy rewards for a specific staker.
-///
-/// * **pool** generator where the a user was staked.
-///
-/// * **user** staker for which we claim accrued proxy rewards.
+/// Deposits token factory LP tokens sent along with the message.
 ///
-/// * **to** address that will receive the proxy rewards.
-pub fn send_pending_rewards(
-    deps: Deps,
+/// * **recipient** address that will take ownership of the staked LP tokens. Defaults to the sender.
+fn deposit_token_factory_lp(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    let (lp_token, amount) = match info.funds.as_slice() {
+        [lp] if is_token_factory_denom(&lp.denom) && !lp.amount.is_zero() => {
+            (validate_lp_token(deps.api, &lp.denom)?, lp.amount)
+        }
+        _ => return Err(ContractError::InvalidLpFunds {}),
+    };
+    let account = addr_opt_validate(deps.api, &recipient)?.unwrap_or(info.sender);
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if !POOL_INFO.has(deps.storage, &lp_token) {
+        create_pool(deps.branch(), &env, &lp_token, &cfg)?;
+    }
+
+    update_rewards_and_execute(
+        deps,
+        env,
+        Some(vec![lp_token.clone()]),
+        ExecuteOnReply::Deposit {
+            lp_token,
+            account,
+            amount,
+        },
+    )
+}
+
+/// Distributes pending ASTRO and proxy rewards for a specific staker. External incentive rewards
+/// are moved to the staker's pending incentives by [`update_user_balance`] and are paid out by
+/// [`claim_rewards`] or the `ClaimIncentives` message.
+///
+/// * **pool** generator where the a user was staked.
+///
+/// * **user** staker for which we claim accrued rewards.
+///
+/// * **to** address that will receive the rewards.
+///
+/// * **now** current time. The boost of ended lockups is only counted up to their end time.
+pub fn send_pending_rewards(
+    deps: Deps,
     cfg: &Config,
     pool: &PoolInfo,
     user: &UserInfoV2,
     to: &Addr,
-) -> Result<Vec<WasmMsg>, ContractError> {
+    now: u64,
+) -> Result<Vec<CosmosMsg>, ContractError> {
     if user.amount.is_zero() {
         return Ok(vec![]);
     }
 
     let mut messages = vec![];
 
-    let pending_rewards = (pool.reward_global_index - user.reward_user_index)
-        .checked_mul_uint128(user.virtual_amount)?;
+    let virtual_amount = effective_virtual_amount(user, user.reward_checkpoint_time, now)?;
+    let pending_rewards =
+        (pool.reward_global_index - user.reward_user_index).checked_mul_uint128(virtual_amount)?;
 
     if !pending_rewards.is_zero() {
-        messages.push(WasmMsg::Execute {
-            contract_addr: cfg.vesting_contract.to_string(),
-            msg: to_binary(&VestingExecuteMsg::Claim {
-                recipient: Some(to.to_string()),
-                amount: Some(pending_rewards),
-            })?,
-            funds: vec![],
-        });
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: cfg.vesting_contract.to_string(),
+                msg: to_binary(&VestingExecuteMsg::Claim {
+                    recipient: Some(to.to_string()),
+                    amount: Some(pending_rewards),
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
     }
 
     let proxy_rewards = accumulate_pool_proxy_rewards(pool, user)?;
@@ -1060,29 +1482,35 @@ pub fn send_pending_rewards(
         if !pending_proxy_rewards.is_zero() {
             match &pool.reward_proxy {
                 Some(reward_proxy) if reward_proxy == proxy => {
-                    messages.push(WasmMsg::Execute {
-                        contract_addr: proxy.to_string(),
-                        funds: vec![],
-                        msg: to_binary(&ProxyExecuteMsg::SendRewards {
-                            account: to.to_string(),
-                            amount: pending_proxy_rewards,
-                        })?,
-                    });
+                    messages.push(
+                        WasmMsg::Execute {
+                            contract_addr: proxy.to_string(),
+                            funds: vec![],
+                            msg: to_binary(&ProxyExecuteMsg::SendRewards {
+                                account: to.to_string(),
+                                amount: pending_proxy_rewards,
+                            })?,
+                        }
+                        .into(),
+                    );
                 }
                 _ => {
                     // Old proxy rewards are paid from reward holder
                     let asset_info = PROXY_REWARD_ASSET.load(deps.storage, &proxy)?;
-                    messages.push(WasmMsg::Execute {
-                        contract_addr: proxy_rewards_holder.to_string(),
-                        funds: vec![],
-                        msg: to_binary(&cw1_whitelist::msg::ExecuteMsg::Execute {
-                            msgs: vec![Asset {
-                                info: asset_info,
-                                amount: pending_proxy_rewards,
-                            }
-                            .into_msg::<Empty>(to.clone())?],
-                        })?,
-                    });
+                    messages.push(
+                        WasmMsg::Execute {
+                            contract_addr: proxy_rewards_holder.to_string(),
+                            funds: vec![],
+                            msg: to_binary(&cw1_whitelist::msg::ExecuteMsg::Execute {
+                                msgs: vec![Asset {
+                                    info: asset_info,
+                                    amount: pending_proxy_rewards,
+                                }
+                                .into_msg::<Empty>(to.clone())?],
+                            })?,
+                        }
+                        .into(),
+                    );
                 }
             }
         }
@@ -1115,7 +1543,14 @@ pub fn deposit(
     accumulate_rewards_per_share(&deps.querier, &env, &lp_token, &mut pool, &cfg)?;
 
     // Send pending rewards (if any) to the depositor
-    let mut messages = send_pending_rewards(deps.as_ref(), &cfg, &pool, &user, &beneficiary)?;
+    let mut messages = send_pending_rewards(
+        deps.as_ref(),
+        &cfg,
+        &pool,
+        &user,
+        &beneficiary,
+        env.block.time.seconds(),
+    )?;
 
     let mut lp_balance = query_lp_balance(deps.as_ref(), &env.contract.address, &lp_token, &pool)?;
 
@@ -1123,20 +1558,23 @@ pub fn deposit(
     if !amount.is_zero() && pool.reward_proxy.is_some() {
         // Consider deposited LP tokens
         lp_balance += amount;
-        messages.push(wasm_execute(
-            &lp_token,
-            &Cw20ExecuteMsg::Send {
-                contract: pool.reward_proxy.clone().unwrap().to_string(),
-                msg: to_binary(&ProxyCw20HookMsg::Deposit {})?,
-                amount,
-            },
-            vec![],
-        )?);
+        messages.push(
+            wasm_execute(
+                &lp_token,
+                &Cw20ExecuteMsg::Send {
+                    contract: pool.reward_proxy.clone().unwrap().to_string(),
+                    msg: to_binary(&ProxyCw20HookMsg::Deposit {})?,
+                    amount,
+                },
+                vec![],
+            )?
+            .into(),
+        );
     }
 
     // Update user's LP token balance
     let updated_amount = user.amount.checked_add(amount)?;
-    let mut user = update_user_balance(user, &pool, updated_amount)?;
+    let mut user = update_user_balance(user, &pool, updated_amount, env.block.time.seconds())?;
 
     update_virtual_amount(
         deps.querier,
@@ -1145,6 +1583,7 @@ pub fn deposit(
         &mut user,
         &beneficiary,
         lp_balance,
+        env.block.time.seconds(),
     )?;
 
     POOL_INFO.save(deps.storage, &lp_token, &pool)?;
@@ -1176,6 +1615,9 @@ pub fn withdraw(
     if user.amount < amount {
         return Err(ContractError::BalanceTooSmall {});
     }
+    if user.amount - locked_amount(&user, env.block.time.seconds())? < amount {
+        return Err(ContractError::LpTokensLocked {});
+    }
 
     let cfg = CONFIG.load(deps.storage)?;
     let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
@@ -1183,32 +1625,282 @@ pub fn withdraw(
     accumulate_rewards_per_share(&deps.querier, &env, &lp_token, &mut pool, &cfg)?;
 
     // Send pending rewards to the user
-    let mut send_rewards_msgs = send_pending_rewards(deps.as_ref(), &cfg, &pool, &user, &account)?;
+    let mut send_rewards_msgs = send_pending_rewards(
+        deps.as_ref(),
+        &cfg,
+        &pool,
+        &user,
+        &account,
+        env.block.time.seconds(),
+    )?;
+    send_rewards_msgs.push(lp_transfer_msg(&pool, &lp_token, &account, amount)?);
 
-    // Instantiate the transfer call for the LP token
-    let transfer_msg = match &pool.reward_proxy {
+    // Update user's balance
+    let updated_amount = user.amount.checked_sub(amount)?;
+    let mut user = update_user_balance(user, &pool, updated_amount, env.block.time.seconds())?;
+    let lp_balance = query_lp_balance(deps.as_ref(), &env.contract.address, &lp_token, &pool)?;
+
+    update_virtual_amount(
+        deps.querier,
+        &cfg,
+        &mut pool,
+        &mut user,
+        &account,
+        lp_balance,
+        env.block.time.seconds(),
+    )?;
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    if !user.amount.is_zero() || !user.pending_incentives.is_empty() {
+        USER_INFO.save(deps.storage, (&lp_token, &account), &user)?;
+    } else {
+        USER_INFO.remove(deps.storage, (&lp_token, &account));
+    }
+
+    Ok(Response::new()
+        .add_messages(send_rewards_msgs)
+        .add_attribute("action", "withdraw")
+        .add_attribute("amount", amount))
+}
+
+/// Returns a message which sends LP tokens staked in the generator (or in its reward proxy) to the recipient.
+///
+/// * **pool** generator associated with the `lp_token`.
+///
+/// * **recipient** address which receives the LP tokens.
+fn lp_transfer_msg(
+    pool: &PoolInfo,
+    lp_token: &Addr,
+    recipient: &Addr,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    let msg = match &pool.reward_proxy {
         Some(proxy) => WasmMsg::Execute {
             contract_addr: proxy.to_string(),
             funds: vec![],
             msg: to_binary(&ProxyExecuteMsg::Withdraw {
-                account: account.to_string(),
+                account: recipient.to_string(),
                 amount,
             })?,
-        },
-        None => WasmMsg::Execute {
-            contract_addr: lp_token.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: account.to_string(),
-                amount,
-            })?,
-            funds: vec![],
-        },
+        }
+        .into(),
+        None => Asset {
+            info: lp_token_asset_info(lp_token),
+            amount,
+        }
+        .into_msg(recipient)?,
+    };
+
+    Ok(msg)
+}
+
+/// Sets the lockup tiers available to stakers. Existing lockup positions keep their terms.
+///
+/// * **tiers** new list of lockup tiers.
+fn set_lockup_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<LockupTier>,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut durations = HashSet::new();
+    if tiers.len() > MAX_LOCKUP_TIERS
+        || !tiers.iter().all(|tier| {
+            tier.duration > 0
+                && tier.multiplier >= Decimal::one()
+                && tier.early_unlock_penalty <= Decimal::one()
+                && durations.insert(tier.duration)
+        })
+    {
+        return Err(ContractError::InvalidLockupTiers {});
+    }
+
+    cfg.lockup_tiers = tiers;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "set_lockup_tiers"))
+}
+
+/// Sets the fee charged for scheduling a new external incentive reward stream.
+///
+/// * **fee_info** the fee and its receiver. `None` removes the fee.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn set_incentivization_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_info: Option<IncentivizationFeeInfo>,
+) -> Result<Response, ContractError> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(fee_info) = &fee_info {
+        deps.api.addr_validate(&fee_info.fee_receiver)?;
+        if fee_info.fee.amount.is_zero() {
+            return Err(
+                StdError::generic_err("Incentivization fee must be greater than zero!").into(),
+            );
+        }
+    }
+
+    cfg.incentivization_fee = fee_info;
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "set_incentivization_fee"))
+}
+
+/// Locks deposited LP tokens for the duration of a lockup tier. The user's virtual amount is
+/// boosted by the tier multiplier until the lockup ends.
+///
+/// * **lp_token** LP token whose deposit we lock.
+///
+/// * **account** owner of the deposit.
+///
+/// * **amount** amount of deposited LP tokens to lock.
+///
+/// * **duration** duration of the lockup tier in seconds.
+fn lock(
+    deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+    account: Addr,
+    amount: Uint128,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    let tier = cfg
+        .lockup_tiers
+        .iter()
+        .find(|tier| tier.duration == duration)
+        .cloned()
+        .ok_or(ContractError::LockupTierNotFound { duration })?;
+
+    let user_info = USER_INFO
+        .compatible_load(deps.storage, (&lp_token, &account))
+        .unwrap_or_default();
+
+    if amount.is_zero() || user_info.amount - locked_amount(&user_info, now)? < amount {
+        return Err(ContractError::InvalidLockupAmount {});
+    }
+    let active_lockups = user_info
+        .lockups
+        .iter()
+        .filter(|lockup| lockup.end_time > now)
+        .count();
+    if active_lockups >= MAX_USER_LOCKUPS {
+        return Err(ContractError::LockupsLimitExceeded {});
+    }
+
+    // Otherwise the position couldn't be released early
+    if !tier.early_unlock_penalty.is_zero() {
+        let factory_config: FactoryConfigResponse = deps
+            .querier
+            .query_wasm_smart(&cfg.factory, &FactoryQueryMsg::Config {})?;
+        if factory_config.fee_address.is_none() {
+            return Err(ContractError::MakerNotSet {});
+        }
+    }
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    accumulate_rewards_per_share(&deps.querier, &env, &lp_token, &mut pool, &cfg)?;
+
+    let send_rewards_msgs =
+        send_pending_rewards(deps.as_ref(), &cfg, &pool, &user_info, &account, now)?;
+
+    // Update user's amount
+    let user_amount = user_info.amount;
+    let mut user_info = update_user_balance(user_info, &pool, user_amount, now)?;
+    user_info.lockups.push(LockupPosition {
+        amount,
+        multiplier: tier.multiplier,
+        early_unlock_penalty: tier.early_unlock_penalty,
+        end_time: now + tier.duration,
+        boost: Uint128::zero(),
+    });
+
+    let lp_balance = query_lp_balance(deps.as_ref(), &env.contract.address, &lp_token, &pool)?;
+
+    // Update user's virtual amount
+    update_virtual_amount(
+        deps.querier,
+        &cfg,
+        &mut pool,
+        &mut user_info,
+        &account,
+        lp_balance,
+        now,
+    )?;
+
+    USER_INFO.save(deps.storage, (&lp_token, &account), &user_info)?;
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_messages(send_rewards_msgs)
+        .add_attribute("action", "lock")
+        .add_attribute("amount", amount)
+        .add_attribute("end_time", (now + tier.duration).to_string()))
+}
+
+/// Removes an active lockup position before it ends. The early unlock penalty is deducted from
+/// the user's deposit and sent to the Maker.
+///
+/// * **lp_token** LP token whose lockup we remove.
+///
+/// * **account** user whose lockup we remove.
+///
+/// * **index** index of the lockup position as returned by the [`QueryMsg::UserLockups`] query.
+fn early_unlock(
+    deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+    account: Addr,
+    index: u32,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    let user = USER_INFO
+        .compatible_load(deps.storage, (&lp_token, &account))
+        .unwrap_or_default();
+
+    let position = match user.lockups.get(index as usize) {
+        Some(position) if position.end_time > now => position.clone(),
+        _ => return Err(ContractError::LockupNotFound {}),
     };
-    send_rewards_msgs.push(transfer_msg);
+    let penalty = position.amount * position.early_unlock_penalty;
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    accumulate_rewards_per_share(&deps.querier, &env, &lp_token, &mut pool, &cfg)?;
+
+    // Rewards are settled with the lockups the virtual amount was calculated with
+    let mut messages = send_pending_rewards(deps.as_ref(), &cfg, &pool, &user, &account, now)?;
+
+    if !penalty.is_zero() {
+        let factory_config: FactoryConfigResponse = deps
+            .querier
+            .query_wasm_smart(&cfg.factory, &FactoryQueryMsg::Config {})?;
+        let maker = factory_config
+            .fee_address
+            .ok_or(ContractError::MakerNotSet {})?;
+        messages.push(lp_transfer_msg(&pool, &lp_token, &maker, penalty)?);
+    }
 
     // Update user's balance
-    let updated_amount = user.amount.checked_sub(amount)?;
-    let mut user = update_user_balance(user, &pool, updated_amount)?;
+    let updated_amount = user.amount.checked_sub(penalty)?;
+    let mut user = update_user_balance(user, &pool, updated_amount, now)?;
+    user.lockups.remove(index as usize);
     let lp_balance = query_lp_balance(deps.as_ref(), &env.contract.address, &lp_token, &pool)?;
 
     update_virtual_amount(
@@ -1218,36 +1910,351 @@ pub fn withdraw(
         &mut user,
         &account,
         lp_balance,
+        now,
     )?;
 
     POOL_INFO.save(deps.storage, &lp_token, &pool)?;
 
-    if !user.amount.is_zero() {
+    if !user.amount.is_zero() || !user.pending_incentives.is_empty() {
         USER_INFO.save(deps.storage, (&lp_token, &account), &user)?;
     } else {
         USER_INFO.remove(deps.storage, (&lp_token, &account));
     }
 
     Ok(Response::new()
-        .add_messages(send_rewards_msgs)
-        .add_attribute("action", "withdraw")
-        .add_attribute("amount", amount))
+        .add_messages(messages)
+        .add_attribute("action", "early_unlock")
+        .add_attribute("amount", position.amount)
+        .add_attribute("penalty", penalty))
+}
+
+/// Schedules an external reward stream for a generator. The remaining rewards of an active stream
+/// with the same reward token are added to the new stream. Anyone can top up an active stream, its
+/// end time is never moved backwards. Streams occupying a new or an ended incentive slot are
+/// charged the incentivization fee, if set.
+///
+/// * **lp_token** LP token whose generator we incentivize.
+///
+/// * **reward** reward asset and amount.
+///
+/// * **duration** duration of the reward stream in seconds.
+pub fn incentivize(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    reward: Asset,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
+    let cfg = CONFIG.load(deps.storage)?;
+
+    reward.info.check(deps.api)?;
+    if reward.amount.is_zero() || duration == 0 {
+        return Err(ContractError::InvalidIncentive {});
+    }
+
+    if duration > MAX_INCENTIVE_DURATION {
+        return Err(ContractError::IncentiveDurationTooLong {
+            max: MAX_INCENTIVE_DURATION,
+        });
+    }
+
+    if !cfg.incentive_tokens.contains(&reward.info) {
+        return Err(ContractError::IncentiveTokenNotWhitelisted {
+            token: reward.info.to_string(),
+        });
+    }
+
+    if cfg.blocked_tokens_list.contains(&reward.info) {
+        return Err(StdError::generic_err(format!("Token {} is blocked!", reward.info)).into());
+    }
+
+    if !POOL_INFO.has(deps.storage, &lp_token) {
+        create_pool(deps.branch(), &env, &lp_token, &cfg)?;
+    }
+
+    // LP tokens staked in the Generator can't be used as rewards
+    let reward_addr = match &reward.info {
+        AssetInfo::Token { contract_addr } => contract_addr.clone(),
+        AssetInfo::NativeToken { denom } => Addr::unchecked(denom),
+    };
+    if POOL_INFO.has(deps.storage, &reward_addr) {
+        return Err(StdError::generic_err("LP tokens can't be used as rewards!").into());
+    }
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let now = env.block.time.seconds();
+    accumulate_incentive_rewards(&mut pool, now)?;
+
+    // Ended streams don't occupy incentive slots
+    let active_streams = pool
+        .incentives
+        .iter()
+        .filter(|incentive| incentive.end_time > now && incentive.reward != reward.info)
+        .count();
+    if active_streams >= MAX_POOL_INCENTIVES {
+        return Err(ContractError::IncentivesLimitExceeded {});
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let new_stream = match pool
+        .incentives
+        .iter_mut()
+        .find(|incentive| incentive.reward == reward.info)
+    {
+        Some(incentive) if incentive.end_time > now => {
+            // The creator is kept as undistributed rewards are returned to them
+            incentive.remaining = incentive.remaining.checked_add(reward.amount)?;
+            incentive.end_time = incentive.end_time.max(now + duration);
+            incentive.last_update_time = now;
+            false
+        }
+        Some(incentive) => {
+            // Undistributed rewards of the ended stream are returned to its creator
+            if !incentive.remaining.is_zero() {
+                messages.push(
+                    Asset {
+                        info: incentive.reward.clone(),
+                        amount: incentive.remaining,
+                    }
+                    .into_msg(&incentive.creator)?,
+                );
+            }
+
+            incentive.creator = info.sender.clone();
+            incentive.remaining = reward.amount;
+            incentive.end_time = now + duration;
+            incentive.last_update_time = now;
+            true
+        }
+        None => {
+            pool.incentives.push(IncentiveInfo {
+                reward: reward.info.clone(),
+                creator: info.sender.clone(),
+                remaining: reward.amount,
+                end_time: now + duration,
+                last_update_time: now,
+                reward_global_index: Decimal::zero(),
+            });
+            true
+        }
+    };
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    // The fee is deducted from the sent funds before the native reward is checked
+    let mut funds = info.funds.clone();
+    if let Some(fee_info) = cfg.incentivization_fee.filter(|_| new_stream) {
+        match funds
+            .iter_mut()
+            .find(|coin| coin.denom == fee_info.fee.denom && coin.amount >= fee_info.fee.amount)
+        {
+            Some(coin) => coin.amount -= fee_info.fee.amount,
+            None => {
+                return Err(ContractError::IncentivizationFeeExpected {
+                    fee: fee_info.fee.to_string(),
+                })
+            }
+        }
+        funds.retain(|coin| !coin.amount.is_zero());
+
+        messages.push(
+            BankMsg::Send {
+                to_address: fee_info.fee_receiver,
+                amount: vec![fee_info.fee],
+            }
+            .into(),
+        );
+    }
+
+    match &reward.info {
+        AssetInfo::NativeToken { .. } => reward.assert_sent_native_token_balance(&MessageInfo {
+            sender: info.sender.clone(),
+            funds,
+        })?,
+        AssetInfo::Token { contract_addr } => messages.push(
+            wasm_execute(
+                contract_addr,
+                &Cw20ExecuteMsg::TransferFrom {
+                    owner: info.sender.to_string(),
+                    recipient: env.contract.address.to_string(),
+                    amount: reward.amount,
+                },
+                vec![],
+            )?
+            .into(),
+        ),
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        attr("action", "incentivize"),
+        attr("lp_token", lp_token),
+        attr("reward", reward.to_string()),
+        attr("duration", duration.to_string()),
+    ]))
+}
+
+/// Sends the specified pending external incentive rewards to the staker. [`claim_rewards`] pays out
+/// all of them at once while here every reward token is claimed separately,
+/// so a token whose transfer fails doesn't block the others.
+///
+/// * **lp_token** LP token of the pool.
+///
+/// * **rewards** reward tokens to claim.
+fn claim_incentives(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    rewards: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let mut user = USER_INFO.compatible_load(deps.storage, (&lp_token, &info.sender))?;
+
+    accumulate_incentive_rewards(&mut pool, env.block.time.seconds())?;
+    checkpoint_user_incentives(&pool, &mut user, env.block.time.seconds())?;
+
+    let mut messages = vec![];
+    for reward in rewards {
+        if let Some(index) = user
+            .pending_incentives
+            .iter()
+            .position(|pending| pending.info == reward)
+        {
+            messages.push(
+                user.pending_incentives
+                    .remove(index)
+                    .into_msg(&info.sender)?,
+            );
+        }
+    }
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    if !user.amount.is_zero() || !user.pending_incentives.is_empty() {
+        USER_INFO.save(deps.storage, (&lp_token, &info.sender), &user)?;
+    } else {
+        USER_INFO.remove(deps.storage, (&lp_token, &info.sender));
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "claim_incentives"))
+}
+
+/// Returns the rewards of an ended reward stream which were not distributed because the pool had
+/// no stakers to the stream creator.
+///
+/// * **lp_token** LP token of the pool.
+///
+/// * **reward** reward token of the stream.
+fn refund_incentive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    lp_token: String,
+    reward: AssetInfo,
+) -> Result<Response, ContractError> {
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
+    let now = env.block.time.seconds();
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    accumulate_incentive_rewards(&mut pool, now)?;
+
+    let incentive = pool
+        .incentives
+        .iter_mut()
+        .find(|incentive| incentive.reward == reward)
+        .ok_or(ContractError::IncentiveNotFound {})?;
+
+    if incentive.creator != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if incentive.end_time > now || incentive.remaining.is_zero() {
+        return Err(ContractError::NothingToRefund {});
+    }
+
+    let refund = Asset {
+        info: reward,
+        amount: incentive.remaining,
+    };
+    incentive.remaining = Uint128::zero();
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_message(refund.into_msg(&info.sender)?)
+        .add_attribute("action", "refund_incentive")
+        .add_attribute("refund", refund.to_string()))
+}
+
+/// Adds or removes tokens which can be used as external incentive rewards.
+///
+/// * **add** tokens to add.
+///
+/// * **remove** tokens to remove.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_incentive_tokens(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Option<Vec<AssetInfo>>,
+    remove: Option<Vec<AssetInfo>>,
+) -> Result<Response, ContractError> {
+    if add.is_none() && remove.is_none() {
+        return Err(ContractError::Std(StdError::generic_err(
+            "Need to provide add or remove parameters",
+        )));
+    }
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(asset_infos) = remove {
+        cfg.incentive_tokens
+            .retain(|asset_info| !asset_infos.contains(asset_info));
+    }
+
+    if let Some(asset_infos) = add {
+        for asset_info in asset_infos {
+            asset_info.check(deps.api)?;
+            if !cfg.incentive_tokens.contains(&asset_info) {
+                cfg.incentive_tokens.push(asset_info);
+            }
+        }
+    }
+
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new().add_attribute("action", "update_incentive_tokens"))
 }
+
 /// Withdraw LP tokens without caring about rewards. TO BE USED IN EMERGENCY SITUATIONS ONLY.
 ///
 /// * **lp_token** LP token to withdraw.
 pub fn emergency_withdraw(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     lp_token: String,
 ) -> Result<Response, ContractError> {
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
 
     let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
     let user = USER_INFO.compatible_load(deps.storage, (&lp_token, &info.sender))?;
+    if !locked_amount(&user, env.block.time.seconds())?.is_zero() {
+        return Err(ContractError::LpTokensLocked {});
+    }
 
     // Instantiate the transfer call for the LP token
-    let transfer_msg;
+    let transfer_msg: CosmosMsg;
     if let Some(proxy) = &pool.reward_proxy {
         let accumulated_proxy_rewards: HashMap<_, _> = accumulate_pool_proxy_rewards(&pool, &user)?
             .into_iter()
@@ -1275,16 +2282,14 @@ pub fn emergency_withdraw(
                 amount: user.amount,
             })?,
             funds: vec![],
-        };
+        }
+        .into();
     } else {
-        transfer_msg = WasmMsg::Execute {
-            contract_addr: lp_token.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: info.sender.to_string(),
-                amount: user.amount,
-            })?,
-            funds: vec![],
-        };
+        transfer_msg = Asset {
+            info: lp_token_asset_info(&lp_token),
+            amount: user.amount,
+        }
+        .into_msg(&info.sender)?;
     }
 
     // Change the user's balance
@@ -1314,7 +2319,7 @@ fn send_orphan_proxy_rewards(
         return Err(ContractError::Unauthorized {});
     };
 
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
     let recipient = deps.api.addr_validate(&recipient)?;
 
     let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
@@ -1406,7 +2411,7 @@ fn migrate_proxy(
     lp_token: String,
     new_proxy: String,
 ) -> Result<Response, ContractError> {
-    let lp_addr = deps.api.addr_validate(&lp_token)?;
+    let lp_addr = validate_lp_token(deps.api, &lp_token)?;
     let new_proxy_addr = deps.api.addr_validate(&new_proxy)?;
 
     let cfg = CONFIG.load(deps.storage)?;
@@ -1559,7 +2564,7 @@ fn move_to_proxy(
     lp_token: String,
     proxy: String,
 ) -> Result<Response, ContractError> {
-    let lp_addr = deps.api.addr_validate(&lp_token)?;
+    let lp_addr = validate_lp_token(deps.api, &lp_token)?;
     let proxy_addr = deps.api.addr_validate(&proxy)?;
 
     let cfg = CONFIG.load(deps.storage)?;
@@ -1569,6 +2574,11 @@ fn move_to_proxy(
         return Err(ContractError::Unauthorized {});
     }
 
+    // Reward proxies accept CW20 LP tokens only
+    if is_token_factory_denom(lp_addr.as_str()) {
+        return Err(ContractError::RewardProxyNotAllowed {});
+    }
+
     if !POOL_INFO.has(deps.storage, &lp_addr) {
         create_pool(deps.branch(), &env, &lp_addr, &cfg)?;
     }
@@ -1636,6 +2646,13 @@ fn move_to_proxy(
 ///
 /// * **QueryMsg::SimulateFutureReward { lp_token, future_block }** Returns the amount of token rewards a generator will
 /// distribute up to a future block.
+///
+/// * **QueryMsg::SimulateFutureRewardByTime { lp_token, future_time }** Returns the amount of token rewards a generator will
+/// distribute up to a future time in the time based emission mode.
+///
+/// * **QueryMsg::UserLockups { lp_token, user }** Returns the lockup positions of a user in a specific generator.
+///
+/// * **QueryMsg::ScheduledAllocations { start_after, limit }** Returns the upcoming allocation point changes.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -1672,13 +2689,33 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
             future_block,
         } => {
             let cfg = CONFIG.load(deps.storage)?;
-            let alloc_point =
-                get_alloc_point(&cfg.active_pools, &deps.api.addr_validate(&lp_token)?);
+            if cfg.tokens_per_second.is_some() {
+                return Err(ContractError::TimeBasedEmissions {});
+            }
 
-            Ok(to_binary(&calculate_rewards(
-                future_block - env.block.height,
-                &alloc_point,
+            Ok(to_binary(&simulate_rewards(
+                deps,
+                &cfg,
+                &validate_lp_token(deps.api, &lp_token)?,
+                env.block.height,
+                future_block,
+            )?)?)
+        }
+        QueryMsg::SimulateFutureRewardByTime {
+            lp_token,
+            future_time,
+        } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if cfg.tokens_per_second.is_none() {
+                return Err(ContractError::BlockBasedEmissions {});
+            }
+
+            Ok(to_binary(&simulate_rewards(
+                deps,
                 &cfg,
+                &validate_lp_token(deps.api, &lp_token)?,
+                env.block.time.seconds(),
+                future_time,
             )?)?)
         }
         QueryMsg::BlockedTokensList {} => {
@@ -1699,12 +2736,18 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractErro
                 .keys(deps.storage, None, None, Order::Ascending)
                 .collect::<Result<Vec<Addr>, StdError>>()?,
         )?),
+        QueryMsg::UserLockups { lp_token, user } => {
+            Ok(to_binary(&query_user_lockups(deps, lp_token, user)?)?)
+        }
+        QueryMsg::ScheduledAllocations { start_after, limit } => Ok(to_binary(
+            &query_scheduled_allocations(deps, start_after, limit)?,
+        )?),
     }
 }
 
 /// Return total virtual supply by pool
 pub fn total_virtual_supply(deps: Deps, generator: String) -> Result<Uint128, ContractError> {
-    let generator_addr = deps.api.addr_validate(&generator)?;
+    let generator_addr = validate_lp_token(deps.api, &generator)?;
     let pool = POOL_INFO.load(deps.storage, &generator_addr)?;
 
     Ok(pool.total_virtual_supply)
@@ -1716,7 +2759,7 @@ pub fn total_virtual_supply(deps: Deps, generator: String) -> Result<Uint128, Co
 ///
 /// * **user** user whose balance we query.
 pub fn query_deposit(deps: Deps, lp_token: String, user: String) -> Result<Uint128, ContractError> {
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
     let user = deps.api.addr_validate(&user)?;
 
     let user_info = USER_INFO
@@ -1735,7 +2778,7 @@ pub fn query_virtual_amount(
     lp_token: String,
     user: String,
 ) -> Result<Uint128, ContractError> {
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
     let user = deps.api.addr_validate(&user)?;
 
     let user_info = USER_INFO
@@ -1744,6 +2787,26 @@ pub fn query_virtual_amount(
     Ok(user_info.virtual_amount)
 }
 
+/// Returns the lockup positions of a user in a specific generator. Ended positions are listed
+/// until the user's next interaction with the generator.
+///
+/// * **lp_token** LP token staked by the user.
+///
+/// * **user** user whose lockups we query.
+pub fn query_user_lockups(
+    deps: Deps,
+    lp_token: String,
+    user: String,
+) -> Result<Vec<LockupPosition>, ContractError> {
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
+    let user = deps.api.addr_validate(&user)?;
+
+    let user_info = USER_INFO
+        .compatible_load(deps.storage, (&lp_token, &user))
+        .unwrap_or_default();
+    Ok(user_info.lockups)
+}
+
 /// Calculates and returns the pending token rewards for a specific user.
 ///
 /// * **lp_token** LP token staked by the user whose pending rewards we calculate.
@@ -1757,14 +2820,19 @@ pub fn pending_token(
 ) -> Result<PendingTokenResponse, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
 
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
     let user = deps.api.addr_validate(&user)?;
 
-    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
     let user_info = USER_INFO
         .compatible_load(deps.storage, (&lp_token, &user))
         .unwrap_or_default();
 
+    accumulate_incentive_rewards(&mut pool, env.block.time.seconds())?;
+    let mut incentives_checkpoint = user_info.clone();
+    checkpoint_user_incentives(&pool, &mut incentives_checkpoint, env.block.time.seconds())?;
+    let pending_incentives = incentives_checkpoint.pending_incentives;
+
     let mut pending_on_proxy = None;
 
     if let Some(proxy) = &pool.reward_proxy {
@@ -1803,25 +2871,27 @@ pub fn pending_token(
     let lp_supply = pool.total_virtual_supply;
 
     let mut acc_per_share = pool.reward_global_index;
-    if env.block.height > pool.last_reward_block.u64() && !lp_supply.is_zero() {
-        let alloc_point = get_alloc_point(&cfg.active_pools, &lp_token);
-
-        let token_rewards = calculate_rewards(
-            env.block.height - pool.last_reward_block.u64(),
-            &alloc_point,
-            &cfg,
-        )?;
+    let elapsed = elapsed_since_last_reward(&env, &pool, &cfg);
+    if elapsed > 0 && !lp_supply.is_zero() {
+        let now = block_height_or_time(&env, &cfg);
+        let token_rewards = simulate_rewards(deps, &cfg, &lp_token, now - elapsed, now)?;
         let share = Decimal::from_ratio(token_rewards, lp_supply);
         acc_per_share = pool.reward_global_index.checked_add(share)?;
     }
 
     // we should calculate rewards by virtual amount
-    let pending = (acc_per_share - user_info.reward_user_index)
-        .checked_mul_uint128(user_info.virtual_amount)?;
+    let virtual_amount = effective_virtual_amount(
+        &user_info,
+        user_info.reward_checkpoint_time,
+        env.block.time.seconds(),
+    )?;
+    let pending =
+        (acc_per_share - user_info.reward_user_index).checked_mul_uint128(virtual_amount)?;
 
     Ok(PendingTokenResponse {
         pending,
         pending_on_proxy,
+        pending_incentives,
     })
 }
 
@@ -1832,7 +2902,7 @@ pub fn pending_token(
 fn query_reward_info(deps: Deps, lp_token: String) -> Result<RewardInfoResponse, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
 
     let pool = POOL_INFO.load(deps.storage, &lp_token)?;
 
@@ -1858,7 +2928,7 @@ fn query_orphan_proxy_rewards(
     deps: Deps,
     lp_token: String,
 ) -> Result<Vec<(AssetInfo, Uint128)>, ContractError> {
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
 
     let pool = POOL_INFO.load(deps.storage, &lp_token)?;
     if pool.reward_proxy.is_some() {
@@ -1887,7 +2957,7 @@ fn query_pool_info(
 ) -> Result<PoolInfoResponse, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
-    let lp_token = deps.api.addr_validate(&lp_token)?;
+    let lp_token = validate_lp_token(deps.api, &lp_token)?;
     let pool = POOL_INFO.load(deps.storage, &lp_token)?;
 
     let lp_supply: Uint128;
@@ -1913,23 +2983,18 @@ fn query_pool_info(
             }
         }
         None => {
-            lp_supply = query_token_balance(
-                &deps.querier,
-                lp_token.clone(),
-                env.contract.address.clone(),
-            )?;
+            lp_supply = lp_token_asset_info(&lp_token)
+                .query_pool(&deps.querier, env.contract.address.clone())?;
         }
     }
 
     let alloc_point = get_alloc_point(&config.active_pools, &lp_token);
 
     // Calculate pending ASTRO rewards
-    if env.block.height > pool.last_reward_block.u64() && !lp_supply.is_zero() {
-        pending_astro_rewards = calculate_rewards(
-            env.block.height - pool.last_reward_block.u64(),
-            &alloc_point,
-            &config,
-        )?;
+    let elapsed = elapsed_since_last_reward(&env, &pool, &config);
+    if elapsed > 0 && !lp_supply.is_zero() {
+        let now = block_height_or_time(&env, &config);
+        pending_astro_rewards = simulate_rewards(deps, &config, &lp_token, now - elapsed, now)?;
     }
 
     // Calculate ASTRO tokens being distributed per block to this LP token pool
@@ -1939,11 +3004,25 @@ fn query_pool_info(
         .checked_div(config.total_alloc_point)
         .unwrap_or_else(|_| Uint128::zero());
 
+    // Calculate ASTRO tokens being distributed per second to this LP token pool
+    let astro_tokens_per_second = match config.tokens_per_second {
+        Some(tokens_per_second) => Some(
+            tokens_per_second
+                .checked_mul(alloc_point)?
+                .checked_div(config.total_alloc_point)
+                .unwrap_or_else(|_| Uint128::zero()),
+        ),
+        None => None,
+    };
+
     Ok(PoolInfoResponse {
         alloc_point,
         astro_tokens_per_block,
         last_reward_block: pool.last_reward_block.u64(),
         current_block: env.block.height,
+        astro_tokens_per_second,
+        last_reward_time: pool.last_reward_time.map(|time| time.u64()),
+        current_time: env.block.time.seconds(),
         pending_astro_rewards,
         reward_proxy: pool.reward_proxy,
         pending_proxy_rewards: pending_on_proxy,
@@ -1955,6 +3034,7 @@ fn query_pool_info(
         orphan_proxy_rewards: pool.orphan_proxy_rewards.inner_ref().clone(),
         lp_supply,
         global_reward_index: pool.reward_global_index,
+        incentives: pool.incentives,
     })
 }
 
@@ -1972,7 +3052,7 @@ pub fn query_list_of_stakers(
     start_after: Option<String>,
     limit: Option<u32>,
 ) -> Result<Vec<StakerResponse>, ContractError> {
-    let lp_addr = deps.api.addr_validate(&lp_token)?;
+    let lp_addr = validate_lp_token(deps.api, &lp_token)?;
     let mut active_stakers: Vec<StakerResponse> = vec![];
 
     if POOL_INFO.has(deps.storage, &lp_addr) {
@@ -1999,12 +3079,45 @@ pub fn query_list_of_stakers(
     Ok(active_stakers)
 }
 
+/// Returns the upcoming allocation point changes ordered by their effective block height or timestamp.
+///
+/// * **start_after** optional field that specifies whether the function should return a list of schedules starting from a
+/// specific block height or timestamp onward.
+///
+/// * **limit** max amount of schedules to return.
+pub fn query_scheduled_allocations(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Vec<ScheduledAllocation>, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    SCHEDULED_ALLOCATIONS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (effective_at, pools) = item?;
+            Ok(ScheduledAllocation {
+                effective_at,
+                pools,
+            })
+        })
+        .collect()
+}
+
 /// Calculates and returns the amount of accrued rewards since the last reward checkpoint for a specific generator.
 ///
+/// * **n_periods** the number of blocks (or seconds in the time based emission mode) since the last reward checkpoint.
+///
 /// * **alloc_point** allocation points for specific generator.
-pub fn calculate_rewards(n_blocks: u64, alloc_point: &Uint128, cfg: &Config) -> StdResult<Uint128> {
-    let r = Uint128::from(n_blocks)
-        .checked_mul(cfg.tokens_per_block)?
+pub fn calculate_rewards(
+    n_periods: u64,
+    alloc_point: &Uint128,
+    cfg: &Config,
+) -> StdResult<Uint128> {
+    let r = Uint128::from(n_periods)
+        .checked_mul(cfg.tokens_per_second.unwrap_or(cfg.tokens_per_block))?
         .checked_mul(*alloc_point)?
         .checked_div(cfg.total_alloc_point)
         .unwrap_or_else(|_| Uint128::zero());
@@ -2012,6 +3125,87 @@ pub fn calculate_rewards(n_blocks: u64, alloc_point: &Uint128, cfg: &Config) ->
     Ok(r)
 }
 
+/// Simulates the amount of ASTRO a generator receives from `from` up to `until` (block heights or
+/// timestamps in the time based emission mode). Scheduled allocation points are taken into account
+/// from the moment they take effect, whether they have been applied yet or not.
+///
+/// * **cfg** config with the active allocation points.
+///
+/// * **lp_token** LP token of the generator.
+fn simulate_rewards(
+    deps: Deps,
+    cfg: &Config,
+    lp_token: &Addr,
+    from: u64,
+    until: u64,
+) -> Result<Uint128, ContractError> {
+    let schedules = SCHEDULED_ALLOCATIONS
+        .range(
+            deps.storage,
+            None,
+            Some(Bound::inclusive(until)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let blacklisted_pair_types: Vec<PairType> = if schedules.is_empty() {
+        vec![]
+    } else {
+        deps.querier
+            .query_wasm_smart(&cfg.factory, &FactoryQueryMsg::BlacklistedPairTypes {})?
+    };
+
+    let mut cfg = cfg.clone();
+    let mut rewards = Uint128::zero();
+    let mut current = from;
+    for (effective_at, pools) in schedules {
+        if effective_at > current {
+            let alloc_point = get_alloc_point(&cfg.active_pools, lp_token);
+            rewards = rewards.checked_add(calculate_rewards(
+                effective_at - current,
+                &alloc_point,
+                &cfg,
+            )?)?;
+            current = effective_at;
+        }
+
+        cfg.active_pools =
+            filter_scheduled_pools(deps.querier, &cfg, &blacklisted_pair_types, pools);
+        cfg.total_alloc_point = cfg
+            .active_pools
+            .iter()
+            .map(|(_, alloc_point)| alloc_point)
+            .sum();
+    }
+
+    if until > current {
+        let alloc_point = get_alloc_point(&cfg.active_pools, lp_token);
+        rewards = rewards.checked_add(calculate_rewards(until - current, &alloc_point, &cfg)?)?;
+    }
+
+    Ok(rewards)
+}
+
+/// Returns the number of blocks (or seconds in the time based emission mode) passed since the last
+/// reward checkpoint of the generator.
+///
+/// * **pool** generator whose last reward checkpoint we use.
+pub fn elapsed_since_last_reward(env: &Env, pool: &PoolInfo, cfg: &Config) -> u64 {
+    match (cfg.tokens_per_second, pool.last_reward_time) {
+        (Some(_), Some(last_reward_time)) => env
+            .block
+            .time
+            .seconds()
+            .saturating_sub(last_reward_time.u64()),
+        // The generator was inactive when emissions became time based
+        (Some(_), None) => 0,
+        (None, _) => env
+            .block
+            .height
+            .saturating_sub(pool.last_reward_block.u64()),
+    }
+}
+
 /// Gets allocation point of the pool.
 ///
 /// * **pools** is a vector of set that contains LP token address and allocation point.
@@ -2061,6 +3255,10 @@ pub fn create_pool(
             has_asset_rewards: false,
             reward_global_index: Decimal::zero(),
             total_virtual_supply: Default::default(),
+            last_reward_time: cfg
+                .tokens_per_second
+                .map(|_| cfg.start_time.max(Uint64::from(env.block.time.seconds()))),
+            incentives: vec![],
         },
     )?;
 
@@ -2083,6 +3281,8 @@ pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response,
             "2.2.0" | "2.2.0+togrb" => {
                 migration::migrate_configs_from_v220(&mut deps, &msg)?;
             }
+            // Fields added since v2.3.0 have defaults thus the stored state is read as is
+            "2.3.0" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
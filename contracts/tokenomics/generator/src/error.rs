@@ -45,6 +45,66 @@ pub enum ContractError {
 
     #[error("You can not withdraw 0 LP tokens.")]
     ZeroWithdraw {},
+
+    #[error("ASTRO emissions are time based!")]
+    TimeBasedEmissions {},
+
+    #[error("ASTRO emissions are block based!")]
+    BlockBasedEmissions {},
+
+    #[error("Incentive amount and duration must be greater than zero!")]
+    InvalidIncentive {},
+
+    #[error("Incentive duration must not exceed {max} seconds!")]
+    IncentiveDurationTooLong { max: u64 },
+
+    #[error("Incentivization fee {fee} is expected for a new reward stream!")]
+    IncentivizationFeeExpected { fee: String },
+
+    #[error("Maximum number of pool incentives exceeded!")]
+    IncentivesLimitExceeded {},
+
+    #[error("Token {token} can't be used as an incentive reward!")]
+    IncentiveTokenNotWhitelisted { token: String },
+
+    #[error("Incentive reward stream not found!")]
+    IncentiveNotFound {},
+
+    #[error("The reward stream is active or has no undistributed rewards!")]
+    NothingToRefund {},
+
+    #[error("Exactly one token factory LP token must be sent!")]
+    InvalidLpFunds {},
+
+    #[error("Invalid lockup tiers!")]
+    InvalidLockupTiers {},
+
+    #[error("Lockup tier with duration {duration} not found!")]
+    LockupTierNotFound { duration: u64 },
+
+    #[error("Lockup amount must be greater than zero and not exceed the unlocked deposit!")]
+    InvalidLockupAmount {},
+
+    #[error("Maximum number of active lockups exceeded!")]
+    LockupsLimitExceeded {},
+
+    #[error("Active lockup not found!")]
+    LockupNotFound {},
+
+    #[error("LP tokens are locked!")]
+    LpTokensLocked {},
+
+    #[error("Maker address is not set in the factory!")]
+    MakerNotSet {},
+
+    #[error("Scheduled allocations must take effect in the future!")]
+    InvalidEffectiveAt {},
+
+    #[error("Scheduled allocations must be applied before ASTRO emissions become time based!")]
+    ScheduledAllocationsPending {},
+
+    #[error("Scheduled allocations at {effective_at} not found!")]
+    ScheduledAllocationNotFound { effective_at: u64 },
 }
 
 impl From<OverflowError> for ContractError {
@@ -129,6 +129,11 @@ pub fn migrate_configs_from_v200(deps: &mut DepsMut, msg: &MigrateMsg) -> StdRes
         guardian: cfg_200.guardian,
         blocked_tokens_list: cfg_200.blocked_tokens_list,
         checkpoint_generator_limit: cfg_200.checkpoint_generator_limit,
+        tokens_per_second: None,
+        start_time: Uint64::zero(),
+        lockup_tiers: vec![],
+        incentive_tokens: vec![],
+        incentivization_fee: None,
     };
 
     if let Some(voting_escrow_delegation) = &msg.voting_escrow_delegation {
@@ -157,6 +162,11 @@ pub fn migrate_configs_from_v210(deps: &mut DepsMut, msg: &MigrateMsg) -> StdRes
         blocked_tokens_list: cfg_210.blocked_tokens_list,
         guardian: cfg_210.guardian,
         checkpoint_generator_limit: cfg_210.checkpoint_generator_limit,
+        tokens_per_second: None,
+        start_time: Uint64::zero(),
+        lockup_tiers: vec![],
+        incentive_tokens: vec![],
+        incentivization_fee: None,
     };
 
     if let Some(voting_escrow_delegation) = &msg.voting_escrow_delegation {
@@ -185,6 +195,11 @@ pub fn migrate_configs_from_v220(deps: &mut DepsMut, msg: &MigrateMsg) -> StdRes
         blocked_tokens_list: cfg_220.blocked_tokens_list,
         guardian: cfg_220.guardian,
         checkpoint_generator_limit: cfg_220.checkpoint_generator_limit,
+        tokens_per_second: None,
+        start_time: Uint64::zero(),
+        lockup_tiers: vec![],
+        incentive_tokens: vec![],
+        incentivization_fee: None,
     };
 
     if let Some(voting_escrow_delegation) = &msg.voting_escrow_delegation {
@@ -1,6 +1,7 @@
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo};
 use astroport::common::OwnershipProposal;
 use astroport::restricted_vector::RestrictedVector;
+use astroport::token_factory::{is_token_factory_denom, tf_denom_creator};
 use astroport::DecimalCheckedOps;
 use astroport::{
     generator::{PoolInfo, UserInfo, UserInfoV2},
@@ -8,10 +9,11 @@ use astroport::{
 };
 use astroport_governance::voting_escrow::{get_total_voting_power, get_voting_power};
 use astroport_governance::voting_escrow_delegation::get_adjusted_balance;
-use cosmwasm_std::{Addr, Decimal, Deps, DepsMut, QuerierWrapper, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    Addr, Api, Decimal, Deps, DepsMut, QuerierWrapper, StdResult, Storage, Uint128,
+};
 
 use astroport::generator::Config;
-use cw20::BalanceResponse;
 use cw_storage_plus::{Item, Map};
 
 use std::collections::HashMap;
@@ -39,6 +41,10 @@ pub const OLD_USER_INFO: Map<(&Addr, &Addr), UserInfo> = Map::new("user_info");
 pub const PROXY_REWARDS_HOLDER: Item<Addr> = Item::new("proxy_rewards_holder");
 /// The struct which maps previous proxy addresses to reward assets
 pub const PROXY_REWARD_ASSET: Map<&Addr, AssetInfo> = Map::new("proxy_reward_asset");
+/// This is a map that contains the scheduled allocation points.
+///
+/// The key is the block height or timestamp when the allocation points take effect.
+pub const SCHEDULED_ALLOCATIONS: Map<u64, Vec<(Addr, Uint128)>> = Map::new("scheduled_allocations");
 
 pub trait CompatibleLoader<K, R> {
     fn compatible_load(&self, store: &dyn Storage, key: K) -> StdResult<R>;
@@ -74,6 +80,11 @@ impl CompatibleLoader<(&Addr, &Addr), UserInfoV2> for Map<'_, (&Addr, &Addr), Us
                 reward_user_index: user_index,
                 reward_debt_proxy,
                 virtual_amount: old_user_info.amount,
+                incentive_user_index: Default::default(),
+                lockups: vec![],
+                pending_incentives: vec![],
+                reward_checkpoint_time: 0,
+                incentive_checkpoint_time: 0,
             };
 
             Ok(user_info)
@@ -94,14 +105,58 @@ pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_pro
 /// The default limit of generators to update user emission
 pub const CHECKPOINT_GENERATORS_LIMIT: u32 = 24;
 
+/// The maximum number of active external incentive reward streams per pool
+pub const MAX_POOL_INCENTIVES: usize = 5;
+
+/// The maximum duration of an external incentive reward stream in seconds
+pub const MAX_INCENTIVE_DURATION: u64 = 365 * 86400;
+
+/// The maximum number of lockup tiers
+pub const MAX_LOCKUP_TIERS: usize = 5;
+
+/// The maximum number of active lockup positions per user in a generator
+pub const MAX_USER_LOCKUPS: usize = 10;
+
+/// Returns the amount of LP tokens locked in the user's active lockup positions.
+pub fn locked_amount(user: &UserInfoV2, now: u64) -> StdResult<Uint128> {
+    user.lockups
+        .iter()
+        .filter(|lockup| lockup.end_time > now)
+        .try_fold(Uint128::zero(), |acc, lockup| {
+            acc.checked_add(lockup.amount).map_err(Into::into)
+        })
+}
+
+/// Returns the virtual amount the user accrued rewards with since `since`. The boost of a lockup
+/// position which ended in the meantime is only counted for the time the position was active.
+pub fn effective_virtual_amount(user: &UserInfoV2, since: u64, now: u64) -> StdResult<Uint128> {
+    if now <= since {
+        return Ok(user.virtual_amount);
+    }
+
+    user.lockups
+        .iter()
+        .filter(|lockup| lockup.end_time < now)
+        .try_fold(user.virtual_amount, |acc, lockup| {
+            let expired_boost = lockup
+                .boost
+                .multiply_ratio(now - lockup.end_time.max(since), now - since);
+            acc.checked_sub(expired_boost).map_err(Into::into)
+        })
+}
+
 /// Update user balance.
 pub fn update_user_balance(
     mut user: UserInfoV2,
     pool: &PoolInfo,
     amount: Uint128,
+    now: u64,
 ) -> StdResult<UserInfoV2> {
+    checkpoint_user_incentives(pool, &mut user, now)?;
+
     user.amount = amount;
     user.reward_user_index = pool.reward_global_index;
+    user.reward_checkpoint_time = now;
 
     user.reward_debt_proxy = pool
         .accumulated_proxy_rewards_per_share
@@ -117,6 +172,39 @@ pub fn update_user_balance(
     Ok(user)
 }
 
+/// Moves the external incentive rewards accrued by the user to their pending incentives and
+/// updates the user's incentive indexes. The rewards are sent out separately per token.
+pub fn checkpoint_user_incentives(
+    pool: &PoolInfo,
+    user: &mut UserInfoV2,
+    now: u64,
+) -> StdResult<()> {
+    for reward in accumulate_user_incentive_rewards(pool, user, now)? {
+        if reward.amount.is_zero() {
+            continue;
+        }
+
+        match user
+            .pending_incentives
+            .iter_mut()
+            .find(|pending| pending.info == reward.info)
+        {
+            Some(pending) => pending.amount = pending.amount.checked_add(reward.amount)?,
+            None => user.pending_incentives.push(reward),
+        }
+    }
+
+    user.incentive_user_index = pool
+        .incentives
+        .iter()
+        .map(|incentive| (incentive.reward.clone(), incentive.reward_global_index))
+        .collect::<Vec<_>>()
+        .into();
+    user.incentive_checkpoint_time = now;
+
+    Ok(())
+}
+
 /// Returns the vector of reward amount per proxy taking into account the amount of debited rewards.
 pub fn accumulate_pool_proxy_rewards(
     pool: &PoolInfo,
@@ -145,6 +233,57 @@ pub fn accumulate_pool_proxy_rewards(
         .collect()
 }
 
+/// Distributes external incentive rewards accrued up to `now` among the pool stakers.
+/// Rewards are not distributed while the pool has no stakers.
+pub fn accumulate_incentive_rewards(pool: &mut PoolInfo, now: u64) -> StdResult<()> {
+    for incentive in &mut pool.incentives {
+        let until = now.min(incentive.end_time);
+        if until <= incentive.last_update_time {
+            continue;
+        }
+
+        if !pool.total_virtual_supply.is_zero() {
+            let rewards = incentive.remaining.multiply_ratio(
+                until - incentive.last_update_time,
+                incentive.end_time - incentive.last_update_time,
+            );
+            let share = Decimal::from_ratio(rewards, pool.total_virtual_supply);
+            incentive.reward_global_index = incentive.reward_global_index.checked_add(share)?;
+            incentive.remaining = incentive.remaining.checked_sub(rewards)?;
+        }
+
+        incentive.last_update_time = until;
+    }
+
+    Ok(())
+}
+
+/// Returns the vector of pending external incentive rewards of a user.
+pub fn accumulate_user_incentive_rewards(
+    pool: &PoolInfo,
+    user: &UserInfoV2,
+    now: u64,
+) -> StdResult<Vec<Asset>> {
+    let virtual_amount = effective_virtual_amount(user, user.incentive_checkpoint_time, now)?;
+
+    pool.incentives
+        .iter()
+        .map(|incentive| {
+            let user_index = user
+                .incentive_user_index
+                .load(&incentive.reward)
+                .unwrap_or_default();
+            let amount =
+                (incentive.reward_global_index - user_index).checked_mul_uint128(virtual_amount)?;
+
+            Ok(Asset {
+                info: incentive.reward.clone(),
+                amount,
+            })
+        })
+        .collect()
+}
+
 /// Saves map between a proxy and an asset info if it is not saved yet.
 pub fn update_proxy_asset(deps: DepsMut, proxy_addr: &Addr) -> StdResult<()> {
     if !PROXY_REWARD_ASSET.has(deps.storage, proxy_addr) {
@@ -169,6 +308,11 @@ pub fn update_proxy_asset(deps: DepsMut, proxy_addr: &Addr) -> StdResult<()> {
 /// - S is the total amount of LP tokens staked in a generator
 /// - w_i is a user’s current vxASTRO balance
 /// - W is the total amount of vxASTRO
+///
+/// The result is multiplied by **(b_u + sum(l_j * (m_j - 1))) / b_u** where l_j and m_j are the amount
+/// and the multiplier of the user's active lockup positions. Lockups can't boost the virtual amount
+/// beyond the maximum vxASTRO boost, so it is capped at b_u again. The added amount is split among
+/// the positions in proportion to their bonus. Ended positions are removed.
 pub(crate) fn update_virtual_amount(
     querier: QuerierWrapper,
     cfg: &Config,
@@ -176,6 +320,7 @@ pub(crate) fn update_virtual_amount(
     user_info: &mut UserInfoV2,
     account: &Addr,
     lp_balance: Uint128,
+    now: u64,
 ) -> StdResult<()> {
     let mut user_vp = Uint128::zero();
     let mut total_vp = Uint128::zero();
@@ -205,10 +350,34 @@ pub(crate) fn update_virtual_amount(
         Decimal::zero()
     };
 
-    let current_virtual_amount = user_info
+    let mut current_virtual_amount = user_info
         .amount
         .min(user_virtual_share + vx_share_emission * total_virtual_share);
 
+    user_info.lockups.retain(|lockup| lockup.end_time > now);
+    let total_bonus = user_info
+        .lockups
+        .iter()
+        .try_fold(Uint128::zero(), |acc, lockup| {
+            acc.checked_add(lockup.amount * (lockup.multiplier - Decimal::one()))
+        })?;
+    if !total_bonus.is_zero() {
+        let boosted_virtual_amount = user_info.amount.min(
+            current_virtual_amount
+                .multiply_ratio(user_info.amount.checked_add(total_bonus)?, user_info.amount),
+        );
+        let lockups_boost = boosted_virtual_amount - current_virtual_amount;
+        for lockup in &mut user_info.lockups {
+            let bonus = lockup.amount * (lockup.multiplier - Decimal::one());
+            lockup.boost = lockups_boost.multiply_ratio(bonus, total_bonus);
+        }
+        current_virtual_amount = boosted_virtual_amount;
+    } else {
+        for lockup in &mut user_info.lockups {
+            lockup.boost = Uint128::zero();
+        }
+    }
+
     pool.total_virtual_supply = pool
         .total_virtual_supply
         .checked_sub(user_info.virtual_amount)?
@@ -231,13 +400,30 @@ pub(crate) fn query_lp_balance(
         deps.querier
             .query_wasm_smart(proxy, &ProxyQueryMsg::Deposit {})?
     } else {
-        let res: BalanceResponse = deps.querier.query_wasm_smart(
-            lp_token,
-            &cw20::Cw20QueryMsg::Balance {
-                address: generator_addr.to_string(),
-            },
-        )?;
-        res.balance
+        lp_token_asset_info(lp_token).query_pool(&deps.querier, generator_addr)?
     };
     Ok(lp_amount)
 }
+
+/// Returns the [`AssetInfo`] of an LP token which is either a CW20 contract or a token factory denom.
+pub(crate) fn lp_token_asset_info(lp_token: &Addr) -> AssetInfo {
+    if is_token_factory_denom(lp_token.as_str()) {
+        AssetInfo::NativeToken {
+            denom: lp_token.to_string(),
+        }
+    } else {
+        AssetInfo::Token {
+            contract_addr: lp_token.clone(),
+        }
+    }
+}
+
+/// Validates an LP token address. Token factory denoms are kept as is.
+pub(crate) fn validate_lp_token(api: &dyn Api, lp_token: &str) -> StdResult<Addr> {
+    if is_token_factory_denom(lp_token) {
+        tf_denom_creator(lp_token)?;
+        Ok(Addr::unchecked(lp_token))
+    } else {
+        api.addr_validate(lp_token)
+    }
+}
@@ -9,8 +9,9 @@ use astroport::{
     },
     generator::{
         Config, Cw20HookMsg as GeneratorHookMsg, ExecuteMsg as GeneratorExecuteMsg,
-        InstantiateMsg as GeneratorInstantiateMsg, PendingTokenResponse, PoolInfoResponse,
-        QueryMsg as GeneratorQueryMsg,
+        IncentivizationFeeInfo, InstantiateMsg as GeneratorInstantiateMsg, LockupPosition,
+        LockupTier, MigrateMsg, PendingTokenResponse, PoolInfoResponse,
+        QueryMsg as GeneratorQueryMsg, ScheduledAllocation,
     },
     generator_proxy::{ExecuteMsg as ProxyExecuteMsg, InstantiateMsg as ProxyInstantiateMsg},
     token::InstantiateMsg as TokenInstantiateMsg,
@@ -21,11 +22,19 @@ use astroport::{
 };
 
 use astroport::generator_proxy::ConfigResponse;
-use astroport::pair::StablePoolParams;
+use astroport::pair::{
+    InstantiateMsg as PairInstantiateMsg, QueryMsg as PairQueryMsg, StablePoolParams,
+};
+use astroport::token_factory::{tf_denom, LP_SUBDENOM};
 use astroport_generator::error::ContractError;
-use cosmwasm_std::{to_binary, Addr, Binary, StdResult, Uint128, Uint64};
+use cosmwasm_std::{
+    coin, from_slice, to_binary, Addr, Binary, Decimal, DepsMut, Empty, Env, MessageInfo, Response,
+    StdResult, Uint128, Uint64,
+};
+use cw2::ContractVersion;
 use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{next_block, App, ContractWrapper, Executor};
+use cw_storage_plus::Item;
 
 use crate::test_utils::controller_helper::ControllerHelper;
 use crate::test_utils::{mock_app as mock_app_helper, mock_app, AppExtension};
@@ -807,120 +816,1956 @@ fn proper_deposit_and_withdraw() {
         &[(&lp_cny_eur, 10), (&lp_eur_usd, 10)],
     );
 
-    check_token_balance(&mut app, &lp_cny_eur, &generator_instance, 10);
-    check_token_balance(&mut app, &lp_eur_usd, &generator_instance, 10);
+    check_token_balance(&mut app, &lp_cny_eur, &generator_instance, 10);
+    check_token_balance(&mut app, &lp_eur_usd, &generator_instance, 10);
+
+    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (5000000, None),
+    );
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_eur_usd,
+        USER1,
+        (5000000, None),
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur.to_string(),
+        amount: Uint128::new(10),
+    };
+
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_eur_usd.to_string(),
+        amount: Uint128::zero(),
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::ZeroWithdraw {}, err.downcast().unwrap());
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_eur_usd.to_string(),
+        amount: Uint128::new(10),
+    };
+
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_cny_eur, &generator_instance, 0);
+    check_token_balance(&mut app, &lp_eur_usd, &generator_instance, 0);
+
+    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+
+    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+
+    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
+}
+
+#[test]
+fn token_factory_lp_deposit_and_withdraw() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_token_factory_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+    assert_eq!(
+        lp_cny_eur.as_str(),
+        format!("factory/{pair_cny_eur}/astroport/share")
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &user1, vec![coin(10, lp_cny_eur.as_str())])
+    })
+    .unwrap();
+
+    // Only token factory LP tokens can be sent along with the deposit message
+    let msg = GeneratorExecuteMsg::Deposit { recipient: None };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidLpFunds {}, err.downcast().unwrap());
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &msg,
+        &[coin(10, lp_cny_eur.as_str())],
+    )
+    .unwrap();
+
+    let generator_balance = app
+        .wrap()
+        .query_balance(&generator_instance, lp_cny_eur.as_str())
+        .unwrap();
+    assert_eq!(generator_balance.amount.u128(), 10);
+
+    let deposit: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::Deposit {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(deposit.u128(), 10);
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (10000000, None),
+    );
+
+    // Reward proxies work with CW20 LP tokens only
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::MoveToProxy {
+                lp_token: lp_cny_eur.to_string(),
+                proxy: "proxy".to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::RewardProxyNotAllowed {},
+        err.downcast().unwrap()
+    );
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur.to_string(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let user_balance = app
+        .wrap()
+        .query_balance(&user1, lp_cny_eur.as_str())
+        .unwrap();
+    assert_eq!(user_balance.amount.u128(), 10);
+    check_token_balance(&mut app, &astro_token_instance, &user1, 10000000);
+    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+
+    // Deposit on behalf of another user and withdraw in emergency
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Deposit {
+            recipient: Some(USER2.to_string()),
+        },
+        &[coin(10, lp_cny_eur.as_str())],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        Addr::unchecked(USER2),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::EmergencyWithdraw {
+            lp_token: lp_cny_eur.to_string(),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let user_balance = app
+        .wrap()
+        .query_balance(USER2, lp_cny_eur.as_str())
+        .unwrap();
+    assert_eq!(user_balance.amount.u128(), 10);
+}
+
+#[test]
+fn set_tokens_per_block() {
+    let mut app = mock_app();
+
+    let token_code_id = store_token_code(&mut app);
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let generator_instance = instantiate_generator(
+        &mut app,
+        &factory_instance,
+        &astro_token_instance,
+        Some(OWNER.to_string()),
+    );
+
+    let msg = QueryMsg::Config {};
+    let res: Config = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+
+    assert_eq!(res.tokens_per_block, Uint128::new(10_000000));
+
+    // Set new amount of tokens distributed per block
+    let tokens_per_block = Uint128::new(100);
+
+    let msg = GeneratorExecuteMsg::SetTokensPerBlock {
+        amount: tokens_per_block,
+    };
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        generator_instance.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    let msg = GeneratorQueryMsg::Config {};
+    let res: Config = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res.tokens_per_block, tokens_per_block);
+}
+
+#[test]
+fn time_based_emissions() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user1, 10);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_cny_eur, 10)]);
+
+    app.update_block(|bi| next_block(bi));
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (10_000000, None),
+    );
+
+    let msg = GeneratorExecuteMsg::SetTokensPerSecond {
+        amount: Uint128::new(1_000000),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // Rewards accrued by blocks are kept
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (10_000000, None),
+    );
+
+    let err = app
+        .execute_contract(
+            owner,
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::SetTokensPerBlock {
+                amount: Uint128::new(100),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::TimeBasedEmissions {},
+        err.downcast().unwrap()
+    );
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<Uint128>(
+            &generator_instance,
+            &GeneratorQueryMsg::SimulateFutureReward {
+                lp_token: lp_cny_eur.to_string(),
+                future_block: app.block_info().height + 10,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("ASTRO emissions are time based!"));
+
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::SimulateFutureRewardByTime {
+                lp_token: lp_cny_eur.to_string(),
+                future_time: app.block_info().time.seconds() + 100,
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Uint128::new(100_000000));
+
+    // Blocks without time passing do not emit rewards
+    app.update_block(|bi| bi.height += 100);
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (10_000000, None),
+    );
+
+    app.update_block(|bi| bi.time = bi.time.plus_seconds(10));
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (20_000000, None),
+    );
+
+    let res: PoolInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PoolInfo {
+                lp_token: lp_cny_eur.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.astro_tokens_per_second, Some(Uint128::new(1_000000)));
+    assert_eq!(res.last_reward_time, Some(res.current_time - 10));
+    assert_eq!(res.pending_astro_rewards, Uint128::new(10_000000));
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ClaimRewards {
+            lp_tokens: vec![lp_cny_eur.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &astro_token_instance, &user1, 20_000000);
+    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+}
+
+#[test]
+fn incentivize_pools() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let user3 = Addr::unchecked(USER3);
+
+    app.init_modules(|router, _, storage| {
+        router
+            .bank
+            .init_balance(storage, &user3, vec![coin(1_000_000000, "uusd")])
+    })
+    .unwrap();
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+    let reward_token = instantiate_token(&mut app, token_code_id, "RWD", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    mint_tokens(&mut app, pair_cny_eur.clone(), &lp_cny_eur, &user1, 10);
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user2, 10);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_cny_eur, 10)]);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER2, &[(&lp_cny_eur, 10)]);
+
+    let native_reward = Asset {
+        info: native_asset_info("uusd".to_string()),
+        amount: Uint128::new(100_000000),
+    };
+
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Incentivize {
+                lp_token: lp_cny_eur.to_string(),
+                reward: native_reward.clone(),
+                duration: 100,
+            },
+            &[coin(100_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncentiveTokenNotWhitelisted {
+            token: "uusd".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    let msg = GeneratorExecuteMsg::UpdateIncentiveTokens {
+        add: Some(vec![
+            native_asset_info("uusd".to_string()),
+            token_asset_info(reward_token.clone()),
+        ]),
+        remove: None,
+    };
+    let err = app
+        .execute_contract(user3.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        generator_instance.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Incentivize {
+                lp_token: lp_cny_eur.to_string(),
+                reward: native_reward.clone(),
+                duration: 0,
+            },
+            &[coin(100_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::InvalidIncentive {}, err.downcast().unwrap());
+
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Incentivize {
+                lp_token: lp_cny_eur.to_string(),
+                reward: native_reward.clone(),
+                duration: 365 * 86400 + 1,
+            },
+            &[coin(100_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncentiveDurationTooLong { max: 365 * 86400 },
+        err.downcast().unwrap()
+    );
+
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Incentivize {
+                lp_token: lp_cny_eur.to_string(),
+                reward: native_reward.clone(),
+                duration: 100,
+            },
+            &[coin(10_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Native token balance mismatch between the argument and the transferred"
+    );
+
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Incentivize {
+                lp_token: lp_cny_eur.to_string(),
+                reward: Asset {
+                    info: token_asset_info(lp_cny_eur.clone()),
+                    amount: Uint128::new(10),
+                },
+                duration: 100,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncentiveTokenNotWhitelisted {
+            token: lp_cny_eur.to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user3.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Incentivize {
+            lp_token: lp_cny_eur.to_string(),
+            reward: native_reward,
+            duration: 100,
+        },
+        &[coin(100_000000, "uusd")],
+    )
+    .unwrap();
+
+    mint_tokens(
+        &mut app,
+        Addr::unchecked(OWNER),
+        &reward_token,
+        &user3,
+        50_000000,
+    );
+    app.execute_contract(
+        user3.clone(),
+        reward_token.clone(),
+        &Cw20ExecuteMsg::IncreaseAllowance {
+            spender: generator_instance.to_string(),
+            amount: Uint128::new(50_000000),
+            expires: None,
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user3.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Incentivize {
+            lp_token: lp_cny_eur.to_string(),
+            reward: Asset {
+                info: token_asset_info(reward_token.clone()),
+                amount: Uint128::new(50_000000),
+            },
+            duration: 100,
+        },
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &reward_token, &generator_instance, 50_000000);
+
+    let res: PoolInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PoolInfo {
+                lp_token: lp_cny_eur.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.incentives.len(), 2);
+    let uusd_end_time = res.incentives[0].end_time;
+
+    // Half of the reward streams are distributed
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(50);
+    });
+
+    let res: PendingTokenResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PendingToken {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.pending_incentives,
+        vec![
+            Asset {
+                info: native_asset_info("uusd".to_string()),
+                amount: Uint128::new(25_000000),
+            },
+            Asset {
+                info: token_asset_info(reward_token.clone()),
+                amount: Uint128::new(12_500000),
+            },
+        ]
+    );
+
+    // Incentives are paid out along with ASTRO rewards
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ClaimRewards {
+            lp_tokens: vec![lp_cny_eur.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user1, "uusd").unwrap().amount,
+        Uint128::new(25_000000)
+    );
+    check_token_balance(&mut app, &reward_token, &user1, 12_500000);
+
+    let res: PendingTokenResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PendingToken {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.pending_incentives, vec![]);
+
+    // Nothing is left to claim token by token
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ClaimIncentives {
+            lp_token: lp_cny_eur.to_string(),
+            rewards: vec![
+                native_asset_info("uusd".to_string()),
+                token_asset_info(reward_token.clone()),
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user1, "uusd").unwrap().amount,
+        Uint128::new(25_000000)
+    );
+    check_token_balance(&mut app, &reward_token, &user1, 12_500000);
+
+    // Anyone can top up an active stream, a shorter duration doesn't move its end backwards
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Incentivize {
+            lp_token: lp_cny_eur.to_string(),
+            reward: Asset {
+                info: native_asset_info("uusd".to_string()),
+                amount: Uint128::new(1_000000),
+            },
+            duration: 10,
+        },
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+
+    let res: PoolInfoResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PoolInfo {
+                lp_token: lp_cny_eur.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.incentives[0].end_time, uusd_end_time);
+    assert_eq!(res.incentives[0].creator, user3);
+    assert_eq!(res.incentives[0].remaining, Uint128::new(51_000000));
+
+    // The reward streams end
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    app.execute_contract(
+        user2.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur.to_string(),
+            amount: Uint128::new(10),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user2.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ClaimIncentives {
+            lp_token: lp_cny_eur.to_string(),
+            rewards: vec![
+                native_asset_info("uusd".to_string()),
+                token_asset_info(reward_token.clone()),
+            ],
+        },
+        &[],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user2, "uusd").unwrap().amount,
+        Uint128::new(50_500000)
+    );
+    check_token_balance(&mut app, &reward_token, &user2, 25_000000);
+
+    let res: PendingTokenResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::PendingToken {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.pending_incentives
+            .into_iter()
+            .map(|asset| asset.amount.u128())
+            .collect::<Vec<_>>(),
+        vec![25_500000, 12_500000]
+    );
+
+    // Rewards of a stream without stakers are returned to the creator
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur.to_string(),
+            amount: Uint128::new(10),
+        },
+        &[],
+    )
+    .unwrap();
+    app.execute_contract(
+        user3.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Incentivize {
+            lp_token: lp_cny_eur.to_string(),
+            reward: Asset {
+                info: native_asset_info("uusd".to_string()),
+                amount: Uint128::new(10_000000),
+            },
+            duration: 10,
+        },
+        &[coin(10_000000, "uusd")],
+    )
+    .unwrap();
+
+    let refund_msg = GeneratorExecuteMsg::RefundIncentive {
+        lp_token: lp_cny_eur.to_string(),
+        reward: native_asset_info("uusd".to_string()),
+    };
+    let err = app
+        .execute_contract(user3.clone(), generator_instance.clone(), &refund_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::NothingToRefund {}, err.downcast().unwrap());
+
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(20);
+    });
+
+    let err = app
+        .execute_contract(user1, generator_instance.clone(), &refund_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    app.execute_contract(user3.clone(), generator_instance, &refund_msg, &[])
+        .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user3, "uusd").unwrap().amount,
+        Uint128::new(900_000000)
+    );
+}
+
+#[test]
+fn incentivization_fee() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user3 = Addr::unchecked(USER3);
+    let fee_receiver = Addr::unchecked("fee_receiver");
+
+    app.init_modules(|router, _, storage| {
+        router.bank.init_balance(
+            storage,
+            &user3,
+            vec![coin(1_000_000000, "uluna"), coin(1_000_000000, "uusd")],
+        )?;
+        router
+            .bank
+            .init_balance(storage, &user1, vec![coin(1_000_000000, "uusd")])
+    })
+    .unwrap();
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (_, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![token_asset_info(cny_token), token_asset_info(eur_token)],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    app.execute_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::UpdateIncentiveTokens {
+            add: Some(vec![native_asset_info("uusd".to_string())]),
+            remove: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let fee_msg = GeneratorExecuteMsg::SetIncentivizationFee {
+        fee_info: Some(IncentivizationFeeInfo {
+            fee_receiver: fee_receiver.to_string(),
+            fee: coin(5_000000, "uluna"),
+        }),
+    };
+    let err = app
+        .execute_contract(user3.clone(), generator_instance.clone(), &fee_msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+    app.execute_contract(owner, generator_instance.clone(), &fee_msg, &[])
+        .unwrap();
+
+    let incentivize_msg = |amount: u128, duration: u64| GeneratorExecuteMsg::Incentivize {
+        lp_token: lp_cny_eur.to_string(),
+        reward: Asset {
+            info: native_asset_info("uusd".to_string()),
+            amount: Uint128::new(amount),
+        },
+        duration,
+    };
+
+    // A new stream must pay the fee
+    let err = app
+        .execute_contract(
+            user3.clone(),
+            generator_instance.clone(),
+            &incentivize_msg(100_000000, 100),
+            &[coin(100_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncentivizationFeeExpected {
+            fee: "5000000uluna".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user3.clone(),
+        generator_instance.clone(),
+        &incentivize_msg(100_000000, 100),
+        &[coin(5_000000, "uluna"), coin(100_000000, "uusd")],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap()
+            .query_balance(&fee_receiver, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(5_000000)
+    );
+
+    // Topping up an active stream is free
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &incentivize_msg(10_000000, 100),
+        &[coin(10_000000, "uusd")],
+    )
+    .unwrap();
+
+    // A stream replacing an ended one pays the fee again
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    let err = app
+        .execute_contract(
+            user1,
+            generator_instance.clone(),
+            &incentivize_msg(10_000000, 100),
+            &[coin(10_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncentivizationFeeExpected {
+            fee: "5000000uluna".to_string()
+        },
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        user3,
+        generator_instance.clone(),
+        &incentivize_msg(10_000000, 100),
+        &[coin(5_000000, "uluna"), coin(10_000000, "uusd")],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap()
+            .query_balance(&fee_receiver, "uluna")
+            .unwrap()
+            .amount,
+        Uint128::new(10_000000)
+    );
+}
+
+#[test]
+fn lockup_tiers() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let maker = Addr::unchecked("maker");
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    mint_tokens(&mut app, pair_cny_eur.clone(), &lp_cny_eur, &user1, 100);
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user2, 100);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_cny_eur, 100)]);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER2, &[(&lp_cny_eur, 100)]);
+
+    let lock_msg = |amount: u128, duration: u64| GeneratorExecuteMsg::Lock {
+        lp_token: lp_cny_eur.to_string(),
+        amount: Uint128::new(amount),
+        duration,
+    };
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &lock_msg(50, 90 * 86400),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::LockupTierNotFound {
+            duration: 90 * 86400
+        },
+        err.downcast().unwrap()
+    );
+
+    let tiers = vec![
+        LockupTier {
+            duration: 30 * 86400,
+            multiplier: Decimal::from_ratio(3u8, 2u8),
+            early_unlock_penalty: Decimal::percent(10),
+        },
+        LockupTier {
+            duration: 90 * 86400,
+            multiplier: Decimal::from_ratio(2u8, 1u8),
+            early_unlock_penalty: Decimal::percent(20),
+        },
+    ];
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::SetLockupTiers {
+                tiers: tiers.clone(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let mut invalid_tiers = tiers.clone();
+    invalid_tiers[1].multiplier = Decimal::percent(50);
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::SetLockupTiers {
+                tiers: invalid_tiers,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidLockupTiers {},
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner,
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::SetLockupTiers { tiers },
+        &[],
+    )
+    .unwrap();
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &lock_msg(150, 90 * 86400),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidLockupAmount {},
+        err.downcast().unwrap()
+    );
+
+    // The early unlock penalty can't be charged without the Maker
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &lock_msg(50, 90 * 86400),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::MakerNotSet {}, err.downcast().unwrap());
+
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        factory_instance,
+        &FactoryExecuteMsg::UpdateConfig {
+            token_code_id: None,
+            fee_address: Some(maker.to_string()),
+            generator_address: None,
+            whitelist_code_id: None,
+            coin_registry_address: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &lock_msg(50, 90 * 86400),
+        &[],
+    )
+    .unwrap();
+
+    // 0.4 * 100 * (100 + 50 * (2 - 1)) / 100
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 60);
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user2, 40);
+
+    let total_virtual_supply: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::TotalVirtualSupply {
+                generator: lp_cny_eur.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(total_virtual_supply.u128(), 100);
+
+    // Locked LP tokens can't be withdrawn
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::Withdraw {
+                lp_token: lp_cny_eur.to_string(),
+                amount: Uint128::new(60),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::LpTokensLocked {}, err.downcast().unwrap());
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::EmergencyWithdraw {
+                lp_token: lp_cny_eur.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::LpTokensLocked {}, err.downcast().unwrap());
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur.to_string(),
+            amount: Uint128::new(50),
+        },
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &lp_cny_eur, &user1, 50);
+    // 0.4 * 50 * (50 + 50 * (2 - 1)) / 50
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 40);
+
+    let lockups: Vec<LockupPosition> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::UserLockups {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        lockups,
+        vec![LockupPosition {
+            amount: Uint128::new(50),
+            multiplier: Decimal::from_ratio(2u8, 1u8),
+            early_unlock_penalty: Decimal::percent(20),
+            end_time: app.block_info().time.seconds() + 90 * 86400,
+            boost: Uint128::new(20),
+        }]
+    );
+
+    let early_unlock_msg = GeneratorExecuteMsg::EarlyUnlock {
+        lp_token: lp_cny_eur.to_string(),
+        index: 0,
+    };
+
+    let err = app
+        .execute_contract(
+            user2.clone(),
+            generator_instance.clone(),
+            &early_unlock_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::LockupNotFound {}, err.downcast().unwrap());
+
+    // The penalty is sent to the Maker
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &early_unlock_msg,
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &lp_cny_eur, &maker, 10);
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 16);
+
+    let deposit: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::Deposit {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(deposit.u128(), 40);
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &lock_msg(40, 30 * 86400),
+        &[],
+    )
+    .unwrap();
+    // 0.4 * 40 * 1.5
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 24);
+
+    // The ended lockup is released on the next checkpoint
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(30 * 86400);
+    });
+    app.execute_contract(
+        user2,
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::CheckpointUserBoost {
+            generators: vec![lp_cny_eur.to_string()],
+            user: Some(USER1.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 16);
+
+    let lockups: Vec<LockupPosition> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::UserLockups {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(lockups.is_empty());
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur.to_string(),
+            amount: Uint128::new(40),
+        },
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &lp_cny_eur, &user1, 90);
+}
+
+#[test]
+fn lockup_boost_expiry() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    app.execute_contract(
+        owner,
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::SetLockupTiers {
+            tiers: vec![LockupTier {
+                duration: 100,
+                multiplier: Decimal::from_ratio(2u8, 1u8),
+                early_unlock_penalty: Decimal::zero(),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    mint_tokens(&mut app, pair_cny_eur.clone(), &lp_cny_eur, &user1, 100);
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user2, 50);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_cny_eur, 100)]);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER2, &[(&lp_cny_eur, 50)]);
+
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::Lock {
+            lp_token: lp_cny_eur.to_string(),
+            amount: Uint128::new(100),
+            duration: 100,
+        },
+        &[],
+    )
+    .unwrap();
+    // 0.4 * 100 * (100 + 100 * (2 - 1)) / 100
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 80);
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user2, 20);
+
+    // Nobody checkpoints the user when the lockup ends
+    app.update_block(|bi| {
+        bi.height += 200;
+        bi.time = bi.time.plus_seconds(200);
+    });
+
+    // 200 blocks * 10 ASTRO are distributed among 100 virtual tokens. The boost of 40 only
+    // counts for the first 100 of 200 seconds, so user1 accrues rewards with (80 - 40 / 2) tokens.
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER1,
+        (1_200_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur,
+        USER2,
+        (400_000000, None),
+    );
+
+    // Anyone can release the ended lockup
+    app.execute_contract(
+        user2,
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::CheckpointUserBoost {
+            generators: vec![lp_cny_eur.to_string()],
+            user: Some(USER1.to_string()),
+        },
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &astro_token_instance, &user1, 1_200_000000);
+    check_emission_balance(&mut app, &generator_instance, &lp_cny_eur, &user1, 40);
+
+    let total_virtual_supply: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::TotalVirtualSupply {
+                generator: lp_cny_eur.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(total_virtual_supply.u128(), 60);
+}
+
+#[test]
+fn lockup_multiplier_with_vxastro() {
+    let mut app = mock_app_helper();
+    let owner = Addr::unchecked(OWNER);
+    let helper_controller = ControllerHelper::init(&mut app, &owner);
+
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &helper_controller.factory,
+        None,
+        None,
+        vec![token_asset_info(cny_token), token_asset_info(eur_token)],
+    );
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &helper_controller.generator,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    app.execute_contract(
+        owner,
+        helper_controller.generator.clone(),
+        &GeneratorExecuteMsg::SetLockupTiers {
+            tiers: vec![LockupTier {
+                duration: WEEK,
+                multiplier: Decimal::from_ratio(2u8, 1u8),
+                early_unlock_penalty: Decimal::zero(),
+            }],
+        },
+        &[],
+    )
+    .unwrap();
+
+    // user1 owns 1/30 of the total vxASTRO
+    helper_controller
+        .escrow_helper
+        .mint_xastro(&mut app, USER1, 200);
+    helper_controller
+        .escrow_helper
+        .create_lock(&mut app, USER1, WEEK * 10, 100f32)
+        .unwrap();
+    helper_controller
+        .escrow_helper
+        .mint_xastro(&mut app, USER2, 2900);
+    helper_controller
+        .escrow_helper
+        .create_lock(&mut app, USER2, WEEK * 10, 2900f32)
+        .unwrap();
+
+    mint_tokens(&mut app, pair_cny_eur.clone(), &lp_cny_eur, &user1, 1000);
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user2, 9000);
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &helper_controller.generator,
+        USER2,
+        &[(&lp_cny_eur, 9000)],
+    );
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &helper_controller.generator,
+        USER1,
+        &[(&lp_cny_eur, 1000)],
+    );
+
+    // min(1000, 0.4 * 1000 + 0.6 * 10000 / 30) = 600
+    let vx_virtual_amount: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &helper_controller.generator,
+            &GeneratorQueryMsg::UserVirtualAmount {
+                lp_token: lp_cny_eur.to_string(),
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(vx_virtual_amount.u128() > 500 && vx_virtual_amount.u128() < 800);
+
+    let lock_msg = |amount: u128| GeneratorExecuteMsg::Lock {
+        lp_token: lp_cny_eur.to_string(),
+        amount: Uint128::new(amount),
+        duration: WEEK,
+    };
+
+    // The multiplier applies on top of the vxASTRO boost
+    app.execute_contract(
+        user1.clone(),
+        helper_controller.generator.clone(),
+        &lock_msg(250),
+        &[],
+    )
+    .unwrap();
+    check_emission_balance(
+        &mut app,
+        &helper_controller.generator,
+        &lp_cny_eur,
+        &user1,
+        vx_virtual_amount.multiply_ratio(1250u128, 1000u128).u128(),
+    );
+
+    // But the virtual amount can't exceed the maximum vxASTRO boost
+    app.execute_contract(
+        user1.clone(),
+        helper_controller.generator.clone(),
+        &lock_msg(750),
+        &[],
+    )
+    .unwrap();
+    check_emission_balance(
+        &mut app,
+        &helper_controller.generator,
+        &lp_cny_eur,
+        &user1,
+        1000,
+    );
+}
+
+#[test]
+fn scheduled_allocations() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    let cny_token = instantiate_token(&mut app, token_code_id, "CNY", None);
+    let eur_token = instantiate_token(&mut app, token_code_id, "EUR", None);
+    let usd_token = instantiate_token(&mut app, token_code_id, "USD", None);
+
+    let (pair_cny_eur, lp_cny_eur) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(eur_token.clone()),
+        ],
+    );
+    let (pair_cny_usd, lp_cny_usd) = create_pair(
+        &mut app,
+        &factory_instance,
+        None,
+        None,
+        vec![
+            token_asset_info(cny_token.clone()),
+            token_asset_info(usd_token.clone()),
+        ],
+    );
+
+    let generator_instance =
+        instantiate_generator(&mut app, &factory_instance, &astro_token_instance, None);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        vec![PoolWithProxy {
+            pool: (lp_cny_eur.to_string(), Uint128::from(100u32)),
+            proxy: None,
+        }],
+    );
+
+    mint_tokens(&mut app, pair_cny_eur, &lp_cny_eur, &user1, 10);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_cny_eur, 10)]);
+
+    let start_height = app.block_info().height;
+    let schedule_msg = |effective_at: u64| GeneratorExecuteMsg::ScheduleSetupPools {
+        pools: vec![
+            (lp_cny_eur.to_string(), Uint128::new(50)),
+            (lp_cny_usd.to_string(), Uint128::new(50)),
+        ],
+        effective_at,
+    };
+
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &schedule_msg(start_height + 5),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &schedule_msg(start_height),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidEffectiveAt {},
+        err.downcast().unwrap()
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &schedule_msg(start_height + 5),
+        &[],
+    )
+    .unwrap();
+
+    let scheduled: Vec<ScheduledAllocation> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::ScheduledAllocations {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        scheduled,
+        vec![ScheduledAllocation {
+            effective_at: start_height + 5,
+            pools: vec![
+                (lp_cny_eur.clone(), Uint128::new(50)),
+                (lp_cny_usd.clone(), Uint128::new(50)),
+            ],
+        }]
+    );
+
+    // The generator of the scheduled pool is created right away
+    mint_tokens(&mut app, pair_cny_usd, &lp_cny_usd, &user2, 10);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER2, &[(&lp_cny_usd, 10)]);
 
-    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
-    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
+    // Simulations account for the schedule
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::SimulateFutureReward {
+                lp_token: lp_cny_eur.to_string(),
+                future_block: start_height + 8,
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Uint128::new(65_000000));
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::SimulateFutureReward {
+                lp_token: lp_cny_usd.to_string(),
+                future_block: start_height + 8,
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Uint128::new(15_000000));
 
-    app.update_block(|bi| next_block(bi));
+    app.update_block(|bi| {
+        bi.height += 8;
+        bi.time = bi.time.plus_seconds(40);
+    });
 
+    // Pending rewards account for the schedule which is due but not applied yet
     check_pending_rewards(
         &mut app,
         &generator_instance,
         &lp_cny_eur,
         USER1,
-        (5000000, None),
+        (65_000000, None),
     );
-
     check_pending_rewards(
         &mut app,
         &generator_instance,
-        &lp_eur_usd,
-        USER1,
-        (5000000, None),
+        &lp_cny_usd,
+        USER2,
+        (15_000000, None),
     );
 
-    app.update_block(|bi| next_block(bi));
+    // The schedule is applied on the next interaction with the generator
+    app.execute_contract(
+        user1.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ClaimRewards {
+            lp_tokens: vec![lp_cny_eur.to_string()],
+        },
+        &[],
+    )
+    .unwrap();
 
-    let msg = GeneratorExecuteMsg::Withdraw {
-        lp_token: lp_cny_eur.to_string(),
-        amount: Uint128::new(10),
-    };
+    // 5 blocks with 100% of emissions and 3 blocks with 50% of emissions
+    check_token_balance(&mut app, &astro_token_instance, &user1, 65_000000);
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_usd,
+        USER2,
+        (15_000000, None),
+    );
 
-    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+    let cfg: Config = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &GeneratorQueryMsg::Config {})
         .unwrap();
+    assert_eq!(
+        cfg.active_pools,
+        vec![
+            (lp_cny_eur.clone(), Uint128::new(50)),
+            (lp_cny_usd.clone(), Uint128::new(50)),
+        ]
+    );
+    assert_eq!(cfg.total_alloc_point.u128(), 100);
 
-    let msg = GeneratorExecuteMsg::Withdraw {
-        lp_token: lp_eur_usd.to_string(),
-        amount: Uint128::zero(),
-    };
-
-    let err = app
-        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
-        .unwrap_err();
-    assert_eq!(ContractError::ZeroWithdraw {}, err.downcast().unwrap());
-
-    let msg = GeneratorExecuteMsg::Withdraw {
-        lp_token: lp_eur_usd.to_string(),
-        amount: Uint128::new(10),
-    };
-
-    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+    let scheduled: Vec<ScheduledAllocation> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::ScheduledAllocations {
+                start_after: None,
+                limit: None,
+            },
+        )
         .unwrap();
+    assert!(scheduled.is_empty());
 
-    check_token_balance(&mut app, &lp_cny_eur, &generator_instance, 0);
-    check_token_balance(&mut app, &lp_eur_usd, &generator_instance, 0);
-
-    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
-
-    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
+    // Pending schedules block the switch to time based emissions
+    let current_height = app.block_info().height;
+    app.execute_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &schedule_msg(current_height + 10),
+        &[],
+    )
+    .unwrap();
 
-    app.update_block(|bi| next_block(bi));
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::SetTokensPerSecond {
+                amount: Uint128::new(1_000000),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ScheduledAllocationsPending {},
+        err.downcast().unwrap()
+    );
 
-    check_pending_rewards(&mut app, &generator_instance, &lp_cny_eur, USER1, (0, None));
+    // Cancel pending schedules
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::CancelScheduledSetupPools {
+                effective_at: current_height + 10,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
 
-    check_pending_rewards(&mut app, &generator_instance, &lp_eur_usd, USER1, (0, None));
-}
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::CancelScheduledSetupPools {
+                effective_at: current_height + 11,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ScheduledAllocationNotFound {
+            effective_at: current_height + 11
+        },
+        err.downcast().unwrap()
+    );
 
-#[test]
-fn set_tokens_per_block() {
-    let mut app = mock_app();
+    app.execute_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::CancelScheduledSetupPools {
+            effective_at: current_height + 10,
+        },
+        &[],
+    )
+    .unwrap();
 
-    let token_code_id = store_token_code(&mut app);
-    let astro_token_instance =
-        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    // Due schedules are applied and can't be cancelled
+    app.execute_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &GeneratorExecuteMsg::ScheduleSetupPools {
+            pools: vec![(lp_cny_eur.to_string(), Uint128::new(100))],
+            effective_at: current_height + 2,
+        },
+        &[],
+    )
+    .unwrap();
 
-    let factory_code_id = store_factory_code(&mut app);
-    let pair_code_id = store_pair_code_id(&mut app);
-    let factory_instance =
-        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+    app.update_block(|bi| {
+        bi.height += 3;
+        bi.time = bi.time.plus_seconds(15);
+    });
 
-    let generator_instance = instantiate_generator(
-        &mut app,
-        &factory_instance,
-        &astro_token_instance,
-        Some(OWNER.to_string()),
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &GeneratorExecuteMsg::CancelScheduledSetupPools {
+                effective_at: current_height + 2,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::ScheduledAllocationNotFound {
+            effective_at: current_height + 2
+        },
+        err.downcast().unwrap()
     );
 
-    let msg = QueryMsg::Config {};
-    let res: Config = app
+    let cfg: Config = app
         .wrap()
-        .query_wasm_smart(&generator_instance, &msg)
+        .query_wasm_smart(&generator_instance, &GeneratorQueryMsg::Config {})
         .unwrap();
+    assert_eq!(
+        cfg.active_pools,
+        vec![(lp_cny_eur.clone(), Uint128::new(100))]
+    );
 
-    assert_eq!(res.tokens_per_block, Uint128::new(10_000000));
-
-    // Set new amount of tokens distributed per block
-    let tokens_per_block = Uint128::new(100);
+    let scheduled: Vec<ScheduledAllocation> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &GeneratorQueryMsg::ScheduledAllocations {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(scheduled.is_empty());
 
-    let msg = GeneratorExecuteMsg::SetTokensPerBlock {
-        amount: tokens_per_block,
-    };
     app.execute_contract(
-        Addr::unchecked(OWNER),
-        generator_instance.clone(),
-        &msg,
+        owner,
+        generator_instance,
+        &GeneratorExecuteMsg::SetTokensPerSecond {
+            amount: Uint128::new(1_000000),
+        },
         &[],
     )
     .unwrap();
-
-    let msg = GeneratorQueryMsg::Config {};
-    let res: Config = app
-        .wrap()
-        .query_wasm_smart(&generator_instance, &msg)
-        .unwrap();
-    assert_eq!(res.tokens_per_block, tokens_per_block);
 }
 
 #[test]
@@ -1086,6 +2931,125 @@ fn update_owner() {
     assert_eq!(res.owner.to_string(), new_owner)
 }
 
+#[test]
+fn migrate_from_v230() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked(OWNER);
+
+    let token_code_id = store_token_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
+    let pair_code_id = store_pair_code_id(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let factory_instance =
+        instantiate_factory(&mut app, factory_code_id, token_code_id, pair_code_id, None);
+
+    // Generator which reports v2.3.0 as its contract version
+    fn instantiate_v230(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: GeneratorInstantiateMsg,
+    ) -> Result<Response, ContractError> {
+        let response = astroport_generator::contract::instantiate(deps.branch(), env, info, msg)?;
+        cw2::set_contract_version(deps.storage, "astroport-generator", "2.3.0")?;
+        Ok(response)
+    }
+
+    let generator_v230_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_generator::contract::execute,
+            instantiate_v230,
+            astroport_generator::contract::query,
+        )
+        .with_reply_empty(astroport_generator::contract::reply),
+    ));
+    let generator_code_id = app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_generator::contract::execute,
+            astroport_generator::contract::instantiate,
+            astroport_generator::contract::query,
+        )
+        .with_reply_empty(astroport_generator::contract::reply)
+        .with_migrate_empty(astroport_generator::contract::migrate),
+    ));
+    let whitelist_code_id = store_whitelist_code(&mut app);
+
+    let generator_instance = app
+        .instantiate_contract(
+            generator_v230_code_id,
+            owner.clone(),
+            &GeneratorInstantiateMsg {
+                owner: owner.to_string(),
+                factory: factory_instance.to_string(),
+                guardian: None,
+                start_block: Uint64::from(app.block_info().height),
+                astro_token: token_asset_info(astro_token_instance),
+                tokens_per_block: Uint128::new(10_000000),
+                vesting_contract: "vesting".to_string(),
+                generator_controller: Some(owner.to_string()),
+                voting_escrow_delegation: None,
+                voting_escrow: None,
+                whitelist_code_id,
+                tokens_per_second: None,
+                start_time: None,
+            },
+            &[],
+            "Generator",
+            Some(owner.to_string()),
+        )
+        .unwrap();
+
+    let migrate_msg = MigrateMsg {
+        factory: None,
+        generator_controller: None,
+        blocked_list_tokens: None,
+        guardian: None,
+        whitelist_code_id: None,
+        voting_escrow: None,
+        voting_escrow_delegation: None,
+        generator_limit: None,
+    };
+    app.migrate_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &migrate_msg,
+        generator_code_id,
+    )
+    .unwrap();
+
+    let version: ContractVersion = from_slice(
+        &app.wrap()
+            .query_wasm_raw(&generator_instance, b"contract_info")
+            .unwrap()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(version.contract, "astroport-generator");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+
+    // The state stored by v2.3.0 is still readable
+    let config: Config = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &GeneratorQueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.owner, owner);
+    assert_eq!(config.tokens_per_block, Uint128::new(10_000000));
+    assert_eq!(config.lockup_tiers, vec![]);
+
+    // The current version can not be migrated from
+    let err = app
+        .migrate_contract(
+            owner.clone(),
+            generator_instance,
+            &migrate_msg,
+            generator_code_id,
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::MigrationError {}, err.downcast().unwrap());
+}
+
 #[test]
 fn disabling_pool() {
     let mut app = mock_app();
@@ -3839,6 +5803,36 @@ fn store_pair_code_id(app: &mut App) -> u64 {
     app.store_code(pair_contract)
 }
 
+/// Stores a minimal pair contract whose LP token is a token factory denom. Token factory messages
+/// are not supported by [`App`], so the LP coins are minted via the bank module in tests.
+fn store_token_factory_pair_code_id(app: &mut App) -> u64 {
+    const PAIR_INFO: Item<PairInfo> = Item::new("pair_info");
+
+    let pair_contract = Box::new(ContractWrapper::new_with_empty(
+        |_, _, _, _: Empty| -> StdResult<Response> { unimplemented!() },
+        |deps, env, _, msg: PairInstantiateMsg| -> StdResult<Response> {
+            PAIR_INFO.save(
+                deps.storage,
+                &PairInfo {
+                    asset_infos: msg.asset_infos,
+                    liquidity_token: Addr::unchecked(tf_denom(&env.contract.address, LP_SUBDENOM)),
+                    contract_addr: env.contract.address,
+                    pair_type: PairType::Xyk {},
+                },
+            )?;
+            Ok(Response::new())
+        },
+        |deps, _, msg: PairQueryMsg| -> StdResult<Binary> {
+            match msg {
+                PairQueryMsg::Pair {} => to_binary(&PAIR_INFO.load(deps.storage)?),
+                _ => unimplemented!(),
+            }
+        },
+    ));
+
+    app.store_code(pair_contract)
+}
+
 fn store_pair_stable_code_id(app: &mut App) -> u64 {
     let pair_contract = Box::new(
         ContractWrapper::new_with_empty(
@@ -4026,6 +6020,8 @@ fn instantiate_generator(
         voting_escrow_delegation: None,
         voting_escrow: None,
         whitelist_code_id,
+        tokens_per_second: None,
+        start_time: None,
     };
 
     let generator_instance = app
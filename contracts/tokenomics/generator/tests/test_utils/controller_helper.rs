@@ -118,6 +118,8 @@ impl ControllerHelper {
             start_block: Uint64::from(router.block_info().height),
             vesting_contract: vesting_instance.to_string(),
             whitelist_code_id,
+            tokens_per_second: None,
+            start_time: None,
         };
 
         let generator = router
@@ -1,27 +1,35 @@
 use crate::error::ContractError;
-use crate::state::{BRIDGES, CONFIG, OWNERSHIP_PROPOSAL};
+use crate::routing::RouteGraph;
+use crate::state::{
+    BridgeOrigins, BRIDGES, BRIDGE_ORIGINS, COLLECTED_TOTALS, COLLECTION_HISTORY, COLLECT_CONFIGS,
+    CONFIG, DISTRIBUTED_AMOUNTS, KEEPER, LAST_COLLECTED, ORACLES, OWNERSHIP_PROPOSAL,
+};
 use std::cmp::min;
 
-use crate::migration::{migrate_from_v1, migrate_from_v120};
+use crate::migration::{migrate_from_v1, migrate_from_v120, migrate_legacy_split};
 
 use crate::utils::{
-    build_distribute_msg, build_send_msg, build_swap_msg, try_build_swap_msg,
-    update_second_receiver_cfg, validate_bridge, BRIDGES_EXECUTION_MAX_DEPTH,
-    BRIDGES_INITIAL_DEPTH,
+    build_distribute_msg, build_receiver_msg, build_send_msg, build_swap_msg, build_withdraw_msg,
+    check_swap_price, get_lp_pair, try_build_swap_msg, update_second_receiver_cfg, validate_bridge,
+    validate_receivers, BRIDGES_EXECUTION_MAX_DEPTH, BRIDGES_INITIAL_DEPTH,
 };
-use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
+use astroport::asset::{addr_opt_validate, Asset, AssetInfo, PairInfo};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::UpdateAddr;
 use astroport::maker::{
-    AssetWithLimit, BalancesResponse, Config, ConfigResponse, ExecuteMsg, InstantiateMsg,
-    MigrateMsg, QueryMsg, SecondReceiverConfig, SecondReceiverParams,
+    AssetCollectConfig, AssetWithLimit, BalancesResponse, CollectionEpoch, Config, ConfigResponse,
+    ExecuteMsg, FeeReceiverParams, InstantiateMsg, KeeperBounty, MigrateMsg, QueryMsg,
+    SecondReceiverConfig, SecondReceiverParams, TwapProtection, COLLECTION_EPOCH_LENGTH,
+    MAX_KEEPER_BOUNTY_PERCENT,
 };
 use astroport::pair::MAX_ALLOWED_SLIPPAGE;
 use cosmwasm_std::{
     attr, entry_point, to_binary, Addr, Attribute, Binary, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Order, Response, StdError, StdResult, SubMsg, Uint128, Uint64,
+    MessageInfo, Order, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult, Uint128, Uint64,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
@@ -29,9 +37,19 @@ use std::str::FromStr;
 const CONTRACT_NAME: &str = "astroport-maker";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Reply ID of swaps to ASTRO which are recorded in the collection accounting.
+const SWAP_TO_ASTRO_REPLY_ID: u64 = 1;
+/// Reply ID of swaps to bridge assets which record the fee assets the bridge assets originate from.
+const SWAP_TO_BRIDGE_REPLY_ID: u64 = 2;
 /// Sets the default maximum spread (as a percentage) used when swapping fee tokens to ASTRO.
 const DEFAULT_MAX_SPREAD: u64 = 5; // 5%
 
+/// ## Pagination settings
+/// The maximum limit for reading distributed amounts and collection history
+const MAX_LIMIT: u32 = 30;
+/// The default limit for reading distributed amounts and collection history
+const DEFAULT_LIMIT: u32 = 10;
+
 /// Creates a new contract with the specified parameters in [`InstantiateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -87,6 +105,10 @@ pub fn instantiate(
         governance_percent,
         max_spread,
         second_receiver_cfg: None,
+        twap_protection: None,
+        auto_routing: false,
+        receivers: vec![],
+        keeper_bounty: None,
     };
 
     update_second_receiver_cfg(deps.as_ref(), &mut cfg, &msg.second_receiver_params)?;
@@ -151,6 +173,7 @@ pub fn instantiate(
 ///             governance_percent,
 ///             max_spread,
 ///             second_receiver_params,
+///             auto_routing,
 ///         }** Updates general contract settings stores in the [`Config`].
 ///
 /// * **ExecuteMsg::UpdateBridges { add, remove }** Adds or removes bridge assets used to swap fee tokens to ASTRO.
@@ -166,6 +189,16 @@ pub fn instantiate(
 /// * **ExecuteMsg::ClaimOwnership {}** Claims contract ownership.
 ///
 /// * **ExecuteMsg::EnableRewards** Enables collected ASTRO (pre Maker upgrade) to be distributed to xASTRO stakers.
+///
+/// * **ExecuteMsg::UpdateTwapProtection { params }** Sets or disables the check of fee swaps against reference prices.
+///
+/// * **ExecuteMsg::UpdateOracles { add, remove }** Adds or removes oracles used as reference price sources for pairs.
+///
+/// * **ExecuteMsg::UpdateReceivers { receivers }** Sets weighted receivers of the distributed ASTRO.
+///
+/// * **ExecuteMsg::UpdateKeeperBounty { params }** Sets or disables the bounty paid to callers of `Collect`.
+///
+/// * **ExecuteMsg::UpdateCollectConfigs { set, remove }** Sets or removes the collect parameters of fee tokens.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -174,7 +207,7 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Collect { assets } => collect(deps, env, assets),
+        ExecuteMsg::Collect { assets } => collect(deps, env, info, assets),
         ExecuteMsg::UpdateConfig {
             factory_contract,
             staking_contract,
@@ -183,6 +216,7 @@ pub fn execute(
             basic_asset,
             max_spread,
             second_receiver_params,
+            auto_routing,
         } => update_config(
             deps,
             info,
@@ -193,6 +227,7 @@ pub fn execute(
             basic_asset,
             max_spread,
             second_receiver_params,
+            auto_routing,
         ),
         ExecuteMsg::UpdateBridges { add, remove } => update_bridges(deps, info, add, remove),
         ExecuteMsg::SwapBridgeAssets { assets, depth } => {
@@ -256,15 +291,94 @@ pub fn execute(
 
             Ok(Response::default().add_attribute("action", "enable_rewards"))
         }
+        ExecuteMsg::UpdateTwapProtection { params } => update_twap_protection(deps, info, params),
+        ExecuteMsg::UpdateOracles { add, remove } => update_oracles(deps, info, add, remove),
+        ExecuteMsg::UpdateReceivers { receivers } => update_receivers(deps, info, receivers),
+        ExecuteMsg::UpdateKeeperBounty { params } => update_keeper_bounty(deps, info, params),
+        ExecuteMsg::UpdateCollectConfigs { set, remove } => {
+            update_collect_configs(deps, info, set, remove)
+        }
+    }
+}
+
+/// The entry point to the contract for processing replies from submessages.
+/// Records the ASTRO obtained by swaps to ASTRO in the collection accounting of the current epoch
+/// and the fee assets the bridge assets obtained by swaps to bridges originate from.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg {
+        Reply {
+            id: id @ (SWAP_TO_ASTRO_REPLY_ID | SWAP_TO_BRIDGE_REPLY_ID),
+            result: SubMsgResult::Ok(response),
+        } => {
+            // Find the swap event of the pair
+            let swap = response.events.iter().find_map(|event| {
+                let attr_value = |key: &str| {
+                    event
+                        .attributes
+                        .iter()
+                        .find(|attr| attr.key == key)
+                        .map(|attr| attr.value.as_str())
+                };
+
+                match (
+                    attr_value("action"),
+                    attr_value("ask_asset"),
+                    attr_value("offer_asset"),
+                    attr_value("offer_amount"),
+                    attr_value("return_amount"),
+                ) {
+                    (
+                        Some("swap"),
+                        Some(ask_asset),
+                        Some(offer_asset),
+                        Some(offer_amount),
+                        Some(return_amount),
+                    ) => Some((ask_asset, offer_asset, offer_amount, return_amount)),
+                    _ => None,
+                }
+            });
+
+            if let Some((ask_asset, offer_asset, offer_amount, return_amount)) = swap {
+                let offer_amount = Uint128::from_str(offer_amount)?;
+                let return_amount = Uint128::from_str(return_amount)?;
+                let obtained = split_by_origin(
+                    deps.storage,
+                    &deps.querier,
+                    &env.contract.address,
+                    offer_asset,
+                    offer_amount,
+                    return_amount,
+                )?;
+
+                if id == SWAP_TO_ASTRO_REPLY_ID {
+                    record_astro_obtained(deps.storage, &env, return_amount, obtained)?;
+                } else {
+                    add_bridge_origins(deps.storage, ask_asset, obtained)?;
+                }
+            }
+
+            Ok(Response::new())
+        }
+        _ => Err(StdError::generic_err(format!("Unknown reply id: {}", msg.id)).into()),
     }
 }
 
 /// Swaps fee tokens to ASTRO and distribute the resulting ASTRO to xASTRO and vxASTRO stakers.
+/// If the keeper bounty is enabled, assets collected within the cooldown are skipped and the caller
+/// receives the bounty on the ASTRO obtained by the call if at least one asset was swapped.
+///
+/// Assets with a balance below `min_collect_amount` are skipped and no more than `max_swap_per_call`
+/// of an asset is collected according to the asset collect parameters.
+///
+/// Liquidity is withdrawn for the LP tokens of the factory pairs and the underlying assets are swapped
+/// to ASTRO along with bridge assets.
 ///
 /// * **assets** array with fee tokens being swapped to ASTRO.
 fn collect(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     assets: Vec<AssetWithLimit>,
 ) -> Result<Response, ContractError> {
     let mut cfg = CONFIG.load(deps.storage)?;
@@ -281,23 +395,90 @@ fn collect(
         return Err(ContractError::DuplicatedAsset {});
     }
 
+    // Skip dust and cap the collected amounts according to the asset collect parameters
+    let (capped_assets, mut skipped_assets) =
+        apply_collect_configs(deps.as_ref(), &env.contract.address, &astro, assets)?;
+
+    // ASTRO is never swapped, so it doesn't take part in the keeper cooldown
+    let block_time = env.block.time.seconds();
+    let mut assets = vec![];
+    for a in capped_assets.into_iter().filter(|a| a.info.ne(&astro)) {
+        if let Some(bounty) = &cfg.keeper_bounty {
+            let last_collected = LAST_COLLECTED.may_load(deps.storage, a.info.to_string())?;
+            if matches!(last_collected, Some(time) if block_time < time + bounty.cooldown) {
+                skipped_assets.push(attr("skipped_asset", format!("{}: cooldown", a.info)));
+                continue;
+            }
+        }
+        assets.push(a);
+    }
+
+    // The keeper bounty is paid only on ASTRO obtained by this call
+    let astro_before = astro.query_pool(&deps.querier, &env.contract.address)?;
+
+    // Withdraw liquidity for LP tokens
+    let mut withdraw_msgs = vec![];
+    let mut unwrapped_assets: Vec<AssetInfo> = vec![];
+    let mut collected = vec![];
+    let mut fee_assets = vec![];
+    for a in assets {
+        match get_lp_pair(deps.storage, &deps.querier, &cfg.factory_contract, &a.info)? {
+            Some(pair) => {
+                let amount = query_collect_amount(deps.as_ref(), &env.contract.address, &a)?;
+                if !amount.is_zero() {
+                    withdraw_msgs.push(build_withdraw_msg(&pair, amount)?);
+                    collected.push(Asset {
+                        info: a.info,
+                        amount,
+                    });
+                    unwrapped_assets.extend(
+                        pair.asset_infos
+                            .into_iter()
+                            .filter(|info| !info.equal(&astro)),
+                    );
+                }
+            }
+            None => fee_assets.push(a),
+        }
+    }
+
     // Swap all non ASTRO tokens
-    let (mut response, bridge_assets) = swap_assets(
-        deps.as_ref(),
-        &env.contract.address,
-        &cfg,
-        assets.into_iter().filter(|a| a.info.ne(&astro)).collect(),
-        true,
-    )?;
+    let (mut response, mut bridge_assets, mut swapped) =
+        swap_assets(deps.branch(), &env, &cfg, fee_assets, false)?;
+    collected.append(&mut swapped);
+
+    // Only assets which were actually withdrawn or swapped start the cooldown and earn the bounty
+    let mut keeper = None;
+    if cfg.keeper_bounty.is_some() && !collected.is_empty() {
+        for asset in &collected {
+            LAST_COLLECTED.save(deps.storage, asset.info.to_string(), &block_time)?;
+        }
+        keeper = Some((info.sender, astro_before));
+    }
+    record_collected(deps.storage, &env, collected)?;
+
+    // The underlying assets of LP tokens are swapped along with bridge assets
+    response.messages.extend(withdraw_msgs);
+    for info in unwrapped_assets {
+        if !bridge_assets.iter().any(|asset| asset.equal(&info)) {
+            bridge_assets.push(info);
+        }
+    }
+
+    response = response.add_attributes(skipped_assets);
 
     // If no swap messages - send ASTRO directly to x/vxASTRO stakers
     if response.messages.is_empty() {
-        let (mut distribute_msg, attributes) = distribute(deps, env, &mut cfg)?;
+        let (mut distribute_msg, attributes) = distribute(deps, env, &mut cfg, keeper)?;
         if !distribute_msg.is_empty() {
             response.messages.append(&mut distribute_msg);
             response = response.add_attributes(attributes);
         }
     } else {
+        if let Some(keeper) = keeper {
+            KEEPER.save(deps.storage, &keeper)?;
+        }
+
         response.messages.push(build_distribute_msg(
             env,
             bridge_assets,
@@ -310,66 +491,184 @@ fn collect(
 
 /// This enum describes available token types that can be used as a SwapTarget.
 enum SwapTarget {
-    Astro(SubMsg),
-    Bridge { asset: AssetInfo, msg: SubMsg },
+    Astro {
+        pool: PairInfo,
+        msg: SubMsg,
+    },
+    Bridge {
+        asset: AssetInfo,
+        pool: PairInfo,
+        msg: SubMsg,
+    },
 }
 
-/// Swap all non ASTRO tokens to ASTRO.
-///
-/// * **contract_addr** maker contract address.
+type SwapAssetsResult = (Response, Vec<AssetInfo>, Vec<Asset>);
+
+/// Swap all non ASTRO tokens to ASTRO. Swaps which fail the TWAP protection check are skipped
+/// and reported in the `skipped_swap` attributes. Returns the swap messages, the bridge assets
+/// to swap next and the swapped assets.
 ///
 /// * **assets** array with assets to swap to ASTRO.
 ///
-/// * **with_validation** whether the swap operation should be validated or not.
+/// * **skip_unroutable** whether assets without a route to ASTRO are skipped and reported in the
+/// `skipped_swap` attributes instead of failing.
 fn swap_assets(
-    deps: Deps,
-    contract_addr: &Addr,
+    mut deps: DepsMut,
+    env: &Env,
     cfg: &Config,
     assets: Vec<AssetWithLimit>,
-    with_validation: bool,
-) -> Result<(Response, Vec<AssetInfo>), ContractError> {
+    skip_unroutable: bool,
+) -> Result<SwapAssetsResult, ContractError> {
     let mut response = Response::default();
     let mut bridge_assets = HashMap::new();
+    let mut swapped = vec![];
 
-    for a in assets {
-        // Get balance
-        let mut balance = a.info.query_pool(&deps.querier, contract_addr)?;
-        if let Some(limit) = a.limit {
-            if limit < balance && limit > Uint128::zero() {
-                balance = limit;
-            }
-        }
+    // The factory pairs graph is built only once an asset without a manual route is met
+    let mut graph = None;
 
+    for a in assets {
+        let balance = query_collect_amount(deps.as_ref(), &env.contract.address, &a)?;
         if !balance.is_zero() {
-            let swap_msg = if with_validation {
-                swap(deps, cfg, a.info, balance)?
-            } else {
-                swap_no_validate(deps, cfg, a.info, balance)?
+            let swap_msg = match swap(deps.as_ref(), cfg, &mut graph, a.info.clone(), balance) {
+                Ok(swap_msg) => swap_msg,
+                Err(ContractError::CannotSwap(_)) if skip_unroutable => {
+                    response = response
+                        .add_attribute("skipped_swap", format!("{}: no route to ASTRO", a.info));
+                    continue;
+                }
+                Err(err) => return Err(err),
             };
 
+            let (pool, ask_asset) = match &swap_msg {
+                SwapTarget::Astro { pool, .. } => (pool, &cfg.astro_token),
+                SwapTarget::Bridge { pool, asset, .. } => (pool, asset),
+            };
+            if let Some(reason) =
+                check_swap_price(deps.branch(), env, cfg, pool, &a.info, ask_asset, balance)?
+            {
+                response = response.add_attribute(
+                    "skipped_swap",
+                    format!("{} to {}: {}", a.info, ask_asset, reason),
+                );
+                continue;
+            }
+
             match swap_msg {
-                SwapTarget::Astro(msg) => {
-                    response.messages.push(msg);
+                SwapTarget::Astro { msg, .. } => {
+                    // The ASTRO obtained by the swap is recorded once it is executed
+                    response.messages.push(SubMsg {
+                        id: SWAP_TO_ASTRO_REPLY_ID,
+                        reply_on: ReplyOn::Success,
+                        ..msg
+                    });
                 }
-                SwapTarget::Bridge { asset, msg } => {
-                    response.messages.push(msg);
+                SwapTarget::Bridge { asset, msg, .. } => {
+                    // The fee assets the bridge asset originates from are recorded once the swap
+                    // is executed
+                    if !BRIDGE_ORIGINS.has(deps.storage, asset.to_string()) {
+                        BRIDGE_ORIGINS.save(
+                            deps.storage,
+                            asset.to_string(),
+                            &BridgeOrigins {
+                                asset: asset.clone(),
+                                origins: vec![],
+                            },
+                        )?;
+                    }
+
+                    response.messages.push(SubMsg {
+                        id: SWAP_TO_BRIDGE_REPLY_ID,
+                        reply_on: ReplyOn::Success,
+                        ..msg
+                    });
                     bridge_assets.insert(asset.to_string(), asset);
                 }
             }
+            swapped.push(Asset {
+                info: a.info,
+                amount: balance,
+            });
+        }
+    }
+
+    Ok((response, bridge_assets.into_values().collect(), swapped))
+}
+
+/// Skips assets with a balance below `min_collect_amount` and caps the swapped amounts by
+/// `max_swap_per_call` according to the asset collect parameters. ASTRO is never limited.
+/// Returns the assets to swap and the `skipped_asset` attributes.
+///
+/// * **contract_addr** maker contract address.
+///
+/// * **astro** ASTRO token info.
+///
+/// * **assets** array with assets to collect.
+fn apply_collect_configs(
+    deps: Deps,
+    contract_addr: &Addr,
+    astro: &AssetInfo,
+    assets: Vec<AssetWithLimit>,
+) -> StdResult<(Vec<AssetWithLimit>, Vec<Attribute>)> {
+    let mut skipped_assets = vec![];
+    let mut capped_assets = vec![];
+    for mut a in assets {
+        if a.info.ne(astro) {
+            if let Some(collect_cfg) = COLLECT_CONFIGS.may_load(deps.storage, a.info.to_string())? {
+                let amount = query_collect_amount(deps, contract_addr, &a)?;
+                if amount < collect_cfg.min_collect_amount {
+                    skipped_assets.push(attr(
+                        "skipped_asset",
+                        format!("{}: below min collect amount", a.info),
+                    ));
+                    continue;
+                }
+
+                if let Some(max_swap_per_call) = collect_cfg.max_swap_per_call {
+                    a.limit = Some(min(amount, max_swap_per_call));
+                }
+            }
         }
+        capped_assets.push(a);
     }
 
-    Ok((response, bridge_assets.into_values().collect()))
+    Ok((capped_assets, skipped_assets))
+}
+
+/// Returns the Maker balance of the asset limited by the amount specified for the collect.
+///
+/// * **contract_addr** maker contract address.
+///
+/// * **asset** asset with the optional limit.
+fn query_collect_amount(
+    deps: Deps,
+    contract_addr: &Addr,
+    asset: &AssetWithLimit,
+) -> StdResult<Uint128> {
+    // Get balance
+    let mut balance = asset.info.query_pool(&deps.querier, contract_addr)?;
+    if let Some(limit) = asset.limit {
+        if limit < balance && limit > Uint128::zero() {
+            balance = limit;
+        }
+    }
+
+    Ok(balance)
 }
 
 /// Checks if all required pools and bridges exists and performs a swap operation to ASTRO.
 ///
+/// Manual routes take precedence over the route discovered through the factory pairs.
+///
+/// * **graph** factory pairs graph used to discover routes if auto routing is enabled. It is built
+/// on the first use.
+///
 /// * **from_token** token to swap to ASTRO.
 ///
 /// * **amount_in** amount of tokens to swap.
 fn swap(
     deps: Deps,
     cfg: &Config,
+    graph: &mut Option<RouteGraph>,
     from_token: AssetInfo,
     amount_in: Uint128,
 ) -> Result<SwapTarget, ContractError> {
@@ -394,6 +693,7 @@ fn swap(
         )?;
         return Ok(SwapTarget::Bridge {
             asset: bridge_token,
+            pool: bridge_pool,
             msg,
         });
     }
@@ -403,9 +703,10 @@ fn swap(
         if from_token.ne(default_bridge) {
             let swap_to_default =
                 try_build_swap_msg(&deps.querier, cfg, &from_token, default_bridge, amount_in);
-            if let Ok(msg) = swap_to_default {
+            if let Ok((pool, msg)) = swap_to_default {
                 return Ok(SwapTarget::Bridge {
                     asset: default_bridge.clone(),
+                    pool,
                     msg,
                 });
             }
@@ -415,44 +716,64 @@ fn swap(
     // 3. Check for a direct pair with ASTRO
     let swap_to_astro =
         try_build_swap_msg(&deps.querier, cfg, &from_token, &cfg.astro_token, amount_in);
-    if let Ok(msg) = swap_to_astro {
-        return Ok(SwapTarget::Astro(msg));
+    if let Ok((pool, msg)) = swap_to_astro {
+        return Ok(SwapTarget::Astro { pool, msg });
+    }
+
+    // 4. Check for an automatically discovered route
+    if let Some(target) = auto_route_swap(deps, cfg, graph, &from_token, amount_in)? {
+        return Ok(target);
     }
 
     Err(ContractError::CannotSwap(from_token))
 }
 
-/// Performs a swap operation to ASTRO without additional checks.
-/// was successful.
+/// Builds a swap to the next asset of the automatically discovered route to ASTRO.
+/// Returns [`None`] if auto routing is disabled or there is no route.
+///
+/// * **graph** factory pairs graph used to discover routes. It is built if it wasn't yet.
 ///
 /// * **from_token** token to swap to ASTRO.
 ///
 /// * **amount_in** amount of tokens to swap.
-fn swap_no_validate(
+fn auto_route_swap(
     deps: Deps,
     cfg: &Config,
-    from_token: AssetInfo,
+    graph: &mut Option<RouteGraph>,
+    from_token: &AssetInfo,
     amount_in: Uint128,
-) -> Result<SwapTarget, ContractError> {
-    let astro = cfg.astro_token.clone();
-
-    // Check if next level bridge exists
-    let bridge_token = BRIDGES.load(deps.storage, from_token.to_string());
-    if let Ok(asset) = bridge_token {
-        let msg = try_build_swap_msg(&deps.querier, cfg, &from_token, &asset, amount_in)?;
-        return Ok(SwapTarget::Bridge { asset, msg });
+) -> Result<Option<SwapTarget>, ContractError> {
+    if !cfg.auto_routing {
+        return Ok(None);
     }
 
-    // Check for a direct swap to ASTRO
-    let swap_to_astro = try_build_swap_msg(&deps.querier, cfg, &from_token, &astro, amount_in);
-    if let Ok(msg) = swap_to_astro {
-        return Ok(SwapTarget::Astro(msg));
+    if graph.is_none() {
+        *graph = Some(RouteGraph::build(
+            &deps.querier,
+            &cfg.factory_contract,
+            &cfg.astro_token,
+        )?);
     }
 
-    Err(ContractError::CannotSwap(from_token))
+    let (pool, asset) = match graph {
+        Some(graph) => match graph.next_hop(&deps.querier, from_token)? {
+            Some(hop) => hop,
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+
+    let msg = build_swap_msg(cfg.max_spread, &pool, from_token, Some(&asset), amount_in)?;
+    if asset.equal(&cfg.astro_token) {
+        Ok(Some(SwapTarget::Astro { pool, msg }))
+    } else {
+        Ok(Some(SwapTarget::Bridge { asset, pool, msg }))
+    }
 }
 
-/// Swaps collected fees using bridge assets.
+/// Swaps collected fees using bridge assets. The underlying assets of withdrawn LP tokens are
+/// swapped here as well using the same routes as fee tokens, including the default bridge.
+/// Assets without a route to ASTRO are left in the Maker.
 ///
 /// * **assets** array with fee tokens to swap as well as amount of tokens to swap.
 ///
@@ -461,7 +782,7 @@ fn swap_no_validate(
 /// ## Executor
 /// Only the Maker contract itself can execute this.
 fn swap_bridge_assets(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     assets: Vec<AssetInfo>,
@@ -490,11 +811,20 @@ fn swap_bridge_assets(
         })
         .collect();
 
-    let (response, bridge_assets) =
-        swap_assets(deps.as_ref(), &env.contract.address, &cfg, bridges, false)?;
+    // Bridge hops obey the same collect parameters as collected fee tokens
+    let (bridges, skipped_assets) = apply_collect_configs(
+        deps.as_ref(),
+        &env.contract.address,
+        &cfg.astro_token,
+        bridges,
+    )?;
 
-    // There should always be some messages, if there are none - something went wrong
-    if response.messages.is_empty() {
+    let (mut response, bridge_assets, _) = swap_assets(deps.branch(), &env, &cfg, bridges, true)?;
+    response = response.add_attributes(skipped_assets);
+
+    // There should always be some messages unless swaps were skipped by the TWAP protection
+    // or the collect parameters, if there are none - something went wrong
+    if response.messages.is_empty() && response.attributes.is_empty() {
         return Err(ContractError::Std(StdError::generic_err(
             "Empty swap messages",
         )));
@@ -515,7 +845,10 @@ fn distribute_astro(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
     }
 
     let mut cfg = CONFIG.load(deps.storage)?;
-    let (distribute_msg, attributes) = distribute(deps, env, &mut cfg)?;
+    let keeper = KEEPER.may_load(deps.storage)?;
+    KEEPER.remove(deps.storage);
+
+    let (distribute_msg, attributes) = distribute(deps, env, &mut cfg, keeper)?;
     if distribute_msg.is_empty() {
         return Ok(Response::default());
     }
@@ -528,17 +861,27 @@ fn distribute_astro(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Respon
 type DistributeMsgParts = (Vec<SubMsg>, Vec<Attribute>);
 
 /// Private function that performs the ASTRO token distribution to x/vxASTRO.
+///
+/// ASTRO goes to the weighted receivers if any are set. Otherwise it is split between the second
+/// receiver, the governance contract and the staking contract according to the config. This
+/// fallback is deliberate: it keeps contracts instantiated with the split working and is restored
+/// by setting an empty receivers list. Migrations convert the split into receivers.
+///
+/// * **keeper** caller of `Collect` who receives the keeper bounty and the Maker ASTRO balance
+/// before the swaps of that call.
 fn distribute(
     deps: DepsMut,
     env: Env,
     cfg: &mut Config,
+    keeper: Option<(Addr, Uint128)>,
 ) -> Result<DistributeMsgParts, ContractError> {
     let mut result = vec![];
     let mut attributes = vec![];
 
-    let mut amount = cfg
+    let total_balance = cfg
         .astro_token
         .query_pool(&deps.querier, &env.contract.address)?;
+    let mut amount = total_balance;
     if amount.is_zero() {
         return Ok((result, attributes));
     }
@@ -580,6 +923,94 @@ fn distribute(
         CONFIG.save(deps.storage, cfg)?;
     }
 
+    let epoch_start = collection_epoch_start(&env);
+    let mut epoch = COLLECTION_HISTORY
+        .may_load(deps.storage, epoch_start)?
+        .unwrap_or_default();
+
+    let mut keeper_bounty = Uint128::zero();
+    if let (Some((keeper, astro_before)), Some(bounty)) = (keeper, &cfg.keeper_bounty) {
+        let astro_obtained = min(
+            pure_astro_reward,
+            total_balance.saturating_sub(astro_before),
+        );
+        keeper_bounty = min(astro_obtained * bounty.percent, bounty.max_amount);
+        if !keeper_bounty.is_zero() {
+            amount = amount.checked_sub(keeper_bounty)?;
+
+            let asset = Asset {
+                info: cfg.astro_token.clone(),
+                amount: keeper_bounty,
+            };
+            result.push(SubMsg::new(asset.into_msg(keeper)?));
+            epoch.to_keepers = epoch.to_keepers.checked_add(keeper_bounty)?;
+        }
+    }
+
+    if !cfg.receivers.is_empty() {
+        let total_share: Uint128 = cfg
+            .receivers
+            .iter()
+            .map(|receiver| Uint128::from(receiver.share))
+            .sum();
+
+        // The last receiver gets the remainder left after rounding
+        let mut remaining_amount = amount;
+        for (i, receiver) in cfg.receivers.iter().enumerate() {
+            let receiver_amount = if i == cfg.receivers.len() - 1 {
+                remaining_amount
+            } else {
+                amount.multiply_ratio(Uint128::from(receiver.share), total_share)
+            };
+            remaining_amount = remaining_amount.checked_sub(receiver_amount)?;
+
+            if !receiver_amount.is_zero() {
+                let asset = Asset {
+                    info: cfg.astro_token.clone(),
+                    amount: receiver_amount,
+                };
+                result.push(SubMsg::new(build_receiver_msg(&asset, receiver)?));
+                add_distributed_amount(deps.storage, &receiver.address, receiver_amount)?;
+                epoch.to_receivers = epoch.to_receivers.checked_add(receiver_amount)?;
+            }
+        }
+    } else {
+        distribute_by_config(deps.storage, cfg, amount, &mut result, &mut epoch)?;
+    }
+
+    COLLECTION_HISTORY.save(deps.storage, epoch_start, &epoch)?;
+
+    attributes = vec![
+        attr("action", "distribute_astro"),
+        attr("astro_distribution", pure_astro_reward),
+    ];
+    if !current_preupgrade_distribution.is_zero() {
+        attributes.push(attr(
+            "preupgrade_astro_distribution",
+            current_preupgrade_distribution,
+        ));
+    }
+    if !keeper_bounty.is_zero() {
+        attributes.push(attr("keeper_bounty", keeper_bounty));
+    }
+
+    Ok((result, attributes))
+}
+
+/// Splits ASTRO between the second receiver, the governance contract and the staking contract.
+///
+/// * **amount** amount of ASTRO to distribute.
+///
+/// * **result** array the distribution messages are added to.
+///
+/// * **epoch** collection accounting of the current epoch.
+fn distribute_by_config(
+    storage: &mut dyn Storage,
+    cfg: &Config,
+    amount: Uint128,
+    result: &mut Vec<SubMsg>,
+    epoch: &mut CollectionEpoch,
+) -> Result<(), ContractError> {
     let second_receiver_amount = if let Some(second_receiver_cfg) = &cfg.second_receiver_cfg {
         let amount = amount.multiply_ratio(
             Uint128::from(second_receiver_cfg.second_receiver_cut),
@@ -594,7 +1025,9 @@ fn distribute(
 
             result.push(SubMsg::new(
                 asset.into_msg(second_receiver_cfg.second_fee_receiver.to_string())?,
-            ))
+            ));
+            add_distributed_amount(storage, &second_receiver_cfg.second_fee_receiver, amount)?;
+            epoch.to_second_receiver = epoch.to_second_receiver.checked_add(amount)?;
         }
 
         amount
@@ -615,7 +1048,9 @@ fn distribute(
                 },
                 governance_contract.to_string(),
                 None,
-            )?))
+            )?));
+            add_distributed_amount(storage, governance_contract, amount)?;
+            epoch.to_governance = epoch.to_governance.checked_add(amount)?;
         }
 
         amount
@@ -631,21 +1066,176 @@ fn distribute(
                 amount,
             };
             result.push(SubMsg::new(to_staking_asset.into_msg(staking_contract)?));
+            add_distributed_amount(storage, staking_contract, amount)?;
+            epoch.to_staking = epoch.to_staking.checked_add(amount)?;
         }
     }
 
-    attributes = vec![
-        attr("action", "distribute_astro"),
-        attr("astro_distribution", pure_astro_reward),
-    ];
-    if !current_preupgrade_distribution.is_zero() {
-        attributes.push(attr(
-            "preupgrade_astro_distribution",
-            current_preupgrade_distribution,
-        ));
+    Ok(())
+}
+
+/// Returns the start of the collection accounting epoch which contains the current block.
+fn collection_epoch_start(env: &Env) -> u64 {
+    let block_time = env.block.time.seconds();
+    block_time - block_time % COLLECTION_EPOCH_LENGTH
+}
+
+/// Adds collected assets to the accounting of the current epoch and to the totals by asset.
+///
+/// * **collected** array with swapped assets and unwrapped LP tokens.
+fn record_collected(storage: &mut dyn Storage, env: &Env, collected: Vec<Asset>) -> StdResult<()> {
+    if collected.is_empty() {
+        return Ok(());
     }
 
-    Ok((result, attributes))
+    let epoch_start = collection_epoch_start(env);
+    let mut epoch = COLLECTION_HISTORY
+        .may_load(storage, epoch_start)?
+        .unwrap_or_default();
+
+    for asset in collected {
+        match epoch
+            .collected
+            .iter_mut()
+            .find(|collected| collected.info.equal(&asset.info))
+        {
+            Some(collected) => collected.amount = collected.amount.checked_add(asset.amount)?,
+            None => epoch.collected.push(asset.clone()),
+        }
+
+        COLLECTED_TOTALS.update(storage, asset.info.to_string(), |total| -> StdResult<_> {
+            match total {
+                Some(mut total) => {
+                    total.amount = total.amount.checked_add(asset.amount)?;
+                    Ok(total)
+                }
+                None => Ok(asset),
+            }
+        })?;
+    }
+
+    COLLECTION_HISTORY.save(storage, epoch_start, &epoch)
+}
+
+/// Splits the amount obtained by a swap between the fee assets the swapped balance originates from.
+/// The parts of a bridge asset balance obtained from fee assets are consumed pro rata to the swapped
+/// share of the balance. The rest of the amount is credited to the swapped asset itself.
+///
+/// * **contract_addr** maker contract address.
+///
+/// * **offer_asset** swapped asset.
+///
+/// * **offer_amount** swapped amount.
+///
+/// * **return_amount** amount obtained by the swap.
+fn split_by_origin(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    contract_addr: &Addr,
+    offer_asset: &str,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let mut split = vec![];
+    let mut credited = Uint128::zero();
+
+    // The record is kept even if empty, as swaps to the bridge asset may already be scheduled
+    let bridge = BRIDGE_ORIGINS.may_load(storage, offer_asset.to_string())?;
+    if let Some(mut bridge) = bridge.filter(|bridge| !bridge.origins.is_empty()) {
+        // The swapped amount has already left the balance
+        let balance = bridge
+            .asset
+            .query_pool(querier, contract_addr)?
+            .checked_add(offer_amount)?;
+
+        for (origin, amount) in bridge.origins.iter_mut() {
+            let obtained = return_amount.multiply_ratio(*amount, balance);
+            *amount = amount.checked_sub(amount.multiply_ratio(offer_amount, balance))?;
+            credited = credited.checked_add(obtained)?;
+            split.push((origin.clone(), obtained));
+        }
+
+        bridge.origins.retain(|(_, amount)| !amount.is_zero());
+        BRIDGE_ORIGINS.save(storage, offer_asset.to_string(), &bridge)?;
+    }
+
+    split.push((
+        offer_asset.to_string(),
+        return_amount.saturating_sub(credited),
+    ));
+    split.retain(|(_, amount)| !amount.is_zero());
+
+    Ok(split)
+}
+
+/// Adds the amounts of a bridge asset obtained from fee assets to the bridge asset origins.
+///
+/// * **bridge_asset** bridge asset obtained by a swap.
+///
+/// * **obtained** amounts of the bridge asset obtained from each fee asset.
+fn add_bridge_origins(
+    storage: &mut dyn Storage,
+    bridge_asset: &str,
+    obtained: Vec<(String, Uint128)>,
+) -> StdResult<()> {
+    let mut bridge = BRIDGE_ORIGINS.load(storage, bridge_asset.to_string())?;
+
+    for (origin, amount) in obtained {
+        match bridge
+            .origins
+            .iter_mut()
+            .find(|(asset, _)| *asset == origin)
+        {
+            Some((_, total)) => *total = total.checked_add(amount)?,
+            None => bridge.origins.push((origin, amount)),
+        }
+    }
+
+    BRIDGE_ORIGINS.save(storage, bridge_asset.to_string(), &bridge)
+}
+
+/// Adds the ASTRO obtained by a swap to the accounting of the current epoch.
+///
+/// * **amount** amount of ASTRO obtained.
+///
+/// * **obtained** amount of ASTRO credited to each fee asset it originates from.
+fn record_astro_obtained(
+    storage: &mut dyn Storage,
+    env: &Env,
+    amount: Uint128,
+    obtained: Vec<(String, Uint128)>,
+) -> StdResult<()> {
+    let epoch_start = collection_epoch_start(env);
+    let mut epoch = COLLECTION_HISTORY
+        .may_load(storage, epoch_start)?
+        .unwrap_or_default();
+
+    epoch.astro_obtained = epoch.astro_obtained.checked_add(amount)?;
+    for (origin, amount) in obtained {
+        match epoch
+            .astro_obtained_by_asset
+            .iter_mut()
+            .find(|(asset, _)| *asset == origin)
+        {
+            Some((_, total)) => *total = total.checked_add(amount)?,
+            None => epoch.astro_obtained_by_asset.push((origin, amount)),
+        }
+    }
+
+    COLLECTION_HISTORY.save(storage, epoch_start, &epoch)
+}
+
+/// Adds the amount of ASTRO sent to a receiver to the total distributed amount.
+fn add_distributed_amount(
+    storage: &mut dyn Storage,
+    receiver: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    DISTRIBUTED_AMOUNTS.update(storage, receiver.to_string(), |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_add(amount)?)
+    })?;
+
+    Ok(())
 }
 
 /// Updates general contract parameters.
@@ -664,6 +1254,8 @@ fn distribute(
 ///
 /// * **second_receiver_params** describes the second receiver of fees
 ///
+/// * **auto_routing** whether routes to ASTRO are discovered automatically
+///
 /// ## Executor
 /// Only the owner can execute this.
 #[allow(clippy::too_many_arguments)]
@@ -677,6 +1269,7 @@ fn update_config(
     default_bridge_opt: Option<AssetInfo>,
     max_spread: Option<Decimal>,
     second_receiver_params: Option<SecondReceiverParams>,
+    auto_routing: Option<bool>,
 ) -> Result<Response, ContractError> {
     let mut attributes = vec![attr("action", "set_config")];
 
@@ -756,6 +1349,11 @@ fn update_config(
         ));
     }
 
+    if let Some(auto_routing) = auto_routing {
+        config.auto_routing = auto_routing;
+        attributes.push(attr("auto_routing", auto_routing.to_string()));
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attributes(attributes))
@@ -817,6 +1415,199 @@ fn update_bridges(
     Ok(Response::default().add_attribute("action", "update_bridges"))
 }
 
+/// Sets or disables the check of fee swaps against reference prices.
+///
+/// * **params** TWAP protection parameters. The check is disabled if None.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_twap_protection(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: Option<TwapProtection>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_twap_protection")];
+    if let Some(params) = &params {
+        if params.max_deviation.is_zero()
+            || params.max_deviation > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?
+        {
+            return Err(ContractError::IncorrectMaxDeviation {});
+        }
+
+        attributes.push(attr("max_deviation", params.max_deviation.to_string()));
+        attributes.push(attr(
+            "observation_period",
+            params.observation_period.to_string(),
+        ));
+    } else {
+        attributes.push(attr("twap_protection", "disabled"));
+    }
+
+    config.twap_protection = params;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Sets or disables the bounty paid to callers of `Collect`.
+///
+/// * **params** bounty parameters. The bounty is disabled if it is not set.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_keeper_bounty(
+    deps: DepsMut,
+    info: MessageInfo,
+    params: Option<KeeperBounty>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut attributes = vec![attr("action", "update_keeper_bounty")];
+    if let Some(params) = &params {
+        if params.percent.is_zero() || params.percent > Decimal::percent(MAX_KEEPER_BOUNTY_PERCENT)
+        {
+            return Err(ContractError::IncorrectKeeperBounty {});
+        }
+
+        attributes.push(attr("percent", params.percent.to_string()));
+        attributes.push(attr("max_amount", params.max_amount));
+        attributes.push(attr("cooldown", params.cooldown.to_string()));
+    } else {
+        attributes.push(attr("keeper_bounty", "disabled"));
+    }
+
+    config.keeper_bounty = params;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Sets or removes the collect parameters of fee tokens.
+///
+/// * **set** array of fee tokens with their collect parameters.
+///
+/// * **remove** array of fee tokens whose collect parameters are removed.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_collect_configs(
+    deps: DepsMut,
+    info: MessageInfo,
+    set: Option<Vec<(AssetInfo, AssetCollectConfig)>>,
+    remove: Option<Vec<AssetInfo>>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(remove_configs) = remove {
+        for asset in remove_configs {
+            asset.check(deps.api)?;
+            COLLECT_CONFIGS.remove(deps.storage, asset.to_string());
+        }
+    }
+
+    if let Some(set_configs) = set {
+        for (asset, collect_cfg) in set_configs {
+            asset.check(deps.api)?;
+
+            if matches!(collect_cfg.max_swap_per_call, Some(amount) if amount.is_zero()) {
+                return Err(ContractError::IncorrectMaxSwapPerCall(asset.to_string()));
+            }
+
+            COLLECT_CONFIGS.save(deps.storage, asset.to_string(), &collect_cfg)?;
+        }
+    }
+
+    Ok(Response::default().add_attribute("action", "update_collect_configs"))
+}
+
+/// Sets weighted receivers of the distributed ASTRO.
+///
+/// * **receivers** array of receivers. An empty array restores the split between
+/// the staking contract, the governance contract and the second receiver.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_receivers(
+    deps: DepsMut,
+    info: MessageInfo,
+    receivers: Vec<FeeReceiverParams>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.receivers = validate_receivers(deps.as_ref(), receivers)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    let mut attributes = vec![attr("action", "update_receivers")];
+    attributes.extend(config.receivers.iter().map(|receiver| {
+        attr(
+            "receiver",
+            format!("{}:{}", receiver.address, receiver.share),
+        )
+    }));
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// Adds or removes oracles used as reference price sources for specific pairs.
+///
+/// * **add** array of (pair, oracle) contract addresses.
+///
+/// * **remove** array of pair contract addresses whose oracles are removed.
+///
+/// ## Executor
+/// Only the owner can execute this.
+fn update_oracles(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Option<Vec<(String, String)>>,
+    remove: Option<Vec<String>>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(remove_oracles) = remove {
+        for pair in remove_oracles {
+            ORACLES.remove(deps.storage, deps.api.addr_validate(&pair)?.to_string());
+        }
+    }
+
+    if let Some(add_oracles) = add {
+        for (pair, oracle) in add_oracles {
+            let pair = deps.api.addr_validate(&pair)?;
+            let oracle = deps.api.addr_validate(&oracle)?;
+            ORACLES.save(deps.storage, pair.to_string(), &oracle)?;
+        }
+    }
+
+    Ok(Response::default().add_attribute("action", "update_oracles"))
+}
+
 /// Exposes all the queries available in the contract.
 ///
 /// ## Queries
@@ -827,12 +1618,39 @@ fn update_bridges(
 ///
 /// * **QueryMsg::Bridges {}** Returns the bridges used for swapping fee tokens
 /// using a vector of [`(String, String)`] denoting Asset -> Bridge connections.
+///
+/// * **QueryMsg::Oracles {}** Returns the oracles used as reference price sources
+/// using a vector of [`(String, String)`] denoting Pair -> Oracle connections.
+///
+/// * **QueryMsg::AutoRoute { asset }** Returns the route to ASTRO discovered through the factory pairs
+/// for a specific asset.
+///
+/// * **QueryMsg::CollectConfigs {}** Returns the collect parameters of fee tokens
+/// using a vector of [`(String, AssetCollectConfig)`] denoting Asset -> Parameters connections.
+///
+/// * **QueryMsg::CollectionHistory { start_after, limit }** Returns the collection accounting per epoch
+/// using a vector of [`(u64, CollectionEpoch)`] denoting Epoch start -> Accounting connections.
+///
+/// * **QueryMsg::TotalsByAsset {}** Returns the total amount of each asset collected using a vector of [`Asset`].
+///
+/// * **QueryMsg::DistributedAmounts { start_after, limit }** Returns the total amounts of ASTRO
+/// sent to each receiver using a vector of [`(String, Uint128)`] denoting Receiver -> Amount connections.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_get_config(deps)?),
         QueryMsg::Balances { assets } => to_binary(&query_get_balances(deps, env, assets)?),
         QueryMsg::Bridges {} => to_binary(&query_bridges(deps)?),
+        QueryMsg::Oracles {} => to_binary(&query_oracles(deps)?),
+        QueryMsg::AutoRoute { asset } => to_binary(&query_auto_route(deps, asset)?),
+        QueryMsg::CollectConfigs {} => to_binary(&query_collect_configs(deps)?),
+        QueryMsg::CollectionHistory { start_after, limit } => {
+            to_binary(&query_collection_history(deps, start_after, limit)?)
+        }
+        QueryMsg::TotalsByAsset {} => to_binary(&query_totals_by_asset(deps)?),
+        QueryMsg::DistributedAmounts { start_after, limit } => {
+            to_binary(&query_distributed_amounts(deps, start_after, limit)?)
+        }
     }
 }
 
@@ -851,6 +1669,10 @@ fn query_get_config(deps: Deps) -> StdResult<ConfigResponse> {
         pre_upgrade_astro_amount: config.pre_upgrade_astro_amount,
         default_bridge: config.default_bridge,
         second_receiver_cfg: config.second_receiver_cfg,
+        twap_protection: config.twap_protection,
+        auto_routing: config.auto_routing,
+        receivers: config.receivers,
+        keeper_bounty: config.keeper_bounty,
     })
 }
 
@@ -885,6 +1707,79 @@ fn query_bridges(deps: Deps) -> StdResult<Vec<(String, String)>> {
         .collect()
 }
 
+/// Returns oracles used as reference price sources for pairs.
+fn query_oracles(deps: Deps) -> StdResult<Vec<(String, String)>> {
+    ORACLES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|oracle| {
+            let (pair, oracle) = oracle?;
+            Ok((pair, oracle.to_string()))
+        })
+        .collect()
+}
+
+/// Returns the route to ASTRO discovered through the factory pairs for the given asset.
+/// Manual bridges are not taken into account.
+fn query_auto_route(deps: Deps, asset: AssetInfo) -> StdResult<Vec<AssetInfo>> {
+    let config = CONFIG.load(deps.storage)?;
+    let graph = RouteGraph::build(&deps.querier, &config.factory_contract, &config.astro_token)?;
+
+    graph.route(&deps.querier, &asset)
+}
+
+/// Returns the collect parameters of fee tokens.
+fn query_collect_configs(deps: Deps) -> StdResult<Vec<(String, AssetCollectConfig)>> {
+    COLLECT_CONFIGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect()
+}
+
+/// Returns the collection accounting per epoch.
+///
+/// * **start_after** epoch start timestamp to start reading from.
+///
+/// * **limit** number of items to read.
+fn query_collection_history(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, CollectionEpoch)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    COLLECTION_HISTORY
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
+/// Returns the total amount of each asset collected.
+fn query_totals_by_asset(deps: Deps) -> StdResult<Vec<Asset>> {
+    COLLECTED_TOTALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect()
+}
+
+/// Returns the total amounts of ASTRO sent to each receiver.
+///
+/// * **start_after** receiver address to start reading from.
+///
+/// * **limit** number of items to read.
+fn query_distributed_amounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(String, Uint128)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    DISTRIBUTED_AMOUNTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
+
 /// Manages contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
@@ -896,12 +1791,14 @@ pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response
                 migrate_from_v1(deps.branch(), &msg)?;
             }
             "1.2.0" => migrate_from_v120(deps.branch(), msg)?,
-            "1.3.0" => {}
+            "1.3.0" | "1.3.1" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     };
 
+    migrate_legacy_split(deps.branch())?;
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -35,6 +35,24 @@ pub enum ContractError {
     #[error("Incorrect max spread")]
     IncorrectMaxSpread {},
 
+    #[error("Incorrect max deviation")]
+    IncorrectMaxDeviation {},
+
+    #[error("Incorrect keeper bounty percent")]
+    IncorrectKeeperBounty {},
+
+    #[error("Receiver share must be greater than zero")]
+    IncorrectReceiverShare {},
+
+    #[error("Duplicated receiver {0}")]
+    DuplicatedReceiver(String),
+
+    #[error("The number of receivers must not exceed {0}")]
+    TooManyReceivers(usize),
+
+    #[error("Max swap per call for {0} must be greater than zero")]
+    IncorrectMaxSwapPerCall(String),
+
     #[error("Cannot collect. Remove duplicate asset")]
     DuplicatedAsset {},
 
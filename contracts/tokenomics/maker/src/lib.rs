@@ -3,6 +3,7 @@ extern crate cosmwasm_std;
 
 pub mod contract;
 pub mod error;
+pub mod routing;
 pub mod state;
 pub mod utils;
 
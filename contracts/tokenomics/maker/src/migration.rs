@@ -1,11 +1,11 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Decimal, DepsMut, StdResult, Uint128, Uint64};
+use cosmwasm_std::{to_binary, Addr, Binary, Decimal, DepsMut, StdResult, Uint128, Uint64};
 use cw_storage_plus::Item;
 
 use crate::state::CONFIG;
 use crate::utils::update_second_receiver_cfg;
 use astroport::asset::{token_asset_info, AssetInfo};
-use astroport::maker::{Config, MigrateMsg};
+use astroport::maker::{Config, FeeReceiver, MigrateMsg};
 
 pub(crate) fn migrate_from_v1(deps: DepsMut, msg: &MigrateMsg) -> StdResult<()> {
     #[cw_serde]
@@ -44,6 +44,10 @@ pub(crate) fn migrate_from_v1(deps: DepsMut, msg: &MigrateMsg) -> StdResult<()>
         remainder_reward: old_config.remainder_reward,
         pre_upgrade_astro_amount: old_config.pre_upgrade_astro_amount,
         second_receiver_cfg: None,
+        twap_protection: None,
+        auto_routing: false,
+        receivers: vec![],
+        keeper_bounty: None,
     };
 
     update_second_receiver_cfg(deps.as_ref(), &mut new_config, &msg.second_receiver_params)?;
@@ -85,9 +89,75 @@ pub(crate) fn migrate_from_v120(deps: DepsMut, msg: MigrateMsg) -> StdResult<()>
         remainder_reward: cfg_v120.remainder_reward,
         pre_upgrade_astro_amount: cfg_v120.pre_upgrade_astro_amount,
         second_receiver_cfg: None,
+        twap_protection: None,
+        auto_routing: false,
+        receivers: vec![],
+        keeper_bounty: None,
     };
 
     update_second_receiver_cfg(deps.as_ref(), &mut new_config, &msg.second_receiver_params)?;
 
     CONFIG.save(deps.storage, &new_config)
 }
+
+/// Replaces the split between the second receiver, the governance contract and the staking
+/// contract with equivalent weighted receivers. The staking contract comes last, so it gets
+/// the rounding remainder as before. Without the staking contract the rest of the split stays
+/// in the Maker, which receivers can't express, so such configs keep the split.
+pub(crate) fn migrate_legacy_split(deps: DepsMut) -> StdResult<()> {
+    let mut cfg = CONFIG.load(deps.storage)?;
+    if !cfg.receivers.is_empty() {
+        return Ok(());
+    }
+
+    let staking_contract = match &cfg.staking_contract {
+        Some(staking_contract) => staking_contract.clone(),
+        None => return Ok(()),
+    };
+
+    // Shares are expressed in basis points of the distributed amount
+    let mut rest = Uint64::new(10000);
+    let mut receivers = vec![];
+
+    if let Some(second_receiver_cfg) = &cfg.second_receiver_cfg {
+        let share = second_receiver_cfg.second_receiver_cut * Uint64::new(100);
+        if !share.is_zero() {
+            receivers.push(FeeReceiver {
+                address: second_receiver_cfg.second_fee_receiver.clone(),
+                share,
+                msg: None,
+            });
+            rest -= share;
+        }
+    }
+
+    if let Some(governance_contract) = &cfg.governance_contract {
+        let share = rest.multiply_ratio(cfg.governance_percent, 100u64);
+        if !share.is_zero() {
+            // ASTRO is sent to the governance contract the same way as before
+            let msg = match &cfg.astro_token {
+                AssetInfo::Token { .. } => Binary::default(),
+                AssetInfo::NativeToken { .. } => {
+                    to_binary(&astro_satellite_package::ExecuteMsg::TransferAstro {})?
+                }
+            };
+            receivers.push(FeeReceiver {
+                address: governance_contract.clone(),
+                share,
+                msg: Some(msg),
+            });
+            rest -= share;
+        }
+    }
+
+    if !rest.is_zero() {
+        receivers.push(FeeReceiver {
+            address: staking_contract,
+            share: rest,
+            msg: None,
+        });
+    }
+
+    cfg.receivers = receivers;
+    CONFIG.save(deps.storage, &cfg)
+}
@@ -0,0 +1,151 @@
+use crate::utils::BRIDGES_EXECUTION_MAX_DEPTH;
+use astroport::asset::{AssetInfo, PairInfo};
+use astroport::querier::query_pairs_info;
+use cosmwasm_std::{Addr, QuerierWrapper, StdError, StdResult, Uint256};
+use std::collections::{BTreeMap, VecDeque};
+
+/// The maximum number of swaps in an automatically discovered route. It matches the number of swaps
+/// the Maker can execute for a single fee token.
+pub const AUTO_ROUTE_MAX_HOPS: usize = BRIDGES_EXECUTION_MAX_DEPTH as usize + 1;
+/// The number of pairs read from the factory in a single query
+const PAIRS_QUERY_LIMIT: u32 = 30;
+/// The maximum number of factory queries made to build the graph. Pairs beyond the first
+/// `PAIRS_QUERY_LIMIT * MAX_PAIRS_QUERY_PAGES` ones are not used for routing
+const MAX_PAIRS_QUERY_PAGES: usize = 10;
+
+/// This structure describes the graph of the factory pairs used to discover routes to ASTRO.
+pub struct RouteGraph {
+    /// The ASTRO token asset info
+    astro: AssetInfo,
+    /// Pools that contain an asset along with the assets which can be received from them
+    pools: BTreeMap<String, Vec<(PairInfo, AssetInfo)>>,
+    /// The minimum number of swaps needed to get ASTRO for an asset
+    distances: BTreeMap<String, usize>,
+}
+
+impl RouteGraph {
+    /// Reads up to [`MAX_PAIRS_QUERY_PAGES`] pages of pairs from the factory and finds the shortest
+    /// distance to ASTRO for every asset which can be swapped to ASTRO within [`AUTO_ROUTE_MAX_HOPS`] swaps.
+    ///
+    /// * **factory_contract** address of the factory contract.
+    ///
+    /// * **astro_token** represents $ASTRO.
+    pub fn build(
+        querier: &QuerierWrapper,
+        factory_contract: &Addr,
+        astro_token: &AssetInfo,
+    ) -> StdResult<Self> {
+        let mut pools: BTreeMap<String, Vec<(PairInfo, AssetInfo)>> = BTreeMap::new();
+
+        let mut start_after = None;
+        for _ in 0..MAX_PAIRS_QUERY_PAGES {
+            let pairs = query_pairs_info(
+                querier,
+                factory_contract,
+                start_after,
+                Some(PAIRS_QUERY_LIMIT),
+            )?
+            .pairs;
+
+            for pair in &pairs {
+                for from in &pair.asset_infos {
+                    for to in pair.asset_infos.iter().filter(|to| !to.equal(from)) {
+                        pools
+                            .entry(from.to_string())
+                            .or_default()
+                            .push((pair.clone(), to.clone()));
+                    }
+                }
+            }
+
+            match pairs.last() {
+                Some(pair) if pairs.len() == PAIRS_QUERY_LIMIT as usize => {
+                    start_after = Some(pair.asset_infos.clone())
+                }
+                _ => break,
+            }
+        }
+
+        // Breadth-first search starting from ASTRO
+        let mut distances = BTreeMap::from([(astro_token.to_string(), 0)]);
+        let mut queue = VecDeque::from([(astro_token.to_string(), 0)]);
+        while let Some((asset, distance)) = queue.pop_front() {
+            if distance >= AUTO_ROUTE_MAX_HOPS {
+                continue;
+            }
+
+            for (_, next) in pools.get(&asset).into_iter().flatten() {
+                let next = next.to_string();
+                if !distances.contains_key(&next) {
+                    distances.insert(next.clone(), distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        Ok(Self {
+            astro: astro_token.clone(),
+            pools,
+            distances,
+        })
+    }
+
+    /// Returns the pool and the asset to swap `from` to on the way to ASTRO or [`None`] if ASTRO
+    /// can't be reached. Only the routes with the minimum number of swaps are considered and among them
+    /// the pool with the largest product of the `from` and the next asset reserves is chosen.
+    ///
+    /// * **from** asset we want to swap.
+    pub fn next_hop(
+        &self,
+        querier: &QuerierWrapper,
+        from: &AssetInfo,
+    ) -> StdResult<Option<(PairInfo, AssetInfo)>> {
+        let distance = match self.distances.get(&from.to_string()) {
+            Some(distance) if *distance > 0 => *distance,
+            _ => return Ok(None),
+        };
+
+        let mut best: Option<(Uint256, &PairInfo, &AssetInfo)> = None;
+        for (pool, to) in self.pools.get(&from.to_string()).into_iter().flatten() {
+            if self.distances.get(&to.to_string()) != Some(&(distance - 1)) {
+                continue;
+            }
+
+            // A pool with a large reserve on one side only gives a poor rate
+            let depth = Uint256::from(from.query_pool(querier, &pool.contract_addr)?)
+                * Uint256::from(to.query_pool(querier, &pool.contract_addr)?);
+            if depth.is_zero() {
+                continue;
+            }
+
+            if !matches!(best, Some((best_depth, ..)) if best_depth >= depth) {
+                best = Some((depth, pool, to));
+            }
+        }
+
+        Ok(best.map(|(_, pool, to)| (pool.clone(), to.clone())))
+    }
+
+    /// Returns the full route from `from` to ASTRO including both of them.
+    ///
+    /// * **from** asset we want to swap.
+    pub fn route(&self, querier: &QuerierWrapper, from: &AssetInfo) -> StdResult<Vec<AssetInfo>> {
+        let mut route = vec![from.clone()];
+
+        let mut asset = from.clone();
+        while !asset.equal(&self.astro) {
+            asset = match self.next_hop(querier, &asset)? {
+                Some((_, next)) => next,
+                None => {
+                    return Err(StdError::generic_err(format!(
+                        "No route from {from} to {} found",
+                        self.astro
+                    )))
+                }
+            };
+            route.push(asset.clone());
+        }
+
+        Ok(route)
+    }
+}
@@ -1,6 +1,8 @@
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::common::OwnershipProposal;
-use astroport::maker::Config;
+use astroport::maker::{AssetCollectConfig, CollectionEpoch, Config};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal256, Uint128};
 use cw_storage_plus::{Item, Map};
 
 /// Stores the contract configuration at the given key
@@ -11,3 +13,56 @@ pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_pro
 
 /// Stores bridge tokens used to swap fee tokens to ASTRO
 pub const BRIDGES: Map<String, AssetInfo> = Map::new("bridges");
+
+/// Stores oracle contracts used as reference price sources for specific pairs
+pub const ORACLES: Map<String, Addr> = Map::new("oracles");
+
+/// This structure stores the cumulative prices of a pool used to calculate its TWAP.
+#[cw_serde]
+pub struct PriceSnapshot {
+    /// The pool cumulative prices at the time of the snapshot
+    pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+    /// The average prices between the previous snapshot and this one
+    pub average_prices: Vec<(AssetInfo, AssetInfo, Decimal256)>,
+    /// The time of the snapshot (in seconds)
+    pub timestamp: u64,
+}
+
+/// Stores the latest cumulative prices snapshot of each pool used as a reference price source
+pub const PRICE_SNAPSHOTS: Map<String, PriceSnapshot> = Map::new("price_snapshots");
+
+/// Stores the total amount of ASTRO sent to each receiver
+pub const DISTRIBUTED_AMOUNTS: Map<String, Uint128> = Map::new("distributed_amounts");
+
+/// Stores the caller of the current `Collect` who receives the keeper bounty once ASTRO is distributed
+/// and the Maker ASTRO balance before the swaps of that call
+pub const KEEPER: Item<(Addr, Uint128)> = Item::new("keeper");
+
+/// Stores the last time (in seconds) each asset was collected while the keeper bounty is enabled
+pub const LAST_COLLECTED: Map<String, u64> = Map::new("last_collected");
+
+/// Stores the collection accounting for each epoch by the epoch start timestamp
+pub const COLLECTION_HISTORY: Map<u64, CollectionEpoch> = Map::new("collection_history");
+
+/// Stores the total amount of each asset collected
+pub const COLLECTED_TOTALS: Map<String, Asset> = Map::new("collected_totals");
+
+/// Stores the pair of each CW20 fee token checked for being an LP token, or [`None`] if it's not
+/// the LP token of a factory pair
+pub const LP_PAIRS: Map<String, Option<PairInfo>> = Map::new("lp_pairs");
+
+/// Stores the collect parameters of fee tokens
+pub const COLLECT_CONFIGS: Map<String, AssetCollectConfig> = Map::new("collect_configs");
+
+/// This structure describes the part of a bridge asset balance obtained by swapping fee assets.
+#[cw_serde]
+pub struct BridgeOrigins {
+    /// The bridge asset
+    pub asset: AssetInfo,
+    /// The amount of the bridge asset obtained from each fee asset
+    pub origins: Vec<(String, Uint128)>,
+}
+
+/// Stores the fee assets each bridge asset balance was obtained from, so the ASTRO obtained
+/// through bridges is credited to the originating fee assets
+pub const BRIDGE_ORIGINS: Map<String, BridgeOrigins> = Map::new("bridge_origins");
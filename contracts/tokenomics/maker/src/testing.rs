@@ -1,10 +1,17 @@
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, Addr, Decimal, Uint128, Uint64};
+use cosmwasm_std::{
+    coin, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Uint128, Uint64, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::state::CONFIG;
-use astroport::asset::{native_asset_info, token_asset_info};
-use astroport::maker::{Config, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use crate::utils::{build_receiver_msg, build_send_msg};
+use astroport::asset::{native_asset, native_asset_info, token_asset, token_asset_info};
+use astroport::maker::{
+    Config, ConfigResponse, ExecuteMsg, FeeReceiver, InstantiateMsg, MigrateMsg, QueryMsg,
+    SecondReceiverParams,
+};
 use std::str::FromStr;
 
 #[test]
@@ -51,7 +58,11 @@ fn proper_initialization() {
             last_distribution_block: 0,
             remainder_reward: Uint128::zero(),
             pre_upgrade_astro_amount: Uint128::zero(),
-            second_receiver_cfg: None
+            second_receiver_cfg: None,
+            twap_protection: None,
+            auto_routing: false,
+            receivers: vec![],
+            keeper_bounty: None,
         }
     )
 }
@@ -142,3 +153,127 @@ fn update_owner() {
         from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!(new_owner, config.owner);
 }
+
+#[test]
+fn receiver_messages() {
+    let receiver = FeeReceiver {
+        address: Addr::unchecked("receiver"),
+        share: Uint64::new(1),
+        msg: None,
+    };
+    let payload = Binary::from(br#"{"deposit":{}}"#);
+
+    let astro = token_asset(Addr::unchecked("astro"), Uint128::new(100));
+    assert_eq!(
+        build_receiver_msg(&astro, &receiver).unwrap(),
+        astro.clone().into_msg("receiver").unwrap()
+    );
+    assert_eq!(
+        build_receiver_msg(
+            &astro,
+            &FeeReceiver {
+                msg: Some(payload.clone()),
+                ..receiver.clone()
+            }
+        )
+        .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "astro".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "receiver".to_string(),
+                amount: Uint128::new(100),
+                msg: payload.clone(),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let astro = native_asset("astro".to_string(), Uint128::new(100));
+    assert_eq!(
+        build_receiver_msg(&astro, &receiver).unwrap(),
+        astro.clone().into_msg("receiver").unwrap()
+    );
+    assert_eq!(
+        build_receiver_msg(
+            &astro,
+            &FeeReceiver {
+                msg: Some(payload.clone()),
+                ..receiver
+            }
+        )
+        .unwrap(),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "receiver".to_string(),
+            msg: payload,
+            funds: vec![coin(100, "astro")],
+        })
+    );
+}
+
+#[test]
+fn migrate_legacy_split_to_receivers() {
+    let mut deps = mock_dependencies();
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        InstantiateMsg {
+            owner: "owner".to_string(),
+            factory_contract: "factory".to_string(),
+            staking_contract: Some("staking".to_string()),
+            governance_contract: Some("governance".to_string()),
+            governance_percent: Some(Uint64::new(10)),
+            astro_token: token_asset_info(Addr::unchecked("astro-token")),
+            default_bridge: None,
+            max_spread: None,
+            second_receiver_params: Some(SecondReceiverParams {
+                second_fee_receiver: "second_receiver".to_string(),
+                second_receiver_cut: Uint64::new(20),
+            }),
+        },
+    )
+    .unwrap();
+    cw2::set_contract_version(deps.as_mut().storage, "astroport-maker", "1.3.1").unwrap();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            default_bridge: None,
+            second_receiver_params: None,
+        },
+    )
+    .unwrap();
+
+    // 20% go to the second receiver, 10% of the rest to governance and the rest to staking
+    let cfg = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        cfg.receivers,
+        vec![
+            FeeReceiver {
+                address: Addr::unchecked("second_receiver"),
+                share: Uint64::new(2000),
+                msg: None,
+            },
+            FeeReceiver {
+                address: Addr::unchecked("governance"),
+                share: Uint64::new(800),
+                msg: Some(Binary::default()),
+            },
+            FeeReceiver {
+                address: Addr::unchecked("staking"),
+                share: Uint64::new(7200),
+                msg: None,
+            },
+        ]
+    );
+
+    // Governance receives ASTRO the same way as with the split
+    let astro = token_asset(Addr::unchecked("astro-token"), Uint128::new(80));
+    assert_eq!(
+        build_receiver_msg(&astro, &cfg.receivers[1]).unwrap(),
+        build_send_msg(&astro, "governance", None).unwrap()
+    );
+}
@@ -1,17 +1,23 @@
 use crate::error::ContractError;
-use crate::state::BRIDGES;
-use astroport::asset::{Asset, AssetInfo, PairInfo};
+use crate::state::{PriceSnapshot, BRIDGES, LP_PAIRS, ORACLES, PRICE_SNAPSHOTS};
+use astroport::asset::{pair_info_by_pool, Asset, AssetInfo, PairInfo};
 use astroport::maker::{
-    Config, ExecuteMsg, SecondReceiverConfig, SecondReceiverParams, MAX_SECOND_RECEIVER_CUT,
+    Config, ExecuteMsg, FeeReceiver, FeeReceiverParams, SecondReceiverConfig, SecondReceiverParams,
+    TwapProtection, MAX_RECEIVERS, MAX_SECOND_RECEIVER_CUT,
+};
+use astroport::oracle::QueryMsg as OracleQueryMsg;
+use astroport::pair::{
+    CumulativePricesResponse, Cw20HookMsg, QueryMsg as PairQueryMsg, SimulationResponse,
+    TWAP_PRECISION,
 };
-use astroport::pair::Cw20HookMsg;
 use astroport::querier::query_pair_info;
 
 use cosmwasm_std::{
-    coins, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal, Deps, Env, QuerierWrapper,
-    StdError, StdResult, SubMsg, Uint128, WasmMsg,
+    coins, to_binary, wasm_execute, Addr, Binary, CosmosMsg, Decimal, Decimal256, Deps, DepsMut,
+    Env, QuerierWrapper, StdError, StdResult, Storage, SubMsg, Uint128, Uint256, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+use std::collections::HashSet;
 
 /// The default bridge depth for a fee token
 pub const BRIDGES_INITIAL_DEPTH: u64 = 0;
@@ -21,6 +27,7 @@ pub const BRIDGES_MAX_DEPTH: u64 = 2;
 pub const BRIDGES_EXECUTION_MAX_DEPTH: u64 = 3;
 
 /// The function checks from<>to pool exists and creates swap message.
+/// Returns the pool along with the message.
 ///
 /// * **from** asset we want to swap.
 ///
@@ -33,10 +40,10 @@ pub fn try_build_swap_msg(
     from: &AssetInfo,
     to: &AssetInfo,
     amount_in: Uint128,
-) -> Result<SubMsg, ContractError> {
+) -> Result<(PairInfo, SubMsg), ContractError> {
     let pool = get_pool(querier, &cfg.factory_contract, from, to)?;
     let msg = build_swap_msg(cfg.max_spread, &pool, from, Some(to), amount_in)?;
-    Ok(msg)
+    Ok((pool, msg))
 }
 
 /// This function creates swap message.
@@ -196,6 +203,256 @@ pub fn get_pool(
     .map_err(|_| ContractError::InvalidBridgeNoPool(from.to_string(), to.to_string()))
 }
 
+/// Returns the pair if the asset is the LP token of a pair registered in the factory.
+/// The result is cached, so every CW20 fee token is queried only once.
+///
+/// * **factory_contract** address of the factory contract.
+///
+/// * **asset** asset to check.
+pub fn get_lp_pair(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper,
+    factory_contract: &Addr,
+    asset: &AssetInfo,
+) -> StdResult<Option<PairInfo>> {
+    let lp_token = match asset {
+        AssetInfo::Token { contract_addr } => contract_addr,
+        AssetInfo::NativeToken { .. } => return Ok(None),
+    };
+
+    if let Some(pair) = LP_PAIRS.may_load(storage, lp_token.to_string())? {
+        return Ok(pair);
+    }
+
+    let pair = query_lp_pair(querier, factory_contract, lp_token);
+    LP_PAIRS.save(storage, lp_token.to_string(), &pair)?;
+
+    Ok(pair)
+}
+
+/// Queries the minter of the token and checks that it is a pair registered in the factory.
+fn query_lp_pair(
+    querier: &QuerierWrapper,
+    factory_contract: &Addr,
+    lp_token: &Addr,
+) -> Option<PairInfo> {
+    let pair = pair_info_by_pool(querier, lp_token).ok()?;
+    if pair.liquidity_token != *lp_token {
+        return None;
+    }
+
+    // Make sure the minter is a pair created by the factory
+    let registered_pair = query_pair_info(querier, factory_contract, &pair.asset_infos).ok()?;
+    if registered_pair.contract_addr != pair.contract_addr {
+        return None;
+    }
+
+    Some(pair)
+}
+
+/// Creates the message which withdraws liquidity from the pair by sending LP tokens to it.
+///
+/// * **pair** pair to withdraw liquidity from.
+///
+/// * **amount** amount of LP tokens to withdraw.
+pub fn build_withdraw_msg(pair: &PairInfo, amount: Uint128) -> StdResult<SubMsg> {
+    Ok(SubMsg::new(WasmMsg::Execute {
+        contract_addr: pair.liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: pair.contract_addr.to_string(),
+            amount,
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity { assets: vec![] })?,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// Returns the amount of `to` tokens that should be received for `amount_in` of `from` tokens
+/// according to the reference price. The oracle set for the pool takes precedence over the pool TWAP.
+/// Returns [`None`] if the reference price is not available.
+pub fn query_reference_return(
+    deps: DepsMut,
+    env: &Env,
+    protection: &TwapProtection,
+    pool: &PairInfo,
+    from: &AssetInfo,
+    to: &AssetInfo,
+    amount_in: Uint128,
+) -> StdResult<Option<Uint128>> {
+    if let Some(oracle) = ORACLES.may_load(deps.storage, pool.contract_addr.to_string())? {
+        let prices: StdResult<Vec<(AssetInfo, Uint256)>> = deps.querier.query_wasm_smart(
+            oracle,
+            &OracleQueryMsg::Consult {
+                token: from.clone(),
+                amount: amount_in,
+            },
+        );
+
+        return match prices {
+            Ok(prices) => prices
+                .into_iter()
+                .find(|(info, _)| info.equal(to))
+                .map(|(_, amount)| Uint128::try_from(amount).map_err(StdError::from))
+                .transpose(),
+            Err(_) => Ok(None),
+        };
+    }
+
+    let average_prices = match update_price_snapshot(deps, env, protection, pool)? {
+        Some(average_prices) => average_prices,
+        None => return Ok(None),
+    };
+
+    // Cumulative prices are reported for each ordered pair of pool assets
+    let price = average_prices
+        .into_iter()
+        .find(|(offer, ask, _)| offer.equal(from) && ask.equal(to))
+        .map(|(_, _, price)| price);
+    match price {
+        Some(price) if !price.is_zero() => {
+            let price_precision = Uint256::from(10_u128.pow(TWAP_PRECISION.into()));
+            let amount = Uint256::from(amount_in) * price / price_precision;
+            Ok(Some(Uint128::try_from(amount)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Takes a new snapshot of the pool cumulative prices once at least `observation_period` seconds
+/// have passed since the previous one and returns the average prices between the last two snapshots.
+/// Returns [`None`] if the pool doesn't report cumulative prices or there is only one snapshot.
+///
+/// * **pool** pool for which the TWAP is calculated.
+fn update_price_snapshot(
+    deps: DepsMut,
+    env: &Env,
+    protection: &TwapProtection,
+    pool: &PairInfo,
+) -> StdResult<Option<Vec<(AssetInfo, AssetInfo, Decimal256)>>> {
+    let block_time = env.block.time.seconds();
+    let key = pool.contract_addr.to_string();
+    let last = PRICE_SNAPSHOTS.may_load(deps.storage, key.clone())?;
+
+    let average_prices = match last {
+        Some(last) if block_time < last.timestamp + protection.observation_period => {
+            last.average_prices
+        }
+        _ => {
+            let prices: CumulativePricesResponse = match deps
+                .querier
+                .query_wasm_smart(&pool.contract_addr, &PairQueryMsg::CumulativePrices {})
+            {
+                Ok(prices) => prices,
+                Err(_) => return Ok(None),
+            };
+
+            let average_prices = match &last {
+                Some(last) => {
+                    let time_elapsed = block_time - last.timestamp;
+                    prices
+                        .cumulative_prices
+                        .iter()
+                        .filter_map(|(offer, ask, price)| {
+                            last.cumulative_prices
+                                .iter()
+                                .find(|(offer_last, ask_last, _)| {
+                                    offer.equal(offer_last) && ask.equal(ask_last)
+                                })
+                                .map(|(_, _, price_last)| {
+                                    (
+                                        offer.clone(),
+                                        ask.clone(),
+                                        Decimal256::from_ratio(
+                                            Uint256::from(price.wrapping_sub(*price_last)),
+                                            time_elapsed,
+                                        ),
+                                    )
+                                })
+                        })
+                        .collect()
+                }
+                None => vec![],
+            };
+
+            PRICE_SNAPSHOTS.save(
+                deps.storage,
+                key,
+                &PriceSnapshot {
+                    cumulative_prices: prices.cumulative_prices,
+                    average_prices: average_prices.clone(),
+                    timestamp: block_time,
+                },
+            )?;
+
+            average_prices
+        }
+    };
+
+    if average_prices.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(average_prices))
+    }
+}
+
+/// Checks a fee swap against the reference price if TWAP protection is enabled.
+/// Returns the reason why the swap must be skipped or [`None`] if the swap can be performed.
+///
+/// * **pool** pool used for the swap.
+///
+/// * **from** asset we want to swap.
+///
+/// * **to** asset we want to swap to.
+///
+/// * **amount_in** amount of tokens to swap.
+pub fn check_swap_price(
+    mut deps: DepsMut,
+    env: &Env,
+    cfg: &Config,
+    pool: &PairInfo,
+    from: &AssetInfo,
+    to: &AssetInfo,
+    amount_in: Uint128,
+) -> StdResult<Option<String>> {
+    let protection = match &cfg.twap_protection {
+        Some(protection) => protection,
+        None => return Ok(None),
+    };
+
+    let reference_amount =
+        match query_reference_return(deps.branch(), env, protection, pool, from, to, amount_in)? {
+            Some(amount) if !amount.is_zero() => amount,
+            _ => return Ok(Some("no reference price".to_string())),
+        };
+
+    let simulation: StdResult<SimulationResponse> = deps.querier.query_wasm_smart(
+        &pool.contract_addr,
+        &PairQueryMsg::Simulation {
+            offer_asset: Asset {
+                info: from.clone(),
+                amount: amount_in,
+            },
+            ask_asset_info: Some(to.clone()),
+        },
+    );
+    let return_amount = match simulation {
+        Ok(simulation) => simulation.return_amount,
+        Err(_) => return Ok(Some("swap simulation failed".to_string())),
+    };
+
+    if return_amount < reference_amount {
+        let deviation = Decimal::from_ratio(reference_amount - return_amount, reference_amount);
+        if deviation > protection.max_deviation {
+            return Ok(Some(format!(
+                "price deviation {} exceeds {}",
+                deviation, protection.max_deviation
+            )));
+        }
+    }
+
+    Ok(None)
+}
+
 /// For native tokens of type [`AssetInfo`] uses method [`astro_satellite_package::ExecuteMsg::TransferAstro`]
 /// to send a token amount to a recipient.
 ///
@@ -225,6 +482,58 @@ pub fn build_send_msg(
     }
 }
 
+/// Builds the message which sends distributed ASTRO to a receiver. If the receiver has a payload message
+/// ASTRO is sent along with it, otherwise ASTRO is transferred.
+pub fn build_receiver_msg(asset: &Asset, receiver: &FeeReceiver) -> StdResult<CosmosMsg> {
+    match (&receiver.msg, &asset.info) {
+        (None, _) => asset.clone().into_msg(&receiver.address),
+        (Some(msg), AssetInfo::Token { contract_addr }) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: receiver.address.to_string(),
+                amount: asset.amount,
+                msg: msg.clone(),
+            })?,
+            funds: vec![],
+        })),
+        (Some(msg), AssetInfo::NativeToken { .. }) => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: receiver.address.to_string(),
+            msg: msg.clone(),
+            funds: vec![asset.as_coin()?],
+        })),
+    }
+}
+
+/// Validates weighted receivers of the distributed ASTRO.
+pub fn validate_receivers(
+    deps: Deps,
+    receivers: Vec<FeeReceiverParams>,
+) -> Result<Vec<FeeReceiver>, ContractError> {
+    if receivers.len() > MAX_RECEIVERS {
+        return Err(ContractError::TooManyReceivers(MAX_RECEIVERS));
+    }
+
+    let mut uniq = HashSet::new();
+    receivers
+        .into_iter()
+        .map(|params| {
+            let address = deps.api.addr_validate(&params.address)?;
+            if !uniq.insert(address.clone()) {
+                return Err(ContractError::DuplicatedReceiver(params.address));
+            }
+            if params.share.is_zero() {
+                return Err(ContractError::IncorrectReceiverShare {});
+            }
+
+            Ok(FeeReceiver {
+                address,
+                share: params.share,
+                msg: params.msg,
+            })
+        })
+        .collect()
+}
+
 /// Updates the parameters that describe the second receiver of fees
 pub fn update_second_receiver_cfg(
     deps: Deps,
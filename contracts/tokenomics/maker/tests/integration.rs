@@ -3,16 +3,21 @@ use astroport::asset::{
 };
 use astroport::factory::{PairConfig, PairType, UpdateAddr};
 use astroport::maker::{
-    AssetWithLimit, BalancesResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SecondReceiverConfig, SecondReceiverParams,
+    AssetCollectConfig, AssetWithLimit, BalancesResponse, CollectionEpoch, ConfigResponse,
+    ExecuteMsg, FeeReceiver, FeeReceiverParams, InstantiateMsg, KeeperBounty, QueryMsg,
+    SecondReceiverConfig, SecondReceiverParams, TwapProtection, COLLECTION_EPOCH_LENGTH,
+    MAX_KEEPER_BOUNTY_PERCENT, MAX_RECEIVERS,
 };
+use astroport::oracle::QueryMsg as OracleQueryMsg;
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport_governance::utils::EPOCH_START;
 use cosmwasm_std::{
-    attr, coin, to_binary, Addr, Coin, Decimal, QueryRequest, Uint128, Uint64, WasmQuery,
+    attr, coin, to_binary, Addr, Binary, Coin, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    QueryRequest, Response, StdResult, Uint128, Uint256, Uint64, WasmQuery,
 };
 use cw20::{BalanceResponse, Cw20QueryMsg, MinterResponse};
 use cw_multi_test::{next_block, App, ContractWrapper, Executor};
+use cw_storage_plus::Item;
 use std::str::FromStr;
 
 const OWNER: &str = "owner";
@@ -218,11 +223,14 @@ fn instantiate_contracts(
         )
         .unwrap();
 
-    let maker_contract = Box::new(ContractWrapper::new_with_empty(
-        astroport_maker::contract::execute,
-        astroport_maker::contract::instantiate,
-        astroport_maker::contract::query,
-    ));
+    let maker_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_maker::contract::execute,
+            astroport_maker::contract::instantiate,
+            astroport_maker::contract::query,
+        )
+        .with_reply_empty(astroport_maker::contract::reply),
+    );
 
     let market_code_id = router.store_code(maker_contract);
 
@@ -506,6 +514,7 @@ fn update_config() {
         basic_asset: None,
         max_spread: Some(new_max_spread),
         second_receiver_params: None,
+        auto_routing: Some(true),
     };
 
     // Assert cannot update with improper owner
@@ -535,6 +544,7 @@ fn update_config() {
     assert_eq!(res.governance_percent, new_governance_percent);
     assert_eq!(res.governance_contract, Some(new_governance.clone()));
     assert_eq!(res.max_spread, new_max_spread);
+    assert!(res.auto_routing);
 
     let msg = ExecuteMsg::UpdateConfig {
         governance_percent: None,
@@ -547,6 +557,7 @@ fn update_config() {
             second_fee_receiver: "second_fee_receiver".to_string(),
             second_receiver_cut: Default::default(),
         }),
+        auto_routing: None,
     };
 
     let err = router
@@ -565,6 +576,7 @@ fn update_config() {
             second_fee_receiver: "second_fee_receiver".to_string(),
             second_receiver_cut: Uint64::new(10),
         }),
+        auto_routing: None,
     };
 
     router
@@ -2572,3 +2584,1882 @@ fn collect_3pools() {
     assert_eq!(balances[0].amount.u128(), 100_000);
     assert_eq!(balances[1].amount.u128(), 100_000);
 }
+
+/// Stores the assets of the mock oracle
+const ORACLE_ASSETS: Item<Vec<AssetInfo>> = Item::new("assets");
+
+fn mock_oracle_instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: Vec<AssetInfo>,
+) -> StdResult<Response> {
+    ORACLE_ASSETS.save(deps.storage, &msg)?;
+    Ok(Response::new())
+}
+
+fn mock_oracle_execute(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: Empty,
+) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+/// The mock oracle always returns the 1:1 price
+fn mock_oracle_query(deps: Deps, _env: Env, msg: OracleQueryMsg) -> StdResult<Binary> {
+    match msg {
+        OracleQueryMsg::Consult { token, amount } => to_binary(
+            &ORACLE_ASSETS
+                .load(deps.storage)?
+                .into_iter()
+                .filter(|asset| !asset.equal(&token))
+                .map(|asset| (asset, Uint256::from(amount)))
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+
+#[test]
+fn collect_with_twap_protection() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+    let max_spread = Decimal::from_str("0.5").unwrap();
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        governance_percent,
+        Some(max_spread),
+        None,
+        None,
+    );
+
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::from(100_000_u128)),
+            token_asset(astro_token_instance.clone(), Uint128::from(100_000_u128)),
+        ],
+        None,
+    );
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(10_000),
+    );
+
+    let protection = TwapProtection {
+        max_deviation: Decimal::percent(5),
+        observation_period: 60,
+    };
+
+    // Only the owner can set TWAP protection
+    let err = router
+        .execute_contract(
+            user.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateTwapProtection {
+                params: Some(protection.clone()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateTwapProtection {
+                params: Some(TwapProtection {
+                    max_deviation: Decimal::zero(),
+                    observation_period: 60,
+                }),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Incorrect max deviation");
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateTwapProtection {
+                params: Some(protection.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.twap_protection, Some(protection));
+
+    let collect_msg = |limit: u128| ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(test_token_instance.clone()),
+            limit: Some(Uint128::new(limit)),
+        }],
+    };
+    let skipped_swap = |res: &cw_multi_test::AppResponse| {
+        res.events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .find(|attribute| attribute.key == "skipped_swap")
+            .map(|attribute| attribute.value.clone())
+    };
+
+    // The first collect takes a snapshot of the pool cumulative prices, so there is no TWAP yet
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(10_000),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        skipped_swap(&res),
+        Some(format!(
+            "{} to {}: no reference price",
+            test_token_instance, astro_token_instance
+        ))
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(10_000),
+    );
+
+    // The TWAP isn't available until the observation period passes
+    router.update_block(|block| block.time = block.time.plus_seconds(59));
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(10_000),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(
+        skipped_swap(&res),
+        Some(format!(
+            "{} to {}: no reference price",
+            test_token_instance, astro_token_instance
+        ))
+    );
+
+    // Swapping the whole balance moves the price by ~9% which exceeds the max deviation from the TWAP
+    router.update_block(|block| block.time = block.time.plus_seconds(1));
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(10_000),
+            &[],
+        )
+        .unwrap();
+    assert!(skipped_swap(&res).unwrap().starts_with(&format!(
+        "{} to {}: price deviation",
+        test_token_instance, astro_token_instance
+    )));
+
+    // A smaller swap is within the max deviation
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(1_000),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(skipped_swap(&res), None);
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(9_000),
+    );
+
+    // Set the oracle for the pair
+    let oracle_code_id = router.store_code(Box::new(ContractWrapper::new_with_empty(
+        mock_oracle_execute,
+        mock_oracle_instantiate,
+        mock_oracle_query,
+    )));
+    let oracle_instance = router
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &pair_info.asset_infos,
+            &[],
+            "Oracle",
+            None,
+        )
+        .unwrap();
+
+    let err = router
+        .execute_contract(
+            user.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateOracles {
+                add: Some(vec![(
+                    pair_info.contract_addr.to_string(),
+                    oracle_instance.to_string(),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateOracles {
+                add: Some(vec![(
+                    pair_info.contract_addr.to_string(),
+                    oracle_instance.to_string(),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: Vec<(String, String)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Oracles {})
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![(
+            pair_info.contract_addr.to_string(),
+            oracle_instance.to_string()
+        )]
+    );
+
+    // The oracle takes precedence over the pool TWAP
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(9_000),
+            &[],
+        )
+        .unwrap();
+    assert!(skipped_swap(&res).unwrap().starts_with(&format!(
+        "{} to {}: price deviation",
+        test_token_instance, astro_token_instance
+    )));
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(9_000),
+    );
+
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(1_000),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(skipped_swap(&res), None);
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(8_000),
+    );
+
+    // Disable TWAP protection
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateTwapProtection { params: None },
+            &[],
+        )
+        .unwrap();
+
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(8_000),
+            &[],
+        )
+        .unwrap();
+    assert_eq!(skipped_swap(&res), None);
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance,
+        Uint128::zero(),
+    );
+}
+
+#[test]
+fn collect_with_auto_routing() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        governance_percent,
+        Some(Decimal::from_str("0.5").unwrap()),
+        None,
+        None,
+    );
+
+    let [fee_token, shallow_bridge, deep_bridge, lopsided_bridge, unknown_token] =
+        ["FEE", "SHALLOW", "DEEP", "LOPSIDED", "UNKNOWN"].map(|symbol| {
+            instantiate_token(
+                &mut router,
+                owner.clone(),
+                format!("{symbol} token"),
+                symbol.to_string(),
+            )
+        });
+
+    let shallow_pair = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(fee_token.clone(), Uint128::new(100_000)),
+            token_asset(shallow_bridge.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+    for (bridge, amount) in [
+        (&shallow_bridge, 100_000u128),
+        (&deep_bridge, 1_000_000),
+        (&lopsided_bridge, 1_000_000),
+    ] {
+        create_pair(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &factory_instance,
+            vec![
+                token_asset(bridge.clone(), Uint128::new(amount)),
+                token_asset(astro_token_instance.clone(), Uint128::new(amount)),
+            ],
+            None,
+        );
+    }
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(fee_token.clone(), Uint128::new(1_000_000)),
+            token_asset(deep_bridge.clone(), Uint128::new(1_000_000)),
+        ],
+        None,
+    );
+
+    // The pool has the largest FEE reserve, but almost no LOPSIDED tokens
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(fee_token.clone(), Uint128::new(10_000_000)),
+            token_asset(lopsided_bridge.clone(), Uint128::new(10)),
+        ],
+        None,
+    );
+
+    // The route through the deepest pools is chosen
+    let route: Vec<AssetInfo> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::AutoRoute {
+                asset: token_asset_info(fee_token.clone()),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        route,
+        vec![
+            token_asset_info(fee_token.clone()),
+            token_asset_info(deep_bridge.clone()),
+            token_asset_info(astro_token_instance.clone()),
+        ]
+    );
+
+    let err = router
+        .wrap()
+        .query_wasm_smart::<Vec<AssetInfo>>(
+            &maker_instance,
+            &QueryMsg::AutoRoute {
+                asset: token_asset_info(unknown_token.clone()),
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains(&format!(
+        "No route from {} to {} found",
+        unknown_token, astro_token_instance
+    )));
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        fee_token.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(fee_token.clone()),
+            limit: None,
+        }],
+    };
+
+    // Auto routing is disabled by default
+    let err = router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("Cannot swap {}. No swap destinations", fee_token)
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                factory_contract: None,
+                staking_contract: None,
+                governance_contract: None,
+                governance_percent: None,
+                basic_asset: None,
+                max_spread: None,
+                second_receiver_params: None,
+                auto_routing: Some(true),
+            },
+            &[],
+        )
+        .unwrap();
+
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        fee_token.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        deep_bridge.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        shallow_pair.contract_addr.clone(),
+        fee_token.clone(),
+        Uint128::new(100_000),
+    );
+    let res: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_token_instance,
+            &Cw20QueryMsg::Balance {
+                address: staking.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(!res.balance.is_zero());
+
+    // Manual bridges take precedence over the discovered route
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateBridges {
+                add: Some(vec![(
+                    token_asset_info(fee_token.clone()),
+                    token_asset_info(shallow_bridge.clone()),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        fee_token.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        fee_token.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        shallow_pair.contract_addr.clone(),
+        fee_token.clone(),
+        Uint128::new(101_000),
+    );
+
+    // The default bridge takes precedence over the discovered route as well
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateBridges {
+                add: None,
+                remove: Some(vec![token_asset_info(fee_token.clone())]),
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                factory_contract: None,
+                staking_contract: None,
+                governance_contract: None,
+                governance_percent: None,
+                basic_asset: Some(token_asset_info(shallow_bridge.clone())),
+                max_spread: None,
+                second_receiver_params: None,
+                auto_routing: None,
+            },
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        fee_token.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000),
+    );
+
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        fee_token.clone(),
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        shallow_pair.contract_addr.clone(),
+        fee_token,
+        Uint128::new(102_000),
+    );
+}
+
+#[test]
+fn distribute_to_receivers() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, _, maker_instance, governance_instance) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        governance_percent,
+        None,
+        None,
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let receiver = |address: &str, share: u64| FeeReceiverParams {
+        address: address.to_string(),
+        share: Uint64::new(share),
+        msg: None,
+    };
+    let update_receivers =
+        |receivers: Vec<FeeReceiverParams>| ExecuteMsg::UpdateReceivers { receivers };
+
+    let err = router
+        .execute_contract(
+            Addr::unchecked("not_owner"),
+            maker_instance.clone(),
+            &update_receivers(vec![receiver("builder", 1)]),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_receivers(vec![receiver("builder", 1), receiver("dao", 0)]),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Receiver share must be greater than zero"
+    );
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_receivers(vec![receiver("builder", 1), receiver("builder", 2)]),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Duplicated receiver builder");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_receivers(
+                (0..=MAX_RECEIVERS)
+                    .map(|i| receiver(&format!("receiver{i}"), 1))
+                    .collect(),
+            ),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!("The number of receivers must not exceed {MAX_RECEIVERS}")
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_receivers(vec![
+                receiver("builder", 1),
+                receiver("insurance", 2),
+                receiver("dao", 2),
+            ]),
+            &[],
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(
+        res.receivers,
+        vec![
+            FeeReceiver {
+                address: Addr::unchecked("builder"),
+                share: Uint64::new(1),
+                msg: None,
+            },
+            FeeReceiver {
+                address: Addr::unchecked("insurance"),
+                share: Uint64::new(2),
+                msg: None,
+            },
+            FeeReceiver {
+                address: Addr::unchecked("dao"),
+                share: Uint64::new(2),
+                msg: None,
+            },
+        ]
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(astro_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_001),
+    );
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    // The last receiver gets the rounding remainder
+    for (receiver, amount) in [("builder", 200u128), ("insurance", 400), ("dao", 401)] {
+        check_balance(
+            &mut router,
+            Addr::unchecked(receiver),
+            astro_token_instance.clone(),
+            Uint128::new(amount),
+        );
+    }
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+
+    // An empty list restores the split between staking and governance
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_receivers(vec![]),
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(100),
+    );
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        staking.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(90),
+    );
+    check_balance(
+        &mut router,
+        governance_instance.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(10),
+    );
+
+    let res: Vec<(String, Uint128)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::DistributedAmounts {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let mut expected = vec![
+        ("builder".to_string(), Uint128::new(200)),
+        ("dao".to_string(), Uint128::new(401)),
+        (governance_instance.to_string(), Uint128::new(10)),
+        ("insurance".to_string(), Uint128::new(400)),
+        (staking.to_string(), Uint128::new(90)),
+    ];
+    expected.sort();
+    assert_eq!(res, expected);
+
+    let res: Vec<(String, Uint128)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::DistributedAmounts {
+                start_after: Some(expected[2].0.clone()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, vec![expected[3].clone()]);
+}
+
+#[test]
+fn collect_with_keeper_bounty() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let keeper = Addr::unchecked("keeper");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        governance_percent,
+        Some(Decimal::from_str("0.5").unwrap()),
+        None,
+        None,
+    );
+
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(astro_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let bounty = KeeperBounty {
+        percent: Decimal::percent(1),
+        max_amount: Uint128::new(50),
+        cooldown: 100,
+    };
+
+    let err = router
+        .execute_contract(
+            user.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateKeeperBounty {
+                params: Some(bounty.clone()),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateKeeperBounty {
+                params: Some(KeeperBounty {
+                    percent: Decimal::percent(MAX_KEEPER_BOUNTY_PERCENT + 1),
+                    ..bounty.clone()
+                }),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Incorrect keeper bounty percent"
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateKeeperBounty {
+                params: Some(bounty.clone()),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.keeper_bounty, Some(bounty));
+
+    // Collecting ASTRO held by the Maker doesn't earn a bounty
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        astro_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(1_000_000),
+    );
+    let collect_astro_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(astro_token_instance.clone()),
+            limit: None,
+        }],
+    };
+    router
+        .execute_contract(
+            keeper.clone(),
+            maker_instance.clone(),
+            &collect_astro_msg,
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        keeper.clone(),
+        astro_token_instance.clone(),
+        Uint128::zero(),
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(test_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // Collecting an empty balance doesn't start the cooldown
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(10_000),
+    );
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    // 1% of the obtained ASTRO exceeds the max bounty
+    check_balance(
+        &mut router,
+        keeper.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(50),
+    );
+
+    // The asset can't be collected again within the cooldown
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(10_000),
+    );
+    router.update_block(|block| block.time = block.time.plus_seconds(99));
+
+    let res = router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    let skipped_asset = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "skipped_asset")
+        .map(|attribute| attribute.value.clone());
+    assert_eq!(
+        skipped_asset,
+        Some(format!("{}: cooldown", test_token_instance))
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(10_000),
+    );
+    check_balance(
+        &mut router,
+        keeper.clone(),
+        astro_token_instance.clone(),
+        Uint128::new(50),
+    );
+
+    router.update_block(|block| block.time = block.time.plus_seconds(1));
+    router
+        .execute_contract(keeper.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance,
+        Uint128::zero(),
+    );
+    check_balance(&mut router, keeper, astro_token_instance, Uint128::new(100));
+}
+
+#[test]
+fn collect_lp_tokens() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            governance_percent,
+            Some(Decimal::from_str("0.5").unwrap()),
+            None,
+            None,
+        );
+
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+
+    let pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(astro_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    // Protocol-owned liquidity is sent to the Maker
+    router
+        .execute_contract(
+            user.clone(),
+            pair_info.liquidity_token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: maker_instance.to_string(),
+                amount: Uint128::new(10_000),
+            },
+            &[],
+        )
+        .unwrap();
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(pair_info.liquidity_token.clone()),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+    // Liquidity is withdrawn and the underlying TEST tokens are swapped to ASTRO
+    for token in [
+        &pair_info.liquidity_token,
+        &test_token_instance,
+        &astro_token_instance,
+    ] {
+        check_balance(
+            &mut router,
+            maker_instance.clone(),
+            token.clone(),
+            Uint128::zero(),
+        );
+    }
+
+    let staking_balance: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_token_instance,
+            &Cw20QueryMsg::Balance {
+                address: staking.to_string(),
+            },
+        )
+        .unwrap();
+    let governance_balance: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &astro_token_instance,
+            &Cw20QueryMsg::Balance {
+                address: governance_instance.to_string(),
+            },
+        )
+        .unwrap();
+    // Withdrawn ASTRO plus ASTRO obtained for withdrawn TEST
+    assert!(staking_balance.balance + governance_balance.balance > Uint128::new(10_000));
+
+    // Underlying assets without a route to ASTRO are left in the Maker
+    let orphan_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Orphan token".to_string(),
+        "ORPHAN".to_string(),
+    );
+    let orphan_pair_info = create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(orphan_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+    router
+        .execute_contract(
+            user,
+            orphan_pair_info.liquidity_token.clone(),
+            &cw20::Cw20ExecuteMsg::Transfer {
+                recipient: maker_instance.to_string(),
+                amount: Uint128::new(10_000),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res = router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(orphan_pair_info.liquidity_token.clone()),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    let skipped_swap = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "skipped_swap")
+        .map(|attribute| attribute.value.clone());
+    assert_eq!(
+        skipped_swap,
+        Some(format!("{}: no route to ASTRO", orphan_token_instance))
+    );
+
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        orphan_pair_info.liquidity_token,
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance,
+        Uint128::zero(),
+    );
+    check_balance(
+        &mut router,
+        maker_instance,
+        orphan_token_instance,
+        Uint128::new(10_000),
+    );
+}
+
+#[test]
+fn collection_accounting() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            governance_percent,
+            Some(Decimal::from_str("0.5").unwrap()),
+            None,
+            None,
+        );
+
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(astro_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![
+            AssetWithLimit {
+                info: token_asset_info(test_token_instance.clone()),
+                limit: None,
+            },
+            AssetWithLimit {
+                info: token_asset_info(astro_token_instance.clone()),
+                limit: None,
+            },
+        ],
+    };
+    let astro_balance = |router: &mut App, address: &Addr| {
+        router
+            .wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &astro_token_instance,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+            .balance
+    };
+
+    for (token, amount) in [
+        (&test_token_instance, 10_000u128),
+        (&astro_token_instance, 1_000),
+    ] {
+        mint_some_token(
+            &mut router,
+            owner.clone(),
+            token.clone(),
+            maker_instance.clone(),
+            Uint128::new(amount),
+        );
+    }
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    let block_time = router.block_info().time.seconds();
+    let first_epoch = block_time - block_time % COLLECTION_EPOCH_LENGTH;
+    let to_staking = astro_balance(&mut router, &staking);
+    let to_governance = astro_balance(&mut router, &governance_instance);
+
+    let history: Vec<(u64, CollectionEpoch)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::CollectionHistory {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        history,
+        vec![(
+            first_epoch,
+            CollectionEpoch {
+                collected: vec![token_asset(
+                    test_token_instance.clone(),
+                    Uint128::new(10_000)
+                )],
+                // Directly collected ASTRO is distributed but not obtained by swaps
+                astro_obtained: to_staking + to_governance - Uint128::new(1_000),
+                astro_obtained_by_asset: vec![(
+                    test_token_instance.to_string(),
+                    to_staking + to_governance - Uint128::new(1_000)
+                )],
+                to_staking,
+                to_governance,
+                ..Default::default()
+            }
+        )]
+    );
+    assert!(to_staking + to_governance > Uint128::new(1_000));
+
+    // The next collect falls into the next epoch
+    router.update_block(|block| block.time = block.time.plus_seconds(COLLECTION_EPOCH_LENGTH));
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(5_000),
+    );
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+
+    let history: Vec<(u64, CollectionEpoch)> = router
+        .wrap()
+        .query_wasm_smart(
+            &maker_instance,
+            &QueryMsg::CollectionHistory {
+                start_after: Some(first_epoch),
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].0, first_epoch + COLLECTION_EPOCH_LENGTH);
+    assert_eq!(
+        history[0].1.collected,
+        vec![token_asset(
+            test_token_instance.clone(),
+            Uint128::new(5_000)
+        )]
+    );
+    let astro_obtained = astro_balance(&mut router, &staking)
+        + astro_balance(&mut router, &governance_instance)
+        - to_staking
+        - to_governance;
+    assert_eq!(history[0].1.astro_obtained, astro_obtained);
+    assert_eq!(
+        history[0].1.astro_obtained_by_asset,
+        vec![(test_token_instance.to_string(), astro_obtained)]
+    );
+
+    let totals: Vec<Asset> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::TotalsByAsset {})
+        .unwrap();
+    assert_eq!(
+        totals,
+        vec![token_asset(test_token_instance, Uint128::new(15_000))]
+    );
+}
+
+#[test]
+fn collection_accounting_through_bridges() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+
+    let (astro_token_instance, factory_instance, maker_instance, governance_instance) =
+        instantiate_contracts(
+            &mut router,
+            owner.clone(),
+            staking.clone(),
+            Uint64::new(10),
+            Some(Decimal::from_str("0.5").unwrap()),
+            None,
+            None,
+        );
+
+    let fee_token = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Fee token".to_string(),
+        "FEE".to_string(),
+    );
+    let bridge_token = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Bridge token".to_string(),
+        "BRIDGE".to_string(),
+    );
+
+    for assets in [
+        vec![
+            token_asset(fee_token.clone(), Uint128::new(100_000)),
+            token_asset(bridge_token.clone(), Uint128::new(100_000)),
+        ],
+        vec![
+            token_asset(bridge_token.clone(), Uint128::new(100_000)),
+            token_asset(astro_token_instance.clone(), Uint128::new(100_000)),
+        ],
+    ] {
+        create_pair(
+            &mut router,
+            owner.clone(),
+            user.clone(),
+            &factory_instance,
+            assets,
+            None,
+        );
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateBridges {
+                add: Some(vec![(
+                    token_asset_info(fee_token.clone()),
+                    token_asset_info(bridge_token.clone()),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::EnableRewards { blocks: 1 },
+            &[],
+        )
+        .unwrap();
+
+    let collect_msg = |assets: &[&Addr]| ExecuteMsg::Collect {
+        assets: assets
+            .iter()
+            .map(|token| AssetWithLimit {
+                info: token_asset_info((*token).clone()),
+                limit: None,
+            })
+            .collect(),
+    };
+    let astro_distributed = |router: &mut App| {
+        [&staking, &governance_instance]
+            .into_iter()
+            .map(|address| {
+                router
+                    .wrap()
+                    .query_wasm_smart::<BalanceResponse>(
+                        &astro_token_instance,
+                        &Cw20QueryMsg::Balance {
+                            address: address.to_string(),
+                        },
+                    )
+                    .unwrap()
+                    .balance
+            })
+            .sum::<Uint128>()
+    };
+    let query_epoch = |router: &mut App| {
+        let history: Vec<(u64, CollectionEpoch)> = router
+            .wrap()
+            .query_wasm_smart(
+                &maker_instance,
+                &QueryMsg::CollectionHistory {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        history.last().unwrap().1.clone()
+    };
+
+    // The ASTRO obtained through the bridge is credited to the fee token
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        fee_token.clone(),
+        maker_instance.clone(),
+        Uint128::new(10_000),
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(&[&fee_token]),
+            &[],
+        )
+        .unwrap();
+
+    let astro_obtained = astro_distributed(&mut router);
+    assert!(!astro_obtained.is_zero());
+    let epoch = query_epoch(&mut router);
+    assert_eq!(epoch.astro_obtained, astro_obtained);
+    assert_eq!(
+        epoch.astro_obtained_by_asset,
+        vec![(fee_token.to_string(), astro_obtained)]
+    );
+
+    // The bridge token collected as a fee is credited separately from the bridged fee token
+    router.update_block(|block| block.time = block.time.plus_seconds(COLLECTION_EPOCH_LENGTH));
+    for token in [&fee_token, &bridge_token] {
+        mint_some_token(
+            &mut router,
+            owner.clone(),
+            token.clone(),
+            maker_instance.clone(),
+            Uint128::new(5_000),
+        );
+    }
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &collect_msg(&[&fee_token, &bridge_token]),
+            &[],
+        )
+        .unwrap();
+
+    let astro_obtained = astro_distributed(&mut router) - astro_obtained;
+    let epoch = query_epoch(&mut router);
+    assert_eq!(epoch.astro_obtained, astro_obtained);
+    assert_eq!(epoch.astro_obtained_by_asset.len(), 2);
+    assert!(epoch
+        .astro_obtained_by_asset
+        .iter()
+        .all(|(_, amount)| !amount.is_zero()));
+    assert_eq!(
+        epoch
+            .astro_obtained_by_asset
+            .iter()
+            .map(|(_, amount)| *amount)
+            .sum::<Uint128>(),
+        astro_obtained
+    );
+
+    // The bridge balance obtained from the fee token is fully swapped
+    assert_eq!(
+        router
+            .wrap()
+            .query_wasm_smart::<BalanceResponse>(
+                &bridge_token,
+                &Cw20QueryMsg::Balance {
+                    address: maker_instance.to_string(),
+                },
+            )
+            .unwrap()
+            .balance,
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn collect_with_collect_configs() {
+    let owner = Addr::unchecked("owner");
+    let mut router = mock_app(owner.clone(), vec![]);
+    let user = Addr::unchecked("user0000");
+    let staking = Addr::unchecked("staking");
+    let governance_percent = Uint64::new(10);
+
+    let (astro_token_instance, factory_instance, maker_instance, _) = instantiate_contracts(
+        &mut router,
+        owner.clone(),
+        staking.clone(),
+        governance_percent,
+        Some(Decimal::from_str("0.5").unwrap()),
+        None,
+        None,
+    );
+
+    let test_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Test token".to_string(),
+        "TEST".to_string(),
+    );
+
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user.clone(),
+        &factory_instance,
+        vec![
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(astro_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+
+    let collect_cfg = AssetCollectConfig {
+        min_collect_amount: Uint128::new(100),
+        max_swap_per_call: Some(Uint128::new(3_000)),
+    };
+    let update_msg = |collect_cfg: AssetCollectConfig| ExecuteMsg::UpdateCollectConfigs {
+        set: Some(vec![(
+            token_asset_info(test_token_instance.clone()),
+            collect_cfg,
+        )]),
+        remove: None,
+    };
+
+    let err = router
+        .execute_contract(
+            user.clone(),
+            maker_instance.clone(),
+            &update_msg(collect_cfg.clone()),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_msg(AssetCollectConfig {
+                max_swap_per_call: Some(Uint128::zero()),
+                ..collect_cfg.clone()
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        format!(
+            "Max swap per call for {} must be greater than zero",
+            test_token_instance
+        )
+    );
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_msg(collect_cfg.clone()),
+            &[],
+        )
+        .unwrap();
+
+    let res: Vec<(String, AssetCollectConfig)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::CollectConfigs {})
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![(test_token_instance.to_string(), collect_cfg.clone())]
+    );
+
+    let collect_msg = ExecuteMsg::Collect {
+        assets: vec![AssetWithLimit {
+            info: token_asset_info(test_token_instance.clone()),
+            limit: None,
+        }],
+    };
+
+    // Dust is not collected
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(50),
+    );
+    let res = router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    let skipped_asset = res
+        .events
+        .iter()
+        .flat_map(|event| event.attributes.iter())
+        .find(|attribute| attribute.key == "skipped_asset")
+        .map(|attribute| attribute.value.clone());
+    assert_eq!(
+        skipped_asset,
+        Some(format!("{}: below min collect amount", test_token_instance))
+    );
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::new(50),
+    );
+
+    // Large balances are collected in chunks
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        test_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(9_950),
+    );
+    for expected_balance in [7_000u128, 4_000, 1_000] {
+        router
+            .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+            .unwrap();
+        check_balance(
+            &mut router,
+            maker_instance.clone(),
+            test_token_instance.clone(),
+            Uint128::new(expected_balance),
+        );
+    }
+
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateCollectConfigs {
+                set: None,
+                remove: Some(vec![token_asset_info(test_token_instance.clone())]),
+            },
+            &[],
+        )
+        .unwrap();
+
+    let res: Vec<(String, AssetCollectConfig)> = router
+        .wrap()
+        .query_wasm_smart(&maker_instance, &QueryMsg::CollectConfigs {})
+        .unwrap();
+    assert!(res.is_empty());
+
+    router
+        .execute_contract(owner.clone(), maker_instance.clone(), &collect_msg, &[])
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        test_token_instance.clone(),
+        Uint128::zero(),
+    );
+
+    // Bridge hops obey the collect parameters of the bridge asset
+    let fee_token_instance = instantiate_token(
+        &mut router,
+        owner.clone(),
+        "Fee token".to_string(),
+        "FEE".to_string(),
+    );
+    create_pair(
+        &mut router,
+        owner.clone(),
+        user,
+        &factory_instance,
+        vec![
+            token_asset(fee_token_instance.clone(), Uint128::new(100_000)),
+            token_asset(test_token_instance.clone(), Uint128::new(100_000)),
+        ],
+        None,
+    );
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &ExecuteMsg::UpdateBridges {
+                add: Some(vec![(
+                    token_asset_info(fee_token_instance.clone()),
+                    token_asset_info(test_token_instance.clone()),
+                )]),
+                remove: None,
+            },
+            &[],
+        )
+        .unwrap();
+    router
+        .execute_contract(
+            owner.clone(),
+            maker_instance.clone(),
+            &update_msg(AssetCollectConfig {
+                min_collect_amount: Uint128::zero(),
+                max_swap_per_call: Some(Uint128::new(1_000)),
+            }),
+            &[],
+        )
+        .unwrap();
+
+    mint_some_token(
+        &mut router,
+        owner.clone(),
+        fee_token_instance.clone(),
+        maker_instance.clone(),
+        Uint128::new(5_000),
+    );
+    router
+        .execute_contract(
+            owner,
+            maker_instance.clone(),
+            &ExecuteMsg::Collect {
+                assets: vec![AssetWithLimit {
+                    info: token_asset_info(fee_token_instance.clone()),
+                    limit: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+    check_balance(
+        &mut router,
+        maker_instance.clone(),
+        fee_token_instance,
+        Uint128::zero(),
+    );
+
+    // Only 1_000 of ~4_750 TEST obtained for FEE is swapped to ASTRO
+    let bridge_balance: BalanceResponse = router
+        .wrap()
+        .query_wasm_smart(
+            &test_token_instance,
+            &Cw20QueryMsg::Balance {
+                address: maker_instance.to_string(),
+            },
+        )
+        .unwrap();
+    assert!(bridge_balance.balance > Uint128::new(3_500));
+    assert!(bridge_balance.balance < Uint128::new(4_000));
+}
@@ -52,8 +52,9 @@ use ::{
         Config, Cw20HookMsg, ExecuteMsg as GeneratorExecuteMsg, QueryMsg as GeneratorQueryMsg,
         RewardInfoResponse,
     },
+    astroport::token_factory::is_token_factory_denom,
     cosmwasm_std::{
-        to_binary, wasm_execute, CosmosMsg, Deps, Env, QuerierWrapper, StdError, Uint128,
+        coins, to_binary, wasm_execute, CosmosMsg, Deps, Env, QuerierWrapper, StdError, Uint128,
     },
     cw20::Cw20ExecuteMsg,
     cw_asset::AssetInfo,
@@ -72,10 +73,17 @@ impl CwStakingCommand for Astroport {
         self.generator_contract_address =
             self.staking_contract_address(deps, ans_host, &lp_token)?;
 
-        let AssetInfo::Cw20(token_addr) = lp_token.resolve(&deps.querier, ans_host)? else {
-                return Err(StdError::generic_err("expected CW20 as LP token for staking.").into());
-            };
-        self.lp_token_address = token_addr;
+        // Token factory LP denoms are stored as is
+        self.lp_token_address = match lp_token.resolve(&deps.querier, ans_host)? {
+            AssetInfo::Cw20(token_addr) => token_addr,
+            AssetInfo::Native(denom) if is_token_factory_denom(&denom) => Addr::unchecked(denom),
+            _ => {
+                return Err(StdError::generic_err(
+                    "expected CW20 or token factory denom as LP token for staking.",
+                )
+                .into())
+            }
+        };
         self.lp_token = AnsEntryConvertor::new(lp_token).lp_token()?;
         Ok(())
     }
@@ -86,6 +94,15 @@ impl CwStakingCommand for Astroport {
         amount: Uint128,
         _unbonding_period: Option<Duration>,
     ) -> Result<Vec<CosmosMsg>, CwStakingError> {
+        if is_token_factory_denom(self.lp_token_address.as_str()) {
+            return Ok(vec![wasm_execute(
+                self.generator_contract_address.to_string(),
+                &GeneratorExecuteMsg::Deposit { recipient: None },
+                coins(amount.u128(), self.lp_token_address.as_str()),
+            )?
+            .into()]);
+        }
+
         let msg = to_binary(&Cw20HookMsg::Deposit {})?;
         Ok(vec![wasm_execute(
             self.lp_token_address.to_string(),
@@ -202,10 +219,7 @@ impl CwStakingCommand for Astroport {
     fn query_rewards(
         &self,
         querier: &QuerierWrapper,
-    ) -> Result<
-        abstract_staking_adapter_traits::msg::RewardTokensResponse,
-        CwStakingError,
-    > {
+    ) -> Result<abstract_staking_adapter_traits::msg::RewardTokensResponse, CwStakingError> {
         let reward_info: RewardInfoResponse = querier
             .query_wasm_smart(
                 self.generator_contract_address.clone(),
@@ -19,6 +19,7 @@ use crate::querier::{
     query_balance, query_token_balance, query_token_precision, query_token_symbol,
 };
 use crate::token::is_valid_symbol;
+use crate::token_factory::{is_token_factory_denom, tf_denom_creator};
 
 /// UST token denomination
 pub const UUSD_DENOM: &str = "uusd";
@@ -461,8 +462,25 @@ pub fn token_asset_info(contract_addr: Addr) -> AssetInfo {
 
 /// Returns [`PairInfo`] by specified pool address.
 ///
-/// * **pool_addr** address of the pool.
+/// * **pool_addr** address of the LP token contract or the token factory LP denom.
 pub fn pair_info_by_pool(querier: &QuerierWrapper, pool: impl Into<String>) -> StdResult<PairInfo> {
+    let pool = pool.into();
+
+    // Only the creator of a token factory denom is able to mint it
+    if is_token_factory_denom(&pool) {
+        let pair_info: PairInfo =
+            querier.query_wasm_smart(tf_denom_creator(&pool)?, &PairQueryMsg::Pair {})?;
+
+        if pair_info.liquidity_token != pool {
+            return Err(StdError::generic_err(format!(
+                "{pool} is not the LP token of the pair {}",
+                pair_info.contract_addr
+            )));
+        }
+
+        return Ok(pair_info);
+    }
+
     let minter_info: MinterResponse = querier.query_wasm_smart(pool, &Cw20QueryMsg::Minter {})?;
 
     let pair_info: PairInfo =
@@ -2,7 +2,9 @@ use crate::asset::{Asset, AssetInfo};
 use crate::factory::PairType;
 use crate::restricted_vector::RestrictedVector;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{to_binary, Addr, Decimal, Env, StdResult, SubMsg, Uint128, Uint64, WasmMsg};
+use cosmwasm_std::{
+    to_binary, Addr, Coin, Decimal, Env, StdResult, SubMsg, Uint128, Uint64, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
 /// This structure describes the parameters used for creating a contract.
@@ -26,6 +28,11 @@ pub struct InstantiateMsg {
     pub tokens_per_block: Uint128,
     /// Start block for distributing ASTRO
     pub start_block: Uint64,
+    /// Amount of ASTRO distributed per second among all pairs. If set, emissions are time based
+    /// and `tokens_per_block` is not used
+    pub tokens_per_second: Option<Uint128>,
+    /// Start time for distributing ASTRO in the time based emission mode
+    pub start_time: Option<Uint64>,
     /// The ASTRO vesting contract that drips ASTRO rewards
     pub vesting_contract: String,
     /// Whitelist code id
@@ -58,6 +65,25 @@ pub enum ExecuteMsg {
         /// The list of pools with allocation point.
         pools: Vec<(String, Uint128)>,
     },
+    /// Schedules a new list of pools with allocation points which replaces the active pools at
+    /// the specified block height (or timestamp once ASTRO emissions are time based).
+    /// A schedule with the same `effective_at` is overwritten.
+    /// ## Executor
+    /// Only the owner or generator controller can execute this.
+    ScheduleSetupPools {
+        /// The list of pools with allocation point.
+        pools: Vec<(String, Uint128)>,
+        /// The block height or timestamp when the allocation points take effect
+        effective_at: u64,
+    },
+    /// Cancels allocation points scheduled via [`ExecuteMsg::ScheduleSetupPools`] which are not
+    /// applied yet.
+    /// ## Executor
+    /// Only the owner or generator controller can execute this.
+    CancelScheduledSetupPools {
+        /// The block height or timestamp of the schedule
+        effective_at: u64,
+    },
     /// Update the given pool's ASTRO allocation slice
     /// ## Executor
     /// Only the owner or generator controller can execute this.
@@ -67,7 +93,7 @@ pub enum ExecuteMsg {
         /// This flag determines whether the pool gets 3rd party token rewards
         has_asset_rewards: bool,
     },
-    /// Update rewards and return it to user.
+    /// Update rewards and return it to user. Pending external incentive rewards are sent as well.
     ClaimRewards {
         /// the LP token contract address
         lp_tokens: Vec<String>,
@@ -93,6 +119,11 @@ pub enum ExecuteMsg {
     },
     /// Receives a message of type [`Cw20ReceiveMsg`]
     Receive(Cw20ReceiveMsg),
+    /// Deposits token factory LP tokens sent along with the message
+    Deposit {
+        /// The address that will own the deposit. Defaults to the message sender
+        recipient: Option<String>,
+    },
     /// Set a new amount of ASTRO to distribute per block
     /// ## Executor
     /// Only the owner can execute this.
@@ -100,6 +131,13 @@ pub enum ExecuteMsg {
         /// The new amount of ASTRO to distro per block
         amount: Uint128,
     },
+    /// Set a new amount of ASTRO to distribute per second. Switches the contract to time based emissions
+    /// ## Executor
+    /// Only the owner can execute this.
+    SetTokensPerSecond {
+        /// The new amount of ASTRO to distro per second
+        amount: Uint128,
+    },
     /// Creates a request to change contract ownership
     /// ## Executor
     /// Only the current owner can execute this.
@@ -149,6 +187,77 @@ pub enum ExecuteMsg {
         generators: Vec<String>,
         user: Option<String>,
     },
+    /// Schedules an external reward stream for the pool. The rewards are distributed among the pool
+    /// stakers during `duration` seconds. Native rewards must be sent along with the message,
+    /// CW20 rewards are transferred using an allowance. Only whitelisted tokens can be used as rewards.
+    /// Scheduling a new stream costs the incentivization fee, if set. Topping up an active stream
+    /// is free and never shortens it.
+    /// ## Executor
+    /// Anyone can execute this.
+    Incentivize {
+        /// The LP token of the pool to incentivize
+        lp_token: String,
+        /// The reward asset and amount
+        reward: Asset,
+        /// The duration of the reward stream in seconds
+        duration: u64,
+    },
+    /// Sends the specified pending external incentive rewards to the staker.
+    /// Useful if one of the reward tokens can't be transferred and blocks `ClaimRewards`
+    ClaimIncentives {
+        /// The LP token of the pool
+        lp_token: String,
+        /// The reward tokens to claim
+        rewards: Vec<AssetInfo>,
+    },
+    /// Returns the rewards which were not distributed because the pool had no stakers to the
+    /// creator of an ended reward stream
+    /// ## Executor
+    /// Only the stream creator can execute this.
+    RefundIncentive {
+        /// The LP token of the pool
+        lp_token: String,
+        /// The reward token of the stream
+        reward: AssetInfo,
+    },
+    /// Add or remove tokens from the list of tokens which can be used as external incentive rewards
+    /// ## Executor
+    /// Only the owner can execute this.
+    UpdateIncentiveTokens {
+        /// Tokens to add
+        add: Option<Vec<AssetInfo>>,
+        /// Tokens to remove
+        remove: Option<Vec<AssetInfo>>,
+    },
+    /// Sets the lockup tiers available to stakers. Existing lockup positions keep their terms.
+    /// ## Executor
+    /// Only the owner can execute this.
+    SetLockupTiers {
+        tiers: Vec<LockupTier>,
+    },
+    /// Sets the fee charged for scheduling a new external incentive reward stream.
+    /// `None` removes the fee.
+    /// ## Executor
+    /// Only the owner can execute this.
+    SetIncentivizationFee {
+        fee_info: Option<IncentivizationFeeInfo>,
+    },
+    /// Locks deposited LP tokens for the duration of a lockup tier in order to boost their virtual amount
+    Lock {
+        /// The LP token to lock
+        lp_token: String,
+        /// The amount of deposited LP tokens to lock
+        amount: Uint128,
+        /// The lockup tier duration in seconds
+        duration: u64,
+    },
+    /// Releases an active lockup position before it ends. The early unlock penalty is sent to the Maker
+    EarlyUnlock {
+        /// The locked LP token
+        lp_token: String,
+        /// The index of the position as returned by [`QueryMsg::UserLockups`]
+        index: u32,
+    },
     /// Process action after the callback
     Callback {
         action: ExecuteOnReply,
@@ -187,6 +296,11 @@ pub enum ExecuteOnReply {
         /// The new amount of ASTRO to distribute per block
         amount: Uint128,
     },
+    /// Sets a new amount of ASTRO to distribute per second between all active generators
+    SetTokensPerSecond {
+        /// The new amount of ASTRO to distribute per second
+        amount: Uint128,
+    },
     /// Migrate LP tokens and collected rewards to new proxy
     MigrateProxy { lp_addr: Addr, new_proxy_addr: Addr },
     /// Stake LP tokens into new reward proxy
@@ -195,6 +309,26 @@ pub enum ExecuteOnReply {
         prev_proxy_addr: Addr,
         amount: Uint128,
     },
+    /// Locks deposited LP tokens for the duration of a lockup tier
+    Lock {
+        /// The LP token to lock
+        lp_token: Addr,
+        /// The owner of the deposit
+        account: Addr,
+        /// The amount of deposited LP tokens to lock
+        amount: Uint128,
+        /// The lockup tier duration in seconds
+        duration: u64,
+    },
+    /// Releases an active lockup position and sends the penalty to the Maker
+    EarlyUnlock {
+        /// The locked LP token
+        lp_token: Addr,
+        /// The owner of the lockup position
+        account: Addr,
+        /// The index of the lockup position
+        index: u32,
+    },
 }
 
 impl ExecuteOnReply {
@@ -246,6 +380,9 @@ pub enum QueryMsg {
     /// SimulateFutureReward returns the amount of ASTRO that will be distributed until a future block and for a specific generator
     #[returns(Uint128)]
     SimulateFutureReward { lp_token: String, future_block: u64 },
+    /// SimulateFutureRewardByTime returns the amount of ASTRO that will be distributed until a future time and for a specific generator
+    #[returns(Uint128)]
+    SimulateFutureRewardByTime { lp_token: String, future_time: u64 },
     /// Returns a list of stakers for a specific generator
     #[returns(Vec<StakerResponse>)]
     PoolStakers {
@@ -259,6 +396,15 @@ pub enum QueryMsg {
     /// Returns a list of reward proxy contracts which have been ever used
     #[returns(Vec<Addr>)]
     RewardProxiesList {},
+    /// Returns the lockup positions of a user in a specific generator
+    #[returns(Vec<LockupPosition>)]
+    UserLockups { lp_token: String, user: String },
+    /// Returns the upcoming allocation point changes ordered by their effective block height or timestamp
+    #[returns(Vec<ScheduledAllocation>)]
+    ScheduledAllocations {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 /// This structure holds the response returned when querying the amount of pending rewards that can be withdrawn from a 3rd party
@@ -269,6 +415,70 @@ pub struct PendingTokenResponse {
     pub pending: Uint128,
     /// The amount of pending 3rd party reward tokens
     pub pending_on_proxy: Option<Vec<Asset>>,
+    /// The amount of pending external incentive rewards
+    pub pending_incentives: Vec<Asset>,
+}
+
+/// This structure describes a lockup tier available to generator stakers.
+#[cw_serde]
+pub struct LockupTier {
+    /// The lockup duration in seconds
+    pub duration: u64,
+    /// The multiplier applied to the virtual amount of the locked LP tokens
+    pub multiplier: Decimal,
+    /// The share of the locked LP tokens sent to the Maker if the position is released early
+    pub early_unlock_penalty: Decimal,
+}
+
+/// This structure describes the fee charged for scheduling a new external incentive reward stream.
+#[cw_serde]
+pub struct IncentivizationFeeInfo {
+    /// The address which receives the fee
+    pub fee_receiver: String,
+    /// The native coin charged
+    pub fee: Coin,
+}
+
+/// This structure describes allocation points scheduled via [`ExecuteMsg::ScheduleSetupPools`].
+#[cw_serde]
+pub struct ScheduledAllocation {
+    /// The block height or timestamp when the allocation points take effect
+    pub effective_at: u64,
+    /// The list of pools with allocation point
+    pub pools: Vec<(Addr, Uint128)>,
+}
+
+/// This structure describes a user's lockup position in a generator.
+#[cw_serde]
+pub struct LockupPosition {
+    /// The amount of locked LP tokens
+    pub amount: Uint128,
+    /// The multiplier applied to the virtual amount of the locked LP tokens
+    pub multiplier: Decimal,
+    /// The share of the locked LP tokens sent to the Maker if the position is released early
+    pub early_unlock_penalty: Decimal,
+    /// The time when the lockup ends
+    pub end_time: u64,
+    /// The virtual amount added by the position as of the last virtual amount update
+    #[serde(default)]
+    pub boost: Uint128,
+}
+
+/// This structure describes an external reward stream scheduled via [`ExecuteMsg::Incentivize`].
+#[cw_serde]
+pub struct IncentiveInfo {
+    /// [`AssetInfo`] of the reward token
+    pub reward: AssetInfo,
+    /// The address which scheduled the reward stream and receives the undistributed rewards
+    pub creator: Addr,
+    /// The amount of rewards which are not distributed yet
+    pub remaining: Uint128,
+    /// The time when the reward stream ends
+    pub end_time: u64,
+    /// The last time when rewards were distributed
+    pub last_update_time: u64,
+    /// Accumulated amount of rewards per virtual share unit
+    pub reward_global_index: Decimal,
 }
 
 /// This structure describes the main information of pool
@@ -289,6 +499,12 @@ pub struct PoolInfo {
     pub has_asset_rewards: bool,
     /// Total virtual amount
     pub total_virtual_supply: Uint128,
+    /// The last time when token emissions were snapshotted in the time based emission mode
+    #[serde(default)]
+    pub last_reward_time: Option<Uint64>,
+    /// External incentive reward streams
+    #[serde(default)]
+    pub incentives: Vec<IncentiveInfo>,
 }
 
 /// This structure stores the outstanding amount of token rewards that a user accrued.
@@ -318,6 +534,22 @@ pub struct UserInfoV2 {
     pub reward_debt_proxy: RestrictedVector<Addr, Uint128>,
     /// The amount of user boosted emissions
     pub virtual_amount: Uint128,
+    /// External incentive reward indexes of the user. Vector of pairs (reward asset, index).
+    #[serde(default)]
+    pub incentive_user_index: RestrictedVector<AssetInfo, Decimal>,
+    /// Lockup positions of the user. Ended positions are removed on the next virtual amount update.
+    /// Until then their boost is only counted up to their end time
+    #[serde(default)]
+    pub lockups: Vec<LockupPosition>,
+    /// External incentive rewards accrued by the user which are not claimed yet
+    #[serde(default)]
+    pub pending_incentives: Vec<Asset>,
+    /// The time when the user's ASTRO rewards were last checkpointed
+    #[serde(default)]
+    pub reward_checkpoint_time: u64,
+    /// The time when the user's external incentive rewards were last checkpointed
+    #[serde(default)]
+    pub incentive_checkpoint_time: u64,
 }
 
 /// This structure holds the response returned when querying for the token addresses used to reward a specific generator
@@ -340,6 +572,12 @@ pub struct PoolInfoResponse {
     pub last_reward_block: u64,
     /// Current block number. Useful for computing APRs off-chain
     pub current_block: u64,
+    /// Amount of ASTRO tokens being distributed per second to this LP pool in the time based emission mode
+    pub astro_tokens_per_second: Option<Uint128>,
+    /// The last time when token emissions were snapshotted in the time based emission mode
+    pub last_reward_time: Option<u64>,
+    /// Current block time. Useful for computing APRs off-chain
+    pub current_time: u64,
     /// Total amount of ASTRO rewards already accumulated per LP token staked
     pub global_reward_index: Decimal,
     /// Pending amount of total ASTRO rewards which are claimable by stakers right now
@@ -356,6 +594,8 @@ pub struct PoolInfoResponse {
     pub orphan_proxy_rewards: Vec<(Addr, Uint128)>,
     /// Total amount of lp tokens staked in the pool's generator
     pub lp_supply: Uint128,
+    /// External incentive reward streams of the pool
+    pub incentives: Vec<IncentiveInfo>,
 }
 
 /// This structure stores the core parameters for the Generator contract.
@@ -389,6 +629,22 @@ pub struct Config {
     pub guardian: Option<Addr>,
     /// The amount of generators
     pub checkpoint_generator_limit: Option<u32>,
+    /// Total amount of ASTRO rewards per second. If set, emissions are time based and
+    /// `tokens_per_block` is not used
+    #[serde(default)]
+    pub tokens_per_second: Option<Uint128>,
+    /// The timestamp when the time based ASTRO distribution starts
+    #[serde(default)]
+    pub start_time: Uint64,
+    /// Lockup tiers which boost the virtual amount of locked LP tokens
+    #[serde(default)]
+    pub lockup_tiers: Vec<LockupTier>,
+    /// Tokens which can be used as external incentive rewards
+    #[serde(default)]
+    pub incentive_tokens: Vec<AssetInfo>,
+    /// The fee charged for scheduling a new external incentive reward stream
+    #[serde(default)]
+    pub incentivization_fee: Option<IncentivizationFeeInfo>,
 }
 
 /// This structure describes a migration message.
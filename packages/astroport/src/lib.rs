@@ -7,7 +7,9 @@ pub mod generator_proxy;
 pub mod maker;
 pub mod native_coin_registry;
 pub mod native_coin_wrapper;
+pub mod observation;
 pub mod oracle;
+pub mod orderbook;
 pub mod pair;
 pub mod pair_bonded;
 pub mod pair_concentrated;
@@ -18,6 +20,7 @@ pub mod router;
 pub mod shared_multisig;
 pub mod staking;
 pub mod token;
+pub mod token_factory;
 pub mod vesting;
 pub mod xastro_token;
 
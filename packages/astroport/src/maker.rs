@@ -1,7 +1,7 @@
 use crate::asset::{Asset, AssetInfo};
 use crate::factory::UpdateAddr;
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Decimal, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, Decimal, Uint128, Uint64};
 
 /// This structure stores the main parameters for the Maker contract.
 #[cw_serde]
@@ -34,6 +34,20 @@ pub struct Config {
     pub pre_upgrade_astro_amount: Uint128,
     /// Parameters that describe the second receiver of fees
     pub second_receiver_cfg: Option<SecondReceiverConfig>,
+    /// Parameters used to check fee swaps against reference prices
+    #[serde(default)]
+    pub twap_protection: Option<TwapProtection>,
+    /// Whether routes to ASTRO are discovered automatically for fee tokens without bridges
+    #[serde(default)]
+    pub auto_routing: bool,
+    /// Weighted receivers of the distributed ASTRO. If set, they replace the split between
+    /// the staking contract, the governance contract and the second receiver. Migrations convert
+    /// the split into equivalent receivers
+    #[serde(default)]
+    pub receivers: Vec<FeeReceiver>,
+    /// Parameters of the bounty paid to callers of `Collect`
+    #[serde(default)]
+    pub keeper_bounty: Option<KeeperBounty>,
 }
 
 /// This structure stores general parameters for the contract.
@@ -83,6 +97,18 @@ pub enum ExecuteMsg {
         max_spread: Option<Decimal>,
         /// The second receiver parameters of fees
         second_receiver_params: Option<SecondReceiverParams>,
+        /// Whether routes to ASTRO are discovered automatically for fee tokens without bridges
+        auto_routing: Option<bool>,
+    },
+    /// Sets weighted receivers of the distributed ASTRO. An empty list restores the split between
+    /// the staking contract, the governance contract and the second receiver
+    UpdateReceivers { receivers: Vec<FeeReceiverParams> },
+    /// Sets or disables (if None) the bounty paid to callers of `Collect`
+    UpdateKeeperBounty { params: Option<KeeperBounty> },
+    /// Sets or removes the collect parameters of specific fee tokens
+    UpdateCollectConfigs {
+        set: Option<Vec<(AssetInfo, AssetCollectConfig)>>,
+        remove: Option<Vec<AssetInfo>>,
     },
     /// Add bridge tokens used to swap specific fee tokens to ASTRO (effectively declaring a swap route)
     UpdateBridges {
@@ -106,6 +132,15 @@ pub enum ExecuteMsg {
     ClaimOwnership {},
     /// Enables the distribution of current fees accrued in the contract over "blocks" number of blocks
     EnableRewards { blocks: u64 },
+    /// Sets or disables (if None) the check of fee swaps against reference prices
+    UpdateTwapProtection { params: Option<TwapProtection> },
+    /// Add oracle contracts used as reference price sources for specific pairs
+    UpdateOracles {
+        /// Pairs of (pair contract address, oracle contract address)
+        add: Option<Vec<(String, String)>>,
+        /// Pair contract addresses
+        remove: Option<Vec<String>>,
+    },
 }
 
 /// This structure describes the query functions available in the contract.
@@ -120,6 +155,32 @@ pub enum QueryMsg {
     Balances { assets: Vec<AssetInfo> },
     #[returns(Vec<(String, String)>)]
     Bridges {},
+    /// Returns the oracles used as reference price sources in a vector of (pair, oracle) addresses
+    #[returns(Vec<(String, String)>)]
+    Oracles {},
+    /// Returns the route to ASTRO discovered through the factory pairs for the specified asset.
+    /// The route starts with the asset itself and ends with ASTRO
+    #[returns(Vec<AssetInfo>)]
+    AutoRoute { asset: AssetInfo },
+    /// Returns the collect parameters of fee tokens in a vector of (asset, [`AssetCollectConfig`])
+    #[returns(Vec<(String, AssetCollectConfig)>)]
+    CollectConfigs {},
+    /// Returns the collection accounting per epoch in a vector of (epoch start, [`CollectionEpoch`])
+    #[returns(Vec<(u64, CollectionEpoch)>)]
+    CollectionHistory {
+        /// The epoch start timestamp to start reading from
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the total amount of each asset collected by the Maker
+    #[returns(Vec<Asset>)]
+    TotalsByAsset {},
+    /// Returns the total amounts of ASTRO sent to each receiver in a vector of (receiver address, amount)
+    #[returns(Vec<(String, Uint128)>)]
+    DistributedAmounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 /// A custom struct that holds contract parameters and is used to retrieve them.
@@ -147,6 +208,14 @@ pub struct ConfigResponse {
     pub pre_upgrade_astro_amount: Uint128,
     /// Parameters that describe the second receiver of fees
     pub second_receiver_cfg: Option<SecondReceiverConfig>,
+    /// Parameters used to check fee swaps against reference prices
+    pub twap_protection: Option<TwapProtection>,
+    /// Whether routes to ASTRO are discovered automatically for fee tokens without bridges
+    pub auto_routing: bool,
+    /// Weighted receivers of the distributed ASTRO
+    pub receivers: Vec<FeeReceiver>,
+    /// Parameters of the bounty paid to callers of `Collect`
+    pub keeper_bounty: Option<KeeperBounty>,
 }
 
 /// A custom struct used to return multiple asset balances.
@@ -189,5 +258,90 @@ pub struct SecondReceiverConfig {
     pub second_receiver_cut: Uint64,
 }
 
+/// This structure describes the parameters for setting a receiver of the distributed ASTRO.
+#[cw_serde]
+pub struct FeeReceiverParams {
+    /// The receiver address
+    pub address: String,
+    /// The receiver share relative to the sum of all receiver shares
+    pub share: Uint64,
+    /// The message sent along with ASTRO to the receiver contract
+    pub msg: Option<Binary>,
+}
+
+/// This structure stores a receiver of the distributed ASTRO.
+#[cw_serde]
+pub struct FeeReceiver {
+    /// The receiver address
+    pub address: Addr,
+    /// The receiver share relative to the sum of all receiver shares
+    pub share: Uint64,
+    /// The message sent along with ASTRO to the receiver contract
+    pub msg: Option<Binary>,
+}
+
+/// This structure describes the collect parameters of a fee token.
+#[cw_serde]
+pub struct AssetCollectConfig {
+    /// Balances below this amount are not collected
+    pub min_collect_amount: Uint128,
+    /// The maximum amount swapped per `Collect` call. The rest is swapped in the next calls
+    pub max_swap_per_call: Option<Uint128>,
+}
+
+/// This structure describes the collection accounting for an epoch.
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionEpoch {
+    /// The amount of each asset collected (swapped or unwrapped from LP tokens)
+    pub collected: Vec<Asset>,
+    /// The amount of ASTRO obtained by swapping collected assets
+    pub astro_obtained: Uint128,
+    /// The amount of ASTRO obtained from each fee asset. ASTRO obtained through bridges is credited
+    /// to the fee assets the bridge asset balance was obtained from, pro rata to their share of it
+    #[serde(default)]
+    pub astro_obtained_by_asset: Vec<(String, Uint128)>,
+    /// The amount of ASTRO sent to the staking contract
+    pub to_staking: Uint128,
+    /// The amount of ASTRO sent to the governance contract
+    pub to_governance: Uint128,
+    /// The amount of ASTRO sent to the second receiver
+    pub to_second_receiver: Uint128,
+    /// The amount of ASTRO sent to weighted receivers
+    pub to_receivers: Uint128,
+    /// The amount of ASTRO paid as keeper bounties
+    pub to_keepers: Uint128,
+}
+
+/// This structure describes the bounty paid to callers of `Collect` from the distributed ASTRO.
+#[cw_serde]
+pub struct KeeperBounty {
+    /// The share of the ASTRO obtained by the collect which is paid to the caller
+    pub percent: Decimal,
+    /// The maximum bounty paid per call
+    pub max_amount: Uint128,
+    /// The minimum number of seconds between two collects of the same asset.
+    /// Assets collected more recently are skipped
+    pub cooldown: u64,
+}
+
+/// This structure describes the parameters used to protect fee swaps from price manipulation.
+/// Every swap is compared with the reference price which is taken from the oracle set for the pair or,
+/// if there is none, from the TWAP calculated from the pair cumulative prices. Swaps without a reference
+/// price or which deviate too far from it are skipped.
+#[cw_serde]
+pub struct TwapProtection {
+    /// The maximum allowed deviation of the swap return amount from the amount derived from the reference price
+    pub max_deviation: Decimal,
+    /// The minimum period (in seconds) over which the pair TWAP is calculated
+    pub observation_period: u64,
+}
+
 /// The maximum allowed second receiver share (percents)
 pub const MAX_SECOND_RECEIVER_CUT: Uint64 = Uint64::new(50);
+/// The length of the collection accounting epoch (in seconds)
+pub const COLLECTION_EPOCH_LENGTH: u64 = 86400;
+/// The maximum keeper bounty share (percents)
+pub const MAX_KEEPER_BOUNTY_PERCENT: u64 = 5;
+/// The maximum number of weighted receivers
+pub const MAX_RECEIVERS: usize = 10;
@@ -0,0 +1,309 @@
+use astroport_circular_buffer::error::{BufferError, BufferResult};
+use astroport_circular_buffer::{BufferManager, CircularBuffer};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_schema::serde::Serialize;
+use cosmwasm_std::{Decimal, Env, StdError, StdResult, Storage, Uint128, Uint256};
+
+use crate::cosmwasm_ext::AbsDiff;
+use crate::pair_concentrated::OracleObservation;
+
+/// This trait describes observations which are used to derive the price at any point in the past.
+pub trait PriceObservation {
+    /// Returns the time of the observation (in seconds)
+    fn timestamp(&self) -> u64;
+
+    /// Returns the observed price
+    fn price(&self) -> Decimal;
+}
+
+/// Stores trade size observations. We use it to derive prices for external contracts/users.
+#[cw_serde]
+#[derive(Copy)]
+pub struct Observation {
+    pub timestamp: u64,
+    /// Base asset simple moving average (mean)
+    pub base_sma: Uint128,
+    /// Base asset amount that was added at this observation
+    pub base_amount: Uint128,
+    /// Quote asset simple moving average (mean)
+    pub quote_sma: Uint128,
+    /// Quote asset amount that was added at this observation
+    pub quote_amount: Uint128,
+}
+
+impl PriceObservation for Observation {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// The amount of the base asset per one quote asset
+    fn price(&self) -> Decimal {
+        Decimal::from_ratio(self.base_amount, self.quote_amount)
+    }
+}
+
+/// Internal function to calculate new moving average using Uint256.
+/// Overflow is possible only if new average order size is greater than 2^128 - 1 which is unlikely.
+fn safe_sma_calculation(
+    sma: Uint128,
+    oldest_amount: Uint128,
+    count: u32,
+    new_amount: Uint128,
+) -> StdResult<Uint128> {
+    let res = (sma.full_mul(count) + Uint256::from(new_amount) - Uint256::from(oldest_amount))
+        .checked_div(count.into())?;
+    res.try_into().map_err(StdError::from)
+}
+
+/// Calculate and save moving averages of swap sizes.
+///
+/// * **observations** circular buffer with trade size observations.
+pub fn accumulate_swap_sizes(
+    storage: &mut dyn Storage,
+    env: &Env,
+    observations: CircularBuffer<Observation>,
+    base_amount: Uint128,
+    quote_amount: Uint128,
+) -> BufferResult<()> {
+    let mut buffer = BufferManager::new(storage, observations)?;
+
+    let new_observation;
+    if let Some(last_obs) = buffer.read_last(storage)? {
+        // Since this is circular buffer the next index contains the oldest value
+        let count = buffer.capacity();
+        if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
+            let new_base_sma = safe_sma_calculation(
+                last_obs.base_sma,
+                oldest_obs.base_amount,
+                count,
+                base_amount,
+            )?;
+            let new_quote_sma = safe_sma_calculation(
+                last_obs.quote_sma,
+                oldest_obs.quote_amount,
+                count,
+                quote_amount,
+            )?;
+            new_observation = Observation {
+                base_amount,
+                quote_amount,
+                base_sma: new_base_sma,
+                quote_sma: new_quote_sma,
+                timestamp: env.block.time.seconds(),
+            };
+        } else {
+            // Buffer is not full yet
+            let count = Uint128::from(buffer.head());
+            let new_base_sma = (last_obs.base_sma * count + base_amount) / (count + Uint128::one());
+            let new_quote_sma =
+                (last_obs.quote_sma * count + quote_amount) / (count + Uint128::one());
+            new_observation = Observation {
+                base_amount,
+                quote_amount,
+                base_sma: new_base_sma,
+                quote_sma: new_quote_sma,
+                timestamp: env.block.time.seconds(),
+            };
+        }
+    } else {
+        // Buffer is empty
+        new_observation = Observation {
+            timestamp: env.block.time.seconds(),
+            base_sma: base_amount,
+            base_amount,
+            quote_sma: quote_amount,
+            quote_amount,
+        };
+    }
+
+    buffer.instant_push(storage, &new_observation)
+}
+
+/// Performs binary search in circular buffer. Returns left and right bounds of target value.
+/// Either left or right bound may hit in target value.
+fn binary_search<T>(
+    storage: &dyn Storage,
+    buffer: &BufferManager<T>,
+    target: u64,
+    mut start: u32,
+    mut end: u32,
+) -> StdResult<(T, T)>
+where
+    T: PriceObservation + Serialize + DeserializeOwned,
+{
+    loop {
+        let mid = (start + end) / 2;
+
+        // We've checked bounds before calling this function thus these errors should be impossible.
+        let leftward_or_hit = buffer.read_single(storage, mid)?.ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Unexpected error in binary_search: leftward_or_hit is None at index {mid}",
+            ))
+        })?;
+        let rightward_or_hit = buffer.read_single(storage, mid + 1)?.ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Unexpected error in binary_search: rightward_or_hit is None at index {}",
+                mid + 1
+            ))
+        })?;
+
+        if leftward_or_hit.timestamp() <= target && target <= rightward_or_hit.timestamp() {
+            break Ok((leftward_or_hit, rightward_or_hit));
+        }
+        if leftward_or_hit.timestamp() > target {
+            end = mid - 1;
+        } else {
+            start = mid + 1;
+        }
+    }
+}
+
+/// Returns price observation at point that was 'seconds_ago' seconds ago.
+/// The price between two observations is interpolated linearly.
+///
+/// * **observations** circular buffer with observations. It may be not initialized yet.
+pub fn query_observation<T>(
+    storage: &dyn Storage,
+    env: Env,
+    observations: CircularBuffer<T>,
+    seconds_ago: u64,
+) -> StdResult<OracleObservation>
+where
+    T: PriceObservation + Serialize + DeserializeOwned,
+{
+    let buffer = match BufferManager::new(storage, observations) {
+        Ok(buffer) => buffer,
+        Err(BufferError::BufferNotInitialized {}) => {
+            return Err(StdError::generic_err("Buffer is empty"))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    let target = env.block.time.seconds().saturating_sub(seconds_ago);
+
+    let mut oldest_ind = buffer.head();
+    let mut newest_ind = buffer.head() + buffer.capacity() - 1;
+    if !buffer.exists(storage, oldest_ind) {
+        if buffer.head() > 0 {
+            oldest_ind = 0;
+            newest_ind %= buffer.capacity();
+        } else {
+            return Err(StdError::generic_err("Buffer is empty"));
+        }
+    }
+
+    let newest_obs = buffer.read_single(storage, newest_ind)?.unwrap();
+    if target >= newest_obs.timestamp() {
+        return Ok(OracleObservation {
+            timestamp: target,
+            price: newest_obs.price(),
+        });
+    }
+    let oldest_obs = buffer.read_single(storage, oldest_ind)?.unwrap();
+    if target == oldest_obs.timestamp() {
+        return Ok(OracleObservation {
+            timestamp: target,
+            price: oldest_obs.price(),
+        });
+    }
+    if target < oldest_obs.timestamp() {
+        return Err(StdError::generic_err(format!(
+            "Requested observation is too old. Last known observation is at {}",
+            oldest_obs.timestamp()
+        )));
+    }
+
+    let (left, right) = binary_search(storage, &buffer, target, oldest_ind, newest_ind)?;
+
+    let price_left = left.price();
+    let price_right = right.price();
+    let price = if left.timestamp() == target {
+        price_left
+    } else if right.timestamp() == target {
+        price_right
+    } else if price_left == price_right {
+        price_left
+    } else {
+        // Interpolate.
+        let price_slope = price_right.diff(price_left)
+            * Decimal::from_ratio(1u8, right.timestamp() - left.timestamp());
+        let time_interval = Decimal::from_ratio(target - left.timestamp(), 1u8);
+        if price_left > price_right {
+            price_left - price_slope * time_interval
+        } else {
+            price_left + price_slope * time_interval
+        }
+    };
+
+    Ok(OracleObservation {
+        timestamp: target,
+        price,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use cosmwasm_std::Timestamp;
+
+    const OBSERVATIONS: CircularBuffer<Observation> =
+        CircularBuffer::new("observations_state", "observations_buffer");
+
+    #[test]
+    fn observations_before_genesis() {
+        let mut store = MockStorage::new();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1_000);
+
+        let err = query_observation(&store, env.clone(), OBSERVATIONS, 0).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Buffer is empty");
+
+        BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
+        accumulate_swap_sizes(
+            &mut store,
+            &env,
+            OBSERVATIONS,
+            Uint128::from(1000u128),
+            Uint128::from(500u128),
+        )
+        .unwrap();
+
+        // The target time is clamped to zero instead of underflowing
+        let err = query_observation(&store, env, OBSERVATIONS, 2_000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Requested observation is too old. Last known observation is at 1000"
+        );
+    }
+
+    #[test]
+    fn test_swap_observations() {
+        let mut store = MockStorage::new();
+        let env = mock_env();
+        BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
+
+        for _ in 0..50 {
+            accumulate_swap_sizes(
+                &mut store,
+                &env,
+                OBSERVATIONS,
+                Uint128::from(1000u128),
+                Uint128::from(500u128),
+            )
+            .unwrap();
+        }
+
+        let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+
+        assert_eq!(buffer.head(), 0);
+        assert_eq!(
+            buffer.read_last(&store).unwrap().unwrap().base_sma.u128(),
+            1000u128
+        );
+        assert_eq!(
+            buffer.read_last(&store).unwrap().unwrap().quote_sma.u128(),
+            500u128
+        );
+    }
+}
@@ -0,0 +1,913 @@
+//! Venue-agnostic parts of the concentrated pair orderbook integration.
+//! Order ladder computation, reconciliation and fill detection are shared while a particular
+//! central limit orderbook (e.g. Injective exchange module) implements [`OrderbookVenue`].
+
+use std::cmp::Ordering;
+
+use astroport_circular_buffer::error::BufferResult;
+use astroport_circular_buffer::{BufferManager, CircularBuffer};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_schema::serde::de::DeserializeOwned;
+use cosmwasm_schema::serde::Serialize;
+use cosmwasm_std::{
+    BlockInfo, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, QuerierWrapper, StdResult,
+    Storage, Uint128,
+};
+use cw_storage_plus::Item;
+
+use crate::asset::{Asset, AssetInfo, AssetInfoExt};
+use crate::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+
+/// Circular buffer size which stores orderbook fills
+pub const FILLS_SIZE: u32 = 1000;
+
+/// Circular buffer which stores recent orderbook fills
+pub const FILLS: CircularBuffer<OrderbookFill> = CircularBuffer::new("fills_state", "fills_buffer");
+
+/// Cumulative orderbook fills
+pub const FILLS_TOTAL: Item<OrderbookFillsTotal> = Item::new("fills_total");
+
+/// Storage key of [`OrderbookState`]
+const ORDERBOOK_STATE_KEY: &str = "orderbook_config";
+
+/// Defines how order sizes and prices are distributed across the order ladder.
+/// Regardless of the strategy, order prices are never better for takers than the pool's liquidity curve.
+#[cw_serde]
+pub enum OrderLadder {
+    /// Orders have equal sizes, their prices follow the pool's liquidity curve.
+    LiquidityCurve {},
+    /// Orders have equal sizes and are evenly spaced by `price_step` starting from the oracle price.
+    Uniform { price_step: Decimal },
+    /// Each next order is `size_ratio` times larger than the previous one,
+    /// their prices follow the pool's liquidity curve.
+    Geometric { size_ratio: Decimal },
+}
+
+impl Default for OrderLadder {
+    fn default() -> Self {
+        OrderLadder::LiquidityCurve {}
+    }
+}
+
+/// Limits on the liquidity the pool deploys into the orderbook venue.
+#[cw_serde]
+pub struct OrderbookRiskLimits {
+    /// Maximum fraction of each pool reserve which can be placed as orders
+    pub max_exposure: Decimal,
+    /// Maximum inventory drift, i.e. relative difference between base and quote reserves valued at the price scale.
+    /// Once it is exceeded, orders on the side which further increases the drift are pulled.
+    pub max_inventory_drift: Decimal,
+}
+
+impl Default for OrderbookRiskLimits {
+    fn default() -> Self {
+        Self {
+            max_exposure: Decimal::one(),
+            max_inventory_drift: Decimal::one(),
+        }
+    }
+}
+
+/// Reconciliation state of the orderbook integration.
+/// **M** identifies the venue market and **A** the venue account the pair trades from.
+#[cw_serde]
+pub struct OrderbookState<M, A> {
+    /// Market which is being used to deploy liquidity to
+    pub market_id: M,
+    /// Subaccount used for the orderbook
+    pub subaccount: A,
+    /// Stores asset infos. We duplicate it in OB state to decrease noop gas usage on begin blocker.
+    pub asset_infos: Vec<AssetInfo>,
+    /// Minimum allowed price tick size in the orderbook
+    pub min_price_tick_size: Decimal256,
+    /// Minimum allowed quantity tick size in the orderbook
+    pub min_quantity_tick_size: Decimal256,
+    /// This flag is set when trades, deposits or withdrawals have occurred in the previous block.
+    pub need_reconcile: bool,
+    /// Last balances of the subaccount on the previous begin blocker
+    pub last_balances: Vec<Asset>,
+    /// The number of trades on each side of the order book.
+    /// The higher this number is, the more gas the contract consumes on begin blocker and
+    /// the more liquidity the contract places in the order book.
+    pub orders_number: u8,
+    /// Minimum number of trades to accumulate average trade size.
+    /// Orderbook integration will not be enabled until this number is reached.
+    pub min_trades_to_avg: u32,
+    /// Whether the pool is ready to integrate with the orderbook (MIN_TRADES_TO_AVG is reached)
+    pub ready: bool,
+    /// The shape of the order ladder placed on each side of the orderbook
+    #[serde(default)]
+    pub ladder: OrderLadder,
+    /// Minimum distance between orders and the oracle price
+    #[serde(default)]
+    pub spread: Decimal,
+    /// Limits on the liquidity deployed into the orderbook
+    #[serde(default)]
+    pub risk_limits: OrderbookRiskLimits,
+}
+
+impl<M, A> OrderbookState<M, A>
+where
+    M: Serialize + DeserializeOwned,
+    A: Serialize + DeserializeOwned,
+{
+    pub fn load(storage: &dyn Storage) -> StdResult<Self> {
+        Item::new(ORDERBOOK_STATE_KEY).load(storage)
+    }
+
+    pub fn save(&self, storage: &mut dyn Storage) -> StdResult<()> {
+        Item::new(ORDERBOOK_STATE_KEY).save(storage, self)
+    }
+
+    /// Set flag to trigger reconciliation on next begin blocker
+    pub fn reconcile(self, storage: &mut dyn Storage) -> StdResult<()> {
+        Self {
+            need_reconcile: true,
+            ..self
+        }
+        .save(storage)
+    }
+
+    /// Set flag that reconciliation is done. Save current venue balances.
+    pub fn reconciliation_done(
+        self,
+        storage: &mut dyn Storage,
+        new_balances: Vec<Asset>,
+    ) -> StdResult<()> {
+        Self {
+            need_reconcile: false,
+            last_balances: new_balances,
+            ..self
+        }
+        .save(storage)
+    }
+
+    /// If min_trades_to_avg has been reached, set ready flag to true.
+    pub fn ready(&mut self, ready: bool) {
+        self.ready = ready;
+    }
+}
+
+/// Single order of the ladder. Amount is always denominated in base asset.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LadderOrder {
+    pub price: Decimal256,
+    pub amount: Decimal256,
+}
+
+/// Orders to be placed on both sides of the orderbook.
+#[derive(Debug, Default)]
+pub struct Ladder {
+    pub sells: Vec<LadderOrder>,
+    pub buys: Vec<LadderOrder>,
+}
+
+/// Market and pool parameters needed to build an order ladder.
+pub struct LadderParams {
+    /// The number of orders on each side of the orderbook
+    pub orders_number: u8,
+    /// Oracle price of the base asset denominated in quote asset
+    pub oracle_price: Decimal256,
+    /// Minimum distance between orders and the oracle price
+    pub spread: Decimal256,
+    /// Average trade size in base asset adjusted to min quantity tick size
+    pub avg_base_trade_size: Decimal256,
+    /// Average trade size in quote asset
+    pub avg_quote_trade_size: Decimal256,
+    /// Minimum allowed price tick size in the orderbook
+    pub min_price_tick_size: Decimal256,
+    /// Minimum allowed quantity tick size in the orderbook
+    pub min_quantity_tick_size: Decimal256,
+}
+
+fn floor_to_tick(val: Decimal256, tick: Decimal256) -> Decimal256 {
+    (val / tick).floor() * tick
+}
+
+fn ceil_to_tick(val: Decimal256, tick: Decimal256) -> Decimal256 {
+    (val / tick).ceil() * tick
+}
+
+/// Returns relative size of the i-th order (starting from 1).
+fn order_weight(ladder: &OrderLadder, i: u8) -> Decimal256 {
+    match ladder {
+        OrderLadder::Geometric { size_ratio } => Decimal256::from(*size_ratio).pow(i as u32 - 1),
+        OrderLadder::LiquidityCurve {} | OrderLadder::Uniform { .. } => Decimal256::one(),
+    }
+}
+
+/// Returns the distance from the oracle price for the i-th order (starting from 1)
+/// if the ladder defines order prices on its own.
+fn ladder_price_offset(ladder: &OrderLadder, spread: Decimal256, i: u8) -> Option<Decimal256> {
+    match ladder {
+        OrderLadder::Uniform { price_step } => {
+            Some(spread + Decimal256::from(*price_step) * Decimal256::from_ratio(i - 1, 1u8))
+        }
+        OrderLadder::LiquidityCurve {} | OrderLadder::Geometric { .. } => None,
+    }
+}
+
+/// Builds orders for both sides of the orderbook.
+/// The pool's liquidity curve defines the worst prices the orderbook may offer to takers for
+/// the cumulative amount of orders. Order prices are never closer to the oracle price than the spread.
+///
+/// Returns `None` if any order would have zero price, thus the contract must leave the orderbook.
+/// Orders which the pool is not able to back are not placed.
+///
+/// * **swap** simulates a swap against the pool: (offer amount, ask asset index) -> return amount.
+pub fn build_ladder<F>(
+    ladder: &OrderLadder,
+    params: &LadderParams,
+    swap: F,
+) -> StdResult<Option<Ladder>>
+where
+    F: Fn(Decimal256, usize) -> StdResult<Decimal256>,
+{
+    let mut result = Ladder::default();
+
+    let min_sell_price = params.oracle_price * (Decimal256::one() + params.spread);
+    let max_buy_price = params.oracle_price * (Decimal256::one() - params.spread);
+
+    let mut total_weight = Decimal256::zero();
+    let mut sell_liquidity = Decimal256::zero();
+    let mut buy_liquidity = Decimal256::zero();
+    let mut base_buy_amount = Decimal256::zero();
+
+    for i in 1..=params.orders_number {
+        let weight = order_weight(ladder, i);
+        total_weight += weight;
+
+        let quote_sell_amount = params.avg_quote_trade_size * total_weight;
+        let buy_amount = floor_to_tick(
+            params.avg_base_trade_size * weight,
+            params.min_quantity_tick_size,
+        );
+        base_buy_amount += buy_amount;
+
+        let (base_sell_amount, quote_buy_amount) =
+            match (swap(quote_sell_amount, 0), swap(base_buy_amount, 1)) {
+                (Ok(base_sell_amount), Ok(quote_buy_amount)) => {
+                    (base_sell_amount, quote_buy_amount)
+                }
+                _ => break,
+            };
+
+        let sell_amount = floor_to_tick(
+            base_sell_amount * weight / total_weight,
+            params.min_quantity_tick_size,
+        );
+        if sell_amount.is_zero() || buy_amount.is_zero() {
+            continue;
+        }
+
+        let mut sell_price = quote_sell_amount.saturating_sub(sell_liquidity) / sell_amount;
+        let mut buy_price = quote_buy_amount.saturating_sub(buy_liquidity) / buy_amount;
+
+        // If price is zero we cancel all orders and withdraw liquidity.
+        if sell_price.is_zero() || buy_price.is_zero() {
+            return Ok(None);
+        }
+
+        if !params.spread.is_zero() {
+            sell_price = sell_price.max(min_sell_price);
+            buy_price = buy_price.min(max_buy_price);
+        }
+        if let Some(offset) = ladder_price_offset(ladder, params.spread, i) {
+            sell_price = sell_price.max(params.oracle_price * (Decimal256::one() + offset));
+            buy_price =
+                buy_price.min(params.oracle_price * Decimal256::one().saturating_sub(offset));
+        }
+
+        let sell_price = ceil_to_tick(sell_price, params.min_price_tick_size);
+        let buy_price = floor_to_tick(buy_price, params.min_price_tick_size);
+        if buy_price.is_zero() {
+            return Ok(None);
+        }
+
+        sell_liquidity += sell_price * sell_amount;
+        buy_liquidity += buy_price * buy_amount;
+
+        result.sells.push(LadderOrder {
+            price: sell_price,
+            amount: sell_amount,
+        });
+        result.buys.push(LadderOrder {
+            price: buy_price,
+            amount: buy_amount,
+        });
+    }
+
+    Ok(Some(result))
+}
+
+impl Ladder {
+    /// Returns total [base, quote] amounts needed to place all orders.
+    pub fn total_deposit(&self) -> [Decimal256; 2] {
+        let base = self
+            .sells
+            .iter()
+            .fold(Decimal256::zero(), |acc, order| acc + order.amount);
+        let quote = self.buys.iter().fold(Decimal256::zero(), |acc, order| {
+            acc + order.price * order.amount
+        });
+
+        [base, quote]
+    }
+}
+
+/// Central limit orderbook which a pair deploys part of its liquidity to.
+pub trait OrderbookVenue {
+    /// Custom message type of the chain where the venue lives
+    type Msg: CustomMsg;
+    /// Custom query type of the chain where the venue lives
+    type Query: CustomQuery;
+
+    /// Returns pair balances on the venue in the order of **asset_infos**.
+    /// Balances include funds locked in open orders.
+    fn query_balances(
+        &self,
+        querier: &QuerierWrapper<Self::Query>,
+        asset_infos: &[AssetInfo],
+    ) -> StdResult<Vec<Asset>>;
+
+    /// Returns orders filled since the venue balances were **last_balances**.
+    /// By default fills are derived from venue balance changes,
+    /// venues which expose trade history may query it instead.
+    fn query_fill(
+        &self,
+        _querier: &QuerierWrapper<Self::Query>,
+        last_balances: &[Asset],
+        balances: &[Asset],
+    ) -> StdResult<Option<CumulativeFill>> {
+        Ok(CumulativeFill::from_balances(last_balances, balances))
+    }
+
+    /// Moves funds from the pair to the venue.
+    fn deposit_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>>;
+
+    /// Moves funds from the venue back to the pair.
+    fn withdraw_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>>;
+
+    /// Cancels all pair orders.
+    fn cancel_all_orders_msg(&self) -> StdResult<CosmosMsg<Self::Msg>>;
+
+    /// Places orders. Prices and amounts are precision-adjusted, amounts are denominated in base asset.
+    fn place_orders_msg(&self, ladder: &Ladder) -> StdResult<CosmosMsg<Self::Msg>>;
+}
+
+/// Builds messages which cancel all orders, adjust venue balances to match **deposits**
+/// and place new orders.
+///
+/// * **balances** - current pair balances on the venue.
+///
+/// * **deposits** - balances needed to place all orders of the ladder.
+pub fn reconcile_msgs<V>(
+    venue: &V,
+    ladder: &Ladder,
+    balances: &[Asset],
+    deposits: &[Asset],
+) -> StdResult<Vec<CosmosMsg<V::Msg>>>
+where
+    V: OrderbookVenue,
+{
+    let mut messages = vec![venue.cancel_all_orders_msg()?];
+
+    for (need, current) in deposits.iter().zip(balances) {
+        match need.amount.cmp(&current.amount) {
+            Ordering::Greater => messages
+                .push(venue.deposit_msg(&need.info.with_balance(need.amount - current.amount))?),
+            Ordering::Less => messages
+                .push(venue.withdraw_msg(&need.info.with_balance(current.amount - need.amount))?),
+            Ordering::Equal => {}
+        }
+    }
+
+    messages.push(venue.place_orders_msg(ladder)?);
+
+    Ok(messages)
+}
+
+/// Orders filled on the venue since the last reconciliation. Derived from venue balance changes.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CumulativeFill {
+    /// Whether the pair bought base asset
+    pub is_buy: bool,
+    /// Base asset amount filled
+    pub base_amount: Uint128,
+    /// Quote asset amount filled
+    pub quote_amount: Uint128,
+}
+
+impl CumulativeFill {
+    /// Returns None if the base asset balance has not changed.
+    pub fn from_balances(last_balances: &[Asset], balances: &[Asset]) -> Option<Self> {
+        let is_buy = match last_balances[0].amount.cmp(&balances[0].amount) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => return None,
+        };
+
+        Some(Self {
+            is_buy,
+            base_amount: last_balances[0].amount.diff(balances[0].amount),
+            quote_amount: last_balances[1].amount.diff(balances[1].amount),
+        })
+    }
+}
+
+/// Orders filled on the venue along with the maker fee the pair pays for them.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CumulativeTrade {
+    pub fill: CumulativeFill,
+    /// Maker fee charged in the asset the pair has given away. None if the fee is not charged
+    pub maker_fee: Option<Asset>,
+}
+
+impl CumulativeTrade {
+    /// Converts the trade into a fill record. Fill id is assigned when the record is saved.
+    pub fn into_fill(
+        self,
+        block: &BlockInfo,
+        base_precision: u8,
+        quote_precision: u8,
+    ) -> StdResult<OrderbookFill> {
+        Ok(OrderbookFill {
+            id: 0,
+            height: block.height,
+            timestamp: block.time.seconds(),
+            is_buy: self.fill.is_buy,
+            base_amount: self.fill.base_amount,
+            quote_amount: self.fill.quote_amount,
+            price: self.fill.quote_amount.to_decimal256(quote_precision)?
+                / self.fill.base_amount.to_decimal256(base_precision)?,
+            maker_fee: self.maker_fee,
+        })
+    }
+}
+
+/// Queries orders filled on the venue since **last_balances** were saved and calculates the maker fee.
+/// Returns None if no orders were filled.
+///
+/// * **balances** - current pair balances on the venue.
+///
+/// * **maker_fee_rate** - share of the filled amount charged as the maker fee. None if the fee is not charged.
+pub fn query_cumulative_trade<V>(
+    venue: &V,
+    querier: &QuerierWrapper<V::Query>,
+    last_balances: &[Asset],
+    balances: &[Asset],
+    maker_fee_rate: Option<Decimal>,
+) -> StdResult<Option<CumulativeTrade>>
+where
+    V: OrderbookVenue,
+{
+    let fill = match venue.query_fill(querier, last_balances, balances)? {
+        Some(fill) => fill,
+        None => return Ok(None),
+    };
+
+    let maker_fee = maker_fee_rate.map(|rate| {
+        if fill.is_buy {
+            last_balances[1].info.with_balance(fill.quote_amount * rate)
+        } else {
+            last_balances[0].info.with_balance(fill.base_amount * rate)
+        }
+    });
+
+    Ok(Some(CumulativeTrade { fill, maker_fee }))
+}
+
+/// Orderbook trades filled between two begin blocker calls.
+#[cw_serde]
+pub struct OrderbookFill {
+    /// Sequential fill number
+    pub id: u64,
+    /// Block height when the fill was processed
+    pub height: u64,
+    /// Block timestamp when the fill was processed
+    pub timestamp: u64,
+    /// Whether the pool bought base asset on the orderbook
+    pub is_buy: bool,
+    /// Base asset amount filled
+    pub base_amount: Uint128,
+    /// Quote asset amount filled
+    pub quote_amount: Uint128,
+    /// Effective fill price denominated in quote asset
+    pub price: Decimal256,
+    /// Maker fee charged from the filled amount. None if the fee address is not set
+    pub maker_fee: Option<Asset>,
+}
+
+/// Cumulative orderbook fills.
+#[cw_serde]
+#[derive(Default)]
+pub struct OrderbookFillsTotal {
+    /// The number of processed fills
+    pub fills: u64,
+    /// Total base asset amount bought by the pool
+    pub base_bought: Uint128,
+    /// Total base asset amount sold by the pool
+    pub base_sold: Uint128,
+    /// Total quote asset amount spent by the pool
+    pub quote_spent: Uint128,
+    /// Total quote asset amount received by the pool
+    pub quote_received: Uint128,
+    /// Total maker fees charged in base asset
+    pub base_fees: Uint128,
+    /// Total maker fees charged in quote asset
+    pub quote_fees: Uint128,
+}
+
+/// Assigns the next id to the fill, saves it in the buffer and updates cumulative totals.
+/// The buffer is initialized on the first fill for contracts which were deployed before fills were recorded.
+pub fn record_fill(storage: &mut dyn Storage, mut fill: OrderbookFill) -> BufferResult<()> {
+    if BufferManager::new(storage, FILLS).is_err() {
+        BufferManager::init(storage, FILLS, FILLS_SIZE)?;
+    }
+
+    let mut totals = FILLS_TOTAL.may_load(storage)?.unwrap_or_default();
+    fill.id = totals.fills;
+    totals.fills += 1;
+
+    // Maker fee is charged in the asset the pool has given away
+    let fee_amount = fill
+        .maker_fee
+        .as_ref()
+        .map(|fee| fee.amount)
+        .unwrap_or_default();
+    if fill.is_buy {
+        totals.base_bought += fill.base_amount;
+        totals.quote_spent += fill.quote_amount;
+        totals.quote_fees += fee_amount;
+    } else {
+        totals.base_sold += fill.base_amount;
+        totals.quote_received += fill.quote_amount;
+        totals.base_fees += fee_amount;
+    }
+    FILLS_TOTAL.save(storage, &totals)?;
+
+    BufferManager::new(storage, FILLS)?.instant_push(storage, &fill)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::native_asset_info;
+    use cosmwasm_std::testing::{mock_env, MockQuerier};
+    use cosmwasm_std::{coins, Empty, StdError};
+    use std::str::FromStr;
+
+    fn dec(val: &str) -> Decimal256 {
+        Decimal256::from_str(val).unwrap()
+    }
+
+    /// Constant product pool with 1000 base and 2000 quote assets, i.e. base price is 2.
+    fn xyk_swap(offer_amount: Decimal256, ask_ind: usize) -> StdResult<Decimal256> {
+        let pools = [dec("1000"), dec("2000")];
+        let offer_ind = 1 - ask_ind;
+        Ok(pools[ask_ind] * offer_amount / (pools[offer_ind] + offer_amount))
+    }
+
+    fn params(orders_number: u8, spread: &str) -> LadderParams {
+        LadderParams {
+            orders_number,
+            oracle_price: dec("2"),
+            spread: dec(spread),
+            avg_base_trade_size: dec("1"),
+            avg_quote_trade_size: dec("2"),
+            min_price_tick_size: dec("0.000001"),
+            min_quantity_tick_size: dec("0.000001"),
+        }
+    }
+
+    fn assert_monotonic(ladder: &Ladder) {
+        assert!(ladder
+            .sells
+            .windows(2)
+            .all(|orders| orders[0].price <= orders[1].price));
+        assert!(ladder
+            .buys
+            .windows(2)
+            .all(|orders| orders[0].price >= orders[1].price));
+        assert!(ladder.buys[0].price < ladder.sells[0].price);
+    }
+
+    #[test]
+    fn liquidity_curve_ladder() {
+        let ladder = build_ladder(&OrderLadder::LiquidityCurve {}, &params(5, "0"), xyk_swap)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(ladder.sells.len(), 5);
+        assert_eq!(ladder.buys.len(), 5);
+        assert!(ladder.buys.iter().all(|order| order.amount == dec("1")));
+        assert_monotonic(&ladder);
+
+        // The first orders are close to the pool price
+        assert!(ladder.sells[0].price > dec("2") && ladder.sells[0].price < dec("2.01"));
+        assert!(ladder.buys[0].price < dec("2") && ladder.buys[0].price > dec("1.99"));
+    }
+
+    #[test]
+    fn spread_from_oracle_price() {
+        let ladder = build_ladder(
+            &OrderLadder::LiquidityCurve {},
+            &params(5, "0.01"),
+            xyk_swap,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert!(ladder.sells.iter().all(|order| order.price >= dec("2.02")));
+        assert!(ladder.buys.iter().all(|order| order.price <= dec("1.98")));
+        assert_monotonic(&ladder);
+    }
+
+    #[test]
+    fn uniform_ladder() {
+        let price_step = Decimal::from_str("0.01").unwrap();
+        let ladder = build_ladder(
+            &OrderLadder::Uniform { price_step },
+            &params(5, "0.005"),
+            xyk_swap,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_monotonic(&ladder);
+        for (i, (sell, buy)) in ladder.sells.iter().zip(&ladder.buys).enumerate() {
+            let offset = dec("0.005") + dec("0.01") * Decimal256::from_ratio(i as u128, 1u8);
+            assert!(sell.price >= dec("2") * (Decimal256::one() + offset));
+            assert!(buy.price <= dec("2") * (Decimal256::one() - offset));
+            assert_eq!(buy.amount, dec("1"));
+        }
+    }
+
+    #[test]
+    fn geometric_ladder() {
+        let size_ratio = Decimal::from_str("1.5").unwrap();
+        let ladder = build_ladder(
+            &OrderLadder::Geometric { size_ratio },
+            &params(4, "0"),
+            xyk_swap,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_monotonic(&ladder);
+        let amounts = ladder
+            .buys
+            .iter()
+            .map(|order| order.amount)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            amounts,
+            vec![dec("1"), dec("1.5"), dec("2.25"), dec("3.375")]
+        );
+        assert!(ladder
+            .sells
+            .windows(2)
+            .all(|orders| orders[0].amount < orders[1].amount));
+    }
+
+    #[test]
+    fn unbacked_orders_are_skipped() {
+        let limited_swap = |offer_amount: Decimal256, ask_ind: usize| {
+            if offer_amount > dec("3") {
+                Err(StdError::generic_err("Not enough liquidity"))
+            } else {
+                xyk_swap(offer_amount, ask_ind)
+            }
+        };
+        let ladder = build_ladder(
+            &OrderLadder::LiquidityCurve {},
+            &params(5, "0"),
+            limited_swap,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(ladder.sells.len(), 1);
+        assert_eq!(ladder.buys.len(), 1);
+    }
+
+    #[test]
+    fn cumulative_fill() {
+        let balances = |base: u128, quote: u128| {
+            vec![
+                native_asset_info("base".to_string()).with_balance(base),
+                native_asset_info("quote".to_string()).with_balance(quote),
+            ]
+        };
+
+        assert_eq!(
+            CumulativeFill::from_balances(&balances(100, 200), &balances(100, 150)),
+            None
+        );
+        assert_eq!(
+            CumulativeFill::from_balances(&balances(100, 200), &balances(110, 180)),
+            Some(CumulativeFill {
+                is_buy: true,
+                base_amount: Uint128::new(10),
+                quote_amount: Uint128::new(20),
+            })
+        );
+        assert_eq!(
+            CumulativeFill::from_balances(&balances(100, 200), &balances(90, 220)),
+            Some(CumulativeFill {
+                is_buy: false,
+                base_amount: Uint128::new(10),
+                quote_amount: Uint128::new(20),
+            })
+        );
+    }
+
+    #[cw_serde]
+    enum MockVenueMsg {
+        CancelAll {},
+        Deposit(Asset),
+        Withdraw(Asset),
+        PlaceOrders { sells: usize, buys: usize },
+    }
+
+    impl CustomMsg for MockVenueMsg {}
+
+    /// Address which holds pair balances on the mock venue
+    const MOCK_VENUE: &str = "venue";
+
+    struct MockVenue;
+
+    impl OrderbookVenue for MockVenue {
+        type Msg = MockVenueMsg;
+        type Query = Empty;
+
+        fn query_balances(
+            &self,
+            querier: &QuerierWrapper<Self::Query>,
+            asset_infos: &[AssetInfo],
+        ) -> StdResult<Vec<Asset>> {
+            asset_infos
+                .iter()
+                .map(|asset_info| {
+                    let coin = querier.query_balance(MOCK_VENUE, asset_info.to_string())?;
+                    Ok(asset_info.with_balance(coin.amount))
+                })
+                .collect()
+        }
+
+        fn deposit_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>> {
+            Ok(CosmosMsg::Custom(MockVenueMsg::Deposit(asset.clone())))
+        }
+
+        fn withdraw_msg(&self, asset: &Asset) -> StdResult<CosmosMsg<Self::Msg>> {
+            Ok(CosmosMsg::Custom(MockVenueMsg::Withdraw(asset.clone())))
+        }
+
+        fn cancel_all_orders_msg(&self) -> StdResult<CosmosMsg<Self::Msg>> {
+            Ok(CosmosMsg::Custom(MockVenueMsg::CancelAll {}))
+        }
+
+        fn place_orders_msg(&self, ladder: &Ladder) -> StdResult<CosmosMsg<Self::Msg>> {
+            Ok(CosmosMsg::Custom(MockVenueMsg::PlaceOrders {
+                sells: ladder.sells.len(),
+                buys: ladder.buys.len(),
+            }))
+        }
+    }
+
+    #[test]
+    fn reconcile_with_mock_venue() {
+        let base = native_asset_info("base".to_string());
+        let quote = native_asset_info("quote".to_string());
+        let balances = vec![base.with_balance(100u128), quote.with_balance(200u128)];
+        let ladder = build_ladder(&OrderLadder::LiquidityCurve {}, &params(3, "0"), xyk_swap)
+            .unwrap()
+            .unwrap();
+        let place_orders_msg = CosmosMsg::Custom(MockVenueMsg::PlaceOrders {
+            sells: ladder.sells.len(),
+            buys: ladder.buys.len(),
+        });
+
+        // Missing base asset is deposited, excess quote asset is withdrawn
+        let deposits = vec![base.with_balance(150u128), quote.with_balance(150u128)];
+        assert_eq!(
+            reconcile_msgs(&MockVenue, &ladder, &balances, &deposits).unwrap(),
+            vec![
+                CosmosMsg::Custom(MockVenueMsg::CancelAll {}),
+                CosmosMsg::Custom(MockVenueMsg::Deposit(base.with_balance(50u128))),
+                CosmosMsg::Custom(MockVenueMsg::Withdraw(quote.with_balance(50u128))),
+                place_orders_msg.clone(),
+            ]
+        );
+
+        // Balances already match the deposits
+        assert_eq!(
+            reconcile_msgs(&MockVenue, &ladder, &balances, &balances).unwrap(),
+            vec![
+                CosmosMsg::Custom(MockVenueMsg::CancelAll {}),
+                place_orders_msg,
+            ]
+        );
+    }
+
+    #[test]
+    fn cumulative_trade_with_mock_venue() {
+        let base = native_asset_info("base".to_string());
+        let quote = native_asset_info("quote".to_string());
+        let asset_infos = [base.clone(), quote.clone()];
+        let last_balances = vec![base.with_balance(100u128), quote.with_balance(200u128)];
+        let venue_balances = |base_amount: u128, quote_amount: u128| {
+            let funds = [coins(base_amount, "base"), coins(quote_amount, "quote")].concat();
+            let querier: MockQuerier = MockQuerier::new(&[(MOCK_VENUE, funds.as_slice())]);
+            let balances = MockVenue
+                .query_balances(&QuerierWrapper::new(&querier), &asset_infos)
+                .unwrap();
+            (querier, balances)
+        };
+
+        // Only quote balance has changed, i.e. the pair withdrew funds, no orders were filled
+        let (querier, balances) = venue_balances(100, 150);
+        assert_eq!(
+            balances,
+            vec![base.with_balance(100u128), quote.with_balance(150u128)]
+        );
+        let trade = query_cumulative_trade(
+            &MockVenue,
+            &QuerierWrapper::new(&querier),
+            &last_balances,
+            &balances,
+            Some(Decimal::percent(10)),
+        )
+        .unwrap();
+        assert_eq!(trade, None);
+
+        // The pair bought base asset, maker fee is charged in quote asset
+        let (querier, balances) = venue_balances(110, 180);
+        let trade = query_cumulative_trade(
+            &MockVenue,
+            &QuerierWrapper::new(&querier),
+            &last_balances,
+            &balances,
+            Some(Decimal::percent(10)),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            trade,
+            CumulativeTrade {
+                fill: CumulativeFill {
+                    is_buy: true,
+                    base_amount: Uint128::new(10),
+                    quote_amount: Uint128::new(20),
+                },
+                maker_fee: Some(quote.with_balance(2u128)),
+            }
+        );
+        let fill = trade.into_fill(&mock_env().block, 6, 6).unwrap();
+        assert_eq!(fill.price, dec("2"));
+        assert_eq!(fill.maker_fee, Some(quote.with_balance(2u128)));
+
+        // The pair sold base asset, maker fee is not charged
+        let (querier, balances) = venue_balances(90, 220);
+        let trade = query_cumulative_trade(
+            &MockVenue,
+            &QuerierWrapper::new(&querier),
+            &last_balances,
+            &balances,
+            None,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            trade,
+            CumulativeTrade {
+                fill: CumulativeFill {
+                    is_buy: false,
+                    base_amount: Uint128::new(10),
+                    quote_amount: Uint128::new(20),
+                },
+                maker_fee: None,
+            }
+        );
+    }
+
+    #[test]
+    fn ladder_total_deposit() {
+        let ladder = build_ladder(&OrderLadder::LiquidityCurve {}, &params(3, "0"), xyk_swap)
+            .unwrap()
+            .unwrap();
+        let [base, quote] = ladder.total_deposit();
+
+        assert_eq!(
+            base,
+            ladder
+                .sells
+                .iter()
+                .fold(Decimal256::zero(), |acc, order| acc + order.amount)
+        );
+        // 3 buy orders of size 1 placed below the pool price 2
+        assert!(quote > dec("5.9") && quote < dec("6"));
+    }
+}
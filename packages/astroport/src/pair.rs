@@ -2,7 +2,10 @@ use cosmwasm_schema::{cw_serde, QueryResponses};
 
 use crate::asset::{Asset, AssetInfo, PairInfo};
 
-use cosmwasm_std::{from_slice, Addr, Binary, Decimal, QuerierWrapper, StdResult, Uint128, Uint64};
+use cosmwasm_std::{
+    from_slice, Addr, Binary, Decimal, Decimal256, QuerierWrapper, StdError, StdResult, Uint128,
+    Uint64,
+};
 use cw20::Cw20ReceiveMsg;
 
 /// The default swap slippage
@@ -50,6 +53,12 @@ pub enum ExecuteMsg {
         max_spread: Option<Decimal>,
         to: Option<String>,
     },
+    /// Withdraw liquidity from the pool by sending token factory LP tokens.
+    /// Only XYK pairs with token factory LP tokens support this, other pairs use CW20 LP tokens.
+    WithdrawLiquidity {
+        #[serde(default)]
+        assets: Vec<Asset>,
+    },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
     /// ProposeNewOwner creates a proposal to change contract ownership.
@@ -123,6 +132,15 @@ pub enum QueryMsg {
         asset_info: AssetInfo,
         block_height: Uint64,
     },
+    /// Returns the largest offer amounts which keep the swap price impact within each of the given
+    /// levels in a vector that contains objects of type [`DepthLevel`].
+    #[returns(Vec<DepthLevel>)]
+    Depth {
+        /// The asset to swap from. It defines the direction of the swaps
+        offer_asset_info: AssetInfo,
+        /// Price impact levels. Each of them must be within (0, 1)
+        price_impacts: Vec<Decimal>,
+    },
 }
 
 /// This struct is used to return a query result with the total amount of LP tokens and assets in a specific pool.
@@ -169,6 +187,17 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
+/// This structure describes a single level of a [`QueryMsg::Depth`] query response.
+#[cw_serde]
+pub struct DepthLevel {
+    /// The price impact level
+    pub price_impact: Decimal,
+    /// The largest amount of offer assets whose swap price impact does not exceed the level
+    pub offer_amount: Uint128,
+    /// The amount of ask assets returned by swapping **offer_amount**
+    pub return_amount: Uint128,
+}
+
 /// This structure is used to return a cumulative prices query response.
 #[cw_serde]
 pub struct CumulativePricesResponse {
@@ -192,6 +221,10 @@ pub struct XYKPoolParams {
     /// They will not be tracked if the parameter is ignored.
     /// It can not be disabled later once enabled.
     pub track_asset_balances: Option<bool>,
+    /// Whether the LP token is minted via the token factory module instead of a CW20 contract.
+    /// The chain must support token factory messages and bank supply queries and the pair
+    /// must be built with the `token-factory` feature.
+    pub token_factory_lp: Option<bool>,
 }
 
 /// This structure stores a XYK pool's configuration.
@@ -246,11 +279,119 @@ pub fn migration_check(
     }
 }
 
+/// The maximum number of price impact levels allowed in a single [`QueryMsg::Depth`] query
+pub const MAX_DEPTH_LEVELS: usize = 10;
+/// The spot price is probed with a swap of 1/DEPTH_PROBE_RATIO of the offer pool
+const DEPTH_PROBE_RATIO: u128 = 1_000_000;
+/// The binary search stops once the offer amount is known with 1/DEPTH_PRECISION relative precision
+const DEPTH_PRECISION: u128 = 10_000;
+
+/// Calculates pool depth for the given price impact levels.
+/// Price impact is the relative difference between the execution price of a swap and the spot price.
+/// The spot price is derived from a tiny probe swap, thus swap fees do not affect the price impact.
+///
+/// A swap can't return more than the ask pool, so the offer amount of a level is searched below
+/// the amount which would drain the ask pool at the level price. Levels are searched in ascending
+/// order starting from the result of the previous level and returned in the given order.
+///
+/// * **offer_pool** is the amount of offer assets in the pool.
+///
+/// * **ask_pool** is the amount of ask assets in the pool.
+///
+/// * **price_impacts** is the list of price impact levels. Each of them must be within (0, 1).
+///
+/// * **simulate** returns the amount of ask assets for the given amount of offer assets.
+/// Simulation errors are treated as if the price impact was exceeded.
+pub fn calc_depth<F>(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    price_impacts: &[Decimal],
+    simulate: F,
+) -> StdResult<Vec<DepthLevel>>
+where
+    F: Fn(Uint128) -> StdResult<Uint128>,
+{
+    if price_impacts.is_empty() || price_impacts.len() > MAX_DEPTH_LEVELS {
+        return Err(StdError::generic_err(format!(
+            "The number of price impact levels must be within [1, {MAX_DEPTH_LEVELS}]"
+        )));
+    }
+
+    if price_impacts
+        .iter()
+        .any(|impact| impact.is_zero() || *impact >= Decimal::one())
+    {
+        return Err(StdError::generic_err("Price impact must be within (0, 1)"));
+    }
+
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(StdError::generic_err("One of the pools is empty"));
+    }
+
+    let probe = (offer_pool / Uint128::from(DEPTH_PROBE_RATIO)).max(Uint128::one());
+    let probe_return = simulate(probe)?;
+    if probe_return.is_zero() {
+        return Err(StdError::generic_err(
+            "Pool is too shallow to calculate depth",
+        ));
+    }
+    let spot_price = Decimal256::from_ratio(probe_return, probe);
+
+    let mut order: Vec<usize> = (0..price_impacts.len()).collect();
+    order.sort_by_key(|&i| price_impacts[i]);
+
+    let mut levels: Vec<Option<DepthLevel>> = vec![None; price_impacts.len()];
+    let mut lo = Uint128::zero();
+    let mut lo_return = Uint128::zero();
+    for i in order {
+        let price_impact = price_impacts[i];
+        let max_impact = Decimal256::from(price_impact);
+        // Returns the swap return amount if the price impact is within the level
+        let within = |offer_amount: Uint128| {
+            simulate(offer_amount).ok().filter(|return_amount| {
+                let price = Decimal256::from_ratio(*return_amount, offer_amount);
+                price >= spot_price || (spot_price - price) / spot_price <= max_impact
+            })
+        };
+
+        // Any offer above ask_pool / (spot_price * (1 - price_impact)) would return more than
+        // the ask pool at the level price
+        let bound = ask_pool
+            .full_mul(probe)
+            .checked_mul(Decimal::one().atomics().into())?
+            / probe_return.full_mul((Decimal::one() - price_impact).atomics());
+        let mut hi = Uint128::try_from(bound)
+            .unwrap_or(Uint128::MAX)
+            .saturating_add(Uint128::one())
+            .max(lo);
+
+        while hi - lo > Uint128::one() && hi - lo > lo / Uint128::from(DEPTH_PRECISION) {
+            let mid = lo + (hi - lo) / Uint128::from(2u8);
+            match within(mid) {
+                Some(return_amount) => {
+                    lo = mid;
+                    lo_return = return_amount;
+                }
+                None => hi = mid,
+            }
+        }
+
+        levels[i] = Some(DepthLevel {
+            price_impact,
+            offer_amount: lo,
+            return_amount: lo_return,
+        });
+    }
+
+    Ok(levels.into_iter().flatten().collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::asset::native_asset_info;
     use cosmwasm_std::{from_binary, to_binary};
+    use std::cell::Cell;
 
     #[cw_serde]
     pub struct LegacyInstantiateMsg {
@@ -303,6 +444,89 @@ mod tests {
         let _: ConfigResponse = from_binary(&ser_msg).unwrap();
     }
 
+    #[test]
+    fn check_depth_constant_product() {
+        let (offer_pool, ask_pool) = (1_000_000_000000u128, 2_000_000_000000u128);
+        let simulate = |offer_amount: Uint128| {
+            Ok(Uint128::from(ask_pool)
+                .multiply_ratio(offer_amount, Uint128::from(offer_pool) + offer_amount))
+        };
+
+        let levels = calc_depth(
+            offer_pool.into(),
+            ask_pool.into(),
+            &[Decimal::percent(1), Decimal::percent(50)],
+            simulate,
+        )
+        .unwrap();
+
+        // For a constant product pool the price impact is offer_amount / (offer_pool + offer_amount)
+        for level in levels {
+            let expected = Uint128::from(offer_pool).multiply_ratio(
+                level.price_impact.atomics(),
+                (Decimal::one() - level.price_impact).atomics(),
+            );
+            let diff = expected.abs_diff(level.offer_amount);
+            assert!(diff <= expected / Uint128::from(1000u16));
+            assert_eq!(level.return_amount, simulate(level.offer_amount).unwrap());
+        }
+
+        let err = calc_depth(
+            offer_pool.into(),
+            ask_pool.into(),
+            &[Decimal::one()],
+            simulate,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Price impact must be within (0, 1)"
+        );
+        let err = calc_depth(offer_pool.into(), ask_pool.into(), &[], simulate).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: The number of price impact levels must be within [1, 10]"
+        );
+    }
+
+    #[test]
+    fn check_depth_levels_order() {
+        let (offer_pool, ask_pool) = (1_000_000_000000u128, 2_000_000_000000u128);
+        let simulations = Cell::new(0u32);
+        let simulate = |offer_amount: Uint128| {
+            simulations.set(simulations.get() + 1);
+            Ok(Uint128::from(ask_pool)
+                .multiply_ratio(offer_amount, Uint128::from(offer_pool) + offer_amount))
+        };
+
+        let price_impacts = [90, 1, 50, 5, 99, 10, 30, 2, 70, 20].map(Decimal::percent);
+        let levels =
+            calc_depth(offer_pool.into(), ask_pool.into(), &price_impacts, simulate).unwrap();
+
+        // Levels are returned in the given order and deeper levels allow larger swaps
+        assert_eq!(
+            levels
+                .iter()
+                .map(|level| level.price_impact)
+                .collect::<Vec<_>>(),
+            price_impacts
+        );
+        let mut sorted = levels.clone();
+        sorted.sort_by_key(|level| level.price_impact);
+        assert!(sorted
+            .windows(2)
+            .all(|pair| pair[0].offer_amount < pair[1].offer_amount));
+
+        // Each level costs a bounded number of simulations as the search range is bounded by
+        // the ask pool and starts from the previous level
+        assert!(simulations.get() <= 1 + 10 * 32);
+
+        // The ask pool can't be drained by a swap within any level
+        assert!(levels
+            .iter()
+            .all(|level| level.return_amount < Uint128::from(ask_pool)));
+    }
+
     #[test]
     fn check_empty_vec_deserialization() {
         let variant: Cw20HookMsg = from_slice(br#"{"withdraw_liquidity": {} }"#).unwrap();
@@ -5,14 +5,17 @@ use crate::pair::{
     ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
     SimulationResponse,
 };
+use crate::pair_concentrated::OracleObservation;
 
-use cosmwasm_std::{Addr, Binary, Decimal, Uint128};
+use cosmwasm_std::{Addr, Api, Binary, Decimal, StdError, StdResult, Uint128};
 use cw20::Cw20ReceiveMsg;
 
 /// The default swap slippage
 pub const DEFAULT_SLIPPAGE: &str = "0.005";
 /// The maximum allowed swap slippage
 pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
+/// The maximum allowed conversion spread in LP mode
+pub const MAX_LP_SPREAD: &str = "0.1";
 
 /// This structure stores the main config parameters for a constant product pair contract.
 #[cw_serde]
@@ -21,6 +24,105 @@ pub struct Config {
     pub pair_info: PairInfo,
     /// The factory contract address
     pub factory_addr: Addr,
+    /// LP mode parameters. If set, the pair holds inventory provided by liquidity providers
+    /// and performs conversions from its reserves.
+    #[serde(default)]
+    pub lp_mode: Option<LpModeParams>,
+    /// The adapter set in the init params. It is empty if the pair implementation builds
+    /// the adapter itself.
+    #[serde(default)]
+    pub adapter: Option<BondedAdapter>,
+    /// The last timestamp when the pair contract updated the asset cumulative prices
+    #[serde(default)]
+    pub block_time_last: u64,
+    /// The last cumulative price for asset 0
+    #[serde(default)]
+    pub price0_cumulative_last: Uint128,
+    /// The last cumulative price for asset 1
+    #[serde(default)]
+    pub price1_cumulative_last: Uint128,
+}
+
+/// This structure describes the parameters of the bonded pair LP mode.
+#[cw_serde]
+pub struct LpModeParams {
+    /// The share of every conversion which stays in the pool as a reward for liquidity providers
+    pub spread: Decimal,
+}
+
+/// This structure describes the init params of a bonded pair which uses the default
+/// [`BondedAdapter`] handling (e.g. the pair bonded template).
+#[cw_serde]
+pub struct BondedPairParams {
+    /// Describes how to convert assets via a 3rd party contract and where to take the exchange rate
+    pub adapter: BondedAdapter,
+    /// LP mode parameters. If set, the pair performs conversions from its own reserves.
+    pub lp_mode: Option<LpModeParams>,
+}
+
+/// This structure describes how a bonded pair converts assets via a 3rd party contract
+/// and where it takes the exchange rate between the underlying and the bonded asset.
+#[cw_serde]
+pub struct BondedAdapter {
+    /// The underlying asset (e.g. ASTRO)
+    pub underlying: AssetInfo,
+    /// The bonded asset (e.g. xASTRO)
+    pub bonded: AssetInfo,
+    /// The contract which converts the underlying asset into the bonded one and back
+    pub converter: Addr,
+    /// The message which bonds the underlying asset. CW20 tokens are sent to the converter
+    /// with this message as a hook, native coins are attached to this message as funds.
+    pub bond_msg: Binary,
+    /// The message which unbonds the bonded asset. If not set, bonded -> underlying swaps are
+    /// possible only in LP mode (e.g. liquid staking tokens with an unbonding period).
+    pub unbond_msg: Option<Binary>,
+    /// The source of the exchange rate
+    pub rate_source: RateSource,
+}
+
+impl BondedAdapter {
+    /// Validates the adapter against the pair assets.
+    pub fn check(&self, api: &dyn Api, asset_infos: &[AssetInfo]) -> StdResult<()> {
+        self.underlying.check(api)?;
+        self.bonded.check(api)?;
+        api.addr_validate(self.converter.as_str())?;
+
+        let assets_match = asset_infos.len() == 2
+            && (asset_infos[0].equal(&self.underlying) && asset_infos[1].equal(&self.bonded)
+                || asset_infos[0].equal(&self.bonded) && asset_infos[1].equal(&self.underlying));
+        if !assets_match {
+            return Err(StdError::generic_err(
+                "Adapter assets must match the pair assets",
+            ));
+        }
+
+        match &self.rate_source {
+            RateSource::StakingBalance { staking_addr } => {
+                api.addr_validate(staking_addr.as_str())?;
+                if self.bonded.is_native_token() {
+                    return Err(StdError::generic_err(
+                        "Staking balance rate source requires a CW20 bonded token",
+                    ));
+                }
+            }
+            RateSource::ExchangeRateQuery { contract, .. } => {
+                api.addr_validate(contract.as_str())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// This enum describes the sources of the bonded asset exchange rate.
+#[cw_serde]
+pub enum RateSource {
+    /// The rate is the underlying asset balance of the staking contract divided by the bonded
+    /// CW20 token supply (e.g. Astroport or Mars staking).
+    StakingBalance { staking_addr: Addr },
+    /// The rate is queried from a contract. The response must contain the `exchange_rate` field
+    /// with the amount of the underlying asset per one bonded asset. Other fields are ignored.
+    ExchangeRateQuery { contract: Addr, msg: Binary },
 }
 
 /// This structure describes the execute messages available in the contract.
@@ -58,6 +160,9 @@ pub enum ExecuteMsg {
         /// Sender who initiated the transaction
         sender: Addr,
     },
+    /// Accumulates the current exchange rate into the cumulative prices and records an observation.
+    /// Anyone can call it to keep the price oracle fresh when there are no swaps.
+    Update {},
 }
 
 /// This structure describes the query messages available in the contract.
@@ -85,4 +190,9 @@ pub enum QueryMsg {
     /// Returns information about the cumulative prices in a [`CumulativePricesResponse`] object
     #[returns(CumulativePricesResponse)]
     CumulativePrices {},
+    /// Returns the exchange rate observation (amount of the underlying asset per one bonded asset)
+    /// at the point that was `seconds_ago` seconds ago. Unlike the cumulative prices, the rate
+    /// doesn't depend on the order of the pair assets.
+    #[returns(OracleObservation)]
+    Observe { seconds_ago: u64 },
 }
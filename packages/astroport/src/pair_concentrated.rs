@@ -1,10 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint64};
+use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, Uint128, Uint64};
+use cw20::Cw20ReceiveMsg;
 
 use crate::asset::PairInfo;
 use crate::asset::{Asset, AssetInfo};
 use crate::pair::{
-    ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
+    ConfigResponse, CumulativePricesResponse, DepthLevel, PoolResponse, ReverseSimulationResponse,
     SimulationResponse,
 };
 
@@ -46,6 +47,12 @@ pub struct UpdatePoolParams {
     pub repeg_profit_threshold: Option<Decimal>,
     pub min_price_scale_delta: Option<Decimal>,
     pub ma_half_time: Option<u64>,
+    /// The pool incentivizer address which accrues a share of LP fees
+    pub incentivizer: Option<String>,
+    /// The share of LP fees accrued for the pool incentivizer instead of being left in the pool
+    pub incentivizer_fee_share: Option<Decimal>,
+    /// Whether to unset the pool incentivizer. Takes precedence over `incentivizer`
+    pub remove_incentivizer: Option<bool>,
 }
 
 /// Amp and gamma should be changed gradually. This structure holds all necessary parameters.
@@ -96,6 +103,48 @@ pub struct ConcentratedPoolConfig {
     pub track_asset_balances: bool,
 }
 
+/// This structure is extended version of [`crate::pair::ExecuteMsg`].
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity allows someone to provide liquidity in the pool
+    ProvideLiquidity {
+        /// The assets available in the pool
+        assets: Vec<Asset>,
+        /// The slippage tolerance that allows liquidity provision only if the price in the pool doesn't move too much
+        slippage_tolerance: Option<Decimal>,
+        /// Determines whether the LP tokens minted for the user is auto_staked in the Generator contract
+        auto_stake: Option<bool>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
+    },
+    /// Swap performs a swap in the pool
+    Swap {
+        offer_asset: Asset,
+        ask_asset_info: Option<AssetInfo>,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
+    /// Update the pair configuration
+    UpdateConfig { params: Binary },
+    /// ProposeNewOwner creates a proposal to change contract ownership.
+    /// The validity period for the proposal is set in the `expires_in` variable.
+    ProposeNewOwner {
+        /// Newly proposed contract owner
+        owner: String,
+        /// The date after which this proposal expires
+        expires_in: u64,
+    },
+    /// DropOwnershipProposal removes the existing offer to change contract ownership.
+    DropOwnershipProposal {},
+    /// Used to claim contract ownership.
+    ClaimOwnership {},
+    /// Permissionless endpoint which sends all fees accrued for the pool incentivizer to its address.
+    ClaimFees {},
+}
+
 /// This structure describes the query messages available in the contract.
 #[cw_serde]
 #[derive(QueryResponses)]
@@ -140,6 +189,60 @@ pub enum QueryMsg {
         asset_info: AssetInfo,
         block_height: Uint64,
     },
+    /// Query price from observations
+    #[returns(OracleObservation)]
+    Observe { seconds_ago: u64 },
+    /// Simulates a swap against the current pool state as if the given parameters were already applied.
+    /// Intended to show the impact of parameter change proposals.
+    #[returns(SimulateWithParamsResponse)]
+    SimulateWithParams {
+        params: ConcentratedPoolUpdateParams,
+        offer_asset: Asset,
+    },
+    /// Returns the largest offer amounts which keep the swap price impact within each of the given
+    /// levels in a vector that contains objects of type [`DepthLevel`].
+    #[returns(Vec<DepthLevel>)]
+    Depth {
+        /// The asset to swap from. It defines the direction of the swaps
+        offer_asset_info: AssetInfo,
+        /// Price impact levels. Each of them must be within (0, 1)
+        price_impacts: Vec<Decimal>,
+    },
+    /// Returns fees accrued for the pool incentivizer
+    #[returns(AccruedFeesResponse)]
+    AccruedFees {},
+}
+
+/// This structure holds the fees accrued for the pool incentivizer.
+#[cw_serde]
+pub struct AccruedFeesResponse {
+    /// The pool incentivizer address
+    pub incentivizer: Option<Addr>,
+    /// The share of LP fees accrued for the pool incentivizer
+    pub fee_share: Decimal,
+    /// Fees accrued since the last claim
+    pub fees: Vec<Asset>,
+}
+
+/// This structure holds the result of a swap simulation with hypothetical pool parameters.
+#[cw_serde]
+pub struct SimulateWithParamsResponse {
+    /// The amount of ask assets returned by the swap
+    pub return_amount: Uint128,
+    /// The spread used in the swap operation
+    pub spread_amount: Uint128,
+    /// The amount of fees charged by the transaction
+    pub commission_amount: Uint128,
+    /// The part of the commission which would be sent to the Maker contract
+    pub maker_fee_amount: Uint128,
+    /// Price scale after the swap. It differs from the current one if the swap triggers a repeg.
+    pub price_scale: Decimal256,
+}
+
+#[cw_serde]
+pub struct OracleObservation {
+    pub timestamp: u64,
+    pub price: Decimal,
 }
 
 #[cw_serde]
@@ -4,10 +4,12 @@ use cw20::Cw20ReceiveMsg;
 
 use crate::asset::PairInfo;
 use crate::asset::{Asset, AssetInfo};
+pub use crate::orderbook::{OrderLadder, OrderbookFill, OrderbookFillsTotal, OrderbookRiskLimits};
 use crate::pair::{
     ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
     SimulationResponse,
 };
+pub use crate::pair_concentrated::OracleObservation;
 use crate::pair_concentrated::{ConcentratedPoolParams, PromoteParams, UpdatePoolParams};
 
 #[cw_serde]
@@ -15,6 +17,15 @@ pub struct OrderbookConfig {
     pub market_id: String,
     pub orders_number: u8,
     pub min_trades_to_avg: u32,
+    /// The shape of the order ladder placed on each side of the orderbook
+    #[serde(default)]
+    pub ladder: OrderLadder,
+    /// Minimum distance between orders and the oracle price
+    #[serde(default)]
+    pub spread: Decimal,
+    /// Limits on the liquidity deployed into the orderbook
+    #[serde(default)]
+    pub risk_limits: OrderbookRiskLimits,
 }
 
 /// This structure holds concentrated pool parameters along with orderbook params specific for Injective.
@@ -109,6 +120,18 @@ pub enum QueryMsg {
     Observe { seconds_ago: u64 },
     #[returns(OrderbookStateResponse)]
     OrderbookState {},
+    /// Returns current orderbook exposure against the risk limits
+    #[returns(OrderbookRiskResponse)]
+    OrderbookRisk {},
+    /// Returns recent orderbook fills ordered by fill id
+    #[returns(Vec<OrderbookFill>)]
+    OrderbookFills {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns aggregated results of the orderbook leg since the integration was enabled
+    #[returns(OrderbookPnlResponse)]
+    OrderbookPnl {},
 }
 
 #[cw_serde]
@@ -132,12 +155,40 @@ pub struct OrderbookStateResponse {
     pub min_trades_to_avg: u32,
     /// Whether the pool is ready to integrate with the orderbook (MIN_TRADES_TO_AVG is reached)
     pub ready: bool,
+    /// The shape of the order ladder placed on each side of the orderbook
+    pub ladder: OrderLadder,
+    /// Minimum distance between orders and the oracle price
+    pub spread: Decimal,
+    /// Limits on the liquidity deployed into the orderbook
+    pub risk_limits: OrderbookRiskLimits,
 }
 
 #[cw_serde]
-pub struct OracleObservation {
-    pub timestamp: u64,
-    pub price: Decimal,
+pub struct OrderbookRiskResponse {
+    /// Current risk limits
+    pub limits: OrderbookRiskLimits,
+    /// Current subaccount balances
+    pub subaccount_balances: Vec<Asset>,
+    /// Fractions of pool reserves [base, quote] which are currently deployed into the subaccount
+    pub exposure: Vec<Decimal256>,
+    /// Relative difference between base and quote reserves valued at the price scale
+    pub inventory_drift: Decimal256,
+    /// Whether buy orders are pulled because the pool holds too much base asset
+    pub buys_paused: bool,
+    /// Whether sell orders are pulled because the pool holds too much quote asset
+    pub sells_paused: bool,
+}
+
+#[cw_serde]
+pub struct OrderbookPnlResponse {
+    /// Cumulative orderbook fills
+    pub totals: OrderbookFillsTotal,
+    /// Price used to value base asset, denominated in quote asset. It is derived from the current price scale
+    pub mark_price: Decimal256,
+    /// Net result of the orderbook leg denominated in quote asset if it is positive
+    pub profit: Decimal256,
+    /// Net result of the orderbook leg denominated in quote asset if it is negative
+    pub loss: Decimal256,
 }
 
 #[cw_serde]
@@ -157,4 +208,11 @@ pub enum ConcentratedObPoolUpdateParams {
     StopChangingAmpGamma {},
     /// Update orderbook params.
     UpdateOrderbookParams { orders_number: u8 },
+    /// Update the order ladder strategy and the minimum spread from the oracle price.
+    UpdateOrderLadder {
+        ladder: OrderLadder,
+        spread: Decimal,
+    },
+    /// Update limits on the liquidity deployed into the orderbook.
+    UpdateRiskLimits(OrderbookRiskLimits),
 }
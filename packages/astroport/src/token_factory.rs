@@ -0,0 +1,261 @@
+use cosmwasm_std::{Addr, StdError, StdResult};
+
+#[cfg(feature = "token-factory")]
+use cosmwasm_std::{Binary, Coin, CosmosMsg};
+
+/// The prefix of all denoms created via the token factory module
+pub const TOKEN_FACTORY_DENOM_PREFIX: &str = "factory/";
+/// The subdenom used by pair contracts for token factory LP tokens
+pub const LP_SUBDENOM: &str = "astroport/share";
+
+/// Returns true if the denom was created via the token factory module.
+pub fn is_token_factory_denom(denom: &str) -> bool {
+    denom.starts_with(TOKEN_FACTORY_DENOM_PREFIX)
+}
+
+/// Returns the full token factory denom for the specified creator and subdenom.
+///
+/// * **creator** address of the denom creator.
+///
+/// * **subdenom** subdenom of the token.
+pub fn tf_denom(creator: &Addr, subdenom: &str) -> String {
+    format!("{TOKEN_FACTORY_DENOM_PREFIX}{creator}/{subdenom}")
+}
+
+/// Returns the creator of a token factory denom. Only the creator can mint and burn the token.
+///
+/// * **denom** token factory denom in the `factory/{creator}/{subdenom}` format.
+pub fn tf_denom_creator(denom: &str) -> StdResult<Addr> {
+    match denom
+        .strip_prefix(TOKEN_FACTORY_DENOM_PREFIX)
+        .and_then(|rest| rest.split_once('/'))
+    {
+        Some((creator, subdenom)) if !creator.is_empty() && !subdenom.is_empty() => {
+            Ok(Addr::unchecked(creator))
+        }
+        _ => Err(StdError::generic_err(format!(
+            "Invalid token factory denom: {denom}"
+        ))),
+    }
+}
+
+/// Returns a message which creates a new token factory denom owned by the sender.
+///
+/// * **sender** the contract which will become the denom admin.
+///
+/// * **subdenom** subdenom of the new token.
+#[cfg(feature = "token-factory")]
+pub fn tf_create_denom_msg(sender: &Addr, subdenom: &str) -> CosmosMsg {
+    let mut value = vec![];
+    encode_bytes(1, sender.as_bytes(), &mut value);
+    encode_bytes(2, subdenom.as_bytes(), &mut value);
+
+    CosmosMsg::Stargate {
+        type_url: "/osmosis.tokenfactory.v1beta1.MsgCreateDenom".to_string(),
+        value: Binary(value),
+    }
+}
+
+/// Returns a message which mints token factory coins to the sender.
+///
+/// * **sender** the denom admin.
+///
+/// * **coin** denom and amount to mint.
+#[cfg(feature = "token-factory")]
+pub fn tf_mint_msg(sender: &Addr, coin: &Coin) -> CosmosMsg {
+    let mut value = vec![];
+    encode_bytes(1, sender.as_bytes(), &mut value);
+    encode_bytes(2, &encode_coin(coin), &mut value);
+
+    CosmosMsg::Stargate {
+        type_url: "/osmosis.tokenfactory.v1beta1.MsgMint".to_string(),
+        value: Binary(value),
+    }
+}
+
+/// Returns a message which burns token factory coins held by the sender.
+///
+/// * **sender** the denom admin.
+///
+/// * **coin** denom and amount to burn.
+#[cfg(feature = "token-factory")]
+pub fn tf_burn_msg(sender: &Addr, coin: &Coin) -> CosmosMsg {
+    let mut value = vec![];
+    encode_bytes(1, sender.as_bytes(), &mut value);
+    encode_bytes(2, &encode_coin(coin), &mut value);
+
+    CosmosMsg::Stargate {
+        type_url: "/osmosis.tokenfactory.v1beta1.MsgBurn".to_string(),
+        value: Binary(value),
+    }
+}
+
+/// Encodes a `cosmos.base.v1beta1.Coin` protobuf message.
+#[cfg(feature = "token-factory")]
+fn encode_coin(coin: &Coin) -> Vec<u8> {
+    let mut buf = vec![];
+    encode_bytes(1, coin.denom.as_bytes(), &mut buf);
+    encode_bytes(2, coin.amount.to_string().as_bytes(), &mut buf);
+    buf
+}
+
+/// Appends a length-delimited protobuf field to the buffer.
+#[cfg(feature = "token-factory")]
+fn encode_bytes(field: u64, bytes: &[u8], buf: &mut Vec<u8>) {
+    encode_varint(field << 3 | 2, buf);
+    encode_varint(bytes.len() as u64, buf);
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(feature = "token-factory")]
+fn encode_varint(mut value: u64, buf: &mut Vec<u8>) {
+    while value >= 0x80 {
+        buf.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_factory_denoms() {
+        let pair = Addr::unchecked("pair");
+        let denom = tf_denom(&pair, LP_SUBDENOM);
+
+        assert_eq!(denom, "factory/pair/astroport/share");
+        assert!(is_token_factory_denom(&denom));
+        assert!(!is_token_factory_denom("uluna"));
+        assert_eq!(tf_denom_creator(&denom).unwrap(), pair);
+        tf_denom_creator("factory/pair").unwrap_err();
+        tf_denom_creator("factory//share").unwrap_err();
+        tf_denom_creator("uluna").unwrap_err();
+    }
+
+    #[cfg(feature = "token-factory")]
+    #[test]
+    fn token_factory_msgs_encoding() {
+        use cosmwasm_std::coin;
+
+        let msg = tf_mint_msg(&Addr::unchecked("pair"), &coin(100, "factory/pair/lp"));
+        let expected = [
+            [10, 4].as_slice(),
+            b"pair",
+            &[18, 22, 10, 15],
+            b"factory/pair/lp",
+            &[18, 3],
+            b"100",
+        ]
+        .concat();
+
+        assert_eq!(
+            msg,
+            CosmosMsg::Stargate {
+                type_url: "/osmosis.tokenfactory.v1beta1.MsgMint".to_string(),
+                value: Binary(expected),
+            }
+        );
+    }
+
+    /// Splits a protobuf message into (field number, value) pairs of length-delimited fields.
+    #[cfg(feature = "token-factory")]
+    fn decode_fields(mut buf: &[u8]) -> Vec<(u64, Vec<u8>)> {
+        let mut fields = vec![];
+        while !buf.is_empty() {
+            let (key, rest) = decode_varint(buf);
+            assert_eq!(key & 7, 2, "Only length-delimited fields are expected");
+            let (len, rest) = decode_varint(rest);
+            let (value, rest) = rest.split_at(len as usize);
+            fields.push((key >> 3, value.to_vec()));
+            buf = rest;
+        }
+        fields
+    }
+
+    #[cfg(feature = "token-factory")]
+    fn decode_varint(buf: &[u8]) -> (u64, &[u8]) {
+        let mut value = 0u64;
+        for (i, byte) in buf.iter().enumerate() {
+            value |= ((byte & 0x7f) as u64) << (7 * i);
+            if byte & 0x80 == 0 {
+                return (value, &buf[i + 1..]);
+            }
+        }
+        panic!("Truncated varint")
+    }
+
+    #[cfg(feature = "token-factory")]
+    fn decode_stargate(msg: CosmosMsg) -> (String, Vec<(u64, Vec<u8>)>) {
+        match msg {
+            CosmosMsg::Stargate { type_url, value } => (type_url, decode_fields(&value)),
+            _ => panic!("Stargate message expected"),
+        }
+    }
+
+    #[cfg(feature = "token-factory")]
+    #[test]
+    fn varint_round_trip() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            300,
+            16_383,
+            16_384,
+            u32::MAX as u64,
+            u64::MAX,
+        ] {
+            let mut buf = vec![];
+            encode_varint(value, &mut buf);
+            assert_eq!(decode_varint(&buf), (value, [].as_slice()));
+        }
+    }
+
+    #[cfg(feature = "token-factory")]
+    #[test]
+    fn token_factory_msgs_round_trip() {
+        use cosmwasm_std::coin;
+
+        // Long enough to require multi-byte length prefixes
+        let sender = Addr::unchecked(format!("pair{}", "0".repeat(150)));
+        let denom = tf_denom(&sender, LP_SUBDENOM);
+
+        let (type_url, fields) = decode_stargate(tf_create_denom_msg(&sender, LP_SUBDENOM));
+        assert_eq!(type_url, "/osmosis.tokenfactory.v1beta1.MsgCreateDenom");
+        assert_eq!(
+            fields,
+            vec![
+                (1, sender.as_bytes().to_vec()),
+                (2, LP_SUBDENOM.as_bytes().to_vec())
+            ]
+        );
+
+        let lp_coin = coin(u128::MAX, &denom);
+        for (msg, expected_type_url) in [
+            (
+                tf_mint_msg(&sender, &lp_coin),
+                "/osmosis.tokenfactory.v1beta1.MsgMint",
+            ),
+            (
+                tf_burn_msg(&sender, &lp_coin),
+                "/osmosis.tokenfactory.v1beta1.MsgBurn",
+            ),
+        ] {
+            let (type_url, fields) = decode_stargate(msg);
+            assert_eq!(type_url, expected_type_url);
+            assert_eq!(fields.len(), 2);
+            assert_eq!(fields[0], (1, sender.as_bytes().to_vec()));
+            assert_eq!(fields[1].0, 2);
+            assert_eq!(
+                decode_fields(&fields[1].1),
+                vec![
+                    (1, denom.as_bytes().to_vec()),
+                    (2, u128::MAX.to_string().into_bytes())
+                ]
+            );
+        }
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::ContractError;
+use astroport::asset::{Asset, AssetInfo};
+use astroport::pair_bonded::{BondedAdapter, RateSource};
+use astroport::querier::query_supply;
+use cosmwasm_schema::serde::Deserialize;
+use cosmwasm_std::{
+    to_binary, CosmosMsg, Decimal, QuerierWrapper, QueryRequest, StdError, StdResult, Uint128,
+    WasmMsg, WasmQuery,
+};
+use cw20::Cw20ExecuteMsg;
+
+/// This structure describes the response expected from [`RateSource::ExchangeRateQuery`].
+/// Unlike other messages it doesn't deny unknown fields, so any contract which has the `exchange_rate` field
+/// in its response can be used as a rate source.
+#[derive(Deserialize)]
+#[serde(crate = "cosmwasm_schema::serde")]
+struct ExchangeRateResponse {
+    exchange_rate: Decimal,
+}
+
+/// Returns the amount of the underlying asset and the corresponding amount of the bonded asset.
+/// If the rate source is empty (e.g. nothing is staked yet) the rate is 1:1.
+pub fn query_rate(
+    querier: &QuerierWrapper,
+    adapter: &BondedAdapter,
+) -> StdResult<(Uint128, Uint128)> {
+    let (underlying, bonded) = match &adapter.rate_source {
+        RateSource::StakingBalance { staking_addr } => {
+            let total_deposit = adapter.underlying.query_pool(querier, staking_addr)?;
+            let total_shares = match &adapter.bonded {
+                AssetInfo::Token { contract_addr } => query_supply(querier, contract_addr)?,
+                AssetInfo::NativeToken { .. } => {
+                    return Err(StdError::generic_err(
+                        "Staking balance rate source requires a CW20 bonded token",
+                    ))
+                }
+            };
+            (total_deposit, total_shares)
+        }
+        RateSource::ExchangeRateQuery { contract, msg } => {
+            let resp: ExchangeRateResponse =
+                querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                    contract_addr: contract.to_string(),
+                    msg: msg.clone(),
+                }))?;
+            (resp.exchange_rate.atomics(), Decimal::one().atomics())
+        }
+    };
+
+    if underlying.is_zero() || bonded.is_zero() {
+        Ok((Uint128::one(), Uint128::one()))
+    } else {
+        Ok((underlying, bonded))
+    }
+}
+
+/// Returns the amount of the ask asset which the converter returns for the offer asset.
+pub fn simulate_conversion(
+    querier: &QuerierWrapper,
+    adapter: &BondedAdapter,
+    offer_asset: &Asset,
+) -> StdResult<Uint128> {
+    let (underlying, bonded) = query_rate(querier, adapter)?;
+
+    if offer_asset.info.equal(&adapter.underlying) {
+        Ok(offer_asset.amount.multiply_ratio(bonded, underlying))
+    } else if offer_asset.info.equal(&adapter.bonded) {
+        Ok(offer_asset.amount.multiply_ratio(underlying, bonded))
+    } else {
+        Err(StdError::generic_err(
+            "Given offer asset doesn't belong to pair",
+        ))
+    }
+}
+
+/// Returns the amount of the offer asset which the converter needs to return the ask asset.
+pub fn reverse_simulate_conversion(
+    querier: &QuerierWrapper,
+    adapter: &BondedAdapter,
+    ask_asset: &Asset,
+) -> StdResult<Uint128> {
+    let (underlying, bonded) = query_rate(querier, adapter)?;
+
+    if ask_asset.info.equal(&adapter.bonded) {
+        Ok(ask_asset.amount.multiply_ratio(underlying, bonded))
+    } else if ask_asset.info.equal(&adapter.underlying) {
+        Ok(ask_asset.amount.multiply_ratio(bonded, underlying))
+    } else {
+        Err(StdError::generic_err(
+            "Given ask asset doesn't belong to pairs",
+        ))
+    }
+}
+
+/// Returns the message which converts the offer asset via the converter contract.
+/// Returns [`ContractError::NotSupported`] if the adapter can't unbond the bonded asset.
+pub fn convert_msg(
+    adapter: &BondedAdapter,
+    offer_asset: &Asset,
+) -> Result<CosmosMsg, ContractError> {
+    let msg = if offer_asset.info.equal(&adapter.underlying) {
+        adapter.bond_msg.clone()
+    } else if offer_asset.info.equal(&adapter.bonded) {
+        adapter
+            .unbond_msg
+            .clone()
+            .ok_or(ContractError::NotSupported {})?
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let msg = match &offer_asset.info {
+        AssetInfo::Token { contract_addr } => WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: adapter.converter.to_string(),
+                amount: offer_asset.amount,
+                msg,
+            })?,
+            funds: vec![],
+        },
+        AssetInfo::NativeToken { .. } => WasmMsg::Execute {
+            contract_addr: adapter.converter.to_string(),
+            msg,
+            funds: vec![offer_asset.as_coin()?],
+        },
+    };
+
+    Ok(msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use astroport::asset::{native_asset, native_asset_info, token_asset};
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{coin, to_binary, Addr, Binary, ContractResult, Empty, SystemResult};
+    use std::str::FromStr;
+
+    fn adapter(unbond_msg: Option<Binary>) -> BondedAdapter {
+        BondedAdapter {
+            underlying: native_asset_info("uluna".to_string()),
+            bonded: AssetInfo::Token {
+                contract_addr: Addr::unchecked("bluna"),
+            },
+            converter: Addr::unchecked("hub"),
+            bond_msg: Binary::from(r#"{"bond":{}}"#.as_bytes()),
+            unbond_msg,
+            rate_source: RateSource::ExchangeRateQuery {
+                contract: Addr::unchecked("hub"),
+                msg: Binary::from(r#"{"state":{}}"#.as_bytes()),
+            },
+        }
+    }
+
+    #[test]
+    fn conversion_messages() {
+        let adapter = adapter(None);
+
+        let msg =
+            convert_msg(&adapter, &native_asset("uluna".to_string(), 100u128.into())).unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "hub".to_string(),
+                msg: adapter.bond_msg.clone(),
+                funds: vec![coin(100, "uluna")],
+            })
+        );
+
+        let bonded = token_asset(Addr::unchecked("bluna"), 100u128.into());
+        assert_eq!(
+            convert_msg(&adapter, &bonded).unwrap_err(),
+            ContractError::NotSupported {}
+        );
+
+        let unbond_msg = Binary::from(r#"{"unbond":{}}"#.as_bytes());
+        let msg = convert_msg(&self::adapter(Some(unbond_msg.clone())), &bonded).unwrap();
+        assert_eq!(
+            msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "bluna".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: "hub".to_string(),
+                    amount: 100u128.into(),
+                    msg: unbond_msg,
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn exchange_rate_query() {
+        let mut mock_querier: MockQuerier<Empty> = MockQuerier::new(&[]);
+        mock_querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(Binary::from(
+                br#"{"total_bonded":"1000","exchange_rate":"1.25"}"#,
+            )))
+        });
+        let querier = QuerierWrapper::new(&mock_querier);
+        let adapter = adapter(None);
+
+        assert_eq!(
+            query_rate(&querier, &adapter).unwrap(),
+            (
+                Decimal::from_str("1.25").unwrap().atomics(),
+                Decimal::one().atomics()
+            )
+        );
+
+        let underlying = native_asset("uluna".to_string(), 100u128.into());
+        let bonded = token_asset(Addr::unchecked("bluna"), 80u128.into());
+        assert_eq!(
+            simulate_conversion(&querier, &adapter, &underlying).unwrap(),
+            bonded.amount
+        );
+        assert_eq!(
+            simulate_conversion(&querier, &adapter, &bonded).unwrap(),
+            underlying.amount
+        );
+        assert_eq!(
+            reverse_simulate_conversion(&querier, &adapter, &bonded).unwrap(),
+            underlying.amount
+        );
+        assert_eq!(
+            reverse_simulate_conversion(&querier, &adapter, &underlying).unwrap(),
+            bonded.amount
+        );
+    }
+}
@@ -1,19 +1,39 @@
+use crate::adapter::{convert_msg, query_rate, reverse_simulate_conversion, simulate_conversion};
 use crate::error::ContractError;
-use crate::state::CONFIG;
-use astroport::asset::{addr_opt_validate, Asset, AssetInfo, PairInfo};
+use crate::oracle::{accumulate_prices, save_observation};
+use crate::state::{Observation, CONFIG, OBSERVATIONS, OBSERVATIONS_SIZE};
+use crate::utils::{
+    assert_max_spread, assert_slippage_tolerance, get_share_in_assets, mint_liquidity_token_message,
+};
+use astroport::asset::{
+    addr_opt_validate, format_lp_token_name, Asset, AssetInfo, CoinsExt, PairInfo,
+    MINIMUM_LIQUIDITY_AMOUNT,
+};
 use astroport::factory::PairType;
+use astroport::observation::query_observation;
 use astroport::pair::{
     migration_check, ConfigResponse, CumulativePricesResponse, Cw20HookMsg, InstantiateMsg,
     PoolResponse, ReverseSimulationResponse, SimulationResponse,
 };
-use astroport::pair_bonded::{Config, ExecuteMsg, QueryMsg};
-use astroport::querier::query_factory_config;
+use astroport::pair_bonded::{
+    BondedAdapter, BondedPairParams, Config, ExecuteMsg, LpModeParams, QueryMsg, MAX_LP_SPREAD,
+};
+use astroport::pair_concentrated::OracleObservation;
+use astroport::querier::{query_factory_config, query_supply};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use astroport_circular_buffer::BufferManager;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult, Uint128,
+    attr, from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse,
+    SubMsgResult, Uint128, WasmMsg,
 };
 use cw2::set_contract_version;
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_utils::parse_instantiate_response_data;
+use std::str::FromStr;
+
+/// A `reply` call code ID used for the LP token instantiation sub-message in LP mode.
+pub const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
 
 pub trait PairBonded<'a> {
     /// Contract name that is used for migration.
@@ -38,19 +58,119 @@ pub trait PairBonded<'a> {
 
         set_contract_version(deps.storage, Self::CONTRACT_NAME, Self::CONTRACT_VERSION)?;
 
+        let (adapter, lp_mode) = match self.bonded_pair_params(&msg)? {
+            Some(params) => {
+                params.adapter.check(deps.api, &msg.asset_infos)?;
+                (Some(params.adapter), params.lp_mode)
+            }
+            None => (None, self.lp_mode_params(deps.as_ref())?),
+        };
+        if let Some(params) = &lp_mode {
+            if params.spread > Decimal::from_str(MAX_LP_SPREAD)? {
+                return Err(ContractError::InvalidLpSpread {});
+            }
+        }
+
         let config = Config {
             pair_info: PairInfo {
-                contract_addr: env.contract.address,
+                contract_addr: env.contract.address.clone(),
                 liquidity_token: Addr::unchecked(""),
                 asset_infos: msg.asset_infos.clone(),
                 pair_type: PairType::Custom(String::from("Bonded")),
             },
             factory_addr: deps.api.addr_validate(&msg.factory_addr)?,
+            lp_mode,
+            adapter,
+            block_time_last: env.block.time.seconds(),
+            price0_cumulative_last: Uint128::zero(),
+            price1_cumulative_last: Uint128::zero(),
         };
 
         CONFIG.save(deps.storage, &config)?;
+        BufferManager::init(deps.storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+
+        if config.lp_mode.is_none() {
+            return Ok(Response::new());
+        }
+
+        let token_name = format_lp_token_name(&msg.asset_infos, &deps.querier)?;
+
+        // Create the LP token contract
+        let sub_msg = SubMsg {
+            msg: WasmMsg::Instantiate {
+                code_id: msg.token_code_id,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: token_name,
+                    symbol: "uLP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: env.contract.address.to_string(),
+                        cap: None,
+                    }),
+                    marketing: None,
+                })?,
+                funds: vec![],
+                admin: None,
+                label: String::from("Astroport LP token"),
+            }
+            .into(),
+            id: INSTANTIATE_TOKEN_REPLY_ID,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+        };
+
+        Ok(Response::new().add_submessage(sub_msg))
+    }
+
+    /// Returns the pair params from the init params. It is called only once, during the contract
+    /// instantiation. The adapter is validated and saved in the pair config, so the default
+    /// [`PairBonded::bonded_adapter`] works without custom code. Implementations which parse their own
+    /// init params return `None`, then [`PairBonded::lp_mode_params`] is used.
+    fn bonded_pair_params(
+        &self,
+        msg: &InstantiateMsg,
+    ) -> Result<Option<BondedPairParams>, ContractError> {
+        match &msg.init_params {
+            Some(init_params) => Ok(Some(from_binary(init_params)?)),
+            None => Err(ContractError::InitParamsNotFound {}),
+        }
+    }
+
+    /// Returns LP mode parameters of the pair. It is called only once, during the contract instantiation
+    /// if [`PairBonded::bonded_pair_params`] returns `None`. LP mode is disabled by default, so liquidity
+    /// provision is not supported and swaps are performed via a 3rd party contract.
+    fn lp_mode_params(&self, _deps: Deps) -> StdResult<Option<LpModeParams>> {
+        Ok(None)
+    }
+
+    /// Processes replies from submessages. Saves the LP token address created in LP mode.
+    fn reply(&self, deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+        match msg {
+            Reply {
+                id: INSTANTIATE_TOKEN_REPLY_ID,
+                result:
+                    SubMsgResult::Ok(SubMsgResponse {
+                        data: Some(data), ..
+                    }),
+            } => {
+                let mut config = CONFIG.load(deps.storage)?;
+
+                if config.pair_info.liquidity_token != Addr::unchecked("") {
+                    return Err(ContractError::Unauthorized {});
+                }
 
-        Ok(Response::new())
+                let init_response = parse_instantiate_response_data(data.as_slice())
+                    .map_err(|e| StdError::generic_err(format!("{e}")))?;
+                config.pair_info.liquidity_token =
+                    deps.api.addr_validate(&init_response.contract_address)?;
+                CONFIG.save(deps.storage, &config)?;
+
+                Ok(Response::new()
+                    .add_attribute("liquidity_token_addr", config.pair_info.liquidity_token))
+            }
+            _ => Err(ContractError::FailedToParseReply {}),
+        }
     }
 
     /// Exposes all the execute functions available in the contract.
@@ -66,7 +186,7 @@ pub trait PairBonded<'a> {
     ///             slippage_tolerance,
     ///             auto_stake,
     ///             receiver,
-    ///         }**  Not supported.
+    ///         }** Provides liquidity in the pair. Supported only in LP mode.
     ///
     /// * **ExecuteMsg::Swap {
     ///             offer_asset,
@@ -74,6 +194,7 @@ pub trait PairBonded<'a> {
     ///             max_spread,
     ///             to,
     ///         }** Performs an swap using the specified parameters. (It needs to be implemented)
+    /// In LP mode the swap is performed from the pool reserves.
     ///
     /// * **ExecuteMsg::AssertAndSend {
     ///             offer_asset,
@@ -83,6 +204,8 @@ pub trait PairBonded<'a> {
     ///             receiver,
     ///             sender,
     ///         }** (internal) Is used as a sub-execution to send received tokens to the receiver and check the spread/price.
+    ///
+    /// * **ExecuteMsg::Update {}** Accumulates the current exchange rate into the cumulative prices. Permissionless.
     fn execute(
         &self,
         deps: DepsMut,
@@ -99,7 +222,44 @@ pub trait PairBonded<'a> {
         match msg {
             ExecuteMsg::UpdateConfig { .. } => Err(ContractError::NotSupported {}),
             ExecuteMsg::Receive(msg) => self.receive_cw20(deps, env, info, msg),
+            ExecuteMsg::ProvideLiquidity {
+                assets,
+                slippage_tolerance,
+                auto_stake,
+                receiver,
+            } if cfg.lp_mode.is_some() => self.provide_liquidity(
+                deps,
+                env,
+                info,
+                assets,
+                slippage_tolerance,
+                auto_stake,
+                receiver,
+            ),
             ExecuteMsg::ProvideLiquidity { .. } => Err(ContractError::NotSupported {}),
+            ExecuteMsg::Swap {
+                offer_asset,
+                belief_price,
+                max_spread,
+                to,
+            } if cfg.lp_mode.is_some() => {
+                offer_asset.info.check(deps.api)?;
+                if !offer_asset.is_native_token() {
+                    return Err(ContractError::Cw20DirectSwap {});
+                }
+                offer_asset.assert_sent_native_token_balance(&info)?;
+
+                let to_addr = addr_opt_validate(deps.api, &to)?;
+                self.swap_from_reserves(
+                    deps,
+                    env,
+                    info.sender,
+                    offer_asset,
+                    belief_price,
+                    max_spread,
+                    to_addr,
+                )
+            }
             ExecuteMsg::Swap {
                 offer_asset,
                 belief_price,
@@ -120,6 +280,10 @@ pub trait PairBonded<'a> {
                 ask_asset_info,
                 receiver,
             ),
+            ExecuteMsg::Update {} => {
+                self.update_prices(deps, &env)?;
+                Ok(Response::new().add_attribute("action", "update_prices"))
+            }
         }
     }
 
@@ -133,28 +297,42 @@ pub trait PairBonded<'a> {
     ///
     /// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
     /// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+    /// The vector is empty if LP mode is disabled.
     ///
     /// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+    /// In LP mode the LP spread is returned as the commission amount.
     ///
     /// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation using
-    /// a [`ReverseSimulationResponse`] object.
+    /// a [`ReverseSimulationResponse`] object. In LP mode the LP spread is returned as the commission amount.
     ///
     /// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
     /// pool using a [`CumulativePricesResponse`] object.
     ///
+    /// * **QueryMsg::Observe { seconds_ago }** Returns the exchange rate observation (amount of the underlying
+    /// asset per one bonded asset) at the point that was `seconds_ago` seconds ago using an [`OracleObservation`] object.
+    ///
     /// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
     fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::Pair {} => to_binary(&self.query_pair_info(deps)?),
             QueryMsg::Pool {} => to_binary(&self.query_pool(deps)?),
-            QueryMsg::Share { .. } => to_binary(&Vec::<Asset>::new()),
-            QueryMsg::Simulation { offer_asset } => {
-                to_binary(&self.query_simulation(deps, env, offer_asset)?)
-            }
-            QueryMsg::ReverseSimulation { ask_asset } => {
-                to_binary(&self.query_reverse_simulation(deps, env, ask_asset)?)
-            }
+            QueryMsg::Share { amount } => to_binary(&self.query_share(deps, amount)?),
+            QueryMsg::Simulation { offer_asset } => match CONFIG.load(deps.storage)?.lp_mode {
+                Some(lp_mode) => {
+                    to_binary(&self.query_lp_simulation(deps, env, offer_asset, &lp_mode)?)
+                }
+                None => to_binary(&self.query_simulation(deps, env, offer_asset)?),
+            },
+            QueryMsg::ReverseSimulation { ask_asset } => match CONFIG.load(deps.storage)?.lp_mode {
+                Some(lp_mode) => {
+                    to_binary(&self.query_lp_reverse_simulation(deps, env, ask_asset, &lp_mode)?)
+                }
+                None => to_binary(&self.query_reverse_simulation(deps, env, ask_asset)?),
+            },
             QueryMsg::CumulativePrices {} => to_binary(&self.query_cumulative_prices(deps, env)?),
+            QueryMsg::Observe { seconds_ago } => {
+                to_binary(&self.query_observation(deps, env, seconds_ago)?)
+            }
             QueryMsg::Config {} => to_binary(&self.query_config(deps)?),
         }
     }
@@ -180,8 +358,8 @@ pub trait PairBonded<'a> {
                 let mut authorized = false;
                 let config = CONFIG.load(deps.storage)?;
 
-                for pool in config.pair_info.asset_infos {
-                    if let AssetInfo::Token { contract_addr, .. } = &pool {
+                for pool in &config.pair_info.asset_infos {
+                    if let AssetInfo::Token { contract_addr, .. } = pool {
                         if contract_addr == info.sender {
                             authorized = true;
                         }
@@ -195,21 +373,38 @@ pub trait PairBonded<'a> {
                 let to_addr = addr_opt_validate(deps.api, &to)?;
                 let contract_addr = info.sender.clone();
                 let sender = deps.api.addr_validate(&cw20_msg.sender)?;
-                self.swap(
-                    deps,
-                    env,
-                    info,
-                    sender,
-                    Asset {
-                        info: AssetInfo::Token { contract_addr },
-                        amount: cw20_msg.amount,
-                    },
-                    belief_price,
-                    max_spread,
-                    to_addr,
-                )
+                let offer_asset = Asset {
+                    info: AssetInfo::Token { contract_addr },
+                    amount: cw20_msg.amount,
+                };
+
+                if config.lp_mode.is_some() {
+                    self.swap_from_reserves(
+                        deps,
+                        env,
+                        sender,
+                        offer_asset,
+                        belief_price,
+                        max_spread,
+                        to_addr,
+                    )
+                } else {
+                    self.swap(
+                        deps,
+                        env,
+                        info,
+                        sender,
+                        offer_asset,
+                        belief_price,
+                        max_spread,
+                        to_addr,
+                    )
+                }
+            }
+            Ok(Cw20HookMsg::WithdrawLiquidity { .. }) => {
+                let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+                self.withdraw_liquidity(deps, env, info, sender, cw20_msg.amount)
             }
-            Ok(Cw20HookMsg::WithdrawLiquidity { .. }) => Err(ContractError::NotSupported {}),
             Err(err) => Err(err.into()),
         }
     }
@@ -243,6 +438,7 @@ pub trait PairBonded<'a> {
         if !offer_asset.is_native_token() {
             return Err(ContractError::Cw20DirectSwap {});
         }
+        offer_asset.assert_sent_native_token_balance(&info)?;
 
         let to_addr = addr_opt_validate(deps.api, &to)?;
 
@@ -258,20 +454,63 @@ pub trait PairBonded<'a> {
         )
     }
 
-    /// Performs a swap with the specified parameters.
-    /// ### Must be implemented
+    /// Returns the adapter which converts assets via a 3rd party contract and provides the exchange rate.
+    /// It is used by the default [`PairBonded::swap`], [`PairBonded::query_simulation`] and
+    /// [`PairBonded::query_reverse_simulation`] implementations. By default the adapter set in the init
+    /// params is loaded from the pair config.
+    fn bonded_adapter(&self, deps: Deps) -> StdResult<BondedAdapter> {
+        CONFIG
+            .load(deps.storage)?
+            .adapter
+            .ok_or_else(|| StdError::generic_err("Bonded adapter is not configured"))
+    }
+
+    /// Performs a swap with the specified parameters. The offer asset is converted via the 3rd party
+    /// contract set in the [`BondedAdapter`], then all received ask assets are sent to the receiver.
+    /// The exchange rate is accumulated before the conversion.
     #[allow(clippy::too_many_arguments)]
     fn swap(
         &self,
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
-        info: MessageInfo,
+        _info: MessageInfo,
         sender: Addr,
         offer_asset: Asset,
-        belief_price: Option<Decimal>,
-        max_spread: Option<Decimal>,
+        _belief_price: Option<Decimal>,
+        _max_spread: Option<Decimal>,
         to: Option<Addr>,
-    ) -> Result<Response, ContractError>;
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        let ask_asset_info = if offer_asset.info.equal(&config.pair_info.asset_infos[0]) {
+            config.pair_info.asset_infos[1].clone()
+        } else if offer_asset.info.equal(&config.pair_info.asset_infos[1]) {
+            config.pair_info.asset_infos[0].clone()
+        } else {
+            return Err(ContractError::AssetMismatch {});
+        };
+
+        self.update_prices(deps.branch(), &env)?;
+
+        let adapter = self.bonded_adapter(deps.as_ref())?;
+        let receiver = to.unwrap_or_else(|| sender.clone());
+
+        let messages = vec![
+            convert_msg(&adapter, &offer_asset)?,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertAndSend {
+                    offer_asset,
+                    ask_asset_info,
+                    sender,
+                    receiver,
+                })?,
+            }),
+        ];
+
+        Ok(Response::new().add_messages(messages))
+    }
 
     /// Returns information about the pair contract in an object of type [`PairInfo`].
     fn query_pair_info(&self, deps: Deps) -> StdResult<PairInfo> {
@@ -282,7 +521,7 @@ pub trait PairBonded<'a> {
     /// Returns the amounts of assets in the pair contract in an object of type [`PoolResponse`].
     fn query_pool(&self, deps: Deps) -> StdResult<PoolResponse> {
         let config = CONFIG.load(deps.storage)?;
-        let (assets, total_share) = self.pool_info(&config)?;
+        let (assets, total_share) = self.pool_info(deps, &config)?;
 
         let resp = PoolResponse {
             assets,
@@ -293,40 +532,119 @@ pub trait PairBonded<'a> {
     }
 
     /// Returns information about a swap simulation in a [`SimulationResponse`] object.
+    /// The conversion rate is taken from the [`BondedAdapter`] rate source.
     fn query_simulation(
         &self,
         deps: Deps,
-        env: Env,
+        _env: Env,
         offer_asset: Asset,
-    ) -> StdResult<SimulationResponse>;
+    ) -> StdResult<SimulationResponse> {
+        let adapter = self.bonded_adapter(deps)?;
+        let return_amount = simulate_conversion(&deps.querier, &adapter, &offer_asset)?;
+
+        Ok(SimulationResponse {
+            return_amount,
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::zero(),
+        })
+    }
 
     /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
-    /// ### Must be implemented
+    /// The conversion rate is taken from the [`BondedAdapter`] rate source.
     fn query_reverse_simulation(
         &self,
         deps: Deps,
-        env: Env,
+        _env: Env,
         ask_asset: Asset,
-    ) -> StdResult<ReverseSimulationResponse>;
+    ) -> StdResult<ReverseSimulationResponse> {
+        let adapter = self.bonded_adapter(deps)?;
+        let offer_amount = reverse_simulate_conversion(&deps.querier, &adapter, &ask_asset)?;
+
+        Ok(ReverseSimulationResponse {
+            offer_amount,
+            spread_amount: Uint128::zero(),
+            commission_amount: Uint128::zero(),
+        })
+    }
+
+    /// Accumulates the cumulative prices and saves the exchange rate observation.
+    /// The exchange rate is taken from the [`BondedAdapter`] rate source. Prices are updated at most once per block.
+    fn update_prices(&self, deps: DepsMut, env: &Env) -> Result<(), ContractError> {
+        let mut config = CONFIG.load(deps.storage)?;
+        let adapter = self.bonded_adapter(deps.as_ref())?;
+        let (underlying, bonded) = query_rate(&deps.querier, &adapter)?;
+
+        if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+            accumulate_prices(env, &config, &adapter, underlying, bonded)?
+        {
+            config.price0_cumulative_last = price0_cumulative_new;
+            config.price1_cumulative_last = price1_cumulative_new;
+            config.block_time_last = block_time;
+            CONFIG.save(deps.storage, &config)?;
+
+            save_observation(
+                deps.storage,
+                &Observation {
+                    timestamp: block_time,
+                    price: Decimal::from_ratio(underlying, bonded),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
 
     /// Returns information about cumulative prices for the assets in the pool using a [`CumulativePricesResponse`] object.
-    fn query_cumulative_prices(
-        &self,
-        deps: Deps,
-        _env: Env,
-    ) -> StdResult<CumulativePricesResponse> {
+    /// The prices are accumulated up to the current block using the current exchange rate.
+    fn query_cumulative_prices(&self, deps: Deps, env: Env) -> StdResult<CumulativePricesResponse> {
         let config = CONFIG.load(deps.storage)?;
-        let (assets, total_share) = self.pool_info(&config)?;
+        let (assets, total_share) = self.pool_info(deps, &config)?;
+
+        let mut price0_cumulative_last = config.price0_cumulative_last;
+        let mut price1_cumulative_last = config.price1_cumulative_last;
+
+        let adapter = self.bonded_adapter(deps)?;
+        let (underlying, bonded) = query_rate(&deps.querier, &adapter)?;
+        if let Some((price0_cumulative_new, price1_cumulative_new, _)) =
+            accumulate_prices(&env, &config, &adapter, underlying, bonded)?
+        {
+            price0_cumulative_last = price0_cumulative_new;
+            price1_cumulative_last = price1_cumulative_new;
+        }
+
+        let cumulative_prices = vec![
+            (
+                assets[0].info.clone(),
+                assets[1].info.clone(),
+                price0_cumulative_last,
+            ),
+            (
+                assets[1].info.clone(),
+                assets[0].info.clone(),
+                price1_cumulative_last,
+            ),
+        ];
 
         let resp = CumulativePricesResponse {
             assets,
             total_share,
-            cumulative_prices: vec![],
+            cumulative_prices,
         };
 
         Ok(resp)
     }
 
+    /// Returns the exchange rate observation at the point that was `seconds_ago` seconds ago.
+    /// The price is the amount of the underlying asset per one bonded asset.
+    fn query_observation(
+        &self,
+        deps: Deps,
+        env: Env,
+        seconds_ago: u64,
+    ) -> StdResult<OracleObservation> {
+        query_observation(deps.storage, env, OBSERVATIONS, seconds_ago)
+    }
+
     /// Returns the pair contract configuration in a [`ConfigResponse`] object.
     fn query_config(&self, deps: Deps) -> StdResult<ConfigResponse> {
         let config = CONFIG.load(deps.storage)?;
@@ -334,15 +652,36 @@ pub trait PairBonded<'a> {
         let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
 
         Ok(ConfigResponse {
-            block_time_last: 0u64,
-            params: None,
+            block_time_last: config.block_time_last,
+            params: config.lp_mode.as_ref().map(to_binary).transpose()?,
             owner: factory_config.owner,
             factory_addr: config.factory_addr,
         })
     }
 
-    /// Returns the total amount of assets in the pool.
-    fn pool_info(&self, config: &Config) -> StdResult<(Vec<Asset>, Uint128)> {
+    /// Returns the amount of LP tokens' underlying assets the pool would refund for a specific amount of LP tokens.
+    fn query_share(&self, deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+        let config = CONFIG.load(deps.storage)?;
+        if config.lp_mode.is_none() {
+            return Ok(vec![]);
+        }
+
+        let (pools, total_share) = self.pool_info(deps, &config)?;
+
+        Ok(get_share_in_assets(&pools, amount, total_share))
+    }
+
+    /// Returns the total amount of assets in the pool. The reserves are always zero unless LP mode is enabled.
+    fn pool_info(&self, deps: Deps, config: &Config) -> StdResult<(Vec<Asset>, Uint128)> {
+        if config.lp_mode.is_some() {
+            let pools = config
+                .pair_info
+                .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+            let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+            return Ok((pools, total_share));
+        }
+
         let pools = vec![
             Asset {
                 amount: Uint128::zero(),
@@ -357,6 +696,331 @@ pub trait PairBonded<'a> {
         Ok((pools, Uint128::zero()))
     }
 
+    /// Provides liquidity in the pair with the specified input parameters. Supported only in LP mode.
+    ///
+    /// * **assets** is an array with assets available in the pool.
+    ///
+    /// * **slippage_tolerance** is an optional parameter which is used to specify how much
+    /// the pool ratio can move until the provide liquidity transaction goes through.
+    ///
+    /// * **auto_stake** is an optional parameter which determines whether the LP tokens minted after
+    /// liquidity provision are automatically staked in the Generator contract on behalf of the LP token receiver.
+    ///
+    /// * **receiver** is an optional parameter which defines the receiver of the LP tokens.
+    /// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+    ///
+    /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
+    #[allow(clippy::too_many_arguments)]
+    fn provide_liquidity(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        assets: [Asset; 2],
+        slippage_tolerance: Option<Decimal>,
+        auto_stake: Option<bool>,
+        receiver: Option<String>,
+    ) -> Result<Response, ContractError> {
+        assets[0].info.check(deps.api)?;
+        assets[1].info.check(deps.api)?;
+
+        let auto_stake = auto_stake.unwrap_or(false);
+
+        let config = CONFIG.load(deps.storage)?;
+        info.funds
+            .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
+        let mut pools = config
+            .pair_info
+            .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+        let deposits = [
+            assets
+                .iter()
+                .find(|a| a.info.equal(&pools[0].info))
+                .map(|a| a.amount)
+                .ok_or(ContractError::AssetMismatch {})?,
+            assets
+                .iter()
+                .find(|a| a.info.equal(&pools[1].info))
+                .map(|a| a.amount)
+                .ok_or(ContractError::AssetMismatch {})?,
+        ];
+
+        if deposits[0].is_zero() || deposits[1].is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let mut messages = vec![];
+        for (i, pool) in pools.iter_mut().enumerate() {
+            // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
+            if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: deposits[i],
+                    })?,
+                    funds: vec![],
+                }));
+            } else {
+                // If the asset is native token, the pool balance is already increased
+                // To calculate the total amount of deposits properly, we should subtract the user deposit from the pool
+                pool.amount = pool.amount.checked_sub(deposits[i])?;
+            }
+        }
+
+        let total_share = query_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+        let share = if total_share.is_zero() {
+            // Initial share is the deposit value denominated in the first pool asset.
+            // Conversion rate is taken from the 3rd party contract.
+            let converted = self
+                .query_simulation(
+                    deps.as_ref(),
+                    env.clone(),
+                    Asset {
+                        info: pools[1].info.clone(),
+                        amount: deposits[1],
+                    },
+                )?
+                .return_amount;
+            let share = deposits[0]
+                .checked_add(converted)?
+                .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+                .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+
+            messages.extend(mint_liquidity_token_message(
+                deps.querier,
+                &config,
+                &env.contract.address,
+                &env.contract.address,
+                MINIMUM_LIQUIDITY_AMOUNT,
+                false,
+            )?);
+
+            // share cannot become zero after minimum liquidity subtraction
+            if share.is_zero() {
+                return Err(ContractError::MinimumLiquidityAmountError {});
+            }
+
+            share
+        } else {
+            // Conversions might have drained one side of the pool completely
+            if pools[0].amount.is_zero() || pools[1].amount.is_zero() {
+                return Err(ContractError::MaxSlippageAssertion {});
+            }
+
+            // Assert slippage tolerance
+            assert_slippage_tolerance(slippage_tolerance, &deposits, &pools)?;
+
+            std::cmp::min(
+                deposits[0].multiply_ratio(total_share, pools[0].amount),
+                deposits[1].multiply_ratio(total_share, pools[1].amount),
+            )
+        };
+
+        // Mint LP tokens for the sender or for the receiver (if set)
+        let receiver =
+            addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
+        messages.extend(mint_liquidity_token_message(
+            deps.querier,
+            &config,
+            &env.contract.address,
+            &receiver,
+            share,
+            auto_stake,
+        )?);
+
+        Ok(Response::new().add_messages(messages).add_attributes(vec![
+            attr("action", "provide_liquidity"),
+            attr("sender", info.sender),
+            attr("receiver", receiver),
+            attr("assets", format!("{}, {}", assets[0], assets[1])),
+            attr("share", share),
+        ]))
+    }
+
+    /// Withdraws liquidity from the pool. Supported only in LP mode.
+    ///
+    /// * **sender** is the address that will receive assets back from the pair contract.
+    ///
+    /// * **amount** is the amount of LP tokens to burn.
+    fn withdraw_liquidity(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        sender: Addr,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+
+        if config.lp_mode.is_none() {
+            return Err(ContractError::NotSupported {});
+        }
+
+        if info.sender != config.pair_info.liquidity_token {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let (pools, total_share) = self.pool_info(deps.as_ref(), &config)?;
+        let refund_assets = get_share_in_assets(&pools, amount, total_share);
+
+        let mut messages = refund_assets
+            .iter()
+            .filter(|asset| !asset.amount.is_zero())
+            .map(|asset| asset.clone().into_msg(sender.clone()))
+            .collect::<StdResult<Vec<_>>>()?;
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.pair_info.liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+            funds: vec![],
+        }));
+
+        Ok(Response::new().add_messages(messages).add_attributes(vec![
+            attr("action", "withdraw_liquidity"),
+            attr("sender", sender),
+            attr("withdrawn_share", amount),
+            attr(
+                "refund_assets",
+                format!("{}, {}", refund_assets[0], refund_assets[1]),
+            ),
+        ]))
+    }
+
+    /// Performs a swap from the pool reserves in LP mode. The conversion rate is taken from
+    /// [`PairBonded::query_simulation`] while the LP spread stays in the pool.
+    /// The exchange rate is accumulated before the swap.
+    ///
+    /// * **sender** sender of the swap operation.
+    ///
+    /// * **offer_asset** proposed asset for swapping. It must already be transferred to the pair.
+    ///
+    /// * **belief_price** used to calculate the maximum swap spread.
+    ///
+    /// * **max_spread** sets the maximum spread of the swap operation.
+    ///
+    /// * **to** sets the recipient of the swap operation.
+    #[allow(clippy::too_many_arguments)]
+    fn swap_from_reserves(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        sender: Addr,
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<Addr>,
+    ) -> Result<Response, ContractError> {
+        let config = CONFIG.load(deps.storage)?;
+        let lp_mode = config.lp_mode.ok_or(ContractError::NotSupported {})?;
+
+        self.update_prices(deps.branch(), &env)?;
+
+        let ask_asset_info = if offer_asset.info.equal(&config.pair_info.asset_infos[0]) {
+            config.pair_info.asset_infos[1].clone()
+        } else if offer_asset.info.equal(&config.pair_info.asset_infos[1]) {
+            config.pair_info.asset_infos[0].clone()
+        } else {
+            return Err(ContractError::AssetMismatch {});
+        };
+
+        let SimulationResponse {
+            return_amount,
+            commission_amount,
+            ..
+        } = self.query_lp_simulation(deps.as_ref(), env.clone(), offer_asset.clone(), &lp_mode)?;
+
+        if return_amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        assert_max_spread(
+            belief_price,
+            max_spread,
+            offer_asset.amount,
+            return_amount + commission_amount,
+            Uint128::zero(),
+        )?;
+
+        let ask_pool = ask_asset_info.query_pool(&deps.querier, &env.contract.address)?;
+        if ask_pool < return_amount {
+            return Err(ContractError::InsufficientLiquidity {
+                asset: ask_asset_info.to_string(),
+            });
+        }
+
+        let receiver = to.unwrap_or_else(|| sender.clone());
+        let return_asset = Asset {
+            info: ask_asset_info.clone(),
+            amount: return_amount,
+        };
+
+        Ok(Response::new()
+            .add_message(return_asset.into_msg(receiver.clone())?)
+            .add_attribute("action", "swap")
+            .add_attribute("sender", sender.to_string())
+            .add_attribute("receiver", receiver.to_string())
+            .add_attribute("offer_asset", offer_asset.info.to_string())
+            .add_attribute("ask_asset", ask_asset_info.to_string())
+            .add_attribute("offer_amount", offer_asset.amount.to_string())
+            .add_attribute("return_amount", return_amount.to_string())
+            .add_attribute("spread_amount", "0")
+            .add_attribute("commission_amount", commission_amount.to_string())
+            .add_attribute("maker_fee_amount", "0"))
+    }
+
+    /// Returns information about a swap simulation in LP mode. The LP spread is charged from the
+    /// conversion result and returned as the commission amount.
+    fn query_lp_simulation(
+        &self,
+        deps: Deps,
+        env: Env,
+        offer_asset: Asset,
+        lp_mode: &LpModeParams,
+    ) -> StdResult<SimulationResponse> {
+        let converted = self.query_simulation(deps, env, offer_asset)?.return_amount;
+        let commission_amount = converted * lp_mode.spread;
+
+        Ok(SimulationResponse {
+            return_amount: converted - commission_amount,
+            spread_amount: Uint128::zero(),
+            commission_amount,
+        })
+    }
+
+    /// Returns information about a reverse swap simulation in LP mode. The LP spread is added on
+    /// top of the requested ask amount and returned as the commission amount.
+    fn query_lp_reverse_simulation(
+        &self,
+        deps: Deps,
+        env: Env,
+        ask_asset: Asset,
+        lp_mode: &LpModeParams,
+    ) -> StdResult<ReverseSimulationResponse> {
+        let converted = ask_asset.amount.multiply_ratio(
+            Decimal::one().atomics(),
+            (Decimal::one() - lp_mode.spread).atomics(),
+        );
+        let commission_amount = converted - ask_asset.amount;
+
+        let offer_amount = self
+            .query_reverse_simulation(
+                deps,
+                env,
+                Asset {
+                    info: ask_asset.info,
+                    amount: converted,
+                },
+            )?
+            .offer_amount;
+
+        Ok(ReverseSimulationResponse {
+            offer_amount,
+            spread_amount: Uint128::zero(),
+            commission_amount,
+        })
+    }
+
     /// Performs an swap operation with the specified parameters. The trader must approve the
     /// pool contract to transfer offer assets from their wallet.
     ///
@@ -1,3 +1,6 @@
+use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair_bonded::MAX_LP_SPREAD;
+use astroport_circular_buffer::error::BufferError;
 use cosmwasm_std::{OverflowError, StdError};
 use thiserror::Error;
 
@@ -7,6 +10,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    CircularBuffer(#[from] BufferError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -36,6 +42,27 @@ pub enum ContractError {
 
     #[error("Failed to migrate the contract")]
     MigrationError {},
+
+    #[error("Event of zero transfer")]
+    InvalidZeroAmount {},
+
+    #[error("Operation exceeds max slippage tolerance")]
+    MaxSlippageAssertion {},
+
+    #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
+    MinimumLiquidityAmountError {},
+
+    #[error("Generator address is not set in factory. Cannot auto-stake")]
+    AutoStakeError {},
+
+    #[error("LP mode spread must not exceed {}", MAX_LP_SPREAD)]
+    InvalidLpSpread {},
+
+    #[error("Not enough {asset} in the pool to perform the swap")]
+    InsufficientLiquidity { asset: String },
+
+    #[error("Failed to parse or process reply message")]
+    FailedToParseReply {},
 }
 
 impl From<OverflowError> for ContractError {
@@ -1,3 +1,6 @@
+pub mod adapter;
 pub mod base;
 pub mod error;
+pub mod oracle;
 pub mod state;
+pub mod utils;
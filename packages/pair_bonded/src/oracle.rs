@@ -0,0 +1,224 @@
+use crate::state::{Observation, OBSERVATIONS, OBSERVATIONS_SIZE};
+use astroport::pair::TWAP_PRECISION;
+use astroport::pair_bonded::{BondedAdapter, Config};
+use astroport_circular_buffer::error::BufferResult;
+use astroport_circular_buffer::BufferManager;
+use cosmwasm_std::{Env, StdResult, Storage, Uint128};
+
+/// Accumulates token prices for the assets in the pool using the exchange rate of the [`BondedAdapter`].
+/// Returns [`None`] if the prices were already accumulated in the current block.
+///
+/// * **underlying** and **bonded** are the amounts of the underlying and the bonded asset which have the same value.
+pub fn accumulate_prices(
+    env: &Env,
+    config: &Config,
+    adapter: &BondedAdapter,
+    underlying: Uint128,
+    bonded: Uint128,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    // Pairs instantiated before price accumulation was introduced start accumulating from the first update
+    if config.block_time_last == 0 {
+        return Ok(Some((
+            config.price0_cumulative_last,
+            config.price1_cumulative_last,
+            block_time,
+        )));
+    }
+
+    let (x, y) = if config.pair_info.asset_infos[0].equal(&adapter.underlying) {
+        (underlying, bonded)
+    } else {
+        (bonded, underlying)
+    };
+
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+    let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+
+    let pcl0 = config.price0_cumulative_last.wrapping_add(
+        time_elapsed
+            .checked_mul(price_precision)?
+            .multiply_ratio(y, x),
+    );
+    let pcl1 = config.price1_cumulative_last.wrapping_add(
+        time_elapsed
+            .checked_mul(price_precision)?
+            .multiply_ratio(x, y),
+    );
+
+    Ok(Some((pcl0, pcl1, block_time)))
+}
+
+/// Saves the exchange rate observation. Initializes the buffer if the pair was instantiated
+/// before observations were introduced.
+pub fn save_observation(storage: &mut dyn Storage, observation: &Observation) -> BufferResult<()> {
+    if OBSERVATIONS.state().may_load(storage)?.is_none() {
+        BufferManager::init(storage, OBSERVATIONS, OBSERVATIONS_SIZE)?;
+    }
+
+    let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
+    buffer.instant_push(storage, observation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use astroport::asset::{AssetInfo, PairInfo};
+    use astroport::factory::PairType;
+    use astroport::observation::query_observation;
+    use astroport::pair_bonded::RateSource;
+    use astroport::pair_concentrated::OracleObservation;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::Decimal;
+    use cosmwasm_std::{Addr, Binary, Timestamp};
+
+    fn adapter() -> BondedAdapter {
+        BondedAdapter {
+            underlying: AssetInfo::Token {
+                contract_addr: Addr::unchecked("astro"),
+            },
+            bonded: AssetInfo::Token {
+                contract_addr: Addr::unchecked("xastro"),
+            },
+            converter: Addr::unchecked("staking"),
+            bond_msg: Binary::default(),
+            unbond_msg: None,
+            rate_source: RateSource::StakingBalance {
+                staking_addr: Addr::unchecked("staking"),
+            },
+        }
+    }
+
+    #[test]
+    fn accumulate_exchange_rate() {
+        let adapter = adapter();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100_000);
+
+        let mut config = Config {
+            pair_info: PairInfo {
+                asset_infos: vec![adapter.underlying.clone(), adapter.bonded.clone()],
+                contract_addr: Addr::unchecked("pair"),
+                liquidity_token: Addr::unchecked(""),
+                pair_type: PairType::Custom("Bonded".to_string()),
+            },
+            factory_addr: Addr::unchecked("factory"),
+            lp_mode: None,
+            adapter: None,
+            block_time_last: 0,
+            price0_cumulative_last: Uint128::zero(),
+            price1_cumulative_last: Uint128::zero(),
+        };
+
+        // Accumulation starts from the first update
+        assert_eq!(
+            accumulate_prices(&env, &config, &adapter, 125u128.into(), 100u128.into()).unwrap(),
+            Some((Uint128::zero(), Uint128::zero(), 100_000))
+        );
+
+        config.block_time_last = 99_900;
+        assert_eq!(
+            accumulate_prices(&env, &config, &adapter, 125u128.into(), 100u128.into()).unwrap(),
+            Some((80_000_000u128.into(), 125_000_000u128.into(), 100_000))
+        );
+
+        // Reversed pair assets
+        config.pair_info.asset_infos.reverse();
+        assert_eq!(
+            accumulate_prices(&env, &config, &adapter, 125u128.into(), 100u128.into()).unwrap(),
+            Some((125_000_000u128.into(), 80_000_000u128.into(), 100_000))
+        );
+
+        config.block_time_last = 100_000;
+        assert_eq!(
+            accumulate_prices(&env, &config, &adapter, 125u128.into(), 100u128.into()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn observations_full_buffer() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100_000);
+
+        let err = query_observation(&deps.storage, env.clone(), OBSERVATIONS, 0).unwrap_err();
+        assert_eq!(err.to_string(), "Generic error: Buffer is empty");
+
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 20).unwrap();
+        let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+
+        let array = (1..=30)
+            .map(|i| Observation {
+                timestamp: env.block.time.seconds() + i * 1000,
+                price: Decimal::from_ratio(100 + i, 100u64),
+            })
+            .collect::<Vec<_>>();
+        buffer.push_many(&array);
+        buffer.commit(&mut deps.storage).unwrap();
+
+        env.block.time = env.block.time.plus_seconds(30_000);
+
+        assert_eq!(
+            OracleObservation {
+                timestamp: 130_000,
+                price: Decimal::from_ratio(130u8, 100u8),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 0).unwrap()
+        );
+        assert_eq!(
+            OracleObservation {
+                timestamp: 120_000,
+                price: Decimal::from_ratio(120u8, 100u8),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 10_000).unwrap()
+        );
+        assert_eq!(
+            OracleObservation {
+                timestamp: 124_500,
+                price: Decimal::from_ratio(1245u16, 1000u16),
+            },
+            query_observation(&deps.storage, env.clone(), OBSERVATIONS, 5_500).unwrap()
+        );
+
+        let err = query_observation(&deps.storage, env, OBSERVATIONS, 35_000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Requested observation is too old. Last known observation is at 111000"
+        );
+    }
+
+    #[test]
+    fn observations_lazy_init() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(100_000);
+
+        for i in 0..3u64 {
+            save_observation(
+                &mut deps.storage,
+                &Observation {
+                    timestamp: env.block.time.seconds() + i * 100,
+                    price: Decimal::from_ratio(10 + i, 10u64),
+                },
+            )
+            .unwrap();
+        }
+
+        let buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+        assert_eq!(buffer.capacity(), OBSERVATIONS_SIZE);
+
+        env.block.time = env.block.time.plus_seconds(1_000);
+        assert_eq!(
+            OracleObservation {
+                timestamp: 100_150,
+                price: Decimal::from_ratio(115u8, 100u8),
+            },
+            query_observation(&deps.storage, env, OBSERVATIONS, 850).unwrap()
+        );
+    }
+}
@@ -1,5 +1,37 @@
+use astroport::observation::PriceObservation;
 use astroport::pair_bonded::Config;
+use astroport_circular_buffer::CircularBuffer;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Decimal;
 use cw_storage_plus::Item;
 
+/// The maximum number of exchange rate observations stored in the circular buffer
+pub const OBSERVATIONS_SIZE: u32 = 3000;
+
+/// Stores the exchange rate observation. It is used to derive the rate at any point in the past.
+/// The rate is always the amount of the underlying asset per one bonded asset regardless of the
+/// order of the pair assets.
+#[cw_serde]
+#[derive(Copy)]
+pub struct Observation {
+    pub timestamp: u64,
+    /// Amount of the underlying asset per one bonded asset
+    pub price: Decimal,
+}
+
+impl PriceObservation for Observation {
+    fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    fn price(&self) -> Decimal {
+        self.price
+    }
+}
+
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
+
+/// Circular buffer to store exchange rate observations
+pub const OBSERVATIONS: CircularBuffer<Observation> =
+    CircularBuffer::new("observations_state", "observations_buffer");
@@ -9,7 +9,7 @@ use astroport::pair_bonded::{ExecuteMsg, QueryMsg};
 use astroport_pair_bonded::base::PairBonded;
 use astroport_pair_bonded::error::ContractError;
 use cosmwasm_std::{
-    entry_point, from_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult,
+    entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
 };
 
 /// Creates a new contract with the specified parameters in [`InstantiateMsg`].
@@ -20,16 +20,7 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    if msg.init_params.is_none() {
-        return Err(ContractError::InitParamsNotFound {});
-    }
-
-    let contract = Contract::new("params");
-    contract.params.save(
-        deps.storage,
-        &from_binary(msg.init_params.as_ref().unwrap())?,
-    )?;
-    contract.instantiate(deps, env, info, msg)
+    Contract {}.instantiate(deps, env, info, msg)
 }
 
 /// Exposes all the execute functions available in the contract via a pair-bonded template.
@@ -40,15 +31,19 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
-    let contract = Contract::new("params");
-    contract.execute(deps, env, info, msg)
+    Contract {}.execute(deps, env, info, msg)
+}
+
+/// The entry point to the contract for processing replies from submessages.
+#[entry_point]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    Contract {}.reply(deps, env, msg)
 }
 
 /// Exposes all the queries available in the contract via a pair-bonded template.
 #[entry_point]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    let contract = Contract::new("params");
-    contract.query(deps, env, msg)
+    Contract {}.query(deps, env, msg)
 }
 
 /// Manages contract migration
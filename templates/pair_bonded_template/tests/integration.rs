@@ -0,0 +1,285 @@
+use astroport::asset::{native_asset, native_asset_info, AssetInfo};
+use astroport::factory::{InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType};
+use astroport::pair::{InstantiateMsg, ReverseSimulationResponse, SimulationResponse};
+use astroport::pair_bonded::{BondedAdapter, BondedPairParams, ExecuteMsg, QueryMsg, RateSource};
+use astroport_pair_bonded::error::ContractError;
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{
+    coin, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+const LUNA: &str = "uluna";
+const AMP_LUNA: &str = "factory/hub/ampluna";
+
+/// The mock liquid staking hub sells ampLUNA at 1.25 LUNA and doesn't support instant unbonding.
+#[cw_serde]
+enum HubExecuteMsg {
+    Bond {},
+}
+
+#[cw_serde]
+enum HubQueryMsg {
+    State {},
+}
+
+#[cw_serde]
+struct HubStateResponse {
+    total_bonded: Uint128,
+    exchange_rate: Decimal,
+}
+
+fn hub_instantiate(_: DepsMut, _: Env, _: MessageInfo, _: Empty) -> StdResult<Response> {
+    Ok(Response::new())
+}
+
+fn hub_execute(_: DepsMut, _: Env, info: MessageInfo, msg: HubExecuteMsg) -> StdResult<Response> {
+    match msg {
+        HubExecuteMsg::Bond {} => {
+            let amount = info
+                .funds
+                .iter()
+                .find(|coin| coin.denom == LUNA)
+                .map(|coin| coin.amount)
+                .ok_or_else(|| StdError::generic_err("No LUNA sent"))?;
+            Ok(Response::new().add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: vec![coin(amount.multiply_ratio(4u8, 5u8).u128(), AMP_LUNA)],
+            }))
+        }
+    }
+}
+
+fn hub_query(_: Deps, _: Env, msg: HubQueryMsg) -> StdResult<Binary> {
+    match msg {
+        HubQueryMsg::State {} => to_binary(&HubStateResponse {
+            total_bonded: Uint128::new(1_000_000_000000),
+            exchange_rate: Decimal::percent(125),
+        }),
+    }
+}
+
+fn store_pair_code(app: &mut App) -> u64 {
+    let pair_contract = Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_bonded_template::execute,
+            astroport_pair_bonded_template::instantiate,
+            astroport_pair_bonded_template::query,
+        )
+        .with_reply_empty(astroport_pair_bonded_template::reply),
+    );
+
+    app.store_code(pair_contract)
+}
+
+fn instantiate_factory(app: &mut App, owner: &Addr, pair_code_id: u64) -> Addr {
+    let factory_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        astroport_factory::contract::execute,
+        astroport_factory::contract::instantiate,
+        astroport_factory::contract::query,
+    )));
+
+    let msg = FactoryInstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            total_fee_bps: 0,
+            pair_type: PairType::Custom("bonded".to_string()),
+            is_disabled: false,
+            is_generator_disabled: false,
+        }],
+        token_code_id: 0,
+        fee_address: None,
+        generator_address: None,
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_owned(),
+    };
+
+    app.instantiate_contract(
+        factory_code_id,
+        owner.clone(),
+        &msg,
+        &[],
+        String::from("Astroport Factory"),
+        None,
+    )
+    .unwrap()
+}
+
+fn adapter(hub: &Addr, bonded: AssetInfo) -> BondedAdapter {
+    BondedAdapter {
+        underlying: native_asset_info(LUNA.to_string()),
+        bonded,
+        converter: hub.clone(),
+        bond_msg: to_binary(&HubExecuteMsg::Bond {}).unwrap(),
+        unbond_msg: None,
+        rate_source: RateSource::ExchangeRateQuery {
+            contract: hub.clone(),
+            msg: to_binary(&HubQueryMsg::State {}).unwrap(),
+        },
+    }
+}
+
+#[test]
+fn native_liquid_staking_pair() {
+    let owner = Addr::unchecked("owner");
+    let user = Addr::unchecked("user");
+
+    let mut app = App::new(|router, _, storage| {
+        router
+            .bank
+            .init_balance(
+                storage,
+                &owner,
+                vec![coin(1_000_000_000000, AMP_LUNA), coin(1_000_000000, LUNA)],
+            )
+            .unwrap()
+    });
+
+    let hub_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        hub_execute,
+        hub_instantiate,
+        hub_query,
+    )));
+    let hub = app
+        .instantiate_contract(hub_code_id, owner.clone(), &Empty {}, &[], "hub", None)
+        .unwrap();
+    app.send_tokens(
+        owner.clone(),
+        hub.clone(),
+        &[coin(1_000_000_000000, AMP_LUNA)],
+    )
+    .unwrap();
+    app.send_tokens(owner.clone(), user.clone(), &[coin(1_000_000000, LUNA)])
+        .unwrap();
+
+    let pair_code_id = store_pair_code(&mut app);
+    let factory = instantiate_factory(&mut app, &owner, pair_code_id);
+
+    let instantiate_msg = |init_params: Option<Binary>| InstantiateMsg {
+        asset_infos: vec![
+            native_asset_info(LUNA.to_string()),
+            native_asset_info(AMP_LUNA.to_string()),
+        ],
+        token_code_id: 0,
+        factory_addr: factory.to_string(),
+        init_params,
+    };
+
+    let err = app
+        .instantiate_contract(
+            pair_code_id,
+            owner.clone(),
+            &instantiate_msg(None),
+            &[],
+            "LUNA-ampLUNA pair",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InitParamsNotFound {},
+        err.downcast().unwrap()
+    );
+
+    // The adapter is validated against the pair assets
+    let err = app
+        .instantiate_contract(
+            pair_code_id,
+            owner.clone(),
+            &instantiate_msg(Some(
+                to_binary(&BondedPairParams {
+                    adapter: adapter(&hub, native_asset_info("ustake".to_string())),
+                    lp_mode: None,
+                })
+                .unwrap(),
+            )),
+            &[],
+            "LUNA-ampLUNA pair",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Adapter assets must match the pair assets"
+    );
+
+    let pair = app
+        .instantiate_contract(
+            pair_code_id,
+            owner.clone(),
+            &instantiate_msg(Some(
+                to_binary(&BondedPairParams {
+                    adapter: adapter(&hub, native_asset_info(AMP_LUNA.to_string())),
+                    lp_mode: None,
+                })
+                .unwrap(),
+            )),
+            &[],
+            "LUNA-ampLUNA pair",
+            None,
+        )
+        .unwrap();
+
+    let sim: SimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair,
+            &QueryMsg::Simulation {
+                offer_asset: native_asset(LUNA.to_string(), Uint128::new(1_000000)),
+            },
+        )
+        .unwrap();
+    assert_eq!(sim.return_amount, Uint128::new(800000));
+
+    let sim: ReverseSimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &pair,
+            &QueryMsg::ReverseSimulation {
+                ask_asset: native_asset(AMP_LUNA.to_string(), Uint128::new(800000)),
+            },
+        )
+        .unwrap();
+    assert_eq!(sim.offer_amount, Uint128::new(1_000000));
+
+    // LUNA is bonded via the hub and ampLUNA is sent to the user
+    app.execute_contract(
+        user.clone(),
+        pair.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset: native_asset(LUNA.to_string(), Uint128::new(1_000000)),
+            belief_price: None,
+            max_spread: None,
+            to: None,
+        },
+        &[coin(1_000000, LUNA)],
+    )
+    .unwrap();
+    assert_eq!(
+        app.wrap().query_balance(&user, AMP_LUNA).unwrap().amount,
+        Uint128::new(800000)
+    );
+    assert_eq!(
+        app.wrap().query_balance(&hub, LUNA).unwrap().amount,
+        Uint128::new(1_000000)
+    );
+    assert!(app.wrap().query_all_balances(&pair).unwrap().is_empty());
+
+    // The hub can't unbond instantly
+    let err = app
+        .execute_contract(
+            user.clone(),
+            pair,
+            &ExecuteMsg::Swap {
+                offer_asset: native_asset(AMP_LUNA.to_string(), Uint128::new(800000)),
+                belief_price: None,
+                max_spread: None,
+                to: None,
+            },
+            &[coin(800000, AMP_LUNA)],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::NotSupported {}, err.downcast().unwrap());
+}